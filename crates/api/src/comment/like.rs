@@ -62,6 +62,7 @@ pub async fn like_comment(
     post_id: orig_comment.post.id,
     person_id: local_user_view.person.id,
     score: data.score,
+    activity_ap_id: None,
   };
 
   // Remove any likes first
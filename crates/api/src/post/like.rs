@@ -53,6 +53,7 @@ pub async fn like_post(
     post_id: data.post_id,
     person_id: local_user_view.person.id,
     score: data.score,
+    activity_ap_id: None,
   };
 
   // Remove any likes first
@@ -0,0 +1,22 @@
+use actix_web::web::{Data, Json, Query};
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::{ListFederatedVoteRejections, ListFederatedVoteRejectionsResponse},
+  utils::is_admin,
+};
+use lemmy_db_schema::source::federated_vote_rejection::FederatedVoteRejection;
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::LemmyResult;
+
+#[tracing::instrument(skip(context))]
+pub async fn list_federated_vote_rejections(
+  data: Query<ListFederatedVoteRejections>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<ListFederatedVoteRejectionsResponse>> {
+  // Only let admins view rejected votes
+  is_admin(&local_user_view)?;
+
+  let rejections = FederatedVoteRejection::list(&mut context.pool(), data.page, data.limit).await?;
+  Ok(Json(ListFederatedVoteRejectionsResponse { rejections }))
+}
@@ -0,0 +1,33 @@
+use actix_web::web::{Data, Json, Query};
+use chrono::{TimeZone, Utc};
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::{ListResolvedObjects, ListResolvedObjectsResponse},
+  utils::is_admin,
+};
+use lemmy_db_schema::source::resolve_object_log::ResolveObjectLog;
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::{LemmyErrorType, LemmyResult};
+
+#[tracing::instrument(skip(context))]
+pub async fn list_resolved_objects(
+  data: Query<ListResolvedObjects>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<ListResolvedObjectsResponse>> {
+  // Only let admins view what users are fetching
+  is_admin(&local_user_view)?;
+
+  let since = data
+    .since
+    .map(|since| {
+      Utc
+        .timestamp_opt(since, 0)
+        .single()
+        .ok_or(LemmyErrorType::InvalidUnixTime)
+    })
+    .transpose()?;
+  let resolved_objects =
+    ResolveObjectLog::list(&mut context.pool(), since, data.page, data.limit).await?;
+  Ok(Json(ListResolvedObjectsResponse { resolved_objects }))
+}
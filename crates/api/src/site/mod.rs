@@ -2,6 +2,9 @@ pub mod block;
 pub mod federated_instances;
 pub mod leave_admin;
 pub mod list_all_media;
+pub mod list_federated_vote_rejections;
+pub mod list_resolved_objects;
 pub mod mod_log;
 pub mod purge;
 pub mod registration_applications;
+pub mod set_instance_vote_rate_limit;
@@ -40,6 +40,7 @@ pub async fn get_mod_log(
   let type_ = data.type_.unwrap_or(All);
   let community_id = data.community_id;
 
+  let is_admin = local_user_view.as_ref().is_some_and(|l| l.local_user.admin);
   let is_mod_or_admin = if let Some(local_user_view) = local_user_view {
     check_community_mod_of_any_or_admin_action(&local_user_view, &mut context.pool())
       .await
@@ -166,6 +167,20 @@ pub async fn get_mod_log(
     Default::default()
   };
 
+  // Snapshots and ids of purged communities are only visible to admins
+  let admin_purged_communities = if is_admin {
+    admin_purged_communities
+  } else {
+    admin_purged_communities
+      .into_iter()
+      .map(|mut p| {
+        p.admin_purge_community.snapshot = None;
+        p.admin_purge_community.community_actor_id = None;
+        p
+      })
+      .collect()
+  };
+
   // Return the jwt
   Ok(Json(GetModlogResponse {
     removed_posts,
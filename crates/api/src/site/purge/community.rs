@@ -1,29 +1,50 @@
 use activitypub_federation::config::Data;
-use actix_web::web::Json;
+use actix_web::web::{Json, Query};
 use lemmy_api_common::{
   context::LemmyContext,
-  request::purge_image_from_pictrs,
   send_activity::{ActivityChannel, SendActivityData},
-  site::PurgeCommunity,
-  utils::{is_admin, purge_image_posts_for_community},
+  site::{
+    GetPurgeCommunitiesProgress,
+    GetPurgeCommunitiesProgressResponse,
+    GetPurgeCommunityStatus,
+    GetPurgeCommunityStatusResponse,
+    ListArchivedReports,
+    ListArchivedReportsResponse,
+    PurgeCommunitiesFromInstance,
+    PurgeCommunitiesFromInstanceResponse,
+    PurgeCommunity,
+    PurgeCommunityContent,
+    PurgeCommunityContentResponse,
+    PurgeCommunityResponse,
+    ResendPurgeCommunity,
+  },
+  utils::{community_images, is_admin, local_post_images, purge_community_images},
   SuccessResponse,
 };
 use lemmy_db_schema::{
   source::{
-    community::Community,
-    moderator::{AdminPurgeCommunity, AdminPurgeCommunityForm},
+    comment::Comment,
+    community::{Community, CommunityPurgeOptions},
+    community_image_purge::CommunityImagePurge,
+    community_purge_progress::{CommunityPurgeProgress, CommunityPurgeProgressForm},
+    local_site::LocalSite,
+    moderator::AdminPurgeCommunity,
+    post::Post,
+    report_archive::ReportArchive,
   },
   traits::Crud,
 };
 use lemmy_db_views::structs::LocalUserView;
-use lemmy_utils::{error::LemmyResult, LemmyErrorType};
+use lemmy_utils::{error::LemmyResult, spawn_try_task, LemmyErrorType};
+use std::collections::HashSet;
+use tracing::warn;
 
 #[tracing::instrument(skip(context))]
 pub async fn purge_community(
   data: Json<PurgeCommunity>,
   context: Data<LemmyContext>,
   local_user_view: LocalUserView,
-) -> LemmyResult<Json<SuccessResponse>> {
+) -> LemmyResult<Json<PurgeCommunityResponse>> {
   // Only let admin purge an item
   is_admin(&local_user_view)?;
 
@@ -32,31 +53,316 @@ pub async fn purge_community(
     .await?
     .ok_or(LemmyErrorType::CouldntFindCommunity)?;
 
-  if let Some(banner) = &community.banner {
-    purge_image_from_pictrs(banner, &context).await.ok();
+  let (posts, comments) =
+    Community::count_posts_and_comments(&mut context.pool(), data.community_id).await?;
+  let images = community_images(&community, &context).await?;
+  let response = PurgeCommunityResponse {
+    success: true,
+    posts,
+    comments,
+    images: images.len().try_into()?,
+  };
+  if data.dry_run.unwrap_or_default() {
+    return Ok(Json(response));
   }
 
-  if let Some(icon) = &community.icon {
-    purge_image_from_pictrs(icon, &context).await.ok();
+  // Large communities can only be purged after the admin has seen what will be deleted
+  let local_site = LocalSite::read(&mut context.pool()).await?;
+  if posts > i64::from(local_site.purge_confirmation_post_threshold)
+    && !data.confirmed.unwrap_or_default()
+  {
+    Err(LemmyErrorType::PurgeRequiresConfirmation {
+      posts: response.posts,
+      comments: response.comments,
+      images: response.images,
+    })?
   }
 
-  purge_image_posts_for_community(data.community_id, &context).await?;
+  let federate = data.federate.unwrap_or(true);
+  Community::purge(
+    &mut context.pool(),
+    data.community_id,
+    local_user_view.person.id,
+    data.reason.clone(),
+    CommunityPurgeOptions {
+      store_snapshot: local_site.store_purge_snapshots,
+      federated: federate,
+      archive_reports: data.archive_reports.unwrap_or_default(),
+      ..Default::default()
+    },
+  )
+  .await?;
+
+  // Images are purged in a background task, see get_purge_community_status. This only starts
+  // once the community is deleted, so that a failed purge doesn't leave it without images.
+  purge_community_images(community.id, images, &context).await?;
+
+  // Without federation the community is only purged locally, and remote instances keep their
+  // copies. The modlog entry records this.
+  if federate {
+    ActivityChannel::submit_activity(
+      SendActivityData::RemoveCommunity {
+        moderator: local_user_view.person.clone(),
+        community,
+        reason: data.reason.clone(),
+        removed: true,
+        purge: true,
+      },
+      &context,
+    )
+    .await?;
+  }
+
+  Ok(Json(response))
+}
+
+/// The number of posts or comments which purge_community_content deletes in one transaction.
+const PURGE_CONTENT_CHUNK_SIZE: usize = 100;
 
-  Community::delete(&mut context.pool(), data.community_id).await?;
+/// Purges the posts and comments of a community which were published within the time range. Each
+/// of them is purged like with purge_post and purge_comment, so that every one gets its own modlog
+/// entry and removal activity.
+#[tracing::instrument(skip(context))]
+pub async fn purge_community_content(
+  data: Json<PurgeCommunityContent>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<PurgeCommunityContentResponse>> {
+  // Only let admin purge an item
+  is_admin(&local_user_view)?;
+  if data.start > data.end {
+    Err(LemmyErrorType::InvalidTimeRange)?
+  }
 
-  // Mod tables
-  let form = AdminPurgeCommunityForm {
-    admin_person_id: local_user_view.person.id,
-    reason: data.reason.clone(),
+  let community = Community::read(&mut context.pool(), data.community_id)
+    .await?
+    .ok_or(LemmyErrorType::CouldntFindCommunity)?;
+  let posts =
+    Post::list_for_community_in_range(&mut context.pool(), community.id, data.start, data.end)
+      .await?;
+  let comments =
+    Comment::list_for_community_in_range(&mut context.pool(), community.id, data.start, data.end)
+      .await?;
+  let images = local_post_images(&posts, &context)?;
+  let response = PurgeCommunityContentResponse {
+    posts: posts.len().try_into()?,
+    comments: comments.len().try_into()?,
+    images: images.len().try_into()?,
   };
-  AdminPurgeCommunity::create(&mut context.pool(), &form).await?;
+  if data.dry_run.unwrap_or_default() {
+    return Ok(Json(response));
+  }
+
+  // A time range can cover much of a large community, so the same confirmation as for purging
+  // the community applies
+  let local_site = LocalSite::read(&mut context.pool()).await?;
+  if response.posts > i64::from(local_site.purge_confirmation_post_threshold)
+    && !data.confirmed.unwrap_or_default()
+  {
+    Err(LemmyErrorType::PurgeRequiresConfirmation {
+      posts: response.posts,
+      comments: response.comments,
+      images: response.images,
+    })?
+  }
+
+  // Each chunk is deleted together with its modlog entries in one transaction, without holding
+  // locks on the whole range
+  for posts in posts.chunks(PURGE_CONTENT_CHUNK_SIZE) {
+    Post::purge_many(
+      &mut context.pool(),
+      posts,
+      local_user_view.person.id,
+      data.reason.clone(),
+    )
+    .await?;
+
+    for post in posts {
+      ActivityChannel::submit_activity(
+        SendActivityData::RemovePost {
+          post: post.clone(),
+          moderator: local_user_view.person.clone(),
+          reason: data.reason.clone(),
+          removed: true,
+        },
+        &context,
+      )
+      .await?;
+    }
+  }
+
+  // Comments of purged posts were deleted together with the post
+  let post_ids = posts.iter().map(|p| p.id).collect::<HashSet<_>>();
+  let comments = comments
+    .into_iter()
+    .filter(|c| !post_ids.contains(&c.post_id))
+    .collect::<Vec<_>>();
+  for comments in comments.chunks(PURGE_CONTENT_CHUNK_SIZE) {
+    Comment::purge_many(
+      &mut context.pool(),
+      comments,
+      local_user_view.person.id,
+      data.reason.clone(),
+    )
+    .await?;
+
+    for comment in comments {
+      ActivityChannel::submit_activity(
+        SendActivityData::RemoveComment {
+          comment: comment.clone(),
+          moderator: local_user_view.person.clone(),
+          community: community.clone(),
+          reason: data.reason.clone(),
+        },
+        &context,
+      )
+      .await?;
+    }
+  }
+
+  // Images are only purged once all content is deleted, so that a failed purge doesn't leave
+  // posts without images. This runs in a background task, see get_purge_community_status.
+  purge_community_images(community.id, images, &context).await?;
+
+  Ok(Json(response))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn purge_communities_from_instance(
+  data: Json<PurgeCommunitiesFromInstance>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<PurgeCommunitiesFromInstanceResponse>> {
+  // Only let admin purge an item
+  is_admin(&local_user_view)?;
+  if local_user_view.person.instance_id == data.instance_id {
+    Err(LemmyErrorType::CantPurgeLocalInstance)?
+  }
+
+  let communities = Community::list_for_instance(&mut context.pool(), data.instance_id).await?;
+  let mut response = PurgeCommunitiesFromInstanceResponse {
+    communities: communities.iter().map(|c| c.id).collect(),
+    ..Default::default()
+  };
+  let mut images = vec![];
+  for community in &communities {
+    let (posts, comments) =
+      Community::count_posts_and_comments(&mut context.pool(), community.id).await?;
+    let community_images = community_images(community, &context).await?;
+    response.posts += posts;
+    response.comments += comments;
+    response.images += i64::try_from(community_images.len())?;
+    images.push(community_images);
+  }
+  if data.dry_run.unwrap_or_default() {
+    return Ok(Json(response));
+  }
+
+  // The confirmation threshold applies to the total number of posts
+  let local_site = LocalSite::read(&mut context.pool()).await?;
+  if response.posts > i64::from(local_site.purge_confirmation_post_threshold)
+    && !data.confirmed.unwrap_or_default()
+  {
+    Err(LemmyErrorType::PurgeRequiresConfirmation {
+      posts: response.posts,
+      comments: response.comments,
+      images: response.images,
+    })?
+  }
+
+  // The progress can be followed with get_purge_communities_progress
+  let form = CommunityPurgeProgressForm {
+    total: response.communities.len().try_into()?,
+  };
+  let progress = CommunityPurgeProgress::create(&mut context.pool(), &form).await?;
+  response.progress_id = Some(progress.id);
+
+  // Purging many communities takes long, so it runs in a background task. Each community is
+  // purged in its own transaction, so that the progress can be followed while this runs, and a
+  // failure only affects that community. Images and federation are handled as soon as a
+  // community is deleted.
+  let moderator = local_user_view.person;
+  let reason = data.reason.clone();
+  let store_snapshot = local_site.store_purge_snapshots;
+  spawn_try_task(async move {
+    for (community, images) in communities.into_iter().zip(images) {
+      let options = CommunityPurgeOptions {
+        store_snapshot,
+        progress_id: Some(progress.id),
+        federated: true,
+        ..Default::default()
+      };
+      let purged = Community::purge(
+        &mut context.pool(),
+        community.id,
+        moderator.id,
+        reason.clone(),
+        options,
+      )
+      .await;
+      if let Err(e) = purged {
+        warn!("Failed to purge community {}: {e}", community.actor_id);
+        CommunityPurgeProgress::add_failed(&mut context.pool(), progress.id).await?;
+        continue;
+      }
+      let community_id = community.id;
+      let actor_id = community.actor_id.clone();
+      let removal = async {
+        purge_community_images(community_id, images, &context).await?;
+        ActivityChannel::submit_activity(
+          SendActivityData::RemoveCommunity {
+            moderator: moderator.clone(),
+            community,
+            reason: reason.clone(),
+            removed: true,
+            purge: true,
+          },
+          &context,
+        )
+        .await
+      };
+      if let Err(e) = removal.await {
+        warn!("Failed to remove images or federate purge of community {actor_id}: {e}");
+      }
+    }
+    CommunityPurgeProgress::finish(&mut context.pool(), progress.id).await?;
+    Ok(())
+  });
+
+  Ok(Json(response))
+}
+
+/// Federates a past community purge again, for example if remote instances missed it because the
+/// activity couldn't be sent.
+#[tracing::instrument(skip(context))]
+pub async fn resend_purge_community(
+  data: Json<ResendPurgeCommunity>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<SuccessResponse>> {
+  is_admin(&local_user_view)?;
+
+  let purge = AdminPurgeCommunity::read(&mut context.pool(), data.admin_purge_community_id)
+    .await?
+    .ok_or(LemmyErrorType::CouldntFindPurge)?;
+  // Older purges didn't store which community was purged
+  let community_actor_id = purge
+    .community_actor_id
+    .ok_or(LemmyErrorType::PurgeCantBeResent)?;
+  // The community itself is gone, so whether it was local is known only from its actor id
+  let hostname = context.settings().get_hostname_without_port()?;
+  if community_actor_id.inner().domain() != Some(hostname.as_str()) {
+    Err(LemmyErrorType::CantResendPurgeOfRemoteCommunity)?
+  }
+  if !purge.federated && !data.confirmed.unwrap_or_default() {
+    Err(LemmyErrorType::PurgeWasntFederated)?
+  }
 
   ActivityChannel::submit_activity(
-    SendActivityData::RemoveCommunity {
+    SendActivityData::ResendPurgeCommunity {
       moderator: local_user_view.person.clone(),
-      community,
-      reason: data.reason.clone(),
-      removed: true,
+      community_actor_id,
+      reason: purge.reason,
     },
     &context,
   )
@@ -64,3 +370,966 @@ pub async fn purge_community(
 
   Ok(Json(SuccessResponse::default()))
 }
+
+#[tracing::instrument(skip(context))]
+pub async fn get_purge_community_status(
+  data: Query<GetPurgeCommunityStatus>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<GetPurgeCommunityStatusResponse>> {
+  is_admin(&local_user_view)?;
+
+  let status = CommunityImagePurge::read(&mut context.pool(), data.community_id)
+    .await?
+    .ok_or(LemmyErrorType::CouldntFindCommunity)?;
+  Ok(Json(GetPurgeCommunityStatusResponse { status }))
+}
+
+#[tracing::instrument(skip(context))]
+pub async fn list_archived_reports(
+  data: Query<ListArchivedReports>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<ListArchivedReportsResponse>> {
+  is_admin(&local_user_view)?;
+
+  let reports = ReportArchive::list(
+    &mut context.pool(),
+    data.community_actor_id.as_ref(),
+    data.page,
+    data.limit,
+  )
+  .await?;
+  Ok(Json(ListArchivedReportsResponse { reports }))
+}
+
+/// Returns the progress of purging multiple communities, or of the latest such purge if no id is
+/// given.
+#[tracing::instrument(skip(context))]
+pub async fn get_purge_communities_progress(
+  data: Query<GetPurgeCommunitiesProgress>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<GetPurgeCommunitiesProgressResponse>> {
+  is_admin(&local_user_view)?;
+
+  let progress = match data.progress_id {
+    Some(id) => CommunityPurgeProgress::read(&mut context.pool(), id).await?,
+    None => CommunityPurgeProgress::read_latest(&mut context.pool()).await?,
+  }
+  .ok_or(LemmyErrorType::CouldntFindPurgeProgress)?;
+  Ok(Json(GetPurgeCommunitiesProgressResponse { progress }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{Days, Utc};
+  use lemmy_db_schema::{
+    source::{
+      comment::CommentInsertForm,
+      community::{CommunityInsertForm, CommunityUpdateForm},
+      instance::Instance,
+      local_site::LocalSiteInsertForm,
+      local_user::{LocalUser, LocalUserInsertForm},
+      moderator::AdminPurgeCommunityForm,
+      person::{Person, PersonInsertForm},
+      post::{Post, PostInsertForm},
+      post_report::{PostReport, PostReportForm},
+      site::{Site, SiteInsertForm},
+    },
+    traits::Reportable,
+  };
+  use lemmy_db_views_moderator::structs::{
+    AdminPurgeCommentView,
+    AdminPurgeCommunityView,
+    AdminPurgePostView,
+    ModlogListParams,
+  };
+  use pretty_assertions::assert_eq;
+  use serial_test::serial;
+  use std::time::Duration;
+  use tokio::time::{sleep, timeout};
+  use url::Url;
+
+  struct TestData {
+    instance: Instance,
+    person: Person,
+    local_user_view: LocalUserView,
+    community: Community,
+    posts: Vec<Post>,
+  }
+
+  /// Creates an admin and a community with the given number of posts. With `with_local_site`, the
+  /// local site is also created, with a purge confirmation threshold of one post and purge
+  /// snapshots enabled.
+  async fn init_data(
+    context: &LemmyContext,
+    post_count: usize,
+    with_local_site: bool,
+  ) -> LemmyResult<TestData> {
+    let pool = &mut context.pool();
+    let instance = Instance::read_or_create(pool, "my_domain.tld".to_string()).await?;
+
+    if with_local_site {
+      let site_form = SiteInsertForm::builder()
+        .name("test site".to_string())
+        .instance_id(instance.id)
+        .build();
+      let site = Site::create(pool, &site_form).await?;
+      let local_site_form = LocalSiteInsertForm::builder()
+        .site_id(site.id)
+        .purge_confirmation_post_threshold(Some(1))
+        .store_purge_snapshots(Some(true))
+        .build();
+      LocalSite::create(pool, &local_site_form).await?;
+    }
+
+    let person_form = PersonInsertForm::builder()
+      .name("purge_admin".into())
+      .public_key("pubkey".to_string())
+      .instance_id(instance.id)
+      .build();
+    let person = Person::create(pool, &person_form).await?;
+    let local_user_form = LocalUserInsertForm::builder()
+      .person_id(person.id)
+      .admin(Some(true))
+      .password_encrypted(String::new())
+      .build();
+    let local_user = LocalUser::create(pool, &local_user_form, vec![]).await?;
+    let local_user_view = LocalUserView::read(pool, local_user.id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindPerson)?;
+
+    let community_form = CommunityInsertForm::builder()
+      .name("purge_community".to_string())
+      .title("nada".to_owned())
+      .public_key("pubkey".to_string())
+      .instance_id(instance.id)
+      .build();
+    let community = Community::create(pool, &community_form).await?;
+    let mut posts = vec![];
+    for i in 0..post_count {
+      let post_form = PostInsertForm::builder()
+        .name(format!("post {i}"))
+        .creator_id(person.id)
+        .community_id(community.id)
+        .build();
+      posts.push(Post::create(pool, &post_form).await?);
+    }
+
+    Ok(TestData {
+      instance,
+      person,
+      local_user_view,
+      community,
+      posts,
+    })
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_dry_run() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 1, false).await?;
+
+    let form = PurgeCommunity {
+      community_id: data.community.id,
+      reason: None,
+      dry_run: Some(true),
+      confirmed: None,
+      federate: None,
+      archive_reports: None,
+    };
+    let res = purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view,
+    )
+    .await?;
+    assert_eq!(1, res.posts);
+    assert_eq!(0, res.comments);
+    assert_eq!(0, res.images);
+
+    // nothing was deleted, and the purge isn't logged
+    let pool = &mut context.pool();
+    assert!(Community::read(pool, data.community.id).await?.is_some());
+    let params = ModlogListParams {
+      community_id: None,
+      mod_person_id: Some(data.person.id),
+      other_person_id: None,
+      post_id: None,
+      comment_id: None,
+      page: None,
+      limit: None,
+      hide_modlog_names: false,
+    };
+    let purges = AdminPurgeCommunityView::list(pool, params).await?;
+    assert!(purges.is_empty());
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_below_threshold() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 1, true).await?;
+
+    let form = PurgeCommunity {
+      community_id: data.community.id,
+      reason: None,
+      dry_run: None,
+      confirmed: None,
+      federate: None,
+      archive_reports: None,
+    };
+    purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view,
+    )
+    .await?;
+
+    let pool = &mut context.pool();
+    assert!(Community::read(pool, data.community.id).await?.is_none());
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_above_threshold() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 2, true).await?;
+
+    let mut form = PurgeCommunity {
+      community_id: data.community.id,
+      reason: None,
+      dry_run: None,
+      confirmed: None,
+      federate: None,
+      archive_reports: None,
+    };
+    let res = purge_community(
+      Json(form.clone()),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::PurgeRequiresConfirmation {
+        posts: 2,
+        comments: 0,
+        images: 0,
+      }),
+      res.err().map(|e| e.error_type)
+    );
+    let pool = &mut context.pool();
+    assert!(Community::read(pool, data.community.id).await?.is_some());
+
+    // purging proceeds once confirmed
+    form.confirmed = Some(true);
+    purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view,
+    )
+    .await?;
+    let pool = &mut context.pool();
+    assert!(Community::read(pool, data.community.id).await?.is_none());
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_counts() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 2, true).await?;
+    let pool = &mut context.pool();
+    let post_form = PostInsertForm::builder()
+      .name("image post".to_string())
+      .creator_id(data.person.id)
+      .community_id(data.community.id)
+      .url(Some(
+        Url::parse("https://my_domain.tld/pictrs/image/purge.png")?.into(),
+      ))
+      .build();
+    let image_post = Post::create(pool, &post_form).await?;
+    for post in data.posts.iter().chain([&image_post]) {
+      let comment_form = CommentInsertForm::builder()
+        .creator_id(data.person.id)
+        .post_id(post.id)
+        .content("purge_counts".to_string())
+        .build();
+      Comment::create(pool, &comment_form, None).await?;
+    }
+
+    // the response of the actual purge contains everything that was deleted
+    let form = PurgeCommunity {
+      community_id: data.community.id,
+      reason: None,
+      dry_run: None,
+      confirmed: Some(true),
+      federate: None,
+      archive_reports: None,
+    };
+    let res = purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view,
+    )
+    .await?;
+    assert_eq!(
+      PurgeCommunityResponse {
+        success: true,
+        posts: 3,
+        comments: 3,
+        images: 1,
+      },
+      res.0
+    );
+    let pool = &mut context.pool();
+    assert!(Community::read(pool, data.community.id).await?.is_none());
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_communities_from_instance() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 0, true).await?;
+    let pool = &mut context.pool();
+    let instance = Instance::read_or_create(pool, "purge_remote.tld".to_string()).await?;
+    let mut communities = vec![];
+    for i in 0..2 {
+      let community_form = CommunityInsertForm::builder()
+        .name(format!("purge_remote_community_{i}"))
+        .title("nada".to_owned())
+        .public_key("pubkey".to_string())
+        .instance_id(instance.id)
+        .build();
+      let community = Community::create(pool, &community_form).await?;
+      communities.push(community.id);
+      if i == 0 {
+        let post_form = PostInsertForm::builder()
+          .name("remote post".to_string())
+          .creator_id(data.person.id)
+          .community_id(community.id)
+          .build();
+        Post::create(pool, &post_form).await?;
+      }
+    }
+
+    // communities of the local instance can't be purged in bulk
+    let form = PurgeCommunitiesFromInstance {
+      instance_id: data.instance.id,
+      reason: None,
+      dry_run: Some(true),
+      confirmed: None,
+    };
+    let res = purge_communities_from_instance(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::CantPurgeLocalInstance),
+      res.err().map(|e| e.error_type)
+    );
+
+    let form = PurgeCommunitiesFromInstance {
+      instance_id: instance.id,
+      reason: None,
+      dry_run: None,
+      confirmed: None,
+    };
+    let res = purge_communities_from_instance(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await?;
+    assert_eq!(communities, res.communities);
+    assert_eq!(1, res.posts);
+
+    // the purge runs in the background, and its progress is kept
+    let form = GetPurgeCommunitiesProgress {
+      progress_id: res.progress_id,
+    };
+    let mut progress = None;
+    for _ in 0..100 {
+      let current = get_purge_communities_progress(
+        Query(form),
+        context.reset_request_count(),
+        data.local_user_view.clone(),
+      )
+      .await?
+      .0
+      .progress;
+      if current.finished.is_some() {
+        progress = Some(current);
+        break;
+      }
+      sleep(Duration::from_millis(50)).await;
+    }
+    let progress = progress.ok_or(LemmyErrorType::CouldntFindPurgeProgress)?;
+    assert_eq!(2, progress.total);
+    assert_eq!(2, progress.purged);
+    assert_eq!(0, progress.failed);
+
+    // both communities are deleted, and each purge is logged
+    let pool = &mut context.pool();
+    for community_id in communities {
+      assert!(Community::read(pool, community_id).await?.is_none());
+    }
+    assert!(Community::read(pool, data.community.id).await?.is_some());
+    let params = ModlogListParams {
+      community_id: None,
+      mod_person_id: Some(data.person.id),
+      other_person_id: None,
+      post_id: None,
+      comment_id: None,
+      page: None,
+      limit: None,
+      hide_modlog_names: false,
+    };
+    let purges = AdminPurgeCommunityView::list(pool, params).await?;
+    assert_eq!(2, purges.len());
+
+    Instance::delete(&mut context.pool(), instance.id).await?;
+    Instance::delete(&mut context.pool(), data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_communities_progress() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 0, false).await?;
+    let pool = &mut context.pool();
+    let community_form = CommunityInsertForm::builder()
+      .name("purge_community_2".to_string())
+      .title("nada".to_owned())
+      .public_key("pubkey".to_string())
+      .instance_id(data.instance.id)
+      .build();
+    let community_2 = Community::create(pool, &community_form).await?;
+
+    let form = CommunityPurgeProgressForm { total: 2 };
+    let progress = CommunityPurgeProgress::create(pool, &form).await?;
+    assert_eq!(0, progress.purged);
+
+    // each purged community is counted right away
+    for (i, community_id) in [data.community.id, community_2.id].into_iter().enumerate() {
+      Community::purge(
+        pool,
+        community_id,
+        data.person.id,
+        None,
+        CommunityPurgeOptions {
+          progress_id: Some(progress.id),
+          federated: true,
+          ..Default::default()
+        },
+      )
+      .await?;
+      let progress = CommunityPurgeProgress::read(pool, progress.id)
+        .await?
+        .ok_or(LemmyErrorType::CouldntFindPurgeProgress)?;
+      assert_eq!(i32::try_from(i)? + 1, progress.purged);
+      assert!(progress.finished.is_none());
+    }
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resend_purge_community() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 0, false).await?;
+    let pool = &mut context.pool();
+    // only purges of local communities can be sent
+    let form = CommunityUpdateForm {
+      actor_id: Some(
+        Url::parse(&format!(
+          "http://{}/c/purge_community",
+          context.settings().hostname
+        ))?
+        .into(),
+      ),
+      ..Default::default()
+    };
+    let community = Community::update(pool, data.community.id, &form).await?;
+
+    // the purge is recorded with the community's actor id, so that it can be sent again
+    Community::purge(
+      pool,
+      data.community.id,
+      data.person.id,
+      None,
+      CommunityPurgeOptions {
+        federated: true,
+        ..Default::default()
+      },
+    )
+    .await?;
+    let params = ModlogListParams {
+      community_id: None,
+      mod_person_id: Some(data.person.id),
+      other_person_id: None,
+      post_id: None,
+      comment_id: None,
+      page: None,
+      limit: None,
+      hide_modlog_names: false,
+    };
+    let purge = AdminPurgeCommunityView::list(pool, params)
+      .await?
+      .into_iter()
+      .next()
+      .ok_or(LemmyErrorType::CouldntFindCommunity)?
+      .admin_purge_community;
+    assert_eq!(Some(community.actor_id), purge.community_actor_id);
+    let form = ResendPurgeCommunity {
+      admin_purge_community_id: purge.id,
+      confirmed: None,
+    };
+    resend_purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await?;
+
+    // purges from before the actor id was stored can't be sent again
+    let form = AdminPurgeCommunityForm {
+      admin_person_id: data.person.id,
+      reason: None,
+      snapshot: None,
+      community_actor_id: None,
+      federated: true,
+    };
+    let old_purge = AdminPurgeCommunity::create(pool, &form).await?;
+    let form = ResendPurgeCommunity {
+      admin_purge_community_id: old_purge.id,
+      confirmed: None,
+    };
+    let res = resend_purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::PurgeCantBeResent),
+      res.err().map(|e| e.error_type)
+    );
+
+    // purges which were done without federation are only sent once confirmed
+    let form = AdminPurgeCommunityForm {
+      admin_person_id: data.person.id,
+      reason: None,
+      snapshot: None,
+      community_actor_id: purge.community_actor_id.clone(),
+      federated: false,
+    };
+    let local_purge = AdminPurgeCommunity::create(pool, &form).await?;
+    let mut form = ResendPurgeCommunity {
+      admin_purge_community_id: local_purge.id,
+      confirmed: None,
+    };
+    let res = resend_purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::PurgeWasntFederated),
+      res.err().map(|e| e.error_type)
+    );
+    form.confirmed = Some(true);
+    resend_purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await?;
+
+    // purges of remote communities aren't federated
+    let form = AdminPurgeCommunityForm {
+      admin_person_id: data.person.id,
+      reason: None,
+      snapshot: None,
+      community_actor_id: Some(Url::parse("https://remote.example/c/purged")?.into()),
+      federated: true,
+    };
+    let remote_purge = AdminPurgeCommunity::create(pool, &form).await?;
+    let form = ResendPurgeCommunity {
+      admin_purge_community_id: remote_purge.id,
+      confirmed: None,
+    };
+    let res = resend_purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::CantResendPurgeOfRemoteCommunity),
+      res.err().map(|e| e.error_type)
+    );
+
+    // the purge has to exist
+    let form = ResendPurgeCommunity {
+      admin_purge_community_id: -1,
+      confirmed: None,
+    };
+    let res = resend_purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view,
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindPurge),
+      res.err().map(|e| e.error_type)
+    );
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_snapshot() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 1, true).await?;
+
+    let form = PurgeCommunity {
+      community_id: data.community.id,
+      reason: None,
+      dry_run: None,
+      confirmed: None,
+      federate: None,
+      archive_reports: None,
+    };
+    purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view,
+    )
+    .await?;
+
+    // the modlog entry contains the posts which existed before the purge
+    let pool = &mut context.pool();
+    let params = ModlogListParams {
+      community_id: None,
+      mod_person_id: Some(data.person.id),
+      other_person_id: None,
+      post_id: None,
+      comment_id: None,
+      page: None,
+      limit: None,
+      hide_modlog_names: false,
+    };
+    let purges = AdminPurgeCommunityView::list(pool, params).await?;
+    assert_eq!(1, purges.len());
+    let snapshot = purges
+      .first()
+      .and_then(|p| p.admin_purge_community.snapshot.as_ref())
+      .map(ToString::to_string)
+      .unwrap_or_default();
+    for post in &data.posts {
+      assert!(snapshot.contains(post.ap_id.as_str()));
+    }
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_without_federation() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 0, true).await?;
+
+    // activities from other tests are never sent in tests, so they need to be removed first
+    while timeout(
+      Duration::from_millis(100),
+      ActivityChannel::retrieve_activity(),
+    )
+    .await
+    .is_ok()
+    {}
+
+    let form = PurgeCommunity {
+      community_id: data.community.id,
+      reason: Some("legal hold".to_string()),
+      dry_run: None,
+      confirmed: None,
+      federate: Some(false),
+      archive_reports: None,
+    };
+    purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view,
+    )
+    .await?;
+
+    // the community is deleted locally, but no removal is sent to other instances
+    let pool = &mut context.pool();
+    assert!(Community::read(pool, data.community.id).await?.is_none());
+    let activity = timeout(
+      Duration::from_millis(100),
+      ActivityChannel::retrieve_activity(),
+    )
+    .await;
+    assert!(activity.is_err());
+
+    // the purge is still logged, including that it wasn't federated
+    let params = ModlogListParams {
+      community_id: None,
+      mod_person_id: Some(data.person.id),
+      other_person_id: None,
+      post_id: None,
+      comment_id: None,
+      page: None,
+      limit: None,
+      hide_modlog_names: false,
+    };
+    let purge = AdminPurgeCommunityView::list(pool, params)
+      .await?
+      .into_iter()
+      .next()
+      .ok_or(LemmyErrorType::CouldntFindCommunity)?
+      .admin_purge_community;
+    assert!(!purge.federated);
+    assert_eq!(Some("legal hold".to_string()), purge.reason);
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_archive_reports() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 1, true).await?;
+    let pool = &mut context.pool();
+    let post = data.posts.first().ok_or(LemmyErrorType::CouldntFindPost)?;
+    let report_form = PostReportForm {
+      creator_id: data.person.id,
+      post_id: post.id,
+      original_post_name: post.name.clone(),
+      original_post_url: None,
+      original_post_body: None,
+      reason: "spam".to_string(),
+    };
+    PostReport::report(pool, &report_form).await?;
+
+    let form = PurgeCommunity {
+      community_id: data.community.id,
+      reason: None,
+      dry_run: None,
+      confirmed: None,
+      federate: None,
+      archive_reports: Some(true),
+    };
+    purge_community(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await?;
+
+    // the report is deleted with the post, but a copy is kept in the archive
+    let pool = &mut context.pool();
+    assert!(Community::read(pool, data.community.id).await?.is_none());
+    let query = ListArchivedReports {
+      community_actor_id: Some(data.community.actor_id.clone()),
+      ..Default::default()
+    };
+    let archived = list_archived_reports(
+      Query(query),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await?
+    .0
+    .reports;
+    assert_eq!(1, archived.len());
+    let report = archived.first().ok_or(LemmyErrorType::CouldntFindObject)?;
+    assert_eq!(post.ap_id, report.object_ap_id);
+    assert_eq!(data.person.actor_id, report.reporter_actor_id);
+    assert_eq!(post.name, report.original_content);
+    assert_eq!("spam", report.reason);
+
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_content() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let data = init_data(&context, 0, true).await?;
+    let pool = &mut context.pool();
+    let now = Utc::now();
+    let mut posts = vec![];
+    // only images on the local pictrs are purged, not links to other sites
+    let urls = [
+      None,
+      Some("http://lemmy-alpha/pictrs/image/recent.png"),
+      Some("https://example.com/newest.png"),
+    ];
+    for (days, url) in [30, 2, 1].into_iter().zip(urls) {
+      let url = url.map(Url::parse).transpose()?;
+      let post_form = PostInsertForm::builder()
+        .name(format!("post from {days} days ago"))
+        .creator_id(data.person.id)
+        .community_id(data.community.id)
+        .url(url.map(Into::into))
+        .published(Some(now - Days::new(days)))
+        .build();
+      posts.push(Post::create(pool, &post_form).await?);
+    }
+    let [old_post, recent_post, newest_post] = posts.as_slice() else {
+      Err(LemmyErrorType::CouldntFindPost)?
+    };
+    let mut comments = vec![];
+    for (post, days) in [(old_post, 30), (old_post, 2), (recent_post, 1)] {
+      let comment_form = CommentInsertForm::builder()
+        .creator_id(data.person.id)
+        .post_id(post.id)
+        .content(format!("comment from {days} days ago"))
+        .published(Some(now - Days::new(days)))
+        .build();
+      comments.push(Comment::create(pool, &comment_form, None).await?);
+    }
+    let [old_comment, recent_comment, _] = comments.as_slice() else {
+      Err(LemmyErrorType::CouldntFindComment)?
+    };
+
+    // the end can't be before the start
+    let form = PurgeCommunityContent {
+      community_id: data.community.id,
+      start: now,
+      end: now - Days::new(7),
+      reason: None,
+      dry_run: None,
+      confirmed: None,
+    };
+    let res = purge_community_content(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::InvalidTimeRange),
+      res.err().map(|e| e.error_type)
+    );
+
+    // a dry run only counts the content
+    let form = PurgeCommunityContent {
+      community_id: data.community.id,
+      start: now - Days::new(7),
+      end: now,
+      reason: Some("spam wave".to_string()),
+      dry_run: Some(true),
+      confirmed: None,
+    };
+    let res = purge_community_content(
+      Json(form.clone()),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await?;
+    assert_eq!(2, res.posts);
+    assert_eq!(2, res.comments);
+    assert_eq!(1, res.images);
+    assert!(Post::read(&mut context.pool(), recent_post.id)
+      .await?
+      .is_some());
+
+    // more posts than the threshold of one need a confirmation
+    let form = PurgeCommunityContent {
+      dry_run: None,
+      ..form
+    };
+    let res = purge_community_content(
+      Json(form.clone()),
+      context.reset_request_count(),
+      data.local_user_view.clone(),
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::PurgeRequiresConfirmation {
+        posts: 2,
+        comments: 2,
+        images: 1
+      }),
+      res.err().map(|e| e.error_type)
+    );
+
+    let form = PurgeCommunityContent {
+      confirmed: Some(true),
+      ..form
+    };
+    let res = purge_community_content(
+      Json(form),
+      context.reset_request_count(),
+      data.local_user_view,
+    )
+    .await?;
+    assert_eq!(2, res.posts);
+    assert_eq!(2, res.comments);
+
+    // only the content from within the range is purged, the community and older content are kept
+    let pool = &mut context.pool();
+    assert!(Community::read(pool, data.community.id).await?.is_some());
+    assert!(Post::read(pool, old_post.id).await?.is_some());
+    assert!(Post::read(pool, recent_post.id).await?.is_none());
+    assert!(Post::read(pool, newest_post.id).await?.is_none());
+    assert!(Comment::read(pool, old_comment.id).await?.is_some());
+    assert!(Comment::read(pool, recent_comment.id).await?.is_none());
+
+    // the image is purged in the background once the posts are deleted
+    let image_purge = CommunityImagePurge::read(pool, data.community.id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindCommunity)?;
+    assert_eq!(1, image_purge.total);
+
+    // each purged post and comment is logged
+    let params = ModlogListParams {
+      community_id: None,
+      mod_person_id: Some(data.person.id),
+      other_person_id: None,
+      post_id: None,
+      comment_id: None,
+      page: None,
+      limit: None,
+      hide_modlog_names: false,
+    };
+    let purged_posts = AdminPurgePostView::list(pool, params).await?;
+    assert_eq!(2, purged_posts.len());
+    let purged_comments = AdminPurgeCommentView::list(pool, params).await?;
+    assert_eq!(1, purged_comments.len());
+
+    CommunityImagePurge::delete(pool, data.community.id).await?;
+    Instance::delete(pool, data.instance.id).await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,27 @@
+use actix_web::web::{Data, Json};
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::{SetInstanceVoteRateLimit, SetInstanceVoteRateLimitResponse},
+  utils::is_admin,
+};
+use lemmy_db_schema::source::instance::Instance;
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::{LemmyErrorType, LemmyResult};
+
+#[tracing::instrument(skip(context))]
+pub async fn set_instance_vote_rate_limit(
+  data: Json<SetInstanceVoteRateLimit>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<SetInstanceVoteRateLimitResponse>> {
+  // Only let admins change the federation limits
+  is_admin(&local_user_view)?;
+
+  if data.vote_rate_limit.is_some_and(|l| l < 0) {
+    Err(LemmyErrorType::InvalidVoteRateLimit)?
+  }
+  let instance =
+    Instance::set_vote_rate_limit(&mut context.pool(), data.instance_id, data.vote_rate_limit)
+      .await?;
+  Ok(Json(SetInstanceVoteRateLimitResponse { instance }))
+}
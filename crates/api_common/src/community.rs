@@ -2,6 +2,7 @@ use lemmy_db_schema::{
   newtypes::{CommunityId, LanguageId, PersonId},
   source::site::Site,
   CommunityVisibility,
+  FederationMode,
   ListingType,
   SortType,
 };
@@ -56,6 +57,16 @@ pub struct CreateCommunity {
   pub posting_restricted_to_mods: Option<bool>,
   pub discussion_languages: Option<Vec<LanguageId>>,
   pub visibility: Option<CommunityVisibility>,
+  /// Which federated post upvotes to accept, overriding the site setting.
+  pub post_upvotes: Option<FederationMode>,
+  /// Which federated post downvotes to accept, overriding the site setting.
+  pub post_downvotes: Option<FederationMode>,
+  /// Which federated comment upvotes to accept, overriding the site setting.
+  pub comment_upvotes: Option<FederationMode>,
+  /// Which federated comment downvotes to accept, overriding the site setting.
+  pub comment_downvotes: Option<FederationMode>,
+  /// Accept federated votes from bot accounts.
+  pub allow_bot_votes: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -132,6 +143,33 @@ pub struct AddModToCommunityResponse {
   pub moderators: Vec<CommunityModeratorView>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Which federated votes a community accepts. Unlike [FederationMode], this can also remove the
+/// community's override, so that the site setting applies again.
+pub enum CommunityFederationMode {
+  /// Use the site setting.
+  Inherit,
+  All,
+  Local,
+  Followers,
+  Disable,
+}
+
+impl CommunityFederationMode {
+  /// The value for the community, where `None` means that the site setting applies.
+  pub fn to_override(self) -> Option<FederationMode> {
+    match self {
+      CommunityFederationMode::Inherit => None,
+      CommunityFederationMode::All => Some(FederationMode::All),
+      CommunityFederationMode::Local => Some(FederationMode::Local),
+      CommunityFederationMode::Followers => Some(FederationMode::Followers),
+      CommunityFederationMode::Disable => Some(FederationMode::Disable),
+    }
+  }
+}
+
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "full", derive(TS))]
@@ -153,6 +191,16 @@ pub struct EditCommunity {
   pub posting_restricted_to_mods: Option<bool>,
   pub discussion_languages: Option<Vec<LanguageId>>,
   pub visibility: Option<CommunityVisibility>,
+  /// Which federated post upvotes to accept, overriding the site setting.
+  pub post_upvotes: Option<CommunityFederationMode>,
+  /// Which federated post downvotes to accept, overriding the site setting.
+  pub post_downvotes: Option<CommunityFederationMode>,
+  /// Which federated comment upvotes to accept, overriding the site setting.
+  pub comment_upvotes: Option<CommunityFederationMode>,
+  /// Which federated comment downvotes to accept, overriding the site setting.
+  pub comment_downvotes: Option<CommunityFederationMode>,
+  /// Accept federated votes from bot accounts.
+  pub allow_bot_votes: Option<bool>,
 }
 
 #[skip_serializing_none]
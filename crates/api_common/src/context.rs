@@ -8,8 +8,12 @@ use lemmy_utils::{
   rate_limit::RateLimitCell,
   settings::{structs::Settings, SETTINGS},
 };
+use moka::future::Cache;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use std::sync::Arc;
+use std::{
+  sync::Arc,
+  time::{Duration, Instant},
+};
 
 #[derive(Clone)]
 pub struct LemmyContext {
@@ -17,6 +21,17 @@ pub struct LemmyContext {
   client: Arc<ClientWithMiddleware>,
   secret: Arc<Secret>,
   rate_limit_cell: RateLimitCell,
+  resolve_negative_cache: Cache<String, ()>,
+  resolve_backoff: Cache<String, ResolveBackoff>,
+  signed_resolve_config: Option<Arc<FederationConfig<LemmyContext>>>,
+}
+
+/// Consecutive failures to resolve a query over federation.
+#[derive(Clone, Copy, Debug)]
+pub struct ResolveBackoff {
+  pub failures: u32,
+  /// The query isn't fetched again before this time.
+  pub retry_at: Instant,
 }
 
 impl LemmyContext {
@@ -31,8 +46,29 @@ impl LemmyContext {
       client: Arc::new(client),
       secret: Arc::new(secret),
       rate_limit_cell,
+      resolve_negative_cache: Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(Duration::from_secs(
+          SETTINGS.resolve_object.negative_cache_ttl,
+        ))
+        .build(),
+      // Failures are only consecutive if the next one follows before the maximum backoff
+      // elapsed once more
+      resolve_backoff: Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(Duration::from_secs(
+          SETTINGS.resolve_object.max_backoff.saturating_mul(2),
+        ))
+        .build(),
+      signed_resolve_config: None,
     }
   }
+  /// Sets the config for fetches of resolve_object which are retried with a signature, see
+  /// [LemmyContext::signed_resolve_config].
+  pub fn with_signed_resolve_config(mut self, config: FederationConfig<LemmyContext>) -> Self {
+    self.signed_resolve_config = Some(Arc::new(config));
+    self
+  }
   pub fn pool(&self) -> DbPool<'_> {
     DbPool::Pool(&self.pool)
   }
@@ -51,6 +87,20 @@ impl LemmyContext {
   pub fn rate_limit_cell(&self) -> &RateLimitCell {
     &self.rate_limit_cell
   }
+  /// Queries which recently failed to resolve over federation.
+  pub fn resolve_negative_cache(&self) -> &Cache<String, ()> {
+    &self.resolve_negative_cache
+  }
+  /// Queries which failed to resolve over federation repeatedly, and when to try them again.
+  pub fn resolve_backoff(&self) -> &Cache<String, ResolveBackoff> {
+    &self.resolve_backoff
+  }
+  /// Like the federation config of resolve_object, but fetches are signed with the site actor.
+  /// Instances in secure mode only serve objects to signed fetches, so fetches which they deny
+  /// with 401 or 403 are retried with this. Only set if the site signs its fetches.
+  pub fn signed_resolve_config(&self) -> Option<&FederationConfig<LemmyContext>> {
+    self.signed_resolve_config.as_deref()
+  }
 
   /// Initialize a context for use in tests which blocks federation network calls.
   ///
@@ -23,7 +23,7 @@ use lemmy_utils::{
   VERSION,
 };
 use mime::Mime;
-use reqwest::{header::CONTENT_TYPE, Client, ClientBuilder};
+use reqwest::{header::CONTENT_TYPE, Client, ClientBuilder, Proxy};
 use reqwest_middleware::ClientWithMiddleware;
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -40,6 +40,16 @@ pub fn client_builder(settings: &Settings) -> ClientBuilder {
     .connect_timeout(REQWEST_TIMEOUT)
 }
 
+/// Builds the client for fetching remote objects with resolve_object. Requests are sent through
+/// the configured proxy, if any.
+pub fn resolve_client_builder(settings: &Settings) -> LemmyResult<ClientBuilder> {
+  let builder = client_builder(settings);
+  Ok(match &settings.resolve_object.proxy {
+    Some(proxy) => builder.proxy(Proxy::all(proxy.as_str())?),
+    None => builder,
+  })
+}
+
 /// Fetches metadata for the given link and optionally generates thumbnail.
 #[tracing::instrument(skip_all)]
 pub async fn fetch_link_metadata(url: &Url, context: &LemmyContext) -> LemmyResult<LinkMetadata> {
@@ -66,6 +66,15 @@ pub enum SendActivityData {
     community: Community,
     reason: Option<String>,
     removed: bool,
+    /// The community was purged, not only removed.
+    purge: bool,
+  },
+  /// Sends the purge of a community again. As the community doesn't exist anymore, only its
+  /// actor id is known.
+  ResendPurgeCommunity {
+    moderator: Person,
+    community_actor_id: DbUrl,
+    reason: Option<String>,
   },
   AddModToCommunity {
     moderator: Person,
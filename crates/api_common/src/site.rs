@@ -1,18 +1,37 @@
 use crate::federate_retry_sleep_duration;
 use chrono::{DateTime, Utc};
 use lemmy_db_schema::{
-  newtypes::{CommentId, CommunityId, InstanceId, LanguageId, PersonId, PostId},
+  newtypes::{
+    CommentId,
+    CommunityId,
+    DbUrl,
+    InstanceId,
+    LanguageId,
+    LocalUserId,
+    PersonId,
+    PostId,
+  },
   source::{
+    comment_edit::CommentEdit,
+    community_image_purge::CommunityImagePurge,
+    community_purge_progress::CommunityPurgeProgress,
+    federated_vote_rejection::FederatedVoteRejection,
     federation_queue_state::FederationQueueState,
     instance::Instance,
     language::Language,
     local_site_url_blocklist::LocalSiteUrlBlocklist,
+    report_archive::ReportArchive,
+    resolve_object_log::ResolveObjectLog,
+    site::Site,
     tagline::Tagline,
   },
+  FederationMode,
   ListingType,
   ModlogActionType,
   PostListingMode,
   RegistrationMode,
+  ResolveObjectType,
+  ResolveRemoteAccess,
   SearchType,
   SortType,
 };
@@ -85,13 +104,53 @@ pub struct SearchResponse {
   pub users: Vec<PersonView>,
 }
 
+#[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "full", derive(TS))]
 #[cfg_attr(feature = "full", ts(export))]
 /// Does an apub fetch for an object.
 pub struct ResolveObject {
-  /// Can be the full url, or a shortened version like: !fediverse@lemmy.ml
+  /// Can be the full url, a shortened version like: !fediverse@lemmy.ml, or a local id like
+  /// post:123. Received posts and comments can also be found by the SHA-256 hash of their
+  /// content, like hash:<hex>.
   pub q: String,
+  /// Return all objects matching the query in `matches`, instead of only the first one.
+  pub all_matches: Option<bool>,
+  /// Maximum time in milliseconds to wait for a remote fetch. Capped at 30 seconds, negative
+  /// values are rejected.
+  pub timeout_ms: Option<i64>,
+  /// Fetch remote objects again even if they are already known locally. Only works for admins.
+  pub refresh: Option<bool>,
+  /// Return a distinct error for objects which exist but can't be viewed, instead of reporting
+  /// them as not found. Only works for admins.
+  pub verbose: Option<bool>,
+  /// If nothing matches exactly, resolve a partial community or user name like `fedi` to the
+  /// known actor with the most similar name.
+  pub fuzzy: Option<bool>,
+  /// For comments, also return the post and the parent comments, so that the thread can be
+  /// rendered without further requests.
+  pub include_context: Option<bool>,
+  /// Only return an object of this type. If the query resolves to a different type of object, the
+  /// request fails with `couldnt_find_object`.
+  pub expected_type: Option<ResolveObjectType>,
+  /// For persons, also return how they relate to the logged in user.
+  pub include_relationship: Option<bool>,
+  /// For newly fetched remote communities, wait until this many of their recent posts were
+  /// received. The community is returned after a short wait at most, the remaining posts follow
+  /// in the background. Capped at 20.
+  pub prefetch_posts: Option<i64>,
+  /// Also return the ActivityPub json of remote objects in `raw_json`, for debugging. Only works
+  /// for admins.
+  pub raw: Option<bool>,
+  /// For posts, also return other posts with the same url in `crossposts`.
+  pub include_crossposts: Option<bool>,
+  /// Build the returned views as this local user, with their blocks and personal flags. For admins
+  /// to debug what a user gets, other users are rejected. Fetching and permissions stay those of
+  /// the admin, and each use is stored in the resolve object log.
+  pub as_user: Option<LocalUserId>,
+  /// For comments, also return the previous versions which were stored when the comment was
+  /// edited on its instance. Only works for moderators of the community and admins.
+  pub include_edit_history: Option<bool>,
 }
 
 #[skip_serializing_none]
@@ -101,10 +160,150 @@ pub struct ResolveObject {
 // TODO Change this to an enum
 /// The response of an apub object fetch.
 pub struct ResolveObjectResponse {
+  /// The canonical id of the resolved object, whatever its type. Not set if `all_matches` was
+  /// requested, then each match has its own.
+  pub ap_id: Option<DbUrl>,
   pub comment: Option<CommentView>,
   pub post: Option<PostView>,
   pub community: Option<CommunityView>,
   pub person: Option<PersonView>,
+  /// The site of an instance, resolved from its domain or actor id.
+  pub site: Option<Site>,
+  /// All objects matching the query, only set if `all_matches` was requested.
+  pub matches: Option<Vec<ResolveObjectResponse>>,
+  /// The post of a resolved comment, only set if `include_context` was requested.
+  pub comment_post: Option<PostView>,
+  /// The closest parents of a resolved comment, starting with the one furthest up in the thread.
+  /// Only set if `include_context` was requested.
+  pub parent_comments: Option<Vec<CommentView>>,
+  /// How a resolved person relates to the logged in user. Only set if `include_relationship` was
+  /// requested.
+  pub person_relationship: Option<PersonRelationship>,
+  /// Other posts with the same url as a resolved post, at most 10. Only set if
+  /// `include_crossposts` was requested.
+  pub crossposts: Option<Vec<PostView>>,
+  /// Previous versions of a resolved comment, oldest first. Only set if `include_edit_history`
+  /// was requested by a moderator or admin.
+  pub edit_history: Option<Vec<CommentEdit>>,
+  /// Set if the object was deleted on its origin instance.
+  pub tombstone: Option<ResolvedTombstone>,
+  /// A moderation activity, only returned to moderators and admins.
+  pub mod_action: Option<ResolvedModAction>,
+  /// A page of a remote collection like an outbox.
+  pub collection_page: Option<ResolvedCollectionPage>,
+  /// The ActivityPub json of a remote object as served by its origin instance, including fields
+  /// which Lemmy doesn't use. Only set if `raw` was requested by an admin, and not for local
+  /// objects or if the object can't be fetched anymore.
+  pub raw_json: Option<String>,
+  /// Whether this instance federates with the instance of a remote object. Only set for admins.
+  pub federation_status: Option<InstanceFederationStatus>,
+  /// True if the object wasn't known locally and had to be fetched over federation.
+  /// Refetching an object which was already known doesn't count.
+  #[serde(default)]
+  pub resolved_remotely: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// How the federation allowlist and blocklist apply to an instance.
+pub enum InstanceFederationStatus {
+  /// The instance is in the allowlist.
+  Allowed,
+  /// The instance is in the blocklist, or the allowlist is used and the instance isn't in it.
+  Blocked,
+  /// The instance isn't in either list, so it federates like any other.
+  Unknown,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// A remote object which was deleted by its origin instance.
+pub struct ResolvedTombstone {
+  pub ap_id: DbUrl,
+  /// The type of the object, if it was known before it got deleted.
+  pub former_type: Option<ResolveObjectType>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// A page of a remote collection like an outbox, resolved by its id.
+pub struct ResolvedCollectionPage {
+  pub ap_id: DbUrl,
+  /// The collection which the page belongs to.
+  pub part_of: Option<DbUrl>,
+  /// The following page, if any.
+  pub next: Option<DbUrl>,
+  /// Ids of the objects on the page, for activities the id of their object. They aren't fetched,
+  /// but can be resolved individually. At most 50 are returned.
+  pub items: Vec<DbUrl>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// A moderation activity like a ban or a report, resolved by its id.
+pub struct ResolvedModAction {
+  pub ap_id: DbUrl,
+  pub kind: ModActionType,
+  /// The moderator who took the action, or the reporter.
+  pub actor_id: DbUrl,
+  /// The object of the action, like the reported post or the banned person.
+  pub object_id: DbUrl,
+  /// The community or site in which the action was taken.
+  pub target_id: Option<DbUrl>,
+  pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// The ActivityPub type of a moderation activity.
+pub enum ModActionType {
+  /// Adding a moderator or featuring a post
+  Add,
+  /// Removing a moderator or unfeaturing a post
+  Remove,
+  /// Banning a person
+  Block,
+  /// Locking a post
+  Lock,
+  /// Reporting content
+  Flag,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// How a person relates to the logged in user.
+pub struct PersonRelationship {
+  /// True if the user blocked the person.
+  pub is_blocked: bool,
+  /// The number of communities which are followed by both the user and the person.
+  pub shared_communities: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Does an apub fetch for multiple objects at once.
+pub struct ResolveObjects {
+  /// Each query is resolved like [[ResolveObject::q]].
+  pub queries: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// The response of a batch apub object fetch.
+pub struct ResolveObjectsResponse {
+  /// The resolved objects in the same order as the queries, or null if not found.
+  pub objects: Vec<Option<ResolveObjectResponse>>,
 }
 
 #[skip_serializing_none]
@@ -184,6 +383,8 @@ pub struct CreateSite {
   pub rate_limit_comment_per_second: Option<i32>,
   pub rate_limit_search: Option<i32>,
   pub rate_limit_search_per_second: Option<i32>,
+  pub rate_limit_resolve_object: Option<i32>,
+  pub rate_limit_resolve_object_per_second: Option<i32>,
   pub federation_enabled: Option<bool>,
   pub federation_debug: Option<bool>,
   pub captcha_enabled: Option<bool>,
@@ -194,6 +395,22 @@ pub struct CreateSite {
   pub registration_mode: Option<RegistrationMode>,
   pub content_warning: Option<String>,
   pub default_post_listing_mode: Option<PostListingMode>,
+  pub log_rejected_votes: Option<bool>,
+  pub min_account_age_for_full_vote: Option<i32>,
+  pub purge_confirmation_post_threshold: Option<i32>,
+  pub store_purge_snapshots: Option<bool>,
+  pub instance_vote_rate_limit: Option<i32>,
+  pub hide_nsfw_from_resolve: Option<bool>,
+  pub resolve_remote_posts: Option<bool>,
+  pub resolve_remote_comments: Option<bool>,
+  pub resolve_remote_actors: Option<bool>,
+  pub min_community_age_for_remote_votes: Option<i32>,
+  pub read_only: Option<bool>,
+  pub resolve_remote_access: Option<ResolveRemoteAccess>,
+  pub resolve_remote_min_account_age: Option<i32>,
+  pub vote_score_change_limit: Option<i32>,
+  pub accept_remote_community_purges: Option<bool>,
+  pub vote_mode_grace_period: Option<i32>,
 }
 
 #[skip_serializing_none]
@@ -257,6 +474,9 @@ pub struct EditSite {
   /// The number of searches allowed in a given time frame.
   pub rate_limit_search: Option<i32>,
   pub rate_limit_search_per_second: Option<i32>,
+  /// The number of resolve_object calls with remote fetching allowed in a given time frame.
+  pub rate_limit_resolve_object: Option<i32>,
+  pub rate_limit_resolve_object_per_second: Option<i32>,
   /// Whether to enable federation.
   pub federation_enabled: Option<bool>,
   /// Enables federation debugging.
@@ -281,6 +501,48 @@ pub struct EditSite {
   pub content_warning: Option<String>,
   /// Default value for [LocalUser.post_listing_mode]
   pub default_post_listing_mode: Option<PostListingMode>,
+  /// Whether to record federated votes which were rejected by the vote federation mode.
+  pub log_rejected_votes: Option<bool>,
+  /// Federated votes from accounts younger than this many days don't count towards the score.
+  /// 0 disables the check, the maximum is 36500.
+  pub min_account_age_for_full_vote: Option<i32>,
+  /// Purging a community with more posts than this requires passing `confirmed`.
+  pub purge_confirmation_post_threshold: Option<i32>,
+  /// Whether to store the names and ap_ids of posts when purging a community.
+  pub store_purge_snapshots: Option<bool>,
+  /// Maximum number of federated votes accepted from each remote instance per minute. 0 disables
+  /// the limit.
+  pub instance_vote_rate_limit: Option<i32>,
+  /// Hide NSFW posts, comments and communities from resolve_object for users who aren't logged
+  /// in.
+  pub hide_nsfw_from_resolve: Option<bool>,
+  /// Whether resolve_object may fetch posts from remote instances. Otherwise only posts which
+  /// are known already are resolved.
+  pub resolve_remote_posts: Option<bool>,
+  /// Whether resolve_object may fetch comments from remote instances.
+  pub resolve_remote_comments: Option<bool>,
+  /// Whether resolve_object may fetch users, communities and sites from remote instances.
+  pub resolve_remote_actors: Option<bool>,
+  /// Federated votes on posts and comments in communities younger than this many days are
+  /// rejected. 0 disables the check, the maximum is 36500.
+  pub min_community_age_for_remote_votes: Option<i32>,
+  /// Read-only mode for maintenance. Incoming federated votes are acknowledged, but not stored.
+  pub read_only: Option<bool>,
+  /// Which users may fetch remote objects with resolve_object. Others can only resolve objects
+  /// which are known already.
+  pub resolve_remote_access: Option<ResolveRemoteAccess>,
+  /// Users whose account is younger than this many days can't fetch remote objects with
+  /// resolve_object. 0 disables the check, the maximum is 36500.
+  pub resolve_remote_min_account_age: Option<i32>,
+  /// Maximum change of the score of a post or comment by federated votes per minute. Further
+  /// votes are queued and applied gradually. 0 disables the limit.
+  pub vote_score_change_limit: Option<i32>,
+  /// Purge communities of other instances when an admin of their instance purges them. Otherwise
+  /// they are only removed.
+  pub accept_remote_community_purges: Option<bool>,
+  /// Accept federated votes which were sent less than this many minutes before their vote
+  /// federation mode was changed. 0 disables the grace period.
+  pub vote_mode_grace_period: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -398,6 +660,152 @@ pub struct PurgePerson {
 pub struct PurgeCommunity {
   pub community_id: CommunityId,
   pub reason: Option<String>,
+  /// Only return what would be deleted, without deleting anything.
+  pub dry_run: Option<bool>,
+  /// Required to purge communities with more posts than the site's
+  /// `purge_confirmation_post_threshold`.
+  pub confirmed: Option<bool>,
+  /// Set to false to purge the community only on this instance, without sending the removal to
+  /// other instances. Defaults to true.
+  pub federate: Option<bool>,
+  /// Keep the unresolved reports of the community's posts and comments in the report archive,
+  /// instead of deleting them with the community.
+  pub archive_reports: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// The content which is deleted by purging a community.
+pub struct PurgeCommunityResponse {
+  pub success: bool,
+  pub posts: i64,
+  pub comments: i64,
+  pub images: i64,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Purges the posts and comments of a community which were published within a time range, eg
+/// after a spam wave. The community and its other content are kept.
+pub struct PurgeCommunityContent {
+  pub community_id: CommunityId,
+  /// The start of the time range, inclusive.
+  pub start: DateTime<Utc>,
+  /// The end of the time range, inclusive.
+  pub end: DateTime<Utc>,
+  pub reason: Option<String>,
+  /// Only return what would be deleted, without deleting anything.
+  pub dry_run: Option<bool>,
+  /// Required to purge more posts than the site's `purge_confirmation_post_threshold`.
+  pub confirmed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// The number of purged posts, comments and images.
+pub struct PurgeCommunityContentResponse {
+  pub posts: i64,
+  pub comments: i64,
+  pub images: i64,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Purges all communities of a remote instance, eg after defederating from it. Local communities
+/// have to be purged one by one. The purge continues in the background after the response, see
+/// [GetPurgeCommunitiesProgress].
+pub struct PurgeCommunitiesFromInstance {
+  pub instance_id: InstanceId,
+  pub reason: Option<String>,
+  /// Only return what would be deleted, without deleting anything.
+  pub dry_run: Option<bool>,
+  /// Required if the communities have more posts in total than the site's
+  /// `purge_confirmation_post_threshold`.
+  pub confirmed: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// The communities and content which are deleted by purging the communities of an instance.
+pub struct PurgeCommunitiesFromInstanceResponse {
+  pub communities: Vec<CommunityId>,
+  pub posts: i64,
+  pub comments: i64,
+  pub images: i64,
+  /// Used to follow the progress with [GetPurgeCommunitiesProgress]. Not set for a dry run.
+  pub progress_id: Option<i32>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Gets the progress of purging multiple communities.
+pub struct GetPurgeCommunitiesProgress {
+  /// If not given, the progress of the latest purge is returned.
+  pub progress_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+pub struct GetPurgeCommunitiesProgressResponse {
+  pub progress: CommunityPurgeProgress,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Federates the purge of a community again, in case other instances didn't receive it.
+pub struct ResendPurgeCommunity {
+  /// The id of the purge in the modlog.
+  pub admin_purge_community_id: i32,
+  /// Required to send a purge which was done without federation, so that it isn't sent to other
+  /// instances by accident.
+  pub confirmed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Gets the progress of purging the images of a purged community.
+pub struct GetPurgeCommunityStatus {
+  pub community_id: CommunityId,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+pub struct GetPurgeCommunityStatusResponse {
+  pub status: CommunityImagePurge,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Lists the reports which were kept when their community was purged with `archive_reports`.
+pub struct ListArchivedReports {
+  /// Only return the reports of this community.
+  pub community_actor_id: Option<DbUrl>,
+  pub page: Option<i64>,
+  pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+pub struct ListArchivedReportsResponse {
+  pub reports: Vec<ReportArchive>,
 }
 
 #[skip_serializing_none]
@@ -440,6 +848,99 @@ pub struct ListRegistrationApplicationsResponse {
   pub registration_applications: Vec<RegistrationApplicationView>,
 }
 
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Fetches a list of rejected federated votes.
+pub struct ListFederatedVoteRejections {
+  pub page: Option<i64>,
+  pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// The list of rejected federated votes.
+pub struct ListFederatedVoteRejectionsResponse {
+  pub rejections: Vec<FederatedVoteRejection>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Fetches a list of remote objects which users fetched with resolve_object. The list only covers
+/// the last 30 days.
+pub struct ListResolvedObjects {
+  /// Only return objects resolved after this time, in unix epoch seconds.
+  pub since: Option<i64>,
+  pub page: Option<i64>,
+  pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// The list of remote objects fetched with resolve_object.
+pub struct ListResolvedObjectsResponse {
+  pub resolved_objects: Vec<ResolveObjectLog>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Checks if a federated vote would be accepted, without applying it.
+pub struct PreviewFederatedVote {
+  /// The ap_id of the voted post or comment.
+  pub object_id: String,
+  /// The actor id of the user sending the vote. The user doesn't need to be known locally.
+  pub actor_id: String,
+  pub score: i16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Whether a federated vote would be accepted.
+pub struct PreviewFederatedVoteResponse {
+  /// The mode which applies to the vote, from the community or site settings.
+  pub federation_mode: FederationMode,
+  pub accepted: bool,
+  /// Why the vote would be rejected, the first of several reasons.
+  pub rejection: Option<VoteRejectionReason>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Why a federated vote isn't applied.
+pub enum VoteRejectionReason {
+  /// The instance is in read-only mode.
+  ReadOnly,
+  /// The voter is a bot account, and the community doesn't accept bot votes.
+  BotAccount,
+  /// The voter's instance exceeded its vote rate limit.
+  RateLimited,
+  /// The voter is banned from the community.
+  BannedFromCommunity,
+  /// The post, or the post of the comment, is locked.
+  PostLocked,
+  /// The community is younger than `min_community_age_for_remote_votes`.
+  CommunityTooNew,
+  /// The vote federation mode doesn't accept the vote.
+  FederationMode,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Processes a federated vote again, as if it was received by the inbox.
+pub struct ReplayFederatedVote {
+  /// The json of the Like or Dislike activity.
+  pub activity: String,
+}
+
 #[skip_serializing_none]
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "full", derive(TS))]
@@ -483,3 +984,21 @@ pub struct BlockInstance {
 pub struct BlockInstanceResponse {
   pub blocked: bool,
 }
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Sets how many federated votes are accepted from a remote instance per minute.
+pub struct SetInstanceVoteRateLimit {
+  pub instance_id: InstanceId,
+  /// 0 disables the limit, if not set the site's `instance_vote_rate_limit` applies.
+  pub vote_rate_limit: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "full", derive(TS))]
+#[cfg_attr(feature = "full", ts(export))]
+pub struct SetInstanceVoteRateLimitResponse {
+  pub instance: Instance,
+}
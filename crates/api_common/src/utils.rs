@@ -11,6 +11,7 @@ use lemmy_db_schema::{
     comment::{Comment, CommentUpdateForm},
     community::{Community, CommunityModerator, CommunityUpdateForm},
     community_block::CommunityBlock,
+    community_image_purge::{CommunityImagePurge, CommunityImagePurgeForm},
     email_verification::{EmailVerification, EmailVerificationForm},
     images::RemoteImage,
     instance::Instance,
@@ -41,6 +42,7 @@ use lemmy_utils::{
   error::{LemmyError, LemmyErrorExt, LemmyErrorType, LemmyResult},
   rate_limit::{ActionType, BucketConfig},
   settings::structs::{PictrsImageMode, Settings},
+  spawn_try_task,
   utils::{
     markdown::{markdown_check_for_blocked_urls, markdown_rewrite_image_links},
     slurs::{build_slur_regex, remove_slurs},
@@ -51,12 +53,22 @@ use moka::future::Cache;
 use once_cell::sync::Lazy;
 use regex::{escape, Regex, RegexSet};
 use rosetta_i18n::{Language, LanguageId};
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
+use tokio::time::sleep;
 use tracing::warn;
 use url::{ParseError, Url};
 use urlencoding::encode;
 
 pub static AUTH_COOKIE_NAME: &str = "jwt";
+/// How often to try purging an image from pictrs before giving up.
+const PURGE_IMAGE_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled after each failed attempt. Shorter in debug mode, so that
+/// tests don't have to wait for the retries.
+const PURGE_IMAGE_RETRY_DELAY: Duration = if cfg!(debug_assertions) {
+  Duration::from_millis(10)
+} else {
+  Duration::from_secs(1)
+};
 
 #[tracing::instrument(skip_all)]
 pub async fn is_mod_or_admin(
@@ -499,6 +511,7 @@ pub fn local_site_rate_limit_to_rate_limit_config(
     ActionType::Comment => (l.comment, l.comment_per_second),
     ActionType::Search => (l.search, l.search_per_second),
     ActionType::ImportUserSettings => (l.import_user_settings, l.import_user_settings_per_second),
+    ActionType::ResolveObject => (l.resolve_object, l.resolve_object_per_second),
   }
   .map(|_key, (capacity, secs_to_refill)| BucketConfig {
     capacity: u32::try_from(capacity).unwrap_or(0),
@@ -666,24 +679,78 @@ async fn delete_local_user_images(person_id: PersonId, context: &LemmyContext) -
   Ok(())
 }
 
-pub async fn purge_image_posts_for_community(
-  banned_community_id: CommunityId,
+/// Returns the banner, icon and post images of a community which are hosted on pictrs.
+pub async fn community_images(
+  community: &Community,
   context: &LemmyContext,
-) -> LemmyResult<()> {
-  let pool = &mut context.pool();
-  let posts = Post::fetch_pictrs_posts_for_community(pool, banned_community_id).await?;
-  for post in posts {
-    if let Some(url) = post.url {
-      purge_image_from_pictrs(&url, context).await.ok();
+) -> LemmyResult<Vec<Url>> {
+  let posts = Post::fetch_pictrs_posts_for_community(&mut context.pool(), community.id).await?;
+  Ok(
+    [community.banner.clone(), community.icon.clone()]
+      .into_iter()
+      .chain(posts.into_iter().flat_map(|p| [p.url, p.thumbnail_url]))
+      .flatten()
+      .map(Into::into)
+      .collect(),
+  )
+}
+
+/// Returns the urls and thumbnails of the posts which are hosted on the pictrs of this instance.
+/// Links to other sites can't be purged.
+pub fn local_post_images(posts: &[Post], context: &LemmyContext) -> LemmyResult<Vec<Url>> {
+  let hostname = context.settings().get_hostname_without_port()?;
+  Ok(
+    posts
+      .iter()
+      .flat_map(|p| [&p.url, &p.thumbnail_url])
+      .flatten()
+      .filter(|u| u.host_str() == Some(hostname.as_str()) && u.path().starts_with("/pictrs/image/"))
+      .map(|u| u.inner().clone())
+      .collect(),
+  )
+}
+
+/// Purges the given images of a community from pictrs. This can take a long time for large
+/// communities, so it runs in a background task. The progress is stored as [CommunityImagePurge],
+/// whose initial state is returned.
+pub async fn purge_community_images(
+  community_id: CommunityId,
+  images: Vec<Url>,
+  context: &LemmyContext,
+) -> LemmyResult<CommunityImagePurge> {
+  let form = CommunityImagePurgeForm {
+    community_id,
+    total: images.len().try_into()?,
+  };
+  let progress = CommunityImagePurge::create(&mut context.pool(), &form).await?;
+
+  let context = context.clone();
+  spawn_try_task(async move {
+    for image in images {
+      let purged = purge_image_with_retry(&image, &context).await;
+      CommunityImagePurge::add_result(&mut context.pool(), community_id, purged).await?;
     }
-    if let Some(thumbnail_url) = post.thumbnail_url {
-      purge_image_from_pictrs(&thumbnail_url, context).await.ok();
+    CommunityImagePurge::finish(&mut context.pool(), community_id).await?;
+    Ok(())
+  });
+  Ok(progress)
+}
+
+/// Tries to purge an image from pictrs, retrying with exponential backoff. Returns false if all
+/// attempts failed.
+async fn purge_image_with_retry(image_url: &Url, context: &LemmyContext) -> bool {
+  let mut delay = PURGE_IMAGE_RETRY_DELAY;
+  for attempt in 1..=PURGE_IMAGE_ATTEMPTS {
+    match purge_image_from_pictrs(image_url, context).await {
+      Ok(()) => return true,
+      Err(e) => warn!("Failed to purge image {image_url} (attempt {attempt}): {e}"),
+    }
+    if attempt < PURGE_IMAGE_ATTEMPTS {
+      sleep(delay).await;
+      delay *= 2;
     }
   }
-
-  Post::remove_pictrs_post_images_and_thumbnails_for_community(pool, banned_community_id).await?;
-
-  Ok(())
+  false
 }
 
 pub async fn remove_user_data(
@@ -920,6 +987,16 @@ fn limit_expire_time(expires: DateTime<Utc>) -> LemmyResult<Option<DateTime<Utc>
   }
 }
 
+/// Returns true if `published` is less than `min_age_days` days ago. A minimum age reaching back
+/// further than can be represented makes everything too young. Negative values disable the check.
+pub fn is_younger_than(published: DateTime<Utc>, min_age_days: i32) -> bool {
+  let min_age = Days::new(min_age_days.try_into().unwrap_or_default());
+  match Utc::now().checked_sub_days(min_age) {
+    Some(oldest) => published > oldest,
+    None => true,
+  }
+}
+
 pub async fn process_markdown(
   text: &str,
   slur_regex: &Option<Regex>,
@@ -1040,6 +1117,7 @@ fn build_proxied_image_url(
 mod tests {
 
   use super::*;
+  use lemmy_db_schema::source::community::CommunityInsertForm;
   use pretty_assertions::assert_eq;
   use serial_test::serial;
 
@@ -1081,6 +1159,19 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_is_younger_than() {
+    let week_ago = Utc::now() - Days::new(7);
+    assert!(!is_younger_than(week_ago, 0));
+    assert!(!is_younger_than(week_ago, -30));
+    assert!(!is_younger_than(week_ago, 6));
+    assert!(is_younger_than(week_ago, 8));
+
+    // too large to subtract from the current time
+    assert!(is_younger_than(week_ago, i32::MAX));
+    assert!(is_younger_than(DateTime::<Utc>::MIN_UTC, i32::MAX));
+  }
+
   #[tokio::test]
   #[serial]
   async fn test_proxy_image_link() {
@@ -1138,4 +1229,50 @@ mod tests {
       Ok(Some(Some(url))) if url == Url::parse(example_url).unwrap().into()
     ));
   }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_purge_community_images_in_background() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let instance =
+      Instance::read_or_create(&mut context.pool(), "my_domain.tld".to_string()).await?;
+    let icon = Url::parse("http://lemmy-alpha/pictrs/image/icon.png")?;
+    let banner = Url::parse("http://lemmy-alpha/pictrs/image/banner.png")?;
+    let form = CommunityInsertForm::builder()
+      .name("purge_images".to_string())
+      .title("nada".to_owned())
+      .public_key("pubkey".to_string())
+      .instance_id(instance.id)
+      .icon(Some(icon.into()))
+      .banner(Some(banner.into()))
+      .build();
+    let community = Community::create(&mut context.pool(), &form).await?;
+
+    // pictrs isn't reachable, so the images can only be purged after retrying several times. The
+    // function returns anyway before that is done.
+    let images = community_images(&community, &context).await?;
+    let progress = purge_community_images(community.id, images, &context).await?;
+    assert_eq!(2, progress.total);
+    assert_eq!((0, 0), (progress.purged, progress.failed));
+    assert!(progress.finished.is_none());
+
+    // once all attempts failed, the images are counted as failed and the purge is finished
+    let mut status = progress;
+    for _ in 0..100 {
+      if status.finished.is_some() {
+        break;
+      }
+      sleep(Duration::from_millis(100)).await;
+      status = CommunityImagePurge::read(&mut context.pool(), community.id)
+        .await?
+        .ok_or(LemmyErrorType::CouldntFindCommunity)?;
+    }
+    assert_eq!((2, 0, 2), (status.total, status.purged, status.failed));
+    assert!(status.finished.is_some());
+
+    CommunityImagePurge::delete(&mut context.pool(), community.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Instance::delete(&mut context.pool(), instance.id).await?;
+    Ok(())
+  }
 }
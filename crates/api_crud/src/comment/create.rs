@@ -152,6 +152,7 @@ pub async fn create_comment(
     post_id: post.id,
     person_id: local_user_view.person.id,
     score: 1,
+    activity_ap_id: None,
   };
 
   CommentLike::like(&mut context.pool(), &like_form)
@@ -98,6 +98,11 @@ pub async fn create_community(
     .posting_restricted_to_mods(data.posting_restricted_to_mods)
     .instance_id(site_view.site.instance_id)
     .visibility(data.visibility)
+    .post_upvotes(data.post_upvotes)
+    .post_downvotes(data.post_downvotes)
+    .comment_upvotes(data.comment_upvotes)
+    .comment_downvotes(data.comment_downvotes)
+    .allow_bot_votes(data.allow_bot_votes)
     .build();
 
   let inserted_community = Community::create(&mut context.pool(), &community_form)
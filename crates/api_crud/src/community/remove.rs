@@ -63,6 +63,7 @@ pub async fn remove_community(
       community,
       reason: data.reason.clone(),
       removed: data.removed,
+      purge: false,
     },
     &context,
   )
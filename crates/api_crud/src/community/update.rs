@@ -2,7 +2,7 @@ use activitypub_federation::config::Data;
 use actix_web::web::Json;
 use lemmy_api_common::{
   build_response::build_community_response,
-  community::{CommunityResponse, EditCommunity},
+  community::{CommunityFederationMode, CommunityResponse, EditCommunity},
   context::LemmyContext,
   request::replace_image,
   send_activity::{ActivityChannel, SendActivityData},
@@ -82,6 +82,17 @@ pub async fn update_community(
     nsfw: data.nsfw,
     posting_restricted_to_mods: data.posting_restricted_to_mods,
     visibility: data.visibility,
+    post_upvotes: data.post_upvotes.map(CommunityFederationMode::to_override),
+    post_downvotes: data
+      .post_downvotes
+      .map(CommunityFederationMode::to_override),
+    comment_upvotes: data
+      .comment_upvotes
+      .map(CommunityFederationMode::to_override),
+    comment_downvotes: data
+      .comment_downvotes
+      .map(CommunityFederationMode::to_override),
+    allow_bot_votes: data.allow_bot_votes,
     updated: Some(Some(naive_now())),
     ..Default::default()
   };
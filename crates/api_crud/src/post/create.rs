@@ -170,6 +170,7 @@ pub async fn create_post(
     post_id,
     person_id,
     score: 1,
+    activity_ap_id: None,
   };
 
   PostLike::like(&mut context.pool(), &like_form)
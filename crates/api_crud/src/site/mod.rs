@@ -40,12 +40,28 @@ pub fn application_question_check(
   }
 }
 
+/// The largest allowed minimum age for accounts or communities, in days.
+const MAX_MIN_AGE_DAYS: i32 = 36500;
+
+/// Checks whether a minimum age site setting is within 0 and [MAX_MIN_AGE_DAYS].
+pub fn site_min_age_check(min_age_days: Option<i32>) -> LemmyResult<()> {
+  match min_age_days {
+    Some(days) if !(0..=MAX_MIN_AGE_DAYS).contains(&days) => Err(LemmyErrorType::InvalidMinAge)?,
+    _ => Ok(()),
+  }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 #[allow(clippy::indexing_slicing)]
 mod tests {
 
-  use crate::site::{application_question_check, site_default_post_listing_type_check};
+  use crate::site::{
+    application_question_check,
+    site_default_post_listing_type_check,
+    site_min_age_check,
+    MAX_MIN_AGE_DAYS,
+  };
   use lemmy_db_schema::{ListingType, RegistrationMode};
 
   #[test]
@@ -56,6 +72,16 @@ mod tests {
     assert!(site_default_post_listing_type_check(&Some(ListingType::Subscribed)).is_err());
   }
 
+  #[test]
+  fn test_site_min_age_check() {
+    assert!(site_min_age_check(None).is_ok());
+    assert!(site_min_age_check(Some(0)).is_ok());
+    assert!(site_min_age_check(Some(MAX_MIN_AGE_DAYS)).is_ok());
+    assert!(site_min_age_check(Some(-1)).is_err());
+    assert!(site_min_age_check(Some(MAX_MIN_AGE_DAYS + 1)).is_err());
+    assert!(site_min_age_check(Some(i32::MAX)).is_err());
+  }
+
   #[test]
   fn test_application_question_check() {
     assert!(
@@ -1,4 +1,8 @@
-use crate::site::{application_question_check, site_default_post_listing_type_check};
+use crate::site::{
+  application_question_check,
+  site_default_post_listing_type_check,
+  site_min_age_check,
+};
 use activitypub_federation::config::Data;
 use actix_web::web::Json;
 use lemmy_api_common::{
@@ -20,6 +24,7 @@ use lemmy_db_schema::{
     federation_allowlist::FederationAllowList,
     federation_blocklist::FederationBlockList,
     local_site::{LocalSite, LocalSiteUpdateForm},
+    local_site_federation::{LocalSiteFederation, LocalSiteFederationUpdateForm},
     local_site_rate_limit::{LocalSiteRateLimit, LocalSiteRateLimitUpdateForm},
     local_site_url_blocklist::LocalSiteUrlBlocklist,
     local_user::LocalUser,
@@ -115,6 +120,8 @@ pub async fn update_site(
     captcha_difficulty: data.captcha_difficulty.clone(),
     reports_email_admins: data.reports_email_admins,
     default_post_listing_mode: data.default_post_listing_mode,
+    purge_confirmation_post_threshold: data.purge_confirmation_post_threshold,
+    store_purge_snapshots: data.store_purge_snapshots,
     ..Default::default()
   };
 
@@ -135,6 +142,8 @@ pub async fn update_site(
     comment_per_second: data.rate_limit_comment_per_second,
     search: data.rate_limit_search,
     search_per_second: data.rate_limit_search_per_second,
+    resolve_object: data.rate_limit_resolve_object,
+    resolve_object_per_second: data.rate_limit_resolve_object_per_second,
     ..Default::default()
   };
 
@@ -142,6 +151,28 @@ pub async fn update_site(
     .await
     .ok();
 
+  let local_site_federation_form = LocalSiteFederationUpdateForm {
+    log_rejected_votes: data.log_rejected_votes,
+    min_account_age_for_full_vote: data.min_account_age_for_full_vote,
+    instance_vote_rate_limit: data.instance_vote_rate_limit,
+    hide_nsfw_from_resolve: data.hide_nsfw_from_resolve,
+    resolve_remote_posts: data.resolve_remote_posts,
+    resolve_remote_comments: data.resolve_remote_comments,
+    resolve_remote_actors: data.resolve_remote_actors,
+    min_community_age_for_remote_votes: data.min_community_age_for_remote_votes,
+    read_only: data.read_only,
+    resolve_remote_access: data.resolve_remote_access,
+    resolve_remote_min_account_age: data.resolve_remote_min_account_age,
+    vote_score_change_limit: data.vote_score_change_limit,
+    accept_remote_community_purges: data.accept_remote_community_purges,
+    vote_mode_grace_period: data.vote_mode_grace_period,
+    ..Default::default()
+  };
+
+  LocalSiteFederation::update(&mut context.pool(), &local_site_federation_form)
+    .await
+    .ok();
+
   // Replace the blocked and allowed instances
   let allowed = data.allowed_instances.clone();
   FederationAllowList::replace(&mut context.pool(), allowed).await?;
@@ -231,6 +262,10 @@ fn validate_update_payload(local_site: &LocalSite, edit_site: &EditSite) -> Lemm
   // Ensure that the sidebar has fewer than the max num characters...
   is_valid_body_field(&edit_site.sidebar, false)?;
 
+  site_min_age_check(edit_site.min_account_age_for_full_vote)?;
+  site_min_age_check(edit_site.min_community_age_for_remote_votes)?;
+  site_min_age_check(edit_site.resolve_remote_min_account_age)?;
+
   application_question_check(
     &local_site.application_question,
     &edit_site.application_question,
@@ -591,6 +626,8 @@ mod tests {
       rate_limit_comment_per_second: None,
       rate_limit_search: None,
       rate_limit_search_per_second: None,
+      rate_limit_resolve_object: None,
+      rate_limit_resolve_object_per_second: None,
       federation_enabled: site_is_federated,
       federation_debug: None,
       captcha_enabled: None,
@@ -603,6 +640,22 @@ mod tests {
       reports_email_admins: None,
       content_warning: None,
       default_post_listing_mode: None,
+      log_rejected_votes: None,
+      min_account_age_for_full_vote: None,
+      purge_confirmation_post_threshold: None,
+      store_purge_snapshots: None,
+      instance_vote_rate_limit: None,
+      hide_nsfw_from_resolve: None,
+      resolve_remote_posts: None,
+      resolve_remote_comments: None,
+      resolve_remote_actors: None,
+      min_community_age_for_remote_votes: None,
+      read_only: None,
+      resolve_remote_access: None,
+      resolve_remote_min_account_age: None,
+      vote_score_change_limit: None,
+      accept_remote_community_purges: None,
+      vote_mode_grace_period: None,
     }
   }
 }
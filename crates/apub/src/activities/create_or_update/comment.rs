@@ -163,6 +163,7 @@ impl ActivityHandler for CreateOrUpdateNote {
       post_id: comment.post_id,
       person_id: comment.creator_id,
       score: 1,
+      activity_ap_id: None,
     };
     CommentLike::like(&mut context.pool(), &like_form).await?;
 
@@ -126,6 +126,7 @@ impl ActivityHandler for CreateOrUpdatePage {
       post_id: post.id,
       person_id: post.creator_id,
       score: 1,
+      activity_ap_id: None,
     };
     PostLike::like(&mut context.pool(), &like_form).await?;
 
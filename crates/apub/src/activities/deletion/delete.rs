@@ -8,12 +8,17 @@ use crate::{
   protocol::{activities::deletion::delete::Delete, IdOrNestedObject},
 };
 use activitypub_federation::{config::Data, kinds::activity::DeleteType, traits::ActivityHandler};
-use lemmy_api_common::context::LemmyContext;
+use lemmy_api_common::{
+  context::LemmyContext,
+  utils::{local_post_images, purge_community_images},
+};
 use lemmy_db_schema::{
   source::{
     comment::{Comment, CommentUpdateForm},
     comment_report::CommentReport,
-    community::{Community, CommunityUpdateForm},
+    community::{Community, CommunityPurgeOptions, CommunityUpdateForm},
+    local_site::LocalSite,
+    local_site_federation::LocalSiteFederation,
     moderator::{
       ModRemoveComment,
       ModRemoveCommentForm,
@@ -64,6 +69,7 @@ impl ActivityHandler for Delete {
         &self.actor.dereference(context).await?,
         self.object.id(),
         reason,
+        self.purge.unwrap_or(false),
         context,
       )
       .await
@@ -88,22 +94,35 @@ impl Delete {
     community: Option<&Community>,
     summary: Option<String>,
     context: &Data<LemmyContext>,
+  ) -> LemmyResult<Delete> {
+    let community_id = community.map(|c| c.actor_id.clone().into());
+    Self::new_for_id(actor, object.id(), to, community_id, summary, context)
+  }
+
+  /// Like [Delete::new], for objects which don't exist in the database anymore.
+  pub(in crate::activities::deletion) fn new_for_id(
+    actor: &ApubPerson,
+    object_id: Url,
+    to: Url,
+    community_id: Option<Url>,
+    summary: Option<String>,
+    context: &Data<LemmyContext>,
   ) -> LemmyResult<Delete> {
     let id = generate_activity_id(
       DeleteType::Delete,
       &context.settings().get_protocol_and_hostname(),
     )?;
-    let cc: Option<Url> = community.map(|c| c.actor_id.clone().into());
     Ok(Delete {
       actor: actor.actor_id.clone().into(),
       to: vec![to],
-      object: IdOrNestedObject::Id(object.id()),
-      cc: cc.into_iter().collect(),
+      object: IdOrNestedObject::Id(object_id),
+      cc: community_id.clone().into_iter().collect(),
       kind: DeleteType::Delete,
       summary,
       id,
-      audience: community.map(|c| c.actor_id.clone().into()),
+      audience: community_id.map(Into::into),
       remove_data: None,
+      purge: None,
     })
   }
 }
@@ -113,6 +132,7 @@ pub(in crate::activities) async fn receive_remove_action(
   actor: &ApubPerson,
   object: &Url,
   reason: Option<String>,
+  purge: bool,
   context: &Data<LemmyContext>,
 ) -> LemmyResult<()> {
   match DeletableObjects::read_from_db(object, context).await? {
@@ -120,6 +140,31 @@ pub(in crate::activities) async fn receive_remove_action(
       if community.local {
         Err(LemmyErrorType::OnlyLocalAdminCanRemoveCommunity)?
       }
+      // Only users of the community's instance can purge it, and only if this instance accepts
+      // remote purges. Otherwise it is only removed.
+      if purge
+        && is_from_community_instance(actor, &community)
+        && LocalSiteFederation::read(&mut context.pool())
+          .await?
+          .accept_remote_community_purges
+      {
+        let local_site = LocalSite::read(&mut context.pool()).await?;
+        // Only post images on the pictrs of this instance are purged. Other urls, like the banner
+        // and icon of the remote community, would make this instance purge the image with the
+        // same alias from its own pictrs.
+        let posts =
+          Post::fetch_pictrs_posts_for_community(&mut context.pool(), community.id).await?;
+        let images = local_post_images(&posts, context)?;
+        let options = CommunityPurgeOptions {
+          store_snapshot: local_site.store_purge_snapshots,
+          federated: true,
+          ..Default::default()
+        };
+        Community::purge(&mut context.pool(), community.id, actor.id, reason, options).await?;
+        // The images are only purged once the community is deleted, see purge_community
+        purge_community_images(community.id, images, context).await?;
+        return Ok(());
+      }
       let form = ModRemoveCommunityForm {
         mod_person_id: actor.id,
         community_id: community.id,
@@ -180,3 +225,95 @@ pub(in crate::activities) async fn receive_remove_action(
   }
   Ok(())
 }
+
+/// Checks if the actor is a user of the instance which hosts the community. Admins aren't
+/// federated, so this can't check that the actor is one of its admins, and like in
+/// [verify_mod_action] an action by a user of the community's own instance is trusted. Moderators
+/// from other instances could otherwise make every instance delete its copy.
+///
+/// [verify_mod_action]: crate::activities::verify_mod_action
+fn is_from_community_instance(actor: &ApubPerson, community: &Community) -> bool {
+  actor.instance_id == community.instance_id
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::api::test::TestInstance;
+  use lemmy_db_schema::source::{
+    community_image_purge::CommunityImagePurge,
+    local_site_federation::LocalSiteFederationUpdateForm,
+  };
+  use serial_test::serial;
+
+  #[tokio::test]
+  #[serial]
+  async fn test_receive_purge_community() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (local, remote) = TestInstance::local_and_remote("receive-purge.example", &context).await?;
+    let other = TestInstance::builder("receive-purge-other.example")
+      .remote()
+      .create(&context)
+      .await?;
+    let admin = remote
+      .create_person("receive_purge_admin", &context)
+      .await?;
+    let moderator = other.create_person("receive_purge_mod", &context).await?;
+    let community = remote.create_community("receive_purge", &context).await?;
+    let community_id = community.actor_id.inner();
+
+    // a purge by a user of another instance only removes the community
+    let actor = ApubPerson(moderator);
+    receive_remove_action(&actor, community_id, None, true, &context).await?;
+    let removed = Community::read(&mut context.pool(), community.id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindCommunity)?;
+    assert!(removed.removed);
+
+    // a purge from the community's instance is only applied if remote purges are accepted
+    let actor = ApubPerson(admin.clone());
+    receive_remove_action(&actor, community_id, None, true, &context).await?;
+    assert!(Community::read(&mut context.pool(), community.id)
+      .await?
+      .is_some());
+
+    // of the images in the community, only those on the pictrs of this instance are purged
+    let hostname = context.settings().get_hostname_without_port()?;
+    for (name, url) in [
+      (
+        "receive_purge_local",
+        format!("https://{hostname}/pictrs/image/local.png"),
+      ),
+      (
+        "receive_purge_remote",
+        remote.url("pictrs/image/remote.png")?.to_string(),
+      ),
+    ] {
+      let post = remote
+        .create_post(name, &admin, &community, &context)
+        .await?;
+      let form = PostUpdateForm {
+        url: Some(Some(Url::parse(&url)?.into())),
+        ..Default::default()
+      };
+      Post::update(&mut context.pool(), post.id, &form).await?;
+    }
+    let form = LocalSiteFederationUpdateForm {
+      accept_remote_community_purges: Some(true),
+      ..Default::default()
+    };
+    LocalSiteFederation::update(&mut context.pool(), &form).await?;
+    receive_remove_action(&actor, community_id, None, true, &context).await?;
+    assert!(Community::read(&mut context.pool(), community.id)
+      .await?
+      .is_none());
+    let progress = CommunityImagePurge::read(&mut context.pool(), community.id).await?;
+    assert_eq!(Some(1), progress.map(|p| p.total));
+
+    CommunityImagePurge::delete(&mut context.pool(), community.id).await?;
+    other.cleanup(&context).await?;
+    remote.cleanup(&context).await?;
+    local.cleanup(&context).await?;
+    Ok(())
+  }
+}
@@ -29,9 +29,11 @@ use activitypub_federation::{
 };
 use lemmy_api_common::{context::LemmyContext, utils::purge_user_account};
 use lemmy_db_schema::{
+  newtypes::DbUrl,
   source::{
     activity::ActivitySendTargets,
     comment::{Comment, CommentUpdateForm},
+    comment_edit::CommentEdit,
     community::{Community, CommunityUpdateForm},
     person::Person,
     post::{Post, PostUpdateForm},
@@ -102,6 +104,53 @@ pub(crate) async fn send_apub_delete_private_message(
   Ok(())
 }
 
+/// Sends a community removal which additionally tells receiving instances to purge their copy of
+/// the community. Instances which don't understand this treat it as a normal removal.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn send_apub_purge_community(
+  actor: Person,
+  community: Community,
+  reason: Option<String>,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<()> {
+  let actor = ApubPerson::from(actor);
+  let object = DeletableObjects::Community(community.clone().into());
+  let mut delete = Delete::new(&actor, object, public(), Some(&community), reason, context)?;
+  delete.purge = Some(true);
+  send_activity_in_community(
+    AnnouncableActivities::Delete(delete),
+    &actor,
+    &community.into(),
+    ActivitySendTargets::empty(),
+    true,
+    context,
+  )
+  .await
+}
+
+/// Sends the purge of a community again, in case it wasn't federated the first time. As the
+/// community and its followers are gone, the activity is sent to all known instances.
+pub(crate) async fn send_apub_resend_purge_community(
+  actor: Person,
+  community_actor_id: DbUrl,
+  reason: Option<String>,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<()> {
+  let actor = ApubPerson::from(actor);
+  let community_id: Url = community_actor_id.into();
+  let mut delete = Delete::new_for_id(
+    &actor,
+    community_id.clone(),
+    public(),
+    Some(community_id),
+    reason,
+    context,
+  )?;
+  delete.purge = Some(true);
+  let inboxes = ActivitySendTargets::to_all_instances();
+  send_lemmy_activity(context, delete, &actor, inboxes, true).await
+}
+
 pub async fn send_apub_delete_user(
   person: Person,
   remove_data: bool,
@@ -282,6 +331,10 @@ async fn receive_delete_action(
       }
     }
     DeletableObjects::Comment(comment) => {
+      // The author deleted the comment, so its previous versions shouldn't be kept either
+      if deleted {
+        CommentEdit::delete_for_comment(&mut context.pool(), comment.id).await?;
+      }
       if deleted != comment.deleted {
         Comment::update(
           &mut context.pool(),
@@ -308,3 +361,83 @@ async fn receive_delete_action(
   }
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{activities::match_outgoing_activities, api::test::TestInstance};
+  use diesel::{ExpressionMethods, QueryDsl};
+  use diesel_async::RunQueryDsl;
+  use lemmy_api_common::send_activity::SendActivityData;
+  use lemmy_db_schema::{
+    schema::sent_activity,
+    source::{activity::SentActivity, community::CommunityPurgeOptions},
+    utils::get_conn,
+  };
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+  use serial_test::serial;
+
+  async fn latest_sent_activity(context: &LemmyContext) -> LemmyResult<Option<SentActivity>> {
+    let pool = &mut context.pool();
+    let conn = &mut get_conn(pool).await?;
+    Ok(
+      sent_activity::table
+        .order_by(sent_activity::id.desc())
+        .first(conn)
+        .await
+        .ok(),
+    )
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resend_purge_community() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let local = TestInstance::builder("example.com")
+      .create(&context)
+      .await?;
+    let admin = local
+      .create_user("resend_purge_admin", true, &context)
+      .await?;
+    let community = local.create_community("resend_purge", &context).await?;
+
+    // the purge itself is never federated, as if sending it failed
+    Community::purge(
+      &mut context.pool(),
+      community.id,
+      admin.person.id,
+      None,
+      CommunityPurgeOptions {
+        federated: true,
+        ..Default::default()
+      },
+    )
+    .await?;
+    let before = latest_sent_activity(&context).await?.map(|a| a.id);
+
+    // sending it again creates a purge activity for all instances
+    let data = SendActivityData::ResendPurgeCommunity {
+      moderator: admin.person.clone(),
+      community_actor_id: community.actor_id.clone(),
+      reason: Some("spam".to_string()),
+    };
+    match_outgoing_activities(data, &context).await?;
+    let sent = latest_sent_activity(&context)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindActivity)?;
+    assert_ne!(before, Some(sent.id));
+    assert!(sent.send_all_instances);
+    assert_eq!(Some(admin.person.actor_id), sent.actor_apub_id);
+    assert_eq!(Some(&json!("Delete")), sent.data.get("type"));
+    assert_eq!(
+      Some(&json!(community.actor_id.as_str())),
+      sent.data.get("object")
+    );
+    assert_eq!(Some(&json!(true)), sent.data.get("purge"));
+    assert_eq!(Some(&json!("spam")), sent.data.get("summary"));
+
+    local.cleanup(&context).await?;
+    Ok(())
+  }
+}
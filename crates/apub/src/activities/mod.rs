@@ -12,6 +12,8 @@ use crate::{
       send_apub_delete_in_community,
       send_apub_delete_private_message,
       send_apub_delete_user,
+      send_apub_purge_community,
+      send_apub_resend_purge_community,
       DeletableObjects,
     },
     voting::send_like_activity,
@@ -324,17 +326,26 @@ pub async fn match_outgoing_activities(
         community,
         reason,
         removed,
+        purge,
       } => {
-        let deletable = DeletableObjects::Community(community.clone().into());
-        send_apub_delete_in_community(
-          moderator,
-          community,
-          deletable,
-          reason.clone().or_else(|| Some(String::new())),
-          removed,
-          &context,
-        )
-        .await
+        // Empty reason marks this as a removal, see receive of Delete
+        let reason = reason.or_else(|| Some(String::new()));
+        if purge && removed {
+          send_apub_purge_community(moderator, community, reason, &context).await
+        } else {
+          let deletable = DeletableObjects::Community(community.clone().into());
+          send_apub_delete_in_community(moderator, community, deletable, reason, removed, &context)
+            .await
+        }
+      }
+      ResendPurgeCommunity {
+        moderator,
+        community_actor_id,
+        reason,
+      } => {
+        // Empty reason marks this as a removal, see receive of Delete
+        let reason = reason.or_else(|| Some(String::new()));
+        send_apub_resend_purge_community(moderator, community_actor_id, reason, &context).await
       }
       AddModToCommunity {
         moderator,
@@ -388,3 +399,22 @@ pub async fn match_outgoing_activities(
   fed_task.await?;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use activitypub_federation::kinds::activity::LikeType;
+  use lemmy_utils::error::LemmyResult;
+  use std::collections::HashSet;
+
+  #[test]
+  fn test_generate_activity_id_unique() -> LemmyResult<()> {
+    // ids are random, so ids generated in the same instant must not collide
+    let mut ids = HashSet::new();
+    for _ in 0..10_000 {
+      let id = generate_activity_id(LikeType::Like, "https://example.com")?;
+      assert!(ids.insert(id));
+    }
+    Ok(())
+  }
+}
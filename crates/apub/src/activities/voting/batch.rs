@@ -0,0 +1,566 @@
+use activitypub_federation::config::Data;
+use anyhow::anyhow;
+use diesel::result::{DatabaseErrorKind, Error};
+use lemmy_api_common::context::LemmyContext;
+use lemmy_db_schema::{
+  newtypes::{CommentId, DbUrl, PersonId, PostId},
+  source::{
+    comment::{CommentLike, CommentLikeForm},
+    post::{PostLike, PostLikeForm},
+  },
+  utils::DbPool,
+};
+use lemmy_utils::{error::LemmyResult, spawn_try_task};
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, slice, time::Duration};
+use tokio::{sync::Mutex, time::sleep};
+use tracing::warn;
+
+/// Score of a vote and the id of the activity which created it, `None` if the vote was undone.
+pub(crate) type PendingVote = Option<(i16, DbUrl)>;
+
+/// The person and post of a post vote.
+type PostVoteKey = (PersonId, PostId);
+
+/// The person and comment of a comment vote.
+type CommentVoteKey = (PersonId, CommentId);
+
+/// Maximum number of votes which are written in one transaction.
+const MAX_TRANSACTION_VOTES: usize = 1000;
+
+/// Number of failed flushes after which a vote is dropped, so that the batch doesn't grow forever
+/// while the database is unreachable.
+const MAX_FLUSH_ATTEMPTS: u8 = 5;
+
+/// A vote which is waiting in the batch.
+#[derive(Clone, Debug)]
+struct BatchedVote {
+  vote: PendingVote,
+  /// Number of flushes which failed to write the vote.
+  failed_flushes: u8,
+}
+
+impl From<PendingVote> for BatchedVote {
+  fn from(vote: PendingVote) -> Self {
+    BatchedVote {
+      vote,
+      failed_flushes: 0,
+    }
+  }
+}
+
+/// Votes which are waiting to be written. Only the latest vote of each person on each object is
+/// kept.
+#[derive(Default)]
+struct PendingVotes {
+  posts: HashMap<PostVoteKey, BatchedVote>,
+  comments: HashMap<CommentVoteKey, (PostId, BatchedVote)>,
+}
+
+impl PendingVotes {
+  fn is_empty(&self) -> bool {
+    self.posts.is_empty() && self.comments.is_empty()
+  }
+
+  fn len(&self) -> usize {
+    self.posts.len() + self.comments.len()
+  }
+
+  /// Puts back votes which couldn't be written. Votes which were added in the meantime are newer,
+  /// so they are kept. Votes which already failed [MAX_FLUSH_ATTEMPTS] times are dropped.
+  fn restore(&mut self, older: PendingVotes) {
+    let mut dropped = 0;
+    for (key, mut vote) in older.posts {
+      vote.failed_flushes += 1;
+      if vote.failed_flushes < MAX_FLUSH_ATTEMPTS {
+        self.posts.entry(key).or_insert(vote);
+      } else {
+        dropped += 1;
+      }
+    }
+    for (key, (post_id, mut vote)) in older.comments {
+      vote.failed_flushes += 1;
+      if vote.failed_flushes < MAX_FLUSH_ATTEMPTS {
+        self.comments.entry(key).or_insert((post_id, vote));
+      } else {
+        dropped += 1;
+      }
+    }
+    if dropped > 0 {
+      warn!("Dropped {dropped} federated votes which couldn't be written");
+    }
+  }
+}
+
+/// Collects incoming federated votes, so that they can be written to the database in a single
+/// transaction instead of one per vote.
+#[derive(Default)]
+pub(crate) struct VoteBatch {
+  pending: Mutex<PendingVotes>,
+}
+
+impl VoteBatch {
+  /// Adds a post vote, replacing any pending vote of the person on the post. Returns true if the
+  /// batch was empty before.
+  pub(crate) async fn add_post_vote(
+    &self,
+    person_id: PersonId,
+    post_id: PostId,
+    vote: PendingVote,
+  ) -> bool {
+    let mut pending = self.pending.lock().await;
+    let was_empty = pending.is_empty();
+    pending.posts.insert((person_id, post_id), vote.into());
+    was_empty
+  }
+
+  /// Adds a comment vote, replacing any pending vote of the person on the comment. Returns true if
+  /// the batch was empty before.
+  pub(crate) async fn add_comment_vote(
+    &self,
+    person_id: PersonId,
+    comment_id: CommentId,
+    post_id: PostId,
+    vote: PendingVote,
+  ) -> bool {
+    let mut pending = self.pending.lock().await;
+    let was_empty = pending.is_empty();
+    pending
+      .comments
+      .insert((person_id, comment_id), (post_id, vote.into()));
+    was_empty
+  }
+
+  /// Returns the score of the person's pending vote on the post, `Some(None)` if the vote was
+  /// undone, or `None` if there is no pending vote.
+  async fn post_score(&self, person_id: PersonId, post_id: PostId) -> Option<Option<i16>> {
+    let pending = self.pending.lock().await;
+    let batched = pending.posts.get(&(person_id, post_id))?;
+    Some(batched.vote.as_ref().map(|(score, _)| *score))
+  }
+
+  /// Returns the score of the person's pending vote on the comment, like
+  /// [VoteBatch::post_score].
+  async fn comment_score(&self, person_id: PersonId, comment_id: CommentId) -> Option<Option<i16>> {
+    let pending = self.pending.lock().await;
+    let (_, batched) = pending.comments.get(&(person_id, comment_id))?;
+    Some(batched.vote.as_ref().map(|(score, _)| *score))
+  }
+
+  /// Writes all pending votes to the database. Votes which are rejected by the database, eg
+  /// because the object was deleted in the meantime, are dropped. Others which couldn't be written
+  /// are kept in the batch for the next flush.
+  pub(crate) async fn flush(&self, pool: &mut DbPool<'_>) -> LemmyResult<()> {
+    let pending = std::mem::take(&mut *self.pending.lock().await);
+    if pending.is_empty() {
+      return Ok(());
+    }
+    let failed = write_pending_votes(pending, pool).await;
+    if failed.is_empty() {
+      return Ok(());
+    }
+    let count = failed.len();
+    self.pending.lock().await.restore(failed);
+    Err(anyhow!("Failed to write {count} federated votes").into())
+  }
+}
+
+/// Returns true if the database rejected the vote itself, so that writing it again won't help.
+fn is_rejected(e: &Error) -> bool {
+  matches!(
+    e,
+    Error::DatabaseError(
+      DatabaseErrorKind::ForeignKeyViolation
+        | DatabaseErrorKind::UniqueViolation
+        | DatabaseErrorKind::NotNullViolation
+        | DatabaseErrorKind::CheckViolation,
+      _
+    )
+  )
+}
+
+/// Writes the votes in transactions of at most [MAX_TRANSACTION_VOTES]. A single rejected vote
+/// fails its whole transaction, so the votes of that transaction are then written one by one.
+/// Returns the votes which couldn't be written and should be tried again.
+async fn write_pending_votes(pending: PendingVotes, pool: &mut DbPool<'_>) -> PendingVotes {
+  let mut failed = PendingVotes::default();
+
+  let posts: Vec<_> = pending.posts.into_iter().collect();
+  for chunk in posts.chunks(MAX_TRANSACTION_VOTES) {
+    match write_post_votes(chunk, pool).await {
+      Ok(()) => continue,
+      Err(e) if !is_rejected(&e) => {
+        failed.posts.extend(chunk.iter().cloned());
+        continue;
+      }
+      Err(_) => {}
+    }
+    for vote in chunk {
+      match write_post_votes(slice::from_ref(vote), pool).await {
+        Ok(()) => {}
+        Err(e) if is_rejected(&e) => warn!("Dropped federated post vote: {e}"),
+        Err(_) => {
+          failed.posts.insert(vote.0, vote.1.clone());
+        }
+      }
+    }
+  }
+
+  let comments: Vec<_> = pending.comments.into_iter().collect();
+  for chunk in comments.chunks(MAX_TRANSACTION_VOTES) {
+    match write_comment_votes(chunk, pool).await {
+      Ok(()) => continue,
+      Err(e) if !is_rejected(&e) => {
+        failed.comments.extend(chunk.iter().cloned());
+        continue;
+      }
+      Err(_) => {}
+    }
+    for vote in chunk {
+      match write_comment_votes(slice::from_ref(vote), pool).await {
+        Ok(()) => {}
+        Err(e) if is_rejected(&e) => warn!("Dropped federated comment vote: {e}"),
+        Err(_) => {
+          failed.comments.insert(vote.0, vote.1.clone());
+        }
+      }
+    }
+  }
+  failed
+}
+
+async fn write_post_votes(
+  votes: &[(PostVoteKey, BatchedVote)],
+  pool: &mut DbPool<'_>,
+) -> Result<(), Error> {
+  let likes: Vec<_> = votes
+    .iter()
+    .filter_map(|((person_id, post_id), batched)| {
+      batched
+        .vote
+        .clone()
+        .map(|(score, activity_ap_id)| PostLikeForm {
+          post_id: *post_id,
+          person_id: *person_id,
+          score,
+          activity_ap_id: Some(activity_ap_id),
+        })
+    })
+    .collect();
+  let removals: Vec<_> = votes.iter().map(|(key, _)| *key).collect();
+  PostLike::replace_many(pool, &removals, &likes).await
+}
+
+async fn write_comment_votes(
+  votes: &[(CommentVoteKey, (PostId, BatchedVote))],
+  pool: &mut DbPool<'_>,
+) -> Result<(), Error> {
+  let likes: Vec<_> = votes
+    .iter()
+    .filter_map(|((person_id, comment_id), (post_id, batched))| {
+      batched
+        .vote
+        .clone()
+        .map(|(score, activity_ap_id)| CommentLikeForm {
+          person_id: *person_id,
+          comment_id: *comment_id,
+          post_id: *post_id,
+          score,
+          activity_ap_id: Some(activity_ap_id),
+        })
+    })
+    .collect();
+  let removals: Vec<_> = votes.iter().map(|(key, _)| *key).collect();
+  CommentLike::replace_many(pool, &removals, &likes).await
+}
+
+static VOTE_BATCH: Lazy<VoteBatch> = Lazy::new(VoteBatch::default);
+
+/// Returns the configured `vote_batch_window`, or `None` if batching is disabled.
+pub(super) fn batch_window(context: &Data<LemmyContext>) -> Option<Duration> {
+  match context.settings().vote_batch_window {
+    0 => None,
+    ms => Some(Duration::from_millis(ms)),
+  }
+}
+
+/// Adds the post vote to the batch if batching is enabled, and returns false otherwise so that the
+/// vote can be written directly.
+pub(super) async fn batch_post_vote(
+  person_id: PersonId,
+  post_id: PostId,
+  vote: PendingVote,
+  context: &Data<LemmyContext>,
+) -> bool {
+  let Some(window) = batch_window(context) else {
+    return false;
+  };
+  if VOTE_BATCH.add_post_vote(person_id, post_id, vote).await {
+    schedule_flush(window, context);
+  }
+  true
+}
+
+/// Adds the comment vote to the batch if batching is enabled, and returns false otherwise so that
+/// the vote can be written directly.
+pub(super) async fn batch_comment_vote(
+  person_id: PersonId,
+  comment_id: CommentId,
+  post_id: PostId,
+  vote: PendingVote,
+  context: &Data<LemmyContext>,
+) -> bool {
+  let Some(window) = batch_window(context) else {
+    return false;
+  };
+  if VOTE_BATCH
+    .add_comment_vote(person_id, comment_id, post_id, vote)
+    .await
+  {
+    schedule_flush(window, context);
+  }
+  true
+}
+
+/// Returns the score of the person's vote on the post which is waiting in the batch, see
+/// [VoteBatch::post_score].
+pub(super) async fn batched_post_score(
+  person_id: PersonId,
+  post_id: PostId,
+) -> Option<Option<i16>> {
+  VOTE_BATCH.post_score(person_id, post_id).await
+}
+
+/// Returns the score of the person's vote on the comment which is waiting in the batch, see
+/// [VoteBatch::post_score].
+pub(super) async fn batched_comment_score(
+  person_id: PersonId,
+  comment_id: CommentId,
+) -> Option<Option<i16>> {
+  VOTE_BATCH.comment_score(person_id, comment_id).await
+}
+
+/// Writes the batch once the window has passed after its first vote. If that fails, it is tried
+/// again after another window.
+fn schedule_flush(window: Duration, context: &Data<LemmyContext>) {
+  let context = context.reset_request_count();
+  spawn_try_task(async move {
+    sleep(window).await;
+    let res = VOTE_BATCH.flush(&mut context.pool()).await;
+    if res.is_err() {
+      schedule_flush(window, &context);
+    }
+    res
+  });
+}
+
+/// Writes the votes which are still waiting in the batch, so that they aren't lost on shutdown.
+pub async fn flush_vote_batch(context: &Data<LemmyContext>) -> LemmyResult<()> {
+  VOTE_BATCH.flush(&mut context.pool()).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use lemmy_db_schema::{
+    aggregates::structs::PostAggregates,
+    source::{
+      community::{Community, CommunityInsertForm},
+      instance::Instance,
+      person::{Person, PersonInsertForm},
+      post::{Post, PostInsertForm},
+    },
+    traits::{Crud, Likeable},
+  };
+  use lemmy_utils::error::LemmyErrorType;
+  use pretty_assertions::assert_eq;
+  use serial_test::serial;
+  use url::Url;
+
+  fn activity_id(i: usize) -> LemmyResult<DbUrl> {
+    Ok(Url::parse(&format!("https://vote-batch.example/activities/like/{i}"))?.into())
+  }
+
+  async fn post_scores(post_id: PostId, context: &LemmyContext) -> LemmyResult<(i64, i64, i64)> {
+    let aggregates = PostAggregates::read(&mut context.pool(), post_id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindPost)?;
+    Ok((aggregates.score, aggregates.upvotes, aggregates.downvotes))
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_vote_batch_matches_unbatched() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let pool = &mut context.pool();
+    let instance = Instance::read_or_create(pool, "vote-batch.example".to_string()).await?;
+    let mut persons = vec![];
+    for i in 0..10 {
+      let form = PersonInsertForm::builder()
+        .name(format!("vote_batch_{i}"))
+        .public_key("pubkey".to_string())
+        .instance_id(instance.id)
+        .build();
+      persons.push(Person::create(pool, &form).await?);
+    }
+    let community_form = CommunityInsertForm::builder()
+      .name("vote_batch".to_string())
+      .title("vote_batch".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(instance.id)
+      .build();
+    let community = Community::create(pool, &community_form).await?;
+    let creator_id = persons.first().ok_or(LemmyErrorType::CouldntFindPerson)?.id;
+    let post_form = PostInsertForm::builder()
+      .name("batched".to_string())
+      .creator_id(creator_id)
+      .community_id(community.id)
+      .build();
+    let batched_post = Post::create(pool, &post_form).await?;
+    let post_form = PostInsertForm::builder()
+      .name("unbatched".to_string())
+      .creator_id(creator_id)
+      .community_id(community.id)
+      .build();
+    let unbatched_post = Post::create(pool, &post_form).await?;
+
+    // each person votes repeatedly, changing and undoing their vote
+    let batch = VoteBatch::default();
+    for (i, person) in persons.iter().cycle().take(100).enumerate() {
+      let score = match (i * 7) % 5 {
+        0 | 1 => Some(1),
+        2 | 3 => Some(-1),
+        _ => None,
+      };
+      let activity_id = activity_id(i)?;
+      let vote = score.map(|score| (score, activity_id));
+      batch
+        .add_post_vote(person.id, batched_post.id, vote.clone())
+        .await;
+
+      PostLike::remove(pool, person.id, unbatched_post.id).await?;
+      if let Some((score, activity_ap_id)) = vote {
+        let form = PostLikeForm {
+          post_id: unbatched_post.id,
+          person_id: person.id,
+          score,
+          activity_ap_id: Some(activity_ap_id),
+        };
+        PostLike::like(pool, &form).await?;
+      }
+    }
+
+    // nothing is written before the flush
+    assert_eq!((0, 0, 0), post_scores(batched_post.id, &context).await?);
+    // but the pending votes are known, so that repeated votes are detected as duplicates
+    for person in &persons {
+      let unbatched = PostLike::read(pool, person.id, unbatched_post.id).await?;
+      assert_eq!(
+        Some(unbatched.map(|l| l.score)),
+        batch.post_score(person.id, batched_post.id).await
+      );
+    }
+    batch.flush(pool).await?;
+    assert_eq!(
+      post_scores(unbatched_post.id, &context).await?,
+      post_scores(batched_post.id, &context).await?
+    );
+    for person in &persons {
+      let batched = PostLike::read(pool, person.id, batched_post.id).await?;
+      let unbatched = PostLike::read(pool, person.id, unbatched_post.id).await?;
+      assert_eq!(
+        unbatched.map(|l| (l.score, l.activity_ap_id)),
+        batched.map(|l| (l.score, l.activity_ap_id))
+      );
+    }
+
+    Instance::delete(pool, instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_vote_batch_failed_flush() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let pool = &mut context.pool();
+    let instance = Instance::read_or_create(pool, "vote-batch.example".to_string()).await?;
+    let form = PersonInsertForm::builder()
+      .name("vote_batch_failed".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(instance.id)
+      .build();
+    let person = Person::create(pool, &form).await?;
+    let community_form = CommunityInsertForm::builder()
+      .name("vote_batch_failed".to_string())
+      .title("vote_batch_failed".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(instance.id)
+      .build();
+    let community = Community::create(pool, &community_form).await?;
+    let post_form = PostInsertForm::builder()
+      .name("vote_batch_failed".to_string())
+      .creator_id(person.id)
+      .community_id(community.id)
+      .build();
+    let post = Post::create(pool, &post_form).await?;
+
+    // votes on posts which don't exist are dropped, without affecting the other votes
+    let [first_id, second_id, good_id, newer_id] = [
+      activity_id(0)?,
+      activity_id(1)?,
+      activity_id(2)?,
+      activity_id(3)?,
+    ];
+    let batch = VoteBatch::default();
+    batch
+      .add_post_vote(person.id, PostId(-1), Some((1, first_id)))
+      .await;
+    batch
+      .add_post_vote(person.id, post.id, Some((1, good_id.clone())))
+      .await;
+    batch
+      .add_post_vote(person.id, PostId(-2), Some((1, second_id.clone())))
+      .await;
+    batch.flush(pool).await?;
+    assert!(batch.pending.lock().await.is_empty());
+    let like = PostLike::read(pool, person.id, post.id).await?;
+    assert_eq!(
+      Some((1, Some(good_id))),
+      like.map(|l| (l.score, l.activity_ap_id))
+    );
+    assert_eq!((1, 1, 0), post_scores(post.id, &context).await?);
+
+    // votes which were added during a failed flush are newer than the ones which are put back
+    let mut older = PendingVotes::default();
+    older
+      .posts
+      .insert((person.id, PostId(-1)), Some((1, activity_id(0)?)).into());
+    older
+      .posts
+      .insert((person.id, PostId(-2)), Some((1, second_id.clone())).into());
+    batch
+      .add_post_vote(person.id, PostId(-1), Some((-1, newer_id.clone())))
+      .await;
+    batch.pending.lock().await.restore(older);
+    let pending = std::mem::take(&mut *batch.pending.lock().await);
+    let scores = [PostId(-1), PostId(-2)].map(|post_id| {
+      pending
+        .posts
+        .get(&(person.id, post_id))
+        .and_then(|v| v.vote.clone())
+    });
+    assert_eq!([Some((-1, newer_id)), Some((1, second_id))], scores);
+
+    // votes are dropped once they failed too often
+    let mut pending = pending;
+    for _ in 0..MAX_FLUSH_ATTEMPTS {
+      let mut restored = PendingVotes::default();
+      restored.restore(pending);
+      pending = restored;
+    }
+    assert!(pending.is_empty());
+
+    Instance::delete(pool, instance.id).await?;
+    Ok(())
+  }
+}
@@ -1,5 +1,11 @@
 use crate::{
-  activities::community::send_activity_in_community,
+  activities::{
+    community::send_activity_in_community,
+    voting::{
+      batch::{batch_comment_vote, batch_post_vote},
+      score_clamp::{remove_queued_vote, ClampedObject},
+    },
+  },
   activity_lists::AnnouncableActivities,
   fetcher::post_or_comment::PostOrComment,
   objects::{comment::ApubComment, community::ApubCommunity, person::ApubPerson, post::ApubPost},
@@ -9,23 +15,32 @@ use crate::{
   },
 };
 use activitypub_federation::{config::Data, fetch::object_id::ObjectId};
-use lemmy_api_common::context::LemmyContext;
+use chrono::{DateTime, Utc};
+use lemmy_api_common::{context::LemmyContext, utils::is_younger_than};
 use lemmy_db_schema::{
   newtypes::DbUrl,
   source::{
     activity::ActivitySendTargets,
     comment::{CommentLike, CommentLikeForm},
     community::Community,
+    local_site_federation::LocalSiteFederation,
     person::Person,
-    post::{PostLike, PostLikeForm},
+    post::{Post, PostLike, PostLikeForm},
+    received_vote_action::ReceivedVoteAction,
   },
-  traits::Likeable,
+  traits::{Crud, Likeable},
 };
 use lemmy_utils::error::LemmyResult;
 
+mod batch;
+mod rate_limit;
+pub mod reconcile;
+mod score_clamp;
 pub mod undo_vote;
 pub mod vote;
 
+pub use batch::flush_vote_batch;
+
 pub(crate) async fn send_like_activity(
   object_id: DbUrl,
   actor: Person,
@@ -52,19 +67,84 @@ pub(crate) async fn send_like_activity(
   }
 }
 
+/// Returns true if the instance is in read-only mode for maintenance. Received votes and undos
+/// are then dropped without storing anything, not even the activity id, but the sender gets a
+/// success response so that it doesn't keep retrying them.
+async fn is_read_only(context: &Data<LemmyContext>) -> bool {
+  LocalSiteFederation::read(&mut context.pool())
+    .await
+    .is_ok_and(|f| f.read_only)
+}
+
+/// Returns true if the voted post, or the post of the voted comment, is locked. Incoming votes and
+/// undos on locked posts are ignored, so that locking also freezes the scores.
+async fn is_post_locked(object: &PostOrComment, context: &Data<LemmyContext>) -> LemmyResult<bool> {
+  match object {
+    PostOrComment::Post(p) => Ok(p.locked),
+    PostOrComment::Comment(c) => Ok(
+      Post::read(&mut context.pool(), c.post_id)
+        .await?
+        .is_some_and(|p| p.locked),
+    ),
+  }
+}
+
+/// Returns true if a vote or undo with a later `published` time was already received from the
+/// actor for the object. Federation doesn't guarantee the order of delivery, so the activity is
+/// outdated and has to be ignored. Activities without `published` time are always applied.
+async fn is_outdated_vote_action(
+  actor: &ApubPerson,
+  object_id: &ObjectId<PostOrComment>,
+  published: Option<DateTime<Utc>>,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<bool> {
+  let Some(published) = published else {
+    return Ok(false);
+  };
+  let action = ReceivedVoteAction {
+    person_id: actor.id,
+    object_id: object_id.clone().into(),
+    published,
+  };
+  Ok(!ReceivedVoteAction::update_if_newer(&mut context.pool(), &action).await?)
+}
+
+/// Votes from accounts younger than the site's `min_account_age_for_full_vote` are stored with a
+/// score of 0, so that they don't count towards the aggregates and aren't listed as votes.
+fn vote_score(
+  vote_type: &VoteType,
+  actor: &ApubPerson,
+  federation: Option<&LocalSiteFederation>,
+) -> i16 {
+  let min_account_age = federation
+    .map(|f| f.min_account_age_for_full_vote)
+    .unwrap_or_default();
+  if is_younger_than(actor.published, min_account_age) {
+    0
+  } else {
+    vote_type.into()
+  }
+}
+
 #[tracing::instrument(skip_all)]
 async fn vote_comment(
-  vote_type: &VoteType,
+  score: i16,
+  activity_id: DbUrl,
   actor: ApubPerson,
   comment: &ApubComment,
   context: &Data<LemmyContext>,
 ) -> LemmyResult<()> {
   let comment_id = comment.id;
+  let vote = Some((score, activity_id.clone()));
+  if batch_comment_vote(actor.id, comment_id, comment.post_id, vote, context).await {
+    return Ok(());
+  }
   let like_form = CommentLikeForm {
     comment_id,
     post_id: comment.post_id,
     person_id: actor.id,
-    score: vote_type.into(),
+    score,
+    activity_ap_id: Some(activity_id),
   };
   let person_id = actor.id;
   CommentLike::remove(&mut context.pool(), person_id, comment_id).await?;
@@ -74,16 +154,22 @@ async fn vote_comment(
 
 #[tracing::instrument(skip_all)]
 async fn vote_post(
-  vote_type: &VoteType,
+  score: i16,
+  activity_id: DbUrl,
   actor: ApubPerson,
   post: &ApubPost,
   context: &Data<LemmyContext>,
 ) -> LemmyResult<()> {
   let post_id = post.id;
+  let vote = Some((score, activity_id.clone()));
+  if batch_post_vote(actor.id, post_id, vote, context).await {
+    return Ok(());
+  }
   let like_form = PostLikeForm {
     post_id: post.id,
     person_id: actor.id,
-    score: vote_type.into(),
+    score,
+    activity_ap_id: Some(activity_id),
   };
   let person_id = actor.id;
   PostLike::remove(&mut context.pool(), person_id, post_id).await?;
@@ -99,6 +185,14 @@ async fn undo_vote_comment(
 ) -> LemmyResult<()> {
   let comment_id = comment.id;
   let person_id = actor.id;
+  remove_queued_vote(
+    ClampedObject::Comment(comment_id, comment.post_id),
+    person_id,
+  )
+  .await;
+  if batch_comment_vote(person_id, comment_id, comment.post_id, None, context).await {
+    return Ok(());
+  }
   CommentLike::remove(&mut context.pool(), person_id, comment_id).await?;
   Ok(())
 }
@@ -111,6 +205,10 @@ async fn undo_vote_post(
 ) -> LemmyResult<()> {
   let post_id = post.id;
   let person_id = actor.id;
+  remove_queued_vote(ClampedObject::Post(post_id), person_id).await;
+  if batch_post_vote(person_id, post_id, None, context).await {
+    return Ok(());
+  }
   PostLike::remove(&mut context.pool(), person_id, post_id).await?;
   Ok(())
 }
@@ -0,0 +1,145 @@
+use activitypub_federation::config::Data;
+use lemmy_api_common::context::LemmyContext;
+use lemmy_db_schema::{
+  newtypes::InstanceId,
+  source::{instance::Instance, local_site_federation::LocalSiteFederation},
+};
+use lemmy_utils::error::LemmyResult;
+use once_cell::sync::Lazy;
+use std::{
+  collections::{HashMap, VecDeque},
+  time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+/// Votes of each instance are counted over this window.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Sliding window limit for incoming federated votes, per origin instance.
+#[derive(Default)]
+pub(crate) struct InstanceVoteRateLimit {
+  accepted: Mutex<HashMap<InstanceId, VecDeque<Instant>>>,
+}
+
+impl InstanceVoteRateLimit {
+  /// Returns true if the instance sent fewer than `limit` accepted votes within the window before
+  /// `now`, and with `count` counts this vote. Dropped votes aren't counted, so that an instance
+  /// can vote again as soon as it slows down.
+  pub(crate) async fn check(
+    &self,
+    instance_id: InstanceId,
+    limit: usize,
+    now: Instant,
+    count: bool,
+  ) -> bool {
+    let mut accepted = self.accepted.lock().await;
+    let votes = accepted.entry(instance_id).or_default();
+    while votes
+      .front()
+      .is_some_and(|t| now.saturating_duration_since(*t) >= WINDOW)
+    {
+      votes.pop_front();
+    }
+    if votes.len() >= limit {
+      return false;
+    }
+    if count {
+      votes.push_back(now);
+    }
+    true
+  }
+}
+
+static VOTE_RATE_LIMIT: Lazy<InstanceVoteRateLimit> = Lazy::new(InstanceVoteRateLimit::default);
+
+/// Returns false if the instance exceeded its vote rate limit, in which case the vote should be
+/// dropped. The limit of the instance takes precedence, if it is not set the site default is used.
+/// Without `count`, the vote isn't counted towards the limit.
+pub(super) async fn check_instance_vote_rate_limit(
+  instance_id: InstanceId,
+  federation: Option<&LocalSiteFederation>,
+  count: bool,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<bool> {
+  let instance_limit = Instance::read_vote_rate_limit(&mut context.pool(), instance_id).await?;
+  let limit = instance_limit
+    .or(federation.map(|f| f.instance_vote_rate_limit))
+    .unwrap_or_default();
+  // 0 disables the limit
+  let limit = usize::try_from(limit).unwrap_or_default();
+  if limit == 0 {
+    return Ok(true);
+  }
+  Ok(
+    VOTE_RATE_LIMIT
+      .check(instance_id, limit, Instant::now(), count)
+      .await,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serial_test::serial;
+
+  #[tokio::test]
+  #[serial]
+  async fn test_instance_vote_rate_limit() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let pool = &mut context.pool();
+    let instance = Instance::read_or_create(pool, "rate-limit.example".to_string())
+      .await?
+      .id;
+    let other_instance = Instance::read_or_create(pool, "other-rate-limit.example".to_string())
+      .await?
+      .id;
+    let rate_limit = InstanceVoteRateLimit::default();
+    let start = Instant::now();
+
+    // checks without counting, like for a preview, don't use up the limit
+    for _ in 0..5 {
+      assert!(rate_limit.check(instance, 3, start, false).await);
+    }
+    for _ in 0..3 {
+      assert!(rate_limit.check(instance, 3, start, true).await);
+    }
+    assert!(!rate_limit.check(instance, 3, start, true).await);
+    // dropped votes don't extend the window
+    assert!(
+      !rate_limit
+        .check(instance, 3, start + WINDOW / 2, true)
+        .await
+    );
+    // other instances have their own limit
+    assert!(rate_limit.check(other_instance, 3, start, true).await);
+    // once the window has passed, votes are accepted again
+    assert!(rate_limit.check(instance, 3, start + WINDOW, true).await);
+
+    Instance::delete(pool, instance).await?;
+    Instance::delete(pool, other_instance).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_read_vote_rate_limit() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let pool = &mut context.pool();
+    let instance = Instance::read_or_create(pool, "cached-rate-limit.example".to_string())
+      .await?
+      .id;
+
+    assert_eq!(None, Instance::read_vote_rate_limit(pool, instance).await?);
+    // changing the limit takes effect right away, even though it is cached
+    Instance::set_vote_rate_limit(pool, instance, Some(3)).await?;
+    assert_eq!(
+      Some(3),
+      Instance::read_vote_rate_limit(pool, instance).await?
+    );
+    Instance::set_vote_rate_limit(pool, instance, None).await?;
+    assert_eq!(None, Instance::read_vote_rate_limit(pool, instance).await?);
+
+    Instance::delete(pool, instance).await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,314 @@
+use crate::{
+  activities::voting::vote::{check_federated_vote, Voter},
+  objects::community::ApubCommunity,
+  protocol::{activities::voting::vote::VoteType, collections::vote_collection::VoteCollection},
+  PostOrComment,
+};
+use activitypub_federation::{
+  config::Data,
+  fetch::fetch_object_http,
+  protocol::verification::verify_domains_match,
+};
+use chrono::{TimeDelta, Utc};
+use lemmy_api_common::context::LemmyContext;
+use lemmy_db_schema::{
+  aggregates::structs::{CommentAggregates, PostAggregates},
+  newtypes::{CommunityId, DbUrl},
+  source::{
+    comment::Comment,
+    community::Community,
+    local_site::LocalSite,
+    local_site_federation::LocalSiteFederation,
+    post::Post,
+  },
+  traits::Crud,
+  FederationMode,
+};
+use lemmy_db_views_actor::structs::CommunityPersonBanView;
+use lemmy_utils::{
+  error::{LemmyErrorType, LemmyResult},
+  settings::structs::VoteReconciliationConfig,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::debug;
+use url::Url;
+
+/// The parts of a remote post or comment which are needed to reconcile its votes.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VotedObject {
+  id: Url,
+  likes: Option<VoteCollection>,
+  dislikes: Option<VoteCollection>,
+}
+
+/// Refetches recently published remote posts and comments from their origin instance, and
+/// corrects the local vote counts where they differ from the counts reported there. Each
+/// correction is stored, so that it can be audited and reverted. Objects which can't be fetched or
+/// don't include their likes are skipped, as are objects where local policy rejects some votes on
+/// purpose, and counts which differ by more than `max_correction`. Returns the number of corrected
+/// objects.
+pub async fn reconcile_votes(
+  config: &VoteReconciliationConfig,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<usize> {
+  let local_site = LocalSite::read(&mut context.pool()).await?;
+  let federation = LocalSiteFederation::read(&mut context.pool()).await?;
+  // Votes of young accounts are stored without score, the origin can't know about that
+  if federation.read_only || federation.min_account_age_for_full_vote > 0 {
+    return Ok(0);
+  }
+  let max_age = Duration::from_secs(config.max_age.saturating_mul(60 * 60));
+  let since = Utc::now() - TimeDelta::from_std(max_age)?;
+  let fetch_delay = Duration::from_millis(config.fetch_delay);
+  let max_objects = i64::from(config.max_objects);
+  let posts = Post::list_recent_remote(&mut context.pool(), since, max_objects).await?;
+  let remaining = max_objects - i64::try_from(posts.len())?;
+  let comments = Comment::list_recent_remote(&mut context.pool(), since, remaining).await?;
+
+  let mut corrected = 0;
+  for (post_id, ap_id) in posts {
+    let Some((post, counts)) = Post::read_with_counts(&mut context.pool(), post_id).await? else {
+      continue;
+    };
+    let community = read_community(post.community_id, context).await?;
+    let object = PostOrComment::Post(post.into());
+    if !accepts_all_votes(&object, &community, &federation, &local_site, context).await? {
+      continue;
+    }
+    if let Some((upvotes, downvotes)) =
+      fetch_vote_counts(&ap_id, &counts.into(), config, context).await
+    {
+      PostAggregates::update_votes(&mut context.pool(), post_id, upvotes, downvotes).await?;
+      corrected += 1;
+    }
+    sleep(fetch_delay).await;
+  }
+  for (comment_id, ap_id) in comments {
+    let Some((comment, counts)) =
+      Comment::read_with_counts(&mut context.pool(), comment_id).await?
+    else {
+      continue;
+    };
+    let post = Post::read(&mut context.pool(), comment.post_id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindPost)?;
+    let community = read_community(post.community_id, context).await?;
+    let object = PostOrComment::Comment(comment.into());
+    if !accepts_all_votes(&object, &community, &federation, &local_site, context).await? {
+      continue;
+    }
+    if let Some((upvotes, downvotes)) =
+      fetch_vote_counts(&ap_id, &counts.into(), config, context).await
+    {
+      CommentAggregates::update_votes(&mut context.pool(), comment_id, upvotes, downvotes).await?;
+      corrected += 1;
+    }
+    sleep(fetch_delay).await;
+  }
+  Ok(corrected)
+}
+
+async fn read_community(
+  community_id: CommunityId,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<ApubCommunity> {
+  Ok(
+    Community::read(&mut context.pool(), community_id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindCommunity)?
+      .into(),
+  )
+}
+
+/// Returns true if every remote vote on the object would be counted locally. Otherwise the local
+/// counts differ from the origin on purpose, for example because downvotes are only accepted from
+/// followers, the community is too new, or banned users voted.
+async fn accepts_all_votes(
+  object: &PostOrComment,
+  community: &ApubCommunity,
+  federation: &LocalSiteFederation,
+  local_site: &LocalSite,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<bool> {
+  // Any remote voter, it is only known that there are votes
+  let voter = Voter {
+    person_id: None,
+    local: false,
+    instance_id: None,
+    bot_account: false,
+  };
+  for kind in [VoteType::Like, VoteType::Dislike] {
+    let check = check_federated_vote(
+      &kind,
+      &voter,
+      object,
+      community,
+      Some(federation),
+      Some(local_site),
+      false,
+      context,
+    )
+    .await?;
+    if check.federation_mode != FederationMode::All || check.rejection.is_some() {
+      return Ok(false);
+    }
+  }
+  Ok(!CommunityPersonBanView::any_in_community(&mut context.pool(), community.id).await?)
+}
+
+/// The local vote counts of an object.
+struct VoteCounts {
+  upvotes: i64,
+  downvotes: i64,
+}
+
+impl From<PostAggregates> for VoteCounts {
+  fn from(counts: PostAggregates) -> Self {
+    VoteCounts {
+      upvotes: counts.upvotes,
+      downvotes: counts.downvotes,
+    }
+  }
+}
+
+impl From<CommentAggregates> for VoteCounts {
+  fn from(counts: CommentAggregates) -> Self {
+    VoteCounts {
+      upvotes: counts.upvotes,
+      downvotes: counts.downvotes,
+    }
+  }
+}
+
+/// Fetches the vote counts of the object from its origin instance. Returns them only if they
+/// differ from the local counts, and by no more than `max_correction`. Software without dislikes
+/// doesn't include them, in that case the local count is kept.
+async fn fetch_vote_counts(
+  ap_id: &DbUrl,
+  local: &VoteCounts,
+  config: &VoteReconciliationConfig,
+  context: &Data<LemmyContext>,
+) -> Option<(i64, i64)> {
+  // Each fetch gets its own request limit
+  let object = fetch_object_http::<_, VotedObject>(ap_id.inner(), &context.reset_request_count())
+    .await
+    .map_err(|e| debug!("Failed to fetch {ap_id} for vote reconciliation: {e}"))
+    .ok()?
+    .object;
+  verify_domains_match(&object.id, ap_id.inner()).ok()?;
+  let remote_upvotes = object.likes?.total_items;
+  let remote_downvotes = object.dislikes.map_or(local.downvotes, |d| d.total_items);
+  if remote_upvotes < 0 || remote_downvotes < 0 {
+    return None;
+  }
+  let max_correction = i64::from(config.max_correction);
+  if remote_upvotes.abs_diff(local.upvotes) > max_correction.unsigned_abs()
+    || remote_downvotes.abs_diff(local.downvotes) > max_correction.unsigned_abs()
+  {
+    debug!("Ignoring bogus vote counts of {ap_id}");
+    return None;
+  }
+  let remote = (remote_upvotes, remote_downvotes);
+  (remote != (local.upvotes, local.downvotes)).then_some(remote)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::api::test::{mock_remote, TestInstance};
+  use lemmy_db_schema::{
+    aggregates::structs::PersonAggregates,
+    source::{community::CommunityUpdateForm, vote_correction::VoteCorrection},
+  };
+  use pretty_assertions::assert_eq;
+  use serial_test::serial;
+
+  #[tokio::test]
+  #[serial]
+  async fn test_reconcile_votes() -> LemmyResult<()> {
+    // the origin instance reports 5 likes and 2 dislikes
+    let mock = mock_remote(|base| {
+      serde_json::json!({
+        "type": "Page",
+        "id": format!("{base}/post/reconcile"),
+        "likes": { "type": "Collection", "totalItems": 5 },
+        "dislikes": { "type": "Collection", "totalItems": 2 },
+      })
+      .to_string()
+    })
+    .await?;
+    let context = mock.context().await?;
+    let local = TestInstance::builder("example.com")
+      .create(&context)
+      .await?;
+    let remote = mock.instance(&context).await?;
+    let person = remote.create_person("reconcile_user", &context).await?;
+    let community = remote.create_community("reconcile", &context).await?;
+    let post = remote
+      .create_post("reconcile", &person, &community, &context)
+      .await?;
+
+    // locally, a dislike was missed and a like arrived which was undone at the origin
+    PostAggregates::update_votes(&mut context.pool(), post.id, 6, 1).await?;
+    let config = VoteReconciliationConfig {
+      fetch_delay: 0,
+      ..Default::default()
+    };
+
+    // counts which differ by too much are ignored
+    let strict_config = VoteReconciliationConfig {
+      max_correction: 0,
+      ..config.clone()
+    };
+    assert_eq!(0, reconcile_votes(&strict_config, &context).await?);
+
+    // downvotes from other instances are rejected in the community, so the counts differ on
+    // purpose
+    let form = CommunityUpdateForm {
+      post_downvotes: Some(Some(FederationMode::Local)),
+      ..Default::default()
+    };
+    Community::update(&mut context.pool(), community.id, &form).await?;
+    assert_eq!(0, reconcile_votes(&config, &context).await?);
+    let form = CommunityUpdateForm {
+      post_downvotes: Some(None),
+      ..Default::default()
+    };
+    Community::update(&mut context.pool(), community.id, &form).await?;
+
+    let corrected = reconcile_votes(&config, &context).await?;
+    assert!(corrected >= 1);
+    let counts = PostAggregates::read(&mut context.pool(), post.id).await?;
+    assert_eq!(
+      Some((5, 2, 3)),
+      counts.map(|c| (c.upvotes, c.downvotes, c.score))
+    );
+    // the score of the creator is corrected as well
+    let person_counts = PersonAggregates::read(&mut context.pool(), person.id).await?;
+    assert_eq!(Some(3), person_counts.map(|c| c.post_score));
+    // there are no stored votes, all of them come from the corrections, and each one is kept
+    let corrections = VoteCorrection::list_for_post(&mut context.pool(), post.id).await?;
+    assert_eq!(
+      vec![(6, 1), (-1, 1)],
+      corrections
+        .iter()
+        .map(|c| (c.upvotes, c.downvotes))
+        .collect::<Vec<_>>()
+    );
+
+    // without objects to check, nothing is fetched
+    let config = VoteReconciliationConfig {
+      max_objects: 0,
+      fetch_delay: 0,
+      ..Default::default()
+    };
+    assert_eq!(0, reconcile_votes(&config, &context).await?);
+
+    remote.cleanup(&context).await?;
+    local.cleanup(&context).await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,588 @@
+use crate::{
+  activities::voting::{
+    batch::{batch_comment_vote, batch_post_vote, batched_comment_score, batched_post_score},
+    vote::check_queued_vote,
+  },
+  fetcher::post_or_comment::PostOrComment,
+  protocol::activities::voting::vote::VoteType,
+};
+use activitypub_federation::config::Data;
+use chrono::{DateTime, Utc};
+use lemmy_api_common::context::LemmyContext;
+use lemmy_db_schema::{
+  newtypes::{CommentId, DbUrl, PersonId, PostId},
+  source::{
+    comment::{CommentLike, CommentLikeForm},
+    local_site_federation::LocalSiteFederation,
+    post::{PostLike, PostLikeForm},
+  },
+  traits::Likeable,
+};
+use lemmy_utils::{error::LemmyResult, spawn_try_task};
+use once_cell::sync::Lazy;
+use std::{
+  collections::{HashMap, VecDeque},
+  time::{Duration, Instant},
+};
+use tokio::{sync::Mutex, time::sleep};
+
+/// Score changes of each object are summed up over this window.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// How often queued votes are checked for whether they can be applied.
+const RELEASE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Maximum number of queued votes for a single post or comment.
+const MAX_QUEUED_PER_OBJECT: usize = 1000;
+
+/// Maximum number of queued votes for all posts and comments together.
+const MAX_QUEUED: usize = 50_000;
+
+/// A voted post or comment. Comment votes also need the post id to be stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ClampedObject {
+  Post(PostId),
+  Comment(CommentId, PostId),
+}
+
+impl From<&PostOrComment> for ClampedObject {
+  fn from(object: &PostOrComment) -> Self {
+    match object {
+      PostOrComment::Post(p) => ClampedObject::Post(p.id),
+      PostOrComment::Comment(c) => ClampedObject::Comment(c.id, c.post_id),
+    }
+  }
+}
+
+/// A federated vote which was held back because it would have changed the score too much. It
+/// keeps what is needed to check the vote again before it is stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct QueuedVote {
+  pub(crate) object: ClampedObject,
+  pub(crate) person_id: PersonId,
+  pub(crate) kind: VoteType,
+  pub(crate) score: i16,
+  pub(crate) activity_id: DbUrl,
+  pub(crate) published: Option<DateTime<Utc>>,
+  pub(crate) received: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct ObjectScore {
+  /// Score changes of the votes which were applied within the window, oldest first.
+  applied: VecDeque<(Instant, i16)>,
+  /// Votes waiting to be applied together with the score change they cause, oldest first.
+  queued: VecDeque<(i16, QueuedVote)>,
+}
+
+impl ObjectScore {
+  fn prune(&mut self, now: Instant) {
+    while self
+      .applied
+      .front()
+      .is_some_and(|(t, _)| now.saturating_duration_since(*t) >= WINDOW)
+    {
+      self.applied.pop_front();
+    }
+  }
+
+  /// Returns true if applying the score change keeps the change within the window at most
+  /// `limit`.
+  fn fits(&self, change: i16, limit: i64) -> bool {
+    let applied: i64 = self.applied.iter().map(|(_, c)| i64::from(*c)).sum();
+    (applied + i64::from(change)).abs() <= limit
+  }
+
+  /// Removes a queued vote of the person, and returns how many votes were removed.
+  fn remove_queued(&mut self, person_id: PersonId) -> usize {
+    let before = self.queued.len();
+    self.queued.retain(|(_, q)| q.person_id != person_id);
+    before - self.queued.len()
+  }
+}
+
+/// Whether a federated vote can be applied right away.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Admission {
+  Apply,
+  /// The vote was queued. `schedule_release` is true if no votes were queued before, so that
+  /// nothing releases them yet.
+  Queued {
+    schedule_release: bool,
+  },
+  /// The queue of the object or all queues together are full, so the vote is dropped.
+  Dropped,
+}
+
+#[derive(Default)]
+struct ClampState {
+  objects: HashMap<ClampedObject, ObjectScore>,
+  /// Number of queued votes of all objects.
+  queued: usize,
+  /// When objects without votes in the window were last removed.
+  last_sweep: Option<Instant>,
+}
+
+impl ClampState {
+  /// Forgets objects whose applied votes all left the window and which have no queued votes. This
+  /// runs at most once per window, so that admitting a vote doesn't go through all objects.
+  fn sweep(&mut self, now: Instant) {
+    if self
+      .last_sweep
+      .is_some_and(|t| now.saturating_duration_since(t) < WINDOW)
+    {
+      return;
+    }
+    self.last_sweep = Some(now);
+    self.objects.retain(|_, o| {
+      o.prune(now);
+      !o.applied.is_empty() || !o.queued.is_empty()
+    });
+  }
+}
+
+/// Limits how much federated votes can change the score of each post or comment within the
+/// window. Votes beyond the limit are queued and applied once earlier votes leave the window, so
+/// that a flood of votes is spread out over time. The queues are kept in memory, so they are
+/// limited to [MAX_QUEUED_PER_OBJECT] and [MAX_QUEUED] votes.
+#[derive(Default)]
+pub(crate) struct ScoreClamp {
+  state: Mutex<ClampState>,
+}
+
+impl ScoreClamp {
+  /// Counts the score change of the vote if it fits into the limit, otherwise queues the vote.
+  /// Once an object has queued votes, new votes wait behind them so that they are applied in
+  /// order. A queued vote of the same person is replaced, as only the latest one counts.
+  pub(crate) async fn admit(
+    &self,
+    vote: QueuedVote,
+    change: i16,
+    limit: i64,
+    now: Instant,
+  ) -> Admission {
+    let mut guard = self.state.lock().await;
+    let clamp = &mut *guard;
+    clamp.sweep(now);
+    let schedule_release = clamp.queued == 0;
+    let state = clamp.objects.entry(vote.object).or_default();
+    state.prune(now);
+    if state.queued.is_empty() && state.fits(change, limit) {
+      state.applied.push_back((now, change));
+      return Admission::Apply;
+    }
+    // A queued vote of the same person is outdated, also if the new one is dropped
+    clamp.queued -= state.remove_queued(vote.person_id);
+    if state.queued.len() >= MAX_QUEUED_PER_OBJECT || clamp.queued >= MAX_QUEUED {
+      return Admission::Dropped;
+    }
+    state.queued.push_back((change, vote));
+    clamp.queued += 1;
+    Admission::Queued { schedule_release }
+  }
+
+  /// Removes a queued vote of the person, because it was undone or replaced by a vote which is
+  /// applied directly.
+  pub(crate) async fn remove_queued(&self, object: ClampedObject, person_id: PersonId) {
+    let mut guard = self.state.lock().await;
+    let clamp = &mut *guard;
+    if let Some(state) = clamp.objects.get_mut(&object) {
+      clamp.queued -= state.remove_queued(person_id);
+    }
+  }
+
+  /// Takes the queued votes which fit into the limit now, and returns them together with the
+  /// number of votes which stay queued. With a limit of 0 all queued votes are returned. Released
+  /// votes still need to be checked before they are stored.
+  pub(crate) async fn release(&self, limit: i64, now: Instant) -> (Vec<QueuedVote>, usize) {
+    let mut guard = self.state.lock().await;
+    let clamp = &mut *guard;
+    let mut released = vec![];
+    for state in clamp.objects.values_mut() {
+      state.prune(now);
+      while let Some((change, _)) = state.queued.front() {
+        if limit > 0 && !state.fits(*change, limit) {
+          break;
+        }
+        state.applied.push_back((now, *change));
+        released.extend(state.queued.pop_front().map(|(_, vote)| vote));
+      }
+    }
+    clamp
+      .objects
+      .retain(|_, o| !o.applied.is_empty() || !o.queued.is_empty());
+    clamp.queued -= released.len();
+    (released, clamp.queued)
+  }
+}
+
+static SCORE_CLAMP: Lazy<ScoreClamp> = Lazy::new(ScoreClamp::default);
+
+/// Returns true if the vote of a remote actor can be applied now. Otherwise it is queued and
+/// applied later, once the score of the object changed less within the window. Does nothing if
+/// the site's `vote_score_change_limit` is 0.
+pub(super) async fn admit_federated_vote(
+  vote: QueuedVote,
+  federation: Option<&LocalSiteFederation>,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<bool> {
+  let limit = score_change_limit(federation);
+  if limit == 0 {
+    return Ok(true);
+  }
+  // Changing an upvote to a downvote changes the score by 2, repeating a vote doesn't change it
+  let change = vote.score - current_score(vote.object, vote.person_id, context).await?;
+  Ok(
+    match SCORE_CLAMP.admit(vote, change, limit, Instant::now()).await {
+      Admission::Apply => true,
+      Admission::Queued { schedule_release } => {
+        if schedule_release {
+          schedule_release_task(context);
+        }
+        false
+      }
+      Admission::Dropped => {
+        tracing::debug!("Dropping federated vote because the score change queue is full");
+        false
+      }
+    },
+  )
+}
+
+/// Returns the score of the person's current vote on the object, or 0 if there is none. Votes
+/// which are still waiting in the batch count, as they are newer than the stored ones.
+async fn current_score(
+  object: ClampedObject,
+  person_id: PersonId,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<i16> {
+  let pool = &mut context.pool();
+  Ok(match object {
+    ClampedObject::Post(post_id) => match batched_post_score(person_id, post_id).await {
+      Some(score) => score.unwrap_or_default(),
+      None => PostLike::read(pool, person_id, post_id)
+        .await?
+        .map(|l| l.score)
+        .unwrap_or_default(),
+    },
+    ClampedObject::Comment(comment_id, _) => {
+      match batched_comment_score(person_id, comment_id).await {
+        Some(score) => score.unwrap_or_default(),
+        None => CommentLike::read(pool, person_id, comment_id)
+          .await?
+          .map(|l| l.score)
+          .unwrap_or_default(),
+      }
+    }
+  })
+}
+
+/// Forgets a queued vote of the person on the object, so that it isn't applied after an undo.
+pub(super) async fn remove_queued_vote(object: ClampedObject, person_id: PersonId) {
+  SCORE_CLAMP.remove_queued(object, person_id).await
+}
+
+fn score_change_limit(federation: Option<&LocalSiteFederation>) -> i64 {
+  federation
+    .map(|f| i64::from(f.vote_score_change_limit))
+    .unwrap_or_default()
+    .max(0)
+}
+
+/// Applies queued votes as they fit into the limit, until no votes are queued anymore. The limit
+/// is read again each time, so that changing it takes effect for queued votes as well. Votes which
+/// would be rejected now are dropped.
+fn schedule_release_task(context: &Data<LemmyContext>) {
+  let context = context.reset_request_count();
+  spawn_try_task(async move {
+    loop {
+      sleep(RELEASE_INTERVAL).await;
+      let federation = LocalSiteFederation::read(&mut context.pool()).await.ok();
+      let limit = score_change_limit(federation.as_ref());
+      let (released, remaining) = SCORE_CLAMP.release(limit, Instant::now()).await;
+      for vote in released {
+        if let Err(e) = write_vote(&vote, &context).await {
+          tracing::warn!("Failed to apply queued vote {}: {e}", vote.activity_id);
+        }
+      }
+      if remaining == 0 {
+        return Ok(());
+      }
+    }
+  });
+}
+
+/// Stores a released vote, replacing any previous vote of the person on the object, if it passes
+/// the checks of the inbox again. If batching is enabled the vote is added to the batch, so that
+/// an older vote waiting there can't overwrite it.
+async fn write_vote(vote: &QueuedVote, context: &Data<LemmyContext>) -> LemmyResult<()> {
+  if !check_queued_vote(vote, context).await? {
+    tracing::debug!(
+      "Dropping queued vote {} which is rejected now",
+      vote.activity_id
+    );
+    return Ok(());
+  }
+  let pending = Some((vote.score, vote.activity_id.clone()));
+  let pool = &mut context.pool();
+  match vote.object {
+    ClampedObject::Post(post_id) => {
+      if batch_post_vote(vote.person_id, post_id, pending, context).await {
+        return Ok(());
+      }
+      let form = PostLikeForm {
+        post_id,
+        person_id: vote.person_id,
+        score: vote.score,
+        activity_ap_id: Some(vote.activity_id.clone()),
+      };
+      PostLike::remove(pool, vote.person_id, post_id).await?;
+      PostLike::like(pool, &form).await?;
+    }
+    ClampedObject::Comment(comment_id, post_id) => {
+      if batch_comment_vote(vote.person_id, comment_id, post_id, pending, context).await {
+        return Ok(());
+      }
+      let form = CommentLikeForm {
+        person_id: vote.person_id,
+        comment_id,
+        post_id,
+        score: vote.score,
+        activity_ap_id: Some(vote.activity_id.clone()),
+      };
+      CommentLike::remove(pool, vote.person_id, comment_id).await?;
+      CommentLike::like(pool, &form).await?;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::api::test::create_local_site;
+  use lemmy_db_schema::{
+    aggregates::structs::PostAggregates,
+    source::{
+      community::{Community, CommunityInsertForm, CommunityPersonBan, CommunityPersonBanForm},
+      instance::Instance,
+      person::{Person, PersonInsertForm},
+      post::{Post, PostInsertForm},
+    },
+    traits::{Bannable, Crud},
+  };
+  use lemmy_utils::error::LemmyErrorType;
+  use pretty_assertions::assert_eq;
+  use serial_test::serial;
+  use url::Url;
+
+  fn upvote(object: ClampedObject, person_id: PersonId, i: usize) -> LemmyResult<QueuedVote> {
+    Ok(QueuedVote {
+      object,
+      person_id,
+      kind: VoteType::Like,
+      score: 1,
+      activity_id: Url::parse(&format!("https://score-clamp.example/activities/like/{i}"))?.into(),
+      published: None,
+      received: Utc::now(),
+    })
+  }
+
+  async fn post_score(post_id: PostId, context: &LemmyContext) -> LemmyResult<i64> {
+    let aggregates = PostAggregates::read(&mut context.pool(), post_id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindPost)?;
+    Ok(aggregates.score)
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_score_clamp_spike() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let pool = &mut context.pool();
+    let instance = Instance::read_or_create(pool, "score-clamp.example".to_string()).await?;
+    create_local_site(instance.id, &context).await?;
+    let mut persons = vec![];
+    for i in 0..5 {
+      let form = PersonInsertForm::builder()
+        .name(format!("score_clamp_{i}"))
+        .public_key("pubkey".to_string())
+        .instance_id(instance.id)
+        .build();
+      persons.push(Person::create(pool, &form).await?);
+    }
+    let community_form = CommunityInsertForm::builder()
+      .name("score_clamp".to_string())
+      .title("score_clamp".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(instance.id)
+      .build();
+    let community = Community::create(pool, &community_form).await?;
+    let creator_id = persons.first().ok_or(LemmyErrorType::CouldntFindPerson)?.id;
+    let post_form = PostInsertForm::builder()
+      .name("spike".to_string())
+      .creator_id(creator_id)
+      .community_id(community.id)
+      .build();
+    let post = Post::create(pool, &post_form).await?;
+    let object = ClampedObject::Post(post.id);
+
+    // a spike of five upvotes with a limit of three, only the first three are applied
+    let clamp = ScoreClamp::default();
+    let start = Instant::now();
+    let mut admissions = vec![];
+    for (i, person) in persons.iter().enumerate() {
+      let vote = upvote(object, person.id, i)?;
+      let admission = clamp.admit(vote.clone(), 1, 3, start).await;
+      if admission == Admission::Apply {
+        write_vote(&vote, &context).await?;
+      }
+      admissions.push(admission);
+    }
+    assert_eq!(
+      vec![
+        Admission::Apply,
+        Admission::Apply,
+        Admission::Apply,
+        Admission::Queued {
+          schedule_release: true
+        },
+        Admission::Queued {
+          schedule_release: false
+        },
+      ],
+      admissions
+    );
+    assert_eq!(3, post_score(post.id, &context).await?);
+
+    // opposite votes still fit, but have to wait behind the queued ones
+    let [first, second, third, fourth, fifth] = persons.as_slice() else {
+      Err(LemmyErrorType::CouldntFindPerson)?
+    };
+    let downvote = QueuedVote {
+      kind: VoteType::Dislike,
+      score: -1,
+      ..upvote(object, fourth.id, 5)?
+    };
+    assert_ne!(Admission::Apply, clamp.admit(downvote, -1, 3, start).await);
+
+    // the queued votes are only released once the earlier ones left the window. the downvote
+    // replaced the queued upvote of the same person.
+    let (released, remaining) = clamp.release(3, start + WINDOW / 2).await;
+    assert_eq!((0, 2), (released.len(), remaining));
+    let (released, remaining) = clamp.release(3, start + WINDOW).await;
+    assert_eq!(
+      vec![(fifth.id, 1), (fourth.id, -1)],
+      released
+        .iter()
+        .map(|v| (v.person_id, v.score))
+        .collect::<Vec<_>>()
+    );
+    assert_eq!(0, remaining);
+
+    // released votes are checked again, the vote of a person who was banned in the meantime isn't
+    // stored
+    let ban_form = CommunityPersonBanForm {
+      community_id: community.id,
+      person_id: fifth.id,
+      expires: None,
+    };
+    CommunityPersonBan::ban(pool, &ban_form).await?;
+    for vote in &released {
+      write_vote(vote, &context).await?;
+    }
+    assert_eq!(2, post_score(post.id, &context).await?);
+    assert_eq!(-1, current_score(object, fourth.id, &context).await?);
+    assert_eq!(0, current_score(object, fifth.id, &context).await?);
+
+    // undone votes are removed from the queue. with a limit of 0 every vote is queued.
+    let vote = upvote(object, fourth.id, 6)?;
+    clamp.admit(vote, 1, 0, start + WINDOW).await;
+    clamp.remove_queued(object, fourth.id).await;
+    let (released, remaining) = clamp.release(0, start + WINDOW * 2).await;
+    assert_eq!((0, 0), (released.len(), remaining));
+
+    // changing an upvote to a downvote counts twice, while repeating a vote doesn't count
+    let now = start + WINDOW * 2;
+    let flip = |person: &Person, i| -> LemmyResult<QueuedVote> {
+      Ok(QueuedVote {
+        kind: VoteType::Dislike,
+        score: -1,
+        ..upvote(object, person.id, i)?
+      })
+    };
+    let admissions = [
+      clamp.admit(flip(first, 7)?, -2, 3, now).await,
+      clamp.admit(upvote(object, third.id, 8)?, 0, 3, now).await,
+      clamp.admit(flip(second, 9)?, -2, 3, now).await,
+    ];
+    assert_eq!(
+      [
+        Admission::Apply,
+        Admission::Apply,
+        Admission::Queued {
+          schedule_release: true
+        }
+      ],
+      admissions
+    );
+
+    Instance::delete(pool, instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_score_clamp_queue_limit() -> LemmyResult<()> {
+    let clamp = ScoreClamp::default();
+    let object = ClampedObject::Post(PostId(1));
+    let now = Instant::now();
+    for i in 0..MAX_QUEUED_PER_OBJECT {
+      let vote = upvote(object, PersonId(i.try_into()?), i)?;
+      assert_ne!(Admission::Dropped, clamp.admit(vote, 1, 0, now).await);
+    }
+
+    // once the queue of the object is full, further votes are dropped
+    let vote = upvote(object, PersonId(-1), 0)?;
+    assert_eq!(Admission::Dropped, clamp.admit(vote, 1, 0, now).await);
+    // while votes on other objects are still queued
+    let vote = upvote(ClampedObject::Post(PostId(2)), PersonId(-1), 0)?;
+    assert_ne!(Admission::Dropped, clamp.admit(vote, 1, 0, now).await);
+
+    let (released, remaining) = clamp.release(0, now).await;
+    assert_eq!((MAX_QUEUED_PER_OBJECT + 1, 0), (released.len(), remaining));
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_score_clamp_forgets_objects() -> LemmyResult<()> {
+    let clamp = ScoreClamp::default();
+    let start = Instant::now();
+    for i in 0..100 {
+      let vote = upvote(ClampedObject::Post(PostId(i)), PersonId(1), i.try_into()?)?;
+      assert_eq!(Admission::Apply, clamp.admit(vote, 1, 3, start).await);
+    }
+    assert_eq!(100, clamp.state.lock().await.objects.len());
+
+    // once the window passed, objects without votes in it are removed when the next vote comes in
+    let vote = upvote(ClampedObject::Post(PostId(100)), PersonId(1), 100)?;
+    assert_eq!(
+      Admission::Apply,
+      clamp.admit(vote, 1, 3, start + WINDOW).await
+    );
+    assert_eq!(1, clamp.state.lock().await.objects.len());
+
+    // objects with queued votes are kept
+    let vote = upvote(ClampedObject::Post(PostId(101)), PersonId(1), 101)?;
+    let queued = clamp.admit(vote, 1, 0, start + WINDOW).await;
+    assert_eq!(
+      Admission::Queued {
+        schedule_release: true
+      },
+      queued
+    );
+    let vote = upvote(ClampedObject::Post(PostId(102)), PersonId(1), 102)?;
+    clamp.admit(vote, 1, 3, start + WINDOW * 2).await;
+    assert_eq!(2, clamp.state.lock().await.objects.len());
+    Ok(())
+  }
+}
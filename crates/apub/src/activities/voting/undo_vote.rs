@@ -2,7 +2,13 @@ use crate::{
   activities::{
     generate_activity_id,
     verify_person_in_community,
-    voting::{undo_vote_comment, undo_vote_post},
+    voting::{
+      is_outdated_vote_action,
+      is_post_locked,
+      is_read_only,
+      undo_vote_comment,
+      undo_vote_post,
+    },
   },
   insert_received_activity,
   objects::{community::ApubCommunity, person::ApubPerson},
@@ -18,6 +24,7 @@ use activitypub_federation::{
   protocol::verification::verify_urls_match,
   traits::{ActivityHandler, Actor},
 };
+use chrono::Utc;
 use lemmy_api_common::context::LemmyContext;
 use lemmy_utils::error::{LemmyError, LemmyResult};
 use url::Url;
@@ -38,6 +45,7 @@ impl UndoVote {
         &context.settings().get_protocol_and_hostname(),
       )?,
       audience: Some(community.id().into()),
+      published: Some(Utc::now()),
     })
   }
 }
@@ -66,9 +74,18 @@ impl ActivityHandler for UndoVote {
 
   #[tracing::instrument(skip_all)]
   async fn receive(self, context: &Data<LemmyContext>) -> LemmyResult<()> {
+    if is_read_only(context).await {
+      return Ok(());
+    }
     insert_received_activity(&self.id, context).await?;
     let actor = self.actor.dereference(context).await?;
     let object = self.object.object.dereference(context).await?;
+    if is_post_locked(&object, context).await? {
+      return Ok(());
+    }
+    if is_outdated_vote_action(&actor, &self.object.object, self.published, context).await? {
+      return Ok(());
+    }
     match object {
       PostOrComment::Post(p) => undo_vote_post(actor, &p, context).await,
       PostOrComment::Comment(c) => undo_vote_comment(actor, &c, context).await,
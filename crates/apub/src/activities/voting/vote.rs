@@ -2,9 +2,22 @@ use crate::{
   activities::{
     generate_activity_id,
     verify_person_in_community,
-    voting::{undo_vote_comment, undo_vote_post, vote_comment, vote_post},
+    voting::{
+      batch::{batched_comment_score, batched_post_score},
+      is_outdated_vote_action,
+      is_post_locked,
+      is_read_only,
+      rate_limit::check_instance_vote_rate_limit,
+      score_clamp::{admit_federated_vote, ClampedObject, QueuedVote},
+      undo_vote_comment,
+      undo_vote_post,
+      vote_comment,
+      vote_post,
+      vote_score,
+    },
   },
-  insert_received_activity,
+  insert_new_received_activity,
+  is_received_activity,
   objects::{community::ApubCommunity, person::ApubPerson},
   protocol::{
     activities::voting::vote::{Vote, VoteType},
@@ -17,11 +30,61 @@ use activitypub_federation::{
   fetch::object_id::ObjectId,
   traits::{ActivityHandler, Actor},
 };
-use lemmy_api_common::{context::LemmyContext, utils::check_bot_account};
-use lemmy_db_schema::source::local_site::LocalSite;
-use lemmy_utils::error::{LemmyError, LemmyResult};
+use chrono::{DateTime, TimeDelta, Utc};
+use lemmy_api_common::{context::LemmyContext, site::VoteRejectionReason, utils::is_younger_than};
+use lemmy_db_schema::{
+  newtypes::{InstanceId, PersonId},
+  source::{
+    comment::{Comment, CommentLike},
+    community::Community,
+    federated_vote_rejection::{FederatedVoteRejection, FederatedVoteRejectionForm},
+    local_site::LocalSite,
+    local_site_federation::LocalSiteFederation,
+    person::Person,
+    post::{Post, PostLike},
+  },
+  traits::{Crud, Likeable},
+  FederationMode,
+};
+use lemmy_db_views_actor::structs::{CommunityFollowerView, CommunityPersonBanView};
+use lemmy_utils::error::{LemmyError, LemmyErrorType, LemmyResult};
+use once_cell::sync::Lazy;
+use prometheus::{default_registry, IntCounterVec, Opts};
 use url::Url;
 
+/// Number of federated votes which were rejected because of the vote federation mode.
+static REJECTED_VOTES: Lazy<IntCounterVec> = Lazy::new(|| {
+  let counter = IntCounterVec::new(
+    Opts::new(
+      "lemmy_federation_rejected_votes",
+      "Number of federated votes rejected because of the vote federation mode",
+    ),
+    &["object_type", "vote_kind"],
+  )
+  .expect("create rejected votes counter");
+  default_registry()
+    .register(Box::new(counter.clone()))
+    .expect("register rejected votes counter");
+  counter
+});
+
+/// Number of federated votes which were dropped because their instance exceeded the vote rate
+/// limit. Counted instead of logged, as an instance over the limit sends many of them.
+static RATE_LIMITED_VOTES: Lazy<IntCounterVec> = Lazy::new(|| {
+  let counter = IntCounterVec::new(
+    Opts::new(
+      "lemmy_federation_rate_limited_votes",
+      "Number of federated votes dropped because their instance exceeded the vote rate limit",
+    ),
+    &["instance"],
+  )
+  .expect("create rate limited votes counter");
+  default_registry()
+    .register(Box::new(counter.clone()))
+    .expect("register rate limited votes counter");
+  counter
+});
+
 impl Vote {
   pub(in crate::activities::voting) fn new(
     object_id: ObjectId<PostOrComment>,
@@ -36,6 +99,7 @@ impl Vote {
       kind: kind.clone(),
       id: generate_activity_id(kind, &context.settings().get_protocol_and_hostname())?,
       audience: Some(community.id().into()),
+      published: Some(Utc::now()),
     })
   }
 }
@@ -55,6 +119,11 @@ impl ActivityHandler for Vote {
 
   #[tracing::instrument(skip_all)]
   async fn verify(&self, context: &Data<LemmyContext>) -> LemmyResult<()> {
+    // Retried deliveries of a vote which was processed already are acknowledged right away,
+    // without dereferencing the object and community again. `receive` skips them as well.
+    if is_received_activity(&self.id, context).await? {
+      return Ok(());
+    }
     let community = self.community(context).await?;
     verify_person_in_community(&self.actor, &community, context).await?;
     Ok(())
@@ -62,28 +131,1437 @@ impl ActivityHandler for Vote {
 
   #[tracing::instrument(skip_all)]
   async fn receive(self, context: &Data<LemmyContext>) -> LemmyResult<()> {
-    insert_received_activity(&self.id, context).await?;
+    if is_read_only(context).await {
+      return Ok(());
+    }
+    // Storing the id fails for concurrent deliveries of the same vote, which all passed `verify`
+    if !insert_new_received_activity(&self.id, context).await? {
+      return Ok(());
+    }
     let actor = self.actor.dereference(context).await?;
     let object = self.object.dereference(context).await?;
+    let community = self.community(context).await?;
 
-    check_bot_account(&actor.0)?;
+    let local_site = LocalSite::read(&mut context.pool()).await?;
+    let federation = LocalSiteFederation::read(&mut context.pool()).await?;
+    let voter = Voter {
+      person_id: Some(actor.id),
+      local: actor.local,
+      instance_id: Some(actor.instance_id),
+      bot_account: actor.bot_account,
+    };
+    let check = check_federated_vote(
+      &self.kind,
+      &voter,
+      &object,
+      &community,
+      Some(&federation),
+      Some(&local_site),
+      true,
+      context,
+    )
+    .await?;
+    let allowed = match check.rejection {
+      None => true,
+      Some(VoteRejectionReason::ReadOnly | VoteRejectionReason::PostLocked) => return Ok(()),
+      Some(VoteRejectionReason::BotAccount) => Err(LemmyErrorType::InvalidBotAction)?,
+      Some(VoteRejectionReason::RateLimited) => {
+        // Drop the vote without touching any previous one, the instance is sending too many
+        RATE_LIMITED_VOTES
+          .with_label_values(&[actor.actor_id.inner().domain().unwrap_or_default()])
+          .inc();
+        return Ok(());
+      }
+      // Banned users and remote users in new communities can't influence the score. Discard the
+      // vote and any previous one.
+      Some(VoteRejectionReason::BannedFromCommunity | VoteRejectionReason::CommunityTooNew) => {
+        return match object {
+          PostOrComment::Post(p) => undo_vote_post(actor, &p, context).await,
+          PostOrComment::Comment(c) => undo_vote_comment(actor, &c, context).await,
+        };
+      }
+      // Votes which were on their way when the mode was changed are still accepted
+      Some(VoteRejectionReason::FederationMode) => is_sent_before_mode_change(
+        &self.kind,
+        &object,
+        &community,
+        &local_site,
+        &federation,
+        self.published,
+        Utc::now(),
+      ),
+    };
+    if is_outdated_vote_action(&actor, &self.object, self.published, context).await? {
+      return Ok(());
+    }
 
-    let enable_downvotes = LocalSite::read(&mut context.pool())
-      .await
-      .map(|l| l.enable_downvotes)
-      .unwrap_or(true);
-    if self.kind == VoteType::Dislike && !enable_downvotes {
-      // If this is a downvote but downvotes are ignored, only undo any existing vote
+    let score = vote_score(&self.kind, &actor, Some(&federation));
+    if !allowed {
+      let object_type = match object {
+        PostOrComment::Post(_) => "post",
+        PostOrComment::Comment(_) => "comment",
+      };
+      REJECTED_VOTES
+        .with_label_values(&[object_type, &self.kind.to_string()])
+        .inc();
+      if federation.log_rejected_votes {
+        let form = FederatedVoteRejectionForm {
+          actor_id: actor.actor_id.clone(),
+          object_id: self.object.clone().into(),
+          score: (&self.kind).into(),
+        };
+        FederatedVoteRejection::create(&mut context.pool(), &form).await?;
+      }
+      // If this vote type is ignored, only undo any existing vote
       match object {
         PostOrComment::Post(p) => undo_vote_post(actor, &p, context).await,
         PostOrComment::Comment(c) => undo_vote_comment(actor, &c, context).await,
       }
+    } else if is_duplicate_vote(score, &actor, &object, context).await? {
+      // The same vote was already applied, eg because the activity was sent again
+      Ok(())
     } else {
+      // Federated votes which would change the score too fast are queued and applied later
+      if !actor.local {
+        let vote = QueuedVote {
+          object: (&object).into(),
+          person_id: actor.id,
+          kind: self.kind.clone(),
+          score,
+          activity_id: self.id.clone().into(),
+          published: self.published,
+          received: Utc::now(),
+        };
+        if !admit_federated_vote(vote, Some(&federation), context).await? {
+          return Ok(());
+        }
+      }
       // Otherwise apply the vote normally
       match object {
-        PostOrComment::Post(p) => vote_post(&self.kind, actor, &p, context).await,
-        PostOrComment::Comment(c) => vote_comment(&self.kind, actor, &c, context).await,
+        PostOrComment::Post(p) => vote_post(score, self.id.into(), actor, &p, context).await,
+        PostOrComment::Comment(c) => vote_comment(score, self.id.into(), actor, &c, context).await,
+      }
+    }
+  }
+}
+
+/// Returns which votes of the given type are accepted for the object. The community setting takes
+/// precedence, if it is not set the site setting is used.
+pub(crate) fn vote_federation_mode(
+  kind: &VoteType,
+  object: &PostOrComment,
+  community: &ApubCommunity,
+  local_site: Option<&LocalSite>,
+) -> FederationMode {
+  if let Some(mode) = community_vote_mode(kind, object, community) {
+    return mode;
+  }
+  match kind {
+    VoteType::Like => FederationMode::All,
+    VoteType::Dislike => {
+      let enable_downvotes = local_site.map(|l| l.enable_downvotes).unwrap_or(true);
+      if enable_downvotes {
+        FederationMode::All
+      } else {
+        FederationMode::Disable
       }
     }
   }
 }
+
+fn community_vote_mode(
+  kind: &VoteType,
+  object: &PostOrComment,
+  community: &ApubCommunity,
+) -> Option<FederationMode> {
+  match (object, kind) {
+    (PostOrComment::Post(_), VoteType::Like) => community.post_upvotes,
+    (PostOrComment::Post(_), VoteType::Dislike) => community.post_downvotes,
+    (PostOrComment::Comment(_), VoteType::Like) => community.comment_upvotes,
+    (PostOrComment::Comment(_), VoteType::Dislike) => community.comment_downvotes,
+  }
+}
+
+/// Returns true if the vote was sent within the site's `vote_mode_grace_period` before its
+/// federation mode was changed. The community and the site store when they last changed their
+/// modes. The published time is set by the sender, so it is only trusted for votes which were
+/// also received within the grace period after the change. Votes without a timestamp are never
+/// accepted.
+fn is_sent_before_mode_change(
+  kind: &VoteType,
+  object: &PostOrComment,
+  community: &ApubCommunity,
+  local_site: &LocalSite,
+  federation: &LocalSiteFederation,
+  published: Option<DateTime<Utc>>,
+  received: DateTime<Utc>,
+) -> bool {
+  let Some(published) = published else {
+    return false;
+  };
+  let grace = TimeDelta::try_minutes(federation.vote_mode_grace_period.into()).unwrap_or_default();
+  let changed = match community_vote_mode(kind, object, community) {
+    Some(_) => community.vote_mode_updated,
+    None => local_site.vote_mode_updated,
+  };
+  changed.is_some_and(|changed| {
+    published <= changed && changed - published < grace && received - changed < grace
+  })
+}
+
+/// Checks a vote which was queued by the score clamp again before it is stored, as the voter may
+/// have been banned, the post locked, the federation mode changed or the site set to read-only in
+/// the meantime. The vote was counted towards the rate limit of the voter's instance when it was
+/// received, so the rate limit doesn't reject it now.
+pub(super) async fn check_queued_vote(
+  vote: &QueuedVote,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<bool> {
+  let pool = &mut context.pool();
+  let Some(person) = Person::read(pool, vote.person_id).await? else {
+    return Ok(false);
+  };
+  let object = match vote.object {
+    ClampedObject::Post(post_id) => Post::read(pool, post_id)
+      .await?
+      .map(|p| PostOrComment::Post(p.into())),
+    ClampedObject::Comment(comment_id, _) => Comment::read(pool, comment_id)
+      .await?
+      .map(|c| PostOrComment::Comment(c.into())),
+  };
+  let Some(object) = object else {
+    return Ok(false);
+  };
+  let community_id = match &object {
+    PostOrComment::Post(p) => p.community_id,
+    PostOrComment::Comment(c) => {
+      Post::read(pool, c.post_id)
+        .await?
+        .ok_or(LemmyErrorType::CouldntFindPost)?
+        .community_id
+    }
+  };
+  let community: ApubCommunity = Community::read(pool, community_id)
+    .await?
+    .ok_or(LemmyErrorType::CouldntFindCommunity)?
+    .into();
+  let local_site = LocalSite::read(pool).await?;
+  let federation = LocalSiteFederation::read(pool).await?;
+  let voter = Voter {
+    person_id: Some(person.id),
+    local: person.local,
+    instance_id: Some(person.instance_id),
+    bot_account: person.bot_account,
+  };
+  let check = check_federated_vote(
+    &vote.kind,
+    &voter,
+    &object,
+    &community,
+    Some(&federation),
+    Some(&local_site),
+    false,
+    context,
+  )
+  .await?;
+  Ok(match check.rejection {
+    None | Some(VoteRejectionReason::RateLimited) => true,
+    Some(VoteRejectionReason::FederationMode) => is_sent_before_mode_change(
+      &vote.kind,
+      &object,
+      &community,
+      &local_site,
+      &federation,
+      vote.published,
+      vote.received,
+    ),
+    Some(_) => false,
+  })
+}
+
+/// The sender of a federated vote. When previewing a vote it may not be known locally, then only
+/// its instance is known, if at all.
+pub(crate) struct Voter {
+  pub(crate) person_id: Option<PersonId>,
+  pub(crate) local: bool,
+  pub(crate) instance_id: Option<InstanceId>,
+  pub(crate) bot_account: bool,
+}
+
+/// The result of [check_federated_vote].
+pub(crate) struct VoteCheck {
+  pub(crate) federation_mode: FederationMode,
+  pub(crate) rejection: Option<VoteRejectionReason>,
+}
+
+/// What the database says about a federated vote, loaded by [check_federated_vote] so that
+/// [decide_federated_vote] doesn't need to read anything.
+pub(crate) struct VoteState {
+  /// The voter's instance is within its vote rate limit, always true for local voters.
+  pub(crate) within_rate_limit: bool,
+  /// The voter is banned from the community. It may have been banned after the activity was
+  /// verified.
+  pub(crate) banned: bool,
+  /// The post, or the post of the comment, is locked.
+  pub(crate) post_locked: bool,
+  /// The voter's instance follows the community. Only loaded if the federation mode is
+  /// [FederationMode::Followers].
+  pub(crate) followed_by_voter_instance: bool,
+}
+
+/// Decides whether a federated vote is applied. The inbox and the vote preview both use this, so
+/// that the preview gives the same answer. With `count`, the vote counts towards the rate limit of
+/// the voter's instance. It is loaded before anything is decided, so this includes votes which are
+/// rejected for another reason.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn check_federated_vote(
+  kind: &VoteType,
+  voter: &Voter,
+  object: &PostOrComment,
+  community: &ApubCommunity,
+  federation: Option<&LocalSiteFederation>,
+  local_site: Option<&LocalSite>,
+  count: bool,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<VoteCheck> {
+  let within_rate_limit = match (voter.local, voter.instance_id) {
+    (false, Some(instance_id)) => {
+      check_instance_vote_rate_limit(instance_id, federation, count, context).await?
+    }
+    _ => true,
+  };
+  let banned = match voter.person_id {
+    Some(person_id) => {
+      CommunityPersonBanView::get(&mut context.pool(), person_id, community.id).await?
+    }
+    None => false,
+  };
+  let federation_mode = vote_federation_mode(kind, object, community, local_site);
+  let followed_by_voter_instance = match voter.instance_id {
+    Some(instance_id) if federation_mode == FederationMode::Followers && !voter.local => {
+      CommunityFollowerView::is_followed_by_instance(&mut context.pool(), community.id, instance_id)
+        .await?
+    }
+    _ => false,
+  };
+  let state = VoteState {
+    within_rate_limit,
+    banned,
+    post_locked: is_post_locked(object, context).await?,
+    followed_by_voter_instance,
+  };
+  Ok(decide_federated_vote(
+    kind, voter, object, community, federation, local_site, &state,
+  ))
+}
+
+/// The policy of [check_federated_vote], which only decides on what was loaded already.
+pub(crate) fn decide_federated_vote(
+  kind: &VoteType,
+  voter: &Voter,
+  object: &PostOrComment,
+  community: &ApubCommunity,
+  federation: Option<&LocalSiteFederation>,
+  local_site: Option<&LocalSite>,
+  state: &VoteState,
+) -> VoteCheck {
+  let federation_mode = vote_federation_mode(kind, object, community, local_site);
+  VoteCheck {
+    federation_mode,
+    rejection: vote_rejection(federation_mode, voter, community, federation, state),
+  }
+}
+
+fn vote_rejection(
+  federation_mode: FederationMode,
+  voter: &Voter,
+  community: &ApubCommunity,
+  federation: Option<&LocalSiteFederation>,
+  state: &VoteState,
+) -> Option<VoteRejectionReason> {
+  if federation.is_some_and(|f| f.read_only) {
+    return Some(VoteRejectionReason::ReadOnly);
+  }
+  // Bot votes are rejected, unless the community explicitly accepts them
+  if voter.bot_account && !community.allow_bot_votes {
+    return Some(VoteRejectionReason::BotAccount);
+  }
+  if !state.within_rate_limit {
+    return Some(VoteRejectionReason::RateLimited);
+  }
+  if state.banned {
+    return Some(VoteRejectionReason::BannedFromCommunity);
+  }
+  if state.post_locked {
+    return Some(VoteRejectionReason::PostLocked);
+  }
+  // Remote votes in new communities are rejected, so that they can't be brigaded before the mods
+  // have settled in
+  let min_community_age = federation
+    .map(|f| f.min_community_age_for_remote_votes)
+    .unwrap_or_default();
+  if !voter.local && is_younger_than(community.published, min_community_age) {
+    return Some(VoteRejectionReason::CommunityTooNew);
+  }
+  if !is_vote_accepted(
+    federation_mode,
+    voter.local,
+    state.followed_by_voter_instance,
+  ) {
+    return Some(VoteRejectionReason::FederationMode);
+  }
+  None
+}
+
+/// Returns true if a vote from the actor is accepted under the given federation mode. Whether the
+/// actor's instance follows the community only matters for [FederationMode::Followers].
+pub(crate) fn is_vote_accepted(
+  mode: FederationMode,
+  actor_local: bool,
+  followed_by_actor_instance: bool,
+) -> bool {
+  match mode {
+    FederationMode::All => true,
+    FederationMode::Local => actor_local,
+    FederationMode::Followers => actor_local || followed_by_actor_instance,
+    FederationMode::Disable => false,
+  }
+}
+
+/// Returns true if the actor already has a vote with the same score for the object. The score is
+/// the one which would be stored for the new vote, which is 0 for young accounts. A vote which is
+/// still waiting in the batch is newer than the stored one, so it is compared instead.
+async fn is_duplicate_vote(
+  score: i16,
+  actor: &ApubPerson,
+  object: &PostOrComment,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<bool> {
+  let current = match object {
+    PostOrComment::Post(p) => match batched_post_score(actor.id, p.id).await {
+      Some(batched) => batched,
+      None => PostLike::read(&mut context.pool(), actor.id, p.id)
+        .await?
+        .map(|l| l.score),
+    },
+    PostOrComment::Comment(c) => match batched_comment_score(actor.id, c.id).await {
+      Some(batched) => batched,
+      None => CommentLike::read(&mut context.pool(), actor.id, c.id)
+        .await?
+        .map(|l| l.score),
+    },
+  };
+  Ok(current == Some(score))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use crate::{
+    api::test::{create_user, MockRemote},
+    http::shared_inbox,
+    objects::{
+      comment::ApubComment,
+      community::tests::parse_lemmy_community,
+      instance::ApubSite,
+      person::tests::parse_lemmy_person,
+      post::ApubPost,
+    },
+    protocol::{activities::voting::undo_vote::UndoVote, tests::file_to_json_object},
+  };
+  use activitypub_federation::{
+    activity_sending::SendActivityTask,
+    http_signatures::generate_actor_keypair,
+    kinds::activity::UndoType,
+    traits::Object,
+  };
+  use actix_web::{http::StatusCode, test::TestRequest};
+  use chrono::TimeDelta;
+  use lemmy_db_schema::{
+    aggregates::structs::{CommentAggregates, PostAggregates},
+    newtypes::{CommentId, DbUrl, PostId},
+    source::{
+      activity::ReceivedActivity,
+      comment::Comment,
+      community::{
+        Community,
+        CommunityFollower,
+        CommunityFollowerForm,
+        CommunityPersonBan,
+        CommunityPersonBanForm,
+        CommunityUpdateForm,
+      },
+      instance::Instance,
+      local_site::LocalSiteInsertForm,
+      local_site_federation::{LocalSiteFederationInsertForm, LocalSiteFederationUpdateForm},
+      person::{Person, PersonInsertForm, PersonUpdateForm},
+      post::{Post, PostUpdateForm},
+      site::Site,
+    },
+    traits::{Bannable, Crud, Followable},
+  };
+  use lemmy_utils::error::LemmyErrorType;
+  use pretty_assertions::assert_eq;
+  use serial_test::serial;
+  use std::sync::{Arc, Mutex};
+
+  /// Votes are only received with a local site and its federation settings, like on a running
+  /// instance. Delete them again with [LocalSite::delete].
+  pub(crate) async fn create_local_site(
+    site: &ApubSite,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<()> {
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    let local_site = LocalSite::create(&mut context.pool(), &local_site_form).await?;
+    let federation_form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .build();
+    LocalSiteFederation::create(&mut context.pool(), &federation_form).await?;
+    Ok(())
+  }
+
+  fn new_vote(kind: VoteType, actor: &ApubPerson, object_id: &DbUrl) -> LemmyResult<Vote> {
+    Ok(Vote {
+      actor: actor.id().into(),
+      object: object_id.clone().into(),
+      kind: kind.clone(),
+      id: generate_activity_id(kind, "https://enterprise.lemmy.ml")?,
+      audience: None,
+      published: None,
+    })
+  }
+
+  async fn receive_vote(
+    kind: VoteType,
+    actor: &ApubPerson,
+    object_id: &DbUrl,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<()> {
+    new_vote(kind, actor, object_id)?.receive(context).await
+  }
+
+  async fn receive_undo_vote(
+    actor: &ApubPerson,
+    object_id: &DbUrl,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<()> {
+    let undo = UndoVote {
+      actor: actor.id().into(),
+      object: new_vote(VoteType::Like, actor, object_id)?,
+      kind: UndoType::Undo,
+      id: generate_activity_id(UndoType::Undo, "https://enterprise.lemmy.ml")?,
+      audience: None,
+      published: None,
+    };
+    undo.receive(context).await
+  }
+
+  async fn lock_post(post: &ApubPost, context: &Data<LemmyContext>) -> LemmyResult<()> {
+    let form = PostUpdateForm {
+      locked: Some(true),
+      ..Default::default()
+    };
+    Post::update(&mut context.pool(), post.id, &form).await?;
+    Ok(())
+  }
+
+  async fn post_votes(post_id: PostId, context: &Data<LemmyContext>) -> LemmyResult<(i64, i64)> {
+    let aggregates = PostAggregates::read(&mut context.pool(), post_id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindPost)?;
+    Ok((aggregates.upvotes, aggregates.downvotes))
+  }
+
+  async fn comment_votes(
+    comment_id: CommentId,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<(i64, i64)> {
+    let aggregates = CommentAggregates::read(&mut context.pool(), comment_id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindComment)?;
+    Ok((aggregates.upvotes, aggregates.downvotes))
+  }
+
+  #[test]
+  fn test_is_vote_accepted() {
+    for (actor_local, followed) in [(false, false), (false, true), (true, false), (true, true)] {
+      assert!(is_vote_accepted(FederationMode::All, actor_local, followed));
+      assert_eq!(
+        actor_local,
+        is_vote_accepted(FederationMode::Local, actor_local, followed)
+      );
+      assert_eq!(
+        actor_local || followed,
+        is_vote_accepted(FederationMode::Followers, actor_local, followed)
+      );
+      assert!(!is_vote_accepted(
+        FederationMode::Disable,
+        actor_local,
+        followed
+      ));
+    }
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_decide_federated_vote() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    let object = PostOrComment::Post(post.clone());
+    let voter = Voter {
+      person_id: Some(person.id),
+      local: false,
+      instance_id: Some(person.instance_id),
+      bot_account: false,
+    };
+    let state = VoteState {
+      within_rate_limit: true,
+      banned: false,
+      post_locked: false,
+      followed_by_voter_instance: false,
+    };
+    let decide = |community: &ApubCommunity, state: &VoteState| {
+      decide_federated_vote(
+        &VoteType::Like,
+        &voter,
+        &object,
+        community,
+        None,
+        None,
+        state,
+      )
+      .rejection
+    };
+
+    // only the loaded state is checked, the voter isn't actually banned and the post isn't locked
+    assert_eq!(None, decide(&community, &state));
+    let banned = VoteState {
+      banned: true,
+      ..state
+    };
+    assert_eq!(
+      Some(VoteRejectionReason::BannedFromCommunity),
+      decide(&community, &banned)
+    );
+    let locked = VoteState {
+      post_locked: true,
+      ..state
+    };
+    assert_eq!(
+      Some(VoteRejectionReason::PostLocked),
+      decide(&community, &locked)
+    );
+    // votes over the rate limit are dropped before anything else is checked
+    let rate_limited = VoteState {
+      within_rate_limit: false,
+      ..banned
+    };
+    assert_eq!(
+      Some(VoteRejectionReason::RateLimited),
+      decide(&community, &rate_limited)
+    );
+
+    // with votes only from followers, it depends on whether the voter's instance follows
+    let form = CommunityUpdateForm {
+      post_upvotes: Some(Some(FederationMode::Followers)),
+      ..Default::default()
+    };
+    let followers_only: ApubCommunity = Community::update(&mut context.pool(), community.id, &form)
+      .await?
+      .into();
+    assert_eq!(
+      Some(VoteRejectionReason::FederationMode),
+      decide(&followers_only, &state)
+    );
+    let followed = VoteState {
+      followed_by_voter_instance: true,
+      ..state
+    };
+    assert_eq!(None, decide(&followers_only, &followed));
+
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_community_disables_downvotes() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+
+    // downvotes are allowed by the site
+    receive_vote(VoteType::Dislike, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 1), post_votes(post.id, &context).await?);
+
+    // once the community disables them, an incoming downvote only undoes the existing vote
+    let form = CommunityUpdateForm {
+      post_downvotes: Some(Some(FederationMode::Disable)),
+      ..Default::default()
+    };
+    Community::update(&mut context.pool(), community.id, &form).await?;
+    receive_vote(VoteType::Dislike, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+
+    // upvotes are still accepted
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_vote_mode_grace_period() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    let local_site = LocalSite::create(&mut context.pool(), &local_site_form).await?;
+    let federation_form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .vote_mode_grace_period(Some(5))
+      .build();
+    let federation = LocalSiteFederation::create(&mut context.pool(), &federation_form).await?;
+    // the community disabled downvotes just now
+    let form = CommunityUpdateForm {
+      post_downvotes: Some(Some(FederationMode::Disable)),
+      ..Default::default()
+    };
+    let community: ApubCommunity = Community::update(&mut context.pool(), community.id, &form)
+      .await?
+      .into();
+    let changed = community
+      .vote_mode_updated
+      .ok_or(LemmyErrorType::CouldntUpdateCommunity)?;
+    let minute = TimeDelta::try_minutes(1).expect("TimeDelta out of bounds");
+    let downvote = |published| -> LemmyResult<Vote> {
+      Ok(Vote {
+        published,
+        ..new_vote(VoteType::Dislike, &person, &post.ap_id)?
+      })
+    };
+
+    // votes without a timestamp, or sent long before the change, are rejected
+    for published in [None, Some(changed - minute * 10)] {
+      downvote(published)?.receive(&context).await?;
+      assert_eq!((0, 0), post_votes(post.id, &context).await?);
+    }
+
+    // a vote sent shortly before the change was on its way already, so it is accepted
+    downvote(Some(changed - minute))?.receive(&context).await?;
+    assert_eq!((0, 1), post_votes(post.id, &context).await?);
+
+    // other edits of the community don't count as a change of the mode, so one sent after the
+    // change is rejected, which also undoes the previous vote
+    let form = CommunityUpdateForm {
+      description: Some(Some("edited".to_string())),
+      updated: Some(Some(changed + minute * 2)),
+      ..Default::default()
+    };
+    let community: ApubCommunity = Community::update(&mut context.pool(), community.id, &form)
+      .await?
+      .into();
+    assert_eq!(Some(changed), community.vote_mode_updated);
+    downvote(Some(changed + minute))?.receive(&context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+
+    // votes received long after the change are rejected, whatever time they claim to be sent at
+    let object = PostOrComment::Post(post.clone());
+    let is_accepted = |published, received| {
+      is_sent_before_mode_change(
+        &VoteType::Dislike,
+        &object,
+        &community,
+        &local_site,
+        &federation,
+        Some(published),
+        received,
+      )
+    };
+    assert!(is_accepted(changed - minute, changed + minute));
+    assert!(!is_accepted(changed - minute, changed + minute * 10));
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_community_allows_bot_votes() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+    let form = PersonUpdateForm {
+      bot_account: Some(true),
+      ..Default::default()
+    };
+    let bot: ApubPerson = Person::update(&mut context.pool(), person.id, &form)
+      .await?
+      .into();
+
+    // by default bot votes are rejected
+    let res = receive_vote(VoteType::Like, &bot, &post.ap_id, &context).await;
+    assert_eq!(
+      Some(LemmyErrorType::InvalidBotAction),
+      res.err().map(|e| e.error_type)
+    );
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+
+    // the community can choose to accept them
+    let form = CommunityUpdateForm {
+      allow_bot_votes: Some(true),
+      ..Default::default()
+    };
+    Community::update(&mut context.pool(), community.id, &form).await?;
+    receive_vote(VoteType::Like, &bot, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_community_accepts_votes_from_followers() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+
+    let form = CommunityUpdateForm {
+      post_upvotes: Some(Some(FederationMode::Followers)),
+      ..Default::default()
+    };
+    Community::update(&mut context.pool(), community.id, &form).await?;
+
+    // nobody on the actor's instance follows the community, so the vote is ignored
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+
+    // a pending follow is not enough
+    let follower_form = PersonInsertForm::builder()
+      .name("follower".into())
+      .public_key("pubkey".to_string())
+      .instance_id(person.instance_id)
+      .build();
+    let follower = Person::create(&mut context.pool(), &follower_form).await?;
+    let mut follow_form = CommunityFollowerForm {
+      community_id: community.id,
+      person_id: follower.id,
+      pending: true,
+    };
+    CommunityFollower::follow(&mut context.pool(), &follow_form).await?;
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+
+    // once another user of the same instance follows the community, the vote is accepted
+    follow_form.pending = false;
+    CommunityFollower::follow(&mut context.pool(), &follow_form).await?;
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), follower.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_rejected_vote_is_logged() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    let local_site = LocalSite::create(&mut context.pool(), &local_site_form).await?;
+    let federation_form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .log_rejected_votes(Some(true))
+      .build();
+    LocalSiteFederation::create(&mut context.pool(), &federation_form).await?;
+    let form = CommunityUpdateForm {
+      post_downvotes: Some(Some(FederationMode::Disable)),
+      ..Default::default()
+    };
+    Community::update(&mut context.pool(), community.id, &form).await?;
+
+    receive_vote(VoteType::Dislike, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+
+    let rejections = FederatedVoteRejection::list(&mut context.pool(), None, None).await?;
+    let [rejection] = rejections.as_slice() else {
+      Err(LemmyErrorType::CouldntFindObject)?
+    };
+    assert_eq!(person.actor_id, rejection.actor_id);
+    assert_eq!(post.ap_id, rejection.object_id);
+    assert_eq!(-1, rejection.score);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_duplicate_vote_is_skipped() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    let like = PostLike::read(&mut context.pool(), person.id, post.id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindPost)?;
+
+    // the same vote again doesn't rewrite the stored like
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    let like_after_duplicate = PostLike::read(&mut context.pool(), person.id, post.id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindPost)?;
+    assert_eq!(like, like_after_duplicate);
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    // changing the vote is still applied
+    receive_vote(VoteType::Dislike, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 1), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  /// Delivers the vote to the shared inbox like a remote instance would, signed by the actor. The
+  /// signed request is captured by a mock server and then passed to the inbox handler.
+  async fn deliver_vote(
+    vote: &Vote,
+    actor: &ApubPerson,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<StatusCode> {
+    let captured = Arc::new(Mutex::new(String::new()));
+    let captured_ = captured.clone();
+    let mut remote = MockRemote::bind().await?;
+    remote.serve(move |request| {
+      if let Ok(mut captured) = captured_.lock() {
+        *captured = request.to_string();
+      }
+      Some("HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string())
+    });
+    let inbox = Url::parse(&format!("{}/inbox", remote.base))?;
+    let remote_context = remote.context().await?;
+    for task in SendActivityTask::prepare(vote, actor, vec![inbox], &remote_context).await? {
+      task.sign_and_send(&remote_context).await?;
+    }
+
+    let request = captured.lock().map(|r| r.clone()).unwrap_or_default();
+    let headers = request.split("\r\n\r\n").next().unwrap_or_default();
+    let mut incoming = TestRequest::post().uri("/inbox");
+    for header in headers.lines().skip(1) {
+      if let Some((name, value)) = header.split_once(": ") {
+        incoming = incoming.append_header((name, value));
+      }
+    }
+    let body = serde_json::to_vec(vote)?;
+    let context = context.reset_request_count();
+    let res = shared_inbox(incoming.to_http_request(), body.into(), context).await?;
+    Ok(res.status())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_redelivered_vote_is_acknowledged() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+    // a key to sign the deliveries of the remote person
+    let keypair = generate_actor_keypair()?;
+    let form = PersonUpdateForm {
+      public_key: Some(keypair.public_key),
+      private_key: Some(Some(keypair.private_key)),
+      ..Default::default()
+    };
+    let person: ApubPerson = Person::update(&mut context.pool(), person.id, &form)
+      .await?
+      .into();
+    // the inbox only accepts activities in remote communities with local followers
+    let follower = create_user("redelivery_follower".to_string(), None, false, &context).await?;
+    let follow_form = CommunityFollowerForm {
+      community_id: community.id,
+      person_id: follower.person.id,
+      pending: false,
+    };
+    CommunityFollower::follow(&mut context.pool(), &follow_form).await?;
+
+    let vote = new_vote(VoteType::Like, &person, &post.ap_id)?;
+    let status = deliver_vote(&vote, &person, &context).await?;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    // the same activity id again succeeds without any work. the object doesn't exist, so
+    // dereferencing it would fail.
+    let redelivered = Vote {
+      object: Url::parse("https://enterprise.lemmy.ml/post/999999")?.into(),
+      ..vote
+    };
+    let status = deliver_vote(&redelivered, &person, &context).await?;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Instance::delete(&mut context.pool(), follower.person.instance_id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_vote_stores_activity_id() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+
+    let vote = new_vote(VoteType::Like, &person, &post.ap_id)?;
+    let activity_id: DbUrl = vote.id.clone().into();
+    vote.receive(&context).await?;
+    let like = PostLike::read(&mut context.pool(), person.id, post.id).await?;
+    assert_eq!(Some(Some(activity_id)), like.map(|l| l.activity_ap_id));
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_young_account_vote_not_counted() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    let local_site = LocalSite::create(&mut context.pool(), &local_site_form).await?;
+    let federation_form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .min_account_age_for_full_vote(Some(30))
+      .build();
+    let federation = LocalSiteFederation::create(&mut context.pool(), &federation_form).await?;
+
+    // the account from the json was created years ago, so its vote counts fully
+    vote_post(
+      vote_score(&VoteType::Like, &person, Some(&federation)),
+      generate_activity_id(VoteType::Like, "https://enterprise.lemmy.ml")?.into(),
+      person.clone(),
+      &post,
+      &context,
+    )
+    .await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    // a vote from a brand-new account is stored, but doesn't change the score
+    let young_form = PersonInsertForm::builder()
+      .name("young".into())
+      .public_key("pubkey".to_string())
+      .instance_id(person.instance_id)
+      .build();
+    let young_person: ApubPerson = Person::create(&mut context.pool(), &young_form)
+      .await?
+      .into();
+    vote_post(
+      vote_score(&VoteType::Like, &young_person, Some(&federation)),
+      generate_activity_id(VoteType::Like, "https://enterprise.lemmy.ml")?.into(),
+      young_person.clone(),
+      &post,
+      &context,
+    )
+    .await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+    let young_like = PostLike::read(&mut context.pool(), young_person.id, post.id).await?;
+    assert_eq!(Some(0), young_like.map(|l| l.score));
+
+    // so the same vote again counts as a duplicate
+    let young_score = vote_score(&VoteType::Like, &young_person, Some(&federation));
+    let object = PostOrComment::Post(post.clone());
+    assert!(is_duplicate_vote(young_score, &young_person, &object, &context).await?);
+
+    // a minimum age reaching back further than can be represented makes every account too young
+    let federation = LocalSiteFederation {
+      min_account_age_for_full_vote: i32::MAX,
+      ..federation
+    };
+    assert_eq!(0, vote_score(&VoteType::Like, &person, Some(&federation)));
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), young_person.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_young_community_rejects_remote_votes() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    let local_site = LocalSite::create(&mut context.pool(), &local_site_form).await?;
+    let federation_form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .min_community_age_for_remote_votes(Some(30))
+      .build();
+    LocalSiteFederation::create(&mut context.pool(), &federation_form).await?;
+
+    // the community from the json was published years ago, so the vote is accepted
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    // in a brand-new community the remote vote is rejected, and the previous one undone
+    let form = CommunityUpdateForm {
+      published: Some(Utc::now()),
+      ..Default::default()
+    };
+    Community::update(&mut context.pool(), community.id, &form).await?;
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_votes_are_dropped_in_read_only_mode() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    let local_site = LocalSite::create(&mut context.pool(), &local_site_form).await?;
+    let federation_form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .read_only(Some(true))
+      .build();
+    LocalSiteFederation::create(&mut context.pool(), &federation_form).await?;
+
+    // the vote succeeds, but neither the vote nor its activity id are stored
+    let vote = new_vote(VoteType::Like, &person, &post.ap_id)?;
+    let activity_id: DbUrl = vote.id.clone().into();
+    vote.clone().receive(&context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+    assert!(PostLike::read(&mut context.pool(), person.id, post.id)
+      .await?
+      .is_none());
+    assert_eq!(
+      0,
+      ReceivedActivity::delete(&mut context.pool(), &activity_id).await?
+    );
+
+    // after the maintenance the same vote is applied
+    let form = LocalSiteFederationUpdateForm {
+      read_only: Some(false),
+      ..Default::default()
+    };
+    LocalSiteFederation::update(&mut context.pool(), &form).await?;
+    vote.receive(&context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    // undos are dropped as well
+    let form = LocalSiteFederationUpdateForm {
+      read_only: Some(true),
+      ..Default::default()
+    };
+    LocalSiteFederation::update(&mut context.pool(), &form).await?;
+    receive_undo_vote(&person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_rejected_vote_increments_counter() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+
+    let form = CommunityUpdateForm {
+      post_downvotes: Some(Some(FederationMode::Disable)),
+      ..Default::default()
+    };
+    Community::update(&mut context.pool(), community.id, &form).await?;
+    let rejected_downvotes = REJECTED_VOTES.with_label_values(&["post", "Dislike"]);
+    let rejected_upvotes = REJECTED_VOTES.with_label_values(&["post", "Like"]);
+    let downvotes_before = rejected_downvotes.get();
+    let upvotes_before = rejected_upvotes.get();
+
+    receive_vote(VoteType::Dislike, &person, &post.ap_id, &context).await?;
+    assert_eq!(downvotes_before + 1, rejected_downvotes.get());
+
+    // accepted votes aren't counted
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!(upvotes_before, rejected_upvotes.get());
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_vote_from_banned_user_is_discarded() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    // after the ban, new votes are discarded and the existing vote is removed
+    let ban_form = CommunityPersonBanForm {
+      community_id: community.id,
+      person_id: person.id,
+      expires: None,
+    };
+    CommunityPersonBan::ban(&mut context.pool(), &ban_form).await?;
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+    let like = PostLike::read(&mut context.pool(), person.id, post.id).await?;
+    assert!(like.is_none());
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_votes_over_instance_rate_limit_are_dropped() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    let local_site = LocalSite::create(&mut context.pool(), &local_site_form).await?;
+    let federation_form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .instance_vote_rate_limit(Some(2))
+      .build();
+    LocalSiteFederation::create(&mut context.pool(), &federation_form).await?;
+
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+    receive_vote(VoteType::Dislike, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 1), post_votes(post.id, &context).await?);
+
+    // the third vote within a minute is dropped, leaving the previous vote in place
+    let domain = person.actor_id.inner().domain().unwrap_or_default();
+    let rate_limited = RATE_LIMITED_VOTES.with_label_values(&[domain]);
+    let rate_limited_before = rate_limited.get();
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((0, 1), post_votes(post.id, &context).await?);
+    assert_eq!(rate_limited_before + 1, rate_limited.get());
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_vote_on_locked_post_is_ignored() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+
+    receive_vote(VoteType::Like, &person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    // once the post is locked, its score can't be changed anymore
+    lock_post(&post, &context).await?;
+    receive_vote(VoteType::Dislike, &person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+    receive_undo_vote(&person, &post.ap_id, &context).await?;
+    assert_eq!((1, 0), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_vote_on_comment_in_locked_post_is_ignored() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+    let url = Url::parse("https://enterprise.lemmy.ml/comment/38741")?;
+    let json = file_to_json_object("assets/lemmy/objects/note.json")?;
+    ApubComment::verify(&json, &url, &context).await?;
+    let comment = ApubComment::from_json(json, &context).await?;
+
+    receive_vote(VoteType::Like, &person, &comment.ap_id, &context).await?;
+    assert_eq!((1, 0), comment_votes(comment.id, &context).await?);
+
+    // locking the post also freezes the scores of its comments
+    lock_post(&post, &context).await?;
+    receive_vote(VoteType::Dislike, &person, &comment.ap_id, &context).await?;
+    assert_eq!((1, 0), comment_votes(comment.id, &context).await?);
+    receive_undo_vote(&person, &comment.ap_id, &context).await?;
+    assert_eq!((1, 0), comment_votes(comment.id, &context).await?);
+
+    Comment::delete(&mut context.pool(), comment.id).await?;
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_out_of_order_undo_vote() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+    let second = TimeDelta::try_seconds(1).expect("TimeDelta out of bounds");
+    let voted = Utc::now();
+    let vote = Vote {
+      published: Some(voted),
+      ..new_vote(VoteType::Like, &person, &post.ap_id)?
+    };
+    let undo = UndoVote {
+      actor: person.id().into(),
+      object: vote.clone(),
+      kind: UndoType::Undo,
+      id: generate_activity_id(UndoType::Undo, "https://enterprise.lemmy.ml")?,
+      audience: None,
+      published: Some(voted + second),
+    };
+
+    // the undo arrives before the vote it undoes, which is then ignored as outdated
+    undo.receive(&context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+    vote.receive(&context).await?;
+    assert_eq!((0, 0), post_votes(post.id, &context).await?);
+
+    // a vote sent after the undo is applied normally
+    let vote = Vote {
+      published: Some(voted + second + second),
+      ..new_vote(VoteType::Dislike, &person, &post.ap_id)?
+    };
+    vote.receive(&context).await?;
+    assert_eq!((0, 1), post_votes(post.id, &context).await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+}
@@ -7,10 +7,15 @@ use lemmy_db_schema::{
 
 pub mod list_comments;
 pub mod list_posts;
+pub mod preview_federated_vote;
 pub mod read_community;
 pub mod read_person;
+pub mod replay_federated_vote;
 pub mod resolve_object;
+pub mod resolve_objects;
 pub mod search;
+#[cfg(test)]
+pub(crate) mod test;
 pub mod user_settings_backup;
 
 /// Returns default listing type, depending if the query is for frontpage or community.
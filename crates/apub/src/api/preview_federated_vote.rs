@@ -0,0 +1,159 @@
+use crate::{
+  activities::voting::vote::{check_federated_vote, Voter},
+  fetcher::post_or_comment::PostOrComment,
+  protocol::{activities::voting::vote::VoteType, InCommunity},
+};
+use activitypub_federation::{config::Data, fetch::object_id::ObjectId};
+use actix_web::web::{Json, Query};
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::{PreviewFederatedVote, PreviewFederatedVoteResponse},
+  utils::is_admin,
+};
+use lemmy_db_schema::{
+  source::{
+    instance::Instance,
+    local_site::LocalSite,
+    local_site_federation::LocalSiteFederation,
+    person::Person,
+  },
+  traits::ApubActor,
+};
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::{LemmyErrorExt, LemmyErrorType, LemmyResult};
+use url::Url;
+
+/// Checks whether a vote from the given actor would be accepted by the inbox, using the same
+/// decision as [crate::activities::voting::vote]. Only local data is read, nothing is fetched.
+#[tracing::instrument(skip(context))]
+pub async fn preview_federated_vote(
+  data: Query<PreviewFederatedVote>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<PreviewFederatedVoteResponse>> {
+  // Only let admins check the vote federation settings
+  is_admin(&local_user_view)?;
+
+  let kind = VoteType::try_from(data.score)?;
+  let object_id = Url::parse(&data.object_id).with_lemmy_type(LemmyErrorType::InvalidUrl)?;
+  let object = ObjectId::<PostOrComment>::from(object_id)
+    .dereference_local(&context)
+    .await
+    .map_err(|_| LemmyErrorType::CouldntFindObject)?;
+  let community = object.community(&context).await?;
+  let actor_id = Url::parse(&data.actor_id).with_lemmy_type(LemmyErrorType::InvalidUrl)?;
+
+  // The actor may not be known yet, in that case its domain is used
+  let voter = match Person::read_from_apub_id(&mut context.pool(), &actor_id.clone().into()).await?
+  {
+    Some(person) => Voter {
+      person_id: Some(person.id),
+      local: person.local,
+      instance_id: Some(person.instance_id),
+      bot_account: person.bot_account,
+    },
+    None => {
+      let domain = actor_id.domain().ok_or(LemmyErrorType::InvalidUrl)?;
+      let instance = Instance::read_from_domain(&mut context.pool(), domain).await?;
+      Voter {
+        person_id: None,
+        local: domain == context.settings().get_hostname_without_port()?,
+        instance_id: instance.map(|i| i.id),
+        bot_account: false,
+      }
+    }
+  };
+
+  let local_site = LocalSite::read(&mut context.pool()).await?;
+  let federation = LocalSiteFederation::read(&mut context.pool()).await?;
+  // The preview doesn't count towards the rate limit of the voter's instance
+  let check = check_federated_vote(
+    &kind,
+    &voter,
+    &object,
+    &community,
+    Some(&federation),
+    Some(&local_site),
+    false,
+    &context,
+  )
+  .await?;
+
+  Ok(Json(PreviewFederatedVoteResponse {
+    federation_mode: check.federation_mode,
+    accepted: check.rejection.is_none(),
+    rejection: check.rejection,
+  }))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+  use super::*;
+  use crate::api::test::TestInstance;
+  use lemmy_api_common::site::VoteRejectionReason;
+  use lemmy_db_schema::{
+    source::{
+      community::{CommunityPersonBan, CommunityPersonBanForm},
+      post::{Post, PostUpdateForm},
+    },
+    traits::{Bannable, Crud},
+  };
+  use serial_test::serial;
+
+  #[tokio::test]
+  #[serial]
+  async fn test_preview_federated_vote_rejection() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (local, remote) = TestInstance::local_and_remote("preview.example", &context).await?;
+    let admin = local.create_user("preview_admin", true, &context).await?;
+    let voter = remote.create_person("preview_voter", &context).await?;
+    let community = local.create_community("preview", &context).await?;
+    let post = local
+      .create_post("preview_post", &admin.person, &community, &context)
+      .await?;
+
+    let preview = |actor_id: String| {
+      let query = PreviewFederatedVote {
+        object_id: post.ap_id.to_string(),
+        actor_id,
+        score: 1,
+      };
+      preview_federated_vote(Query(query), context.reset_request_count(), admin.clone())
+    };
+    let res = preview(voter.actor_id.to_string()).await?.0;
+    assert!(res.accepted);
+    assert_eq!(None, res.rejection);
+
+    // the same checks as in the inbox apply, not only the federation mode
+    let ban_form = CommunityPersonBanForm {
+      community_id: community.id,
+      person_id: voter.id,
+      expires: None,
+    };
+    CommunityPersonBan::ban(&mut context.pool(), &ban_form).await?;
+    let res = preview(voter.actor_id.to_string()).await?.0;
+    assert!(!res.accepted);
+    assert_eq!(
+      Some(VoteRejectionReason::BannedFromCommunity),
+      res.rejection
+    );
+
+    // unknown actors are checked as well
+    let form = PostUpdateForm {
+      locked: Some(true),
+      ..Default::default()
+    };
+    Post::update(&mut context.pool(), post.id, &form).await?;
+    let res = preview("https://preview.example/u/unknown".to_string())
+      .await?
+      .0;
+    assert!(!res.accepted);
+    assert_eq!(Some(VoteRejectionReason::PostLocked), res.rejection);
+
+    remote.cleanup(&context).await?;
+    local.cleanup(&context).await?;
+    Ok(())
+  }
+}
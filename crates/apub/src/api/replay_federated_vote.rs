@@ -0,0 +1,137 @@
+use crate::protocol::activities::voting::vote::Vote;
+use activitypub_federation::{
+  config::Data,
+  protocol::verification::verify_domains_match,
+  traits::ActivityHandler,
+};
+use actix_web::web::Json;
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::ReplayFederatedVote,
+  utils::is_admin,
+  SuccessResponse,
+};
+use lemmy_db_schema::source::activity::ReceivedActivity;
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::{LemmyErrorExt, LemmyErrorType, LemmyResult};
+
+/// Processes a vote activity again, eg if it was dropped because of a bug. The activity is
+/// verified like in the inbox, except for the http signature which isn't available anymore. If it
+/// was received before, it isn't rejected as duplicate.
+#[tracing::instrument(skip(context))]
+pub async fn replay_federated_vote(
+  data: Json<ReplayFederatedVote>,
+  context: Data<LemmyContext>,
+  local_user_view: LocalUserView,
+) -> LemmyResult<Json<SuccessResponse>> {
+  // Without signature the activity could be made up, so only admins may replay votes
+  is_admin(&local_user_view)?;
+
+  let vote: Vote =
+    serde_json::from_str(&data.activity).with_lemmy_type(LemmyErrorType::InvalidActivity)?;
+  verify_domains_match(vote.id(), vote.actor())?;
+  vote.verify(&context).await?;
+  ReceivedActivity::delete(&mut context.pool(), &vote.id().clone().into()).await?;
+  vote.receive(&context).await?;
+
+  Ok(Json(SuccessResponse::default()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    activities::voting::vote::tests::create_local_site,
+    api::test::create_user,
+    objects::{
+      community::tests::parse_lemmy_community,
+      person::tests::parse_lemmy_person,
+      post::ApubPost,
+    },
+    protocol::tests::file_to_json_object,
+  };
+  use activitypub_federation::traits::Object;
+  use lemmy_db_schema::{
+    aggregates::structs::PostAggregates,
+    source::{
+      community::Community,
+      instance::Instance,
+      local_site::LocalSite,
+      person::Person,
+      post::Post,
+      site::Site,
+    },
+    traits::Crud,
+  };
+  use pretty_assertions::assert_eq;
+  use serial_test::serial;
+
+  #[tokio::test]
+  #[serial]
+  async fn test_replay_federated_vote() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let json = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let post = ApubPost::from_json(json, &context).await?;
+    create_local_site(&site, &context).await?;
+    let admin = create_user("replay_vote_admin".to_string(), None, true, &context).await?;
+    let user = create_user("replay_vote_user".to_string(), None, false, &context).await?;
+    let activity = serde_json::json!({
+      "actor": person.actor_id,
+      "object": post.ap_id,
+      "type": "Like",
+      "id": "https://enterprise.lemmy.ml/activities/like/replay",
+    })
+    .to_string();
+    let replay = || {
+      Json(ReplayFederatedVote {
+        activity: activity.clone(),
+      })
+    };
+    let score = || async {
+      let aggregates = PostAggregates::read(&mut context.pool(), post.id).await?;
+      LemmyResult::Ok(aggregates.map(|a| a.score))
+    };
+
+    // only admins can replay votes
+    let res = replay_federated_vote(replay(), context.reset_request_count(), user.clone()).await;
+    assert_eq!(
+      Some(LemmyErrorType::NotAnAdmin),
+      res.err().map(|e| e.error_type)
+    );
+    assert_eq!(Some(0), score().await?);
+
+    // the vote is applied, also when replayed again after it was received already
+    replay_federated_vote(replay(), context.reset_request_count(), admin.clone()).await?;
+    assert_eq!(Some(1), score().await?);
+    replay_federated_vote(replay(), context.reset_request_count(), admin.clone()).await?;
+    assert_eq!(Some(1), score().await?);
+
+    // activities whose id doesn't belong to the actor's instance are rejected
+    let activity = serde_json::json!({
+      "actor": person.actor_id,
+      "object": post.ap_id,
+      "type": "Dislike",
+      "id": "https://other.example/activities/dislike/replay",
+    })
+    .to_string();
+    let res = replay_federated_vote(
+      Json(ReplayFederatedVote { activity }),
+      context.reset_request_count(),
+      admin.clone(),
+    )
+    .await;
+    assert!(res.is_err());
+    assert_eq!(Some(1), score().await?);
+
+    LocalSite::delete(&mut context.pool()).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Instance::delete(&mut context.pool(), admin.person.instance_id).await?;
+    Instance::delete(&mut context.pool(), user.person.instance_id).await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,788 @@
+use crate::{
+  collections::community_outbox::wait_for_outbox_posts,
+  fetcher::{
+    search::{
+      fetch_raw_json,
+      is_blocked_by_person,
+      is_local_query,
+      is_object_blocked_by_person,
+      search_query_to_object_id,
+      search_query_to_object_id_local,
+      search_similar_actor_local,
+      SearchableObjects,
+    },
+    user_or_community::UserOrCommunity,
+  },
+};
+use activitypub_federation::config::Data;
+use actix_web::{
+  web::{Json, Query},
+  HttpRequest,
+};
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::{
+    InstanceFederationStatus,
+    ModActionType,
+    PersonRelationship,
+    ResolveObject,
+    ResolveObjectResponse,
+    ResolvedModAction,
+  },
+  utils::{check_private_instance, is_admin, is_younger_than},
+};
+use lemmy_db_schema::{
+  aggregates::structs::PersonAggregates,
+  newtypes::{CommentId, CommunityId, LocalUserId},
+  source::{
+    comment_edit::CommentEdit,
+    community::{Community, CommunityFollower},
+    instance::Instance,
+    local_site::LocalSite,
+    local_site_federation::LocalSiteFederation,
+    local_user::LocalUser,
+    person_block::PersonBlock,
+    resolve_object_log::{ResolveObjectLog, ResolveObjectLogForm},
+  },
+  traits::ApubActor,
+  utils::DbPool,
+  ResolveObjectType,
+  ResolveRemoteAccess,
+};
+use lemmy_db_views::{
+  post_view::PostQuery,
+  structs::{CommentView, LocalUserView, PostView, SiteView},
+};
+use lemmy_db_views_actor::structs::{CommunityModeratorView, CommunityView, PersonView};
+use lemmy_utils::{
+  error::{LemmyError, LemmyErrorExt, LemmyErrorExt2, LemmyErrorType, LemmyResult},
+  rate_limit::get_ip,
+};
+use once_cell::sync::Lazy;
+use prometheus::{default_registry, HistogramOpts, HistogramVec};
+use std::{
+  net::IpAddr,
+  ops::Deref,
+  time::{Duration, Instant},
+};
+use tracing::{field, Instrument};
+
+/// Time spent to find or fetch the objects of a resolve query, by whether it needed a network
+/// request and by the type of the first found object. Failed resolves are not counted.
+static RESOLVE_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+  let histogram = HistogramVec::new(
+    HistogramOpts::new(
+      "lemmy_resolve_object_duration_seconds",
+      "Time spent to resolve an object, for successful resolves",
+    ),
+    &["source", "object_type"],
+  )
+  .expect("create resolve duration histogram");
+  default_registry()
+    .register(Box::new(histogram.clone()))
+    .expect("register resolve duration histogram");
+  histogram
+});
+
+#[tracing::instrument(skip(context))]
+pub async fn resolve_object(
+  data: Query<ResolveObject>,
+  req: HttpRequest,
+  context: Data<LemmyContext>,
+  local_user_view: Option<LocalUserView>,
+) -> LemmyResult<Json<ResolveObjectResponse>> {
+  let local_site = LocalSite::read(&mut context.pool()).await?;
+  check_private_instance(&local_user_view, &local_site)?;
+  let ip_addr = get_ip(&req.connection_info());
+  resolve(&data, local_user_view.as_ref(), ip_addr, &context)
+    .await
+    .map(Json)
+}
+
+/// Resolves the query, without checking access to a private instance.
+///
+/// Remote lookups of unknown objects and forced refetches are rate limited by ip address, once the
+/// limit is reached only local objects are returned. The same applies to users who aren't allowed
+/// to fetch remote objects by the site settings.
+pub(crate) async fn resolve(
+  data: &ResolveObject,
+  local_user_view: Option<&LocalUserView>,
+  ip_addr: IpAddr,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<ResolveObjectResponse> {
+  let fetch_timeout = data
+    .timeout_ms
+    .map(|t| u64::try_from(t).map(Duration::from_millis))
+    .transpose()
+    .map_err(|_| LemmyErrorType::InvalidTimeout)?;
+  let impersonated = match data.as_user {
+    Some(local_user_id) => {
+      Some(read_impersonated_user(local_user_id, local_user_view, context).await?)
+    }
+    None => None,
+  };
+  // Permissions, rate limits and the log always use the actual user, while blocks and the
+  // returned views are those of the impersonated user
+  let view_as = impersonated.as_ref().or(local_user_view);
+  let is_admin = local_user_view
+    .map(|v| v.local_user.admin)
+    .unwrap_or_default();
+  let person_id = local_user_view.map(|v| v.person.id);
+  // Users don't want to see content of instances which they blocked, but admins may still resolve
+  // it for moderation. When impersonating, the blocks of the impersonated user apply.
+  let blocking_person_id = view_as.filter(|v| !v.local_user.admin).map(|v| v.person.id);
+  if let Some(person_id) = blocking_person_id {
+    if is_blocked_by_person(&data.q, person_id, context).await? {
+      Err(LemmyErrorType::CouldntFindObject)?
+    }
+  }
+
+  let request_count = context.request_count();
+  // Urls of this instance can only refer to local objects, so they are never fetched and don't
+  // count towards the rate limit
+  let is_local = is_local_query(&data.q, context)?;
+  let federation = LocalSiteFederation::read(&mut context.pool()).await?;
+  let may_fetch = !is_local && can_resolve_remote(local_user_view, &federation);
+  // only admins can force a refetch of objects which are already known.
+  let refresh = is_admin && data.refresh.unwrap_or_default();
+  let known_locally = may_fetch
+    && search_query_to_object_id_local(&data.q, context)
+      .await
+      .is_ok();
+  // Only lookups of unknown objects and forced refetches use up the rate limit. Known objects are
+  // at most refetched once they are outdated.
+  let allow_remote = may_fetch
+    && ((known_locally && !refresh) || context.rate_limit_cell().resolve_object().check(ip_addr));
+  // Moderation activities are only looked up for users who may view some of them
+  let mod_actions = match view_as {
+    Some(v) if allow_remote && !v.local_user.admin => {
+      CommunityView::is_mod_of_any_or_admin(&mut context.pool(), v.person.id).await?
+    }
+    Some(v) => v.local_user.admin,
+    None => false,
+  };
+  let started = Instant::now();
+  // Separate spans for fetching and converting show operators where slow resolves spend their
+  // time. Without a subscriber for them they cost next to nothing.
+  let fetch_span = tracing::info_span!(
+    "resolve_object_fetch",
+    object_type = field::Empty,
+    network = field::Empty
+  );
+  let (res, known_locally) = async {
+    if allow_remote {
+      // user is fully authenticated; allow remote lookups as well.
+      let res = search_query_to_object_id(
+        data.q.clone(),
+        fetch_timeout,
+        is_admin,
+        refresh,
+        mod_actions,
+        context,
+      )
+      .await;
+      (res, known_locally)
+    } else {
+      // user isn't authenticated, isn't allowed to fetch remote objects or is rate limited, or the
+      // query is local. only allow a local search.
+      let res = search_query_to_object_id_local(&data.q, context)
+        .await
+        .map(|o| vec![o])
+        .with_lemmy_type(LemmyErrorType::CouldntFindObject);
+      (res, true)
+    }
+  }
+  .instrument(fetch_span.clone())
+  .await;
+  let res = match res {
+    // fall back to the most similar local name, only if explicitly requested
+    Err(e)
+      if data.fuzzy.unwrap_or_default() && e.error_type == LemmyErrorType::CouldntFindObject =>
+    {
+      vec![search_similar_actor_local(&data.q, context).await?]
+    }
+    res => res?,
+  };
+  // Any outgoing request means that the object wasn't known locally, or was outdated or
+  // refreshed. Refetching an object which was already known doesn't count.
+  let network = context.request_count() > request_count;
+  fetch_span.record("network", network);
+  let object_type = res.first().map(SearchableObjects::object_type);
+  fetch_span.record("object_type", object_type);
+  RESOLVE_DURATION
+    .with_label_values(&[
+      if network { "remote" } else { "local" },
+      object_type.unwrap_or_default(),
+    ])
+    .observe(started.elapsed().as_secs_f64());
+  let resolved_remotely = network && !known_locally;
+  // Log remote fetches, so that admins can see what content users are pulling in. Impersonation
+  // is always logged, as it reveals the user's personal data.
+  let impersonated_person_id = impersonated.as_ref().map(|v| v.person.id);
+  if let (true, Some(person_id)) = (
+    resolved_remotely || impersonated_person_id.is_some(),
+    person_id,
+  ) {
+    let form = ResolveObjectLogForm {
+      query: data.q.clone(),
+      person_id,
+      object_type: res.first().and_then(SearchableObjects::resolve_type),
+      impersonated_person_id,
+    };
+    // The log is only for admins, so a failure to write it shouldn't fail the resolve
+    ResolveObjectLog::create(&mut context.pool(), &form)
+      .await
+      .map_err(|e| tracing::warn!("Failed to log resolved object {}: {e}", data.q))
+      .ok();
+  }
+  // A query may match several objects, eg a person and a community with the same name. Leave out
+  // the ones of other types, so that the first match has the expected type.
+  let mut res = res;
+  res.retain(|o| is_expected_type(o, data.expected_type));
+  // The query may also lead to objects of blocked instances, eg through a bare name or a redirect
+  if let Some(person_id) = blocking_person_id {
+    let mut unblocked = vec![];
+    for object in res {
+      if !is_object_blocked_by_person(&object, person_id, context).await? {
+        unblocked.push(object);
+      }
+    }
+    res = unblocked;
+  }
+  let verbose = is_admin && data.verbose.unwrap_or_default();
+  let raw = is_admin && data.raw.unwrap_or_default();
+  let hide_nsfw = view_as.is_none() && federation.hide_nsfw_from_resolve;
+
+  let convert_span = tracing::info_span!("resolve_object_convert", matches = res.len());
+  async {
+    if data.all_matches.unwrap_or_default() {
+      let mut matches = vec![];
+      let mut access_denied = None;
+      for object in res {
+        let raw_json = resolve_raw_json(&object, raw, context).await;
+        // Skip objects which the user isn't allowed to see
+        match convert_response(object, view_as, hide_nsfw, &mut context.pool()).await {
+          Ok(mut m) => {
+            add_includes(&mut m, data, view_as, is_admin, context).await?;
+            matches.push(ResolveObjectResponse {
+              raw_json,
+              resolved_remotely,
+              ..m
+            })
+          }
+          Err(e) if e.error_type == LemmyErrorType::CouldntFindObject => {}
+          Err(e) if e.error_type == LemmyErrorType::ResolvedObjectAccessDenied => {
+            access_denied = Some(e)
+          }
+          Err(e) => return Err(e),
+        }
+      }
+      if matches.is_empty() {
+        return Err(match access_denied {
+          Some(e) => hide_access_denied(e, verbose),
+          None => LemmyErrorType::CouldntFindObject.into(),
+        });
+      }
+      Ok(ResolveObjectResponse {
+        matches: Some(matches),
+        resolved_remotely,
+        ..Default::default()
+      })
+    } else {
+      let object = res
+        .into_iter()
+        .next()
+        .ok_or(LemmyErrorType::CouldntFindObject)?;
+      let raw_json = resolve_raw_json(&object, raw, context).await;
+      let mut res = convert_response(object, view_as, hide_nsfw, &mut context.pool())
+        .await
+        .map_err(|e| hide_access_denied(e, verbose))?;
+      add_includes(&mut res, data, view_as, is_admin, context).await?;
+      // Known communities aren't fetched again, so there is nothing to wait for
+      if resolved_remotely {
+        prefetch_community_posts(&res, data.prefetch_posts).await;
+      }
+      Ok(ResolveObjectResponse {
+        raw_json,
+        resolved_remotely,
+        ..res
+      })
+    }
+  }
+  .instrument(convert_span)
+  .await
+}
+
+/// Reads the user for `as_user`. Only admins may resolve as another user.
+async fn read_impersonated_user(
+  local_user_id: LocalUserId,
+  local_user_view: Option<&LocalUserView>,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<LocalUserView> {
+  let admin = local_user_view.ok_or(LemmyErrorType::NotLoggedIn)?;
+  is_admin(admin)?;
+  let user = LocalUserView::read(&mut context.pool(), local_user_id)
+    .await?
+    .ok_or(LemmyErrorType::CouldntFindPerson)?;
+  Ok(user)
+}
+
+/// Returns true if the user may fetch remote objects. Anonymous users never can, admins always.
+fn can_resolve_remote(
+  local_user_view: Option<&LocalUserView>,
+  federation: &LocalSiteFederation,
+) -> bool {
+  let Some(local_user_view) = local_user_view else {
+    return false;
+  };
+  if local_user_view.local_user.admin {
+    return true;
+  }
+  if is_younger_than(
+    local_user_view.person.published,
+    federation.resolve_remote_min_account_age,
+  ) {
+    return false;
+  }
+  match federation.resolve_remote_access {
+    ResolveRemoteAccess::All => true,
+    ResolveRemoteAccess::VerifiedEmail => local_user_view.local_user.email_verified,
+    ResolveRemoteAccess::Admins => false,
+  }
+}
+
+/// Fetches the json of the object from its origin instance if `raw` was requested. A failed fetch
+/// only leaves out the json, as the object itself was resolved already.
+async fn resolve_raw_json(
+  object: &SearchableObjects,
+  raw: bool,
+  context: &Data<LemmyContext>,
+) -> Option<String> {
+  if !raw {
+    return None;
+  }
+  fetch_raw_json(object, context)
+    .await
+    .map_err(|e| tracing::warn!("Failed to fetch raw json of {}: {e}", object.ap_id()))
+    .ok()
+    .flatten()
+}
+
+/// Maximum number of posts which can be waited for with `prefetch_posts`.
+const MAX_PREFETCH_POSTS: usize = 20;
+
+/// How long to wait for prefetched posts before returning the community.
+const PREFETCH_WAIT: Duration = Duration::from_secs(1);
+
+/// Waits briefly for the first posts of a newly fetched remote community, so that they can already
+/// be shown. Its outbox is fetched in the background anyway, and keeps being received afterwards.
+async fn prefetch_community_posts(res: &ResolveObjectResponse, prefetch_posts: Option<i64>) {
+  let limit = prefetch_posts
+    .and_then(|p| usize::try_from(p).ok())
+    .unwrap_or_default()
+    .min(MAX_PREFETCH_POSTS);
+  let Some(community) = res.community.as_ref().map(|c| &c.community) else {
+    return;
+  };
+  if limit == 0 || community.local {
+    return;
+  }
+  wait_for_outbox_posts(community.id, limit, PREFETCH_WAIT).await;
+}
+
+/// Adds the data which the query asked for besides the object, like the context of a comment, to
+/// the response of one resolved object. Admins always get the federation status.
+async fn add_includes(
+  res: &mut ResolveObjectResponse,
+  data: &ResolveObject,
+  view_as: Option<&LocalUserView>,
+  is_admin: bool,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<()> {
+  if data.include_context.unwrap_or_default() {
+    add_comment_context(res, view_as, &mut context.pool()).await?;
+  }
+  if data.include_relationship.unwrap_or_default() {
+    add_person_relationship(res, view_as, &mut context.pool()).await?;
+  }
+  if data.include_crossposts.unwrap_or_default() {
+    add_crossposts(res, view_as, &mut context.pool()).await?;
+  }
+  if data.include_edit_history.unwrap_or_default() {
+    add_edit_history(res, view_as, &mut context.pool()).await?;
+  }
+  if is_admin {
+    add_federation_status(res, context).await?;
+  }
+  Ok(())
+}
+
+/// Maximum number of parent comments returned with `include_context`.
+const MAX_CONTEXT_PARENTS: usize = 10;
+
+/// Adds the post and the closest parent comments of a resolved comment to the response. Does
+/// nothing for other objects.
+async fn add_comment_context(
+  res: &mut ResolveObjectResponse,
+  local_user_view: Option<&LocalUserView>,
+  pool: &mut DbPool<'_>,
+) -> LemmyResult<()> {
+  let Some(comment) = &res.comment else {
+    return Ok(());
+  };
+  let user_id = local_user_view.map(|v| v.person.id);
+  let is_admin = local_user_view.is_some_and(|v| v.local_user.admin);
+  let post_id = comment.post.id;
+  // The path contains the ids of all parents, after the leading 0 and before the comment itself
+  let comment_id = comment.comment.id;
+  let parent_ids: Vec<_> = comment
+    .comment
+    .path
+    .0
+    .split('.')
+    .skip(1)
+    .filter_map(|id| id.parse().ok().map(CommentId))
+    .filter(|id| *id != comment_id)
+    .collect();
+  let skip = parent_ids.len().saturating_sub(MAX_CONTEXT_PARENTS);
+
+  let mut parents = vec![];
+  for parent_id in parent_ids.into_iter().skip(skip) {
+    if let Some(parent) = CommentView::read(pool, parent_id, user_id).await? {
+      parents.push(parent);
+    }
+  }
+  res.comment_post = PostView::read(pool, post_id, user_id, is_admin).await?;
+  res.parent_comments = Some(parents);
+  Ok(())
+}
+
+/// Maximum number of posts returned with `include_crossposts`.
+const MAX_CROSSPOSTS: usize = 10;
+
+/// Adds the other posts with the same url as a resolved post to the response, like the crossposts
+/// of get_post. Does nothing for other objects.
+async fn add_crossposts(
+  res: &mut ResolveObjectResponse,
+  local_user_view: Option<&LocalUserView>,
+  pool: &mut DbPool<'_>,
+) -> LemmyResult<()> {
+  let Some(post) = res.post.as_ref().map(|p| &p.post) else {
+    return Ok(());
+  };
+  let Some(url) = &post.url else {
+    res.crossposts = Some(vec![]);
+    return Ok(());
+  };
+  let site = SiteView::read_local(pool)
+    .await?
+    .ok_or(LemmyErrorType::LocalSiteNotSetup)?
+    .site;
+  // One more, in case the post itself is among them
+  let mut crossposts = PostQuery {
+    url_search: Some(url.inner().as_str().into()),
+    local_user: local_user_view,
+    limit: Some(i64::try_from(MAX_CROSSPOSTS)? + 1),
+    ..Default::default()
+  }
+  .list(&site, pool)
+  .await?;
+  crossposts.retain(|x| x.post.id != post.id);
+  crossposts.truncate(MAX_CROSSPOSTS);
+  res.crossposts = Some(crossposts);
+  Ok(())
+}
+
+/// Adds the previous versions of a resolved comment to the response, if the user moderates its
+/// community or is an admin. Does nothing for other objects.
+async fn add_edit_history(
+  res: &mut ResolveObjectResponse,
+  local_user_view: Option<&LocalUserView>,
+  pool: &mut DbPool<'_>,
+) -> LemmyResult<()> {
+  let (Some(comment), Some(local_user_view)) = (&res.comment, local_user_view) else {
+    return Ok(());
+  };
+  let person_id = local_user_view.person.id;
+  if CommunityView::is_mod_or_admin(pool, person_id, comment.community.id).await? {
+    res.edit_history = Some(CommentEdit::list(pool, comment.comment.id).await?);
+  }
+  Ok(())
+}
+
+/// Adds how a resolved person relates to the logged in user to the response. Does nothing for
+/// other objects, or without login.
+async fn add_person_relationship(
+  res: &mut ResolveObjectResponse,
+  local_user_view: Option<&LocalUserView>,
+  pool: &mut DbPool<'_>,
+) -> LemmyResult<()> {
+  let (Some(person), Some(local_user_view)) = (&res.person, local_user_view) else {
+    return Ok(());
+  };
+  let viewer_id = local_user_view.person.id;
+  let person_id = person.person.id;
+  res.person_relationship = Some(PersonRelationship {
+    is_blocked: PersonBlock::read(pool, viewer_id, person_id).await?,
+    shared_communities: CommunityFollower::count_shared(pool, viewer_id, person_id).await?,
+  });
+  Ok(())
+}
+
+/// Sets whether the instance of a remote object is allowed or blocked for federation. The lists
+/// are read from the database instead of the federation cache, so that admins see recent changes.
+async fn add_federation_status(
+  res: &mut ResolveObjectResponse,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<()> {
+  let Some(domain) = res.ap_id.as_ref().and_then(|a| a.inner().domain()) else {
+    return Ok(());
+  };
+  if domain == context.settings().get_hostname_without_port()? {
+    return Ok(());
+  }
+  let matches = |instances: &[Instance]| {
+    instances
+      .iter()
+      .any(|i| i.domain.eq_ignore_ascii_case(domain))
+  };
+  let blocklist = Instance::blocklist(&mut context.pool()).await?;
+  let allowlist = Instance::allowlist(&mut context.pool()).await?;
+  let status = if matches(&blocklist) {
+    InstanceFederationStatus::Blocked
+  } else if matches(&allowlist) {
+    InstanceFederationStatus::Allowed
+  } else if !allowlist.is_empty() {
+    InstanceFederationStatus::Blocked
+  } else {
+    InstanceFederationStatus::Unknown
+  };
+  res.federation_status = Some(status);
+  Ok(())
+}
+
+/// Objects which exist but can't be viewed are reported as not found, so that their existence
+/// isn't revealed. Admins can ask for the precise error with `verbose`.
+fn hide_access_denied(mut error: LemmyError, verbose: bool) -> LemmyError {
+  if !verbose && error.error_type == LemmyErrorType::ResolvedObjectAccessDenied {
+    error.error_type = LemmyErrorType::CouldntFindObject;
+  }
+  error
+}
+
+/// Converts the resolved object into a response. Views are read for the viewer, so that their
+/// personal flags like `saved` are set. With `hide_nsfw`, NSFW objects are treated like deleted
+/// ones.
+#[tracing::instrument(
+  name = "resolve_object_view",
+  skip_all,
+  fields(object_type = object.object_type())
+)]
+async fn convert_response(
+  object: SearchableObjects,
+  local_user_view: Option<&LocalUserView>,
+  hide_nsfw: bool,
+  pool: &mut DbPool<'_>,
+) -> LemmyResult<ResolveObjectResponse> {
+  use SearchableObjects::*;
+  let user_id = local_user_view.map(|v| v.person.id);
+  let is_admin = local_user_view.is_some_and(|v| v.local_user.admin);
+  let mut res = ResolveObjectResponse::default();
+  let can_view = match object {
+    Post(p) => {
+      res.ap_id = Some(p.ap_id.clone());
+      let is_mod = is_community_mod(p.removed, p.community_id, local_user_view, pool).await?;
+      res.post = Some(
+        PostView::read(pool, p.id, user_id, is_admin || is_mod)
+          .await
+          .with_lemmy_type(LemmyErrorType::CouldntReadResolvedObject)?
+          .ok_or(LemmyErrorType::CouldntFindObject)?,
+      );
+      can_view_resolved(ResolvedKind::Post, p.deleted, p.removed, is_admin, is_mod)
+    }
+    Comment(c) => {
+      res.ap_id = Some(c.ap_id.clone());
+      let view = CommentView::read(pool, c.id, user_id)
+        .await
+        .with_lemmy_type(LemmyErrorType::CouldntReadResolvedObject)?
+        .ok_or(LemmyErrorType::CouldntFindObject)?;
+      let is_mod = is_community_mod(c.removed, view.community.id, local_user_view, pool).await?;
+      res.comment = Some(view);
+      can_view_resolved(
+        ResolvedKind::Comment,
+        c.deleted,
+        c.removed,
+        is_admin,
+        is_mod,
+      )
+    }
+    PersonOrCommunity(p) => match *p {
+      UserOrCommunity::User(u) => {
+        res.ap_id = Some(u.actor_id.clone());
+        // The aggregates of a person which was just fetched may not exist yet. Then the person is
+        // returned without counts, instead of failing the whole resolve.
+        let view = PersonView::read(pool, u.id)
+          .await
+          .with_lemmy_type(LemmyErrorType::CouldntReadResolvedObject)?;
+        res.person = Some(match view {
+          Some(view) => view,
+          None => PersonView {
+            counts: PersonAggregates {
+              person_id: u.id,
+              ..Default::default()
+            },
+            person: u.deref().clone(),
+            is_admin: LocalUser::is_admin(pool, u.id)
+              .await
+              .with_lemmy_type(LemmyErrorType::CouldntReadResolvedObject)?,
+          },
+        });
+        can_view_resolved(ResolvedKind::Person, u.deleted, false, is_admin, false)
+      }
+      UserOrCommunity::Community(c) => {
+        res.ap_id = Some(c.actor_id.clone());
+        res.community = Some(
+          CommunityView::read(pool, c.id, user_id, is_admin)
+            .await
+            .with_lemmy_type(LemmyErrorType::CouldntReadResolvedObject)?
+            .ok_or(LemmyErrorType::CouldntFindObject)?,
+        );
+        can_view_resolved(
+          ResolvedKind::Community,
+          c.deleted,
+          c.removed,
+          is_admin,
+          false,
+        )
+      }
+    },
+    Site(s) => {
+      res.ap_id = Some(s.actor_id.clone());
+      res.site = Some(s.deref().clone());
+      can_view_resolved(ResolvedKind::Site, false, false, is_admin, false)
+    }
+    // Moderation activities may contain sensitive details like report reasons, so other users
+    // don't learn that they exist
+    ModAction(m) => {
+      if !can_view_mod_action(&m, local_user_view, pool).await? {
+        Err(LemmyErrorType::CouldntFindObject)?
+      }
+      res.ap_id = Some(m.ap_id.clone());
+      res.mod_action = Some(m);
+      true
+    }
+    CollectionPage(c) => {
+      res.ap_id = Some(c.ap_id.clone());
+      res.collection_page = Some(c);
+      true
+    }
+    // Only logged in users learn that the object existed
+    Tombstone(t) => {
+      if local_user_view.is_none() {
+        Err(LemmyErrorType::CouldntFindObject)?
+      }
+      res.ap_id = Some(t.ap_id.clone());
+      res.tombstone = Some(t);
+      true
+    }
+  };
+  if can_view && !(hide_nsfw && is_nsfw(&res)) {
+    Ok(res)
+  } else {
+    Err(LemmyErrorType::ResolvedObjectAccessDenied.into())
+  }
+}
+
+/// Reports can only be viewed by the moderators of their community and admins, other moderation
+/// activities by moderators of any community.
+async fn can_view_mod_action(
+  action: &ResolvedModAction,
+  local_user_view: Option<&LocalUserView>,
+  pool: &mut DbPool<'_>,
+) -> LemmyResult<bool> {
+  let Some(local_user_view) = local_user_view else {
+    return Ok(false);
+  };
+  let person_id = local_user_view.person.id;
+  if action.kind != ModActionType::Flag {
+    return Ok(CommunityView::is_mod_of_any_or_admin(pool, person_id).await?);
+  }
+  let community = match &action.target_id {
+    Some(target_id) => Community::read_from_apub_id(pool, target_id).await?,
+    None => None,
+  };
+  Ok(match community {
+    Some(community) => CommunityView::is_mod_or_admin(pool, person_id, community.id).await?,
+    None => local_user_view.local_user.admin,
+  })
+}
+
+/// Returns true if no type is expected, or if the object has the expected type. Tombstones only
+/// match if the type of the deleted object is known.
+fn is_expected_type(object: &SearchableObjects, expected_type: Option<ResolveObjectType>) -> bool {
+  expected_type.is_none() || object.resolve_type() == expected_type
+}
+
+/// Returns true if the resolved post, comment or community is NSFW, or belongs to an NSFW
+/// community.
+fn is_nsfw(res: &ResolveObjectResponse) -> bool {
+  res
+    .post
+    .as_ref()
+    .is_some_and(|p| p.post.nsfw || p.community.nsfw)
+    || res
+      .comment
+      .as_ref()
+      .is_some_and(|c| c.post.nsfw || c.community.nsfw)
+    || res.community.as_ref().is_some_and(|c| c.community.nsfw)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResolvedKind {
+  Post,
+  Comment,
+  Person,
+  Community,
+  Site,
+}
+
+/// Decides if a resolved object is shown to the viewer. Deleted objects are never shown, as their
+/// creator chose to take them down. Removed posts and comments are shown to admins and to
+/// moderators of their community, other removed objects only to admins.
+fn can_view_resolved(
+  kind: ResolvedKind,
+  deleted: bool,
+  removed: bool,
+  is_admin: bool,
+  is_community_mod: bool,
+) -> bool {
+  if deleted {
+    return false;
+  }
+  if !removed {
+    return true;
+  }
+  match kind {
+    ResolvedKind::Post | ResolvedKind::Comment => is_admin || is_community_mod,
+    ResolvedKind::Person | ResolvedKind::Community | ResolvedKind::Site => is_admin,
+  }
+}
+
+/// Returns true if the viewer moderates the community. This only matters for removed objects, so
+/// the database is only queried for those.
+async fn is_community_mod(
+  removed: bool,
+  community_id: CommunityId,
+  local_user_view: Option<&LocalUserView>,
+  pool: &mut DbPool<'_>,
+) -> LemmyResult<bool> {
+  match local_user_view {
+    Some(v) if removed => {
+      Ok(CommunityModeratorView::is_community_moderator(pool, community_id, v.person.id).await?)
+    }
+    _ => Ok(false),
+  }
+}
+
+#[cfg(test)]
+mod tests;
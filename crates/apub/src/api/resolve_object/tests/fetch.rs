@@ -0,0 +1,537 @@
+use super::{error_type, query_for, Fixture};
+use crate::{
+  api::{
+    resolve_object::{resolve, resolve_object},
+    test::{json_response, mock_remote, mock_remote_context, MockRemote},
+  },
+  fetcher::response_size_limit::ResponseSizeLimit,
+};
+use actix_web::{test::TestRequest, web::Query};
+use chrono::{Days, Utc};
+use lemmy_api_common::{
+  context::LemmyContext,
+  request::resolve_client_builder,
+  site::ResolveObject,
+};
+use lemmy_db_schema::{
+  newtypes::DbUrl,
+  source::{
+    instance::Instance,
+    local_site::LocalSite,
+    local_site_federation::{LocalSiteFederation, LocalSiteFederationUpdateForm},
+    person::{Person, PersonUpdateForm},
+    post::Post,
+    resolve_object_log::ResolveObjectLog,
+  },
+  traits::{ApubActor, Crud},
+  ResolveObjectType,
+  ResolveRemoteAccess,
+};
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::{LemmyErrorType, LemmyResult};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use std::net::Ipv4Addr;
+use url::Url;
+
+#[tokio::test]
+#[serial]
+async fn test_resolved_remotely() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_remote_user").await?;
+  let local_community = f
+    .local
+    .create_community("resolve_remote", &f.context)
+    .await?;
+  let local_post = f
+    .local
+    .create_post(
+      "resolve_remote",
+      &f.user.person,
+      &local_community,
+      &f.context,
+    )
+    .await?;
+  let creator = f
+    .remote
+    .create_person("resolve_remote_creator", &f.context)
+    .await?;
+  let remote_community = f
+    .remote
+    .create_community("resolve_remote", &f.context)
+    .await?;
+  let remote_post = f
+    .remote
+    .create_post("resolve_remote", &creator, &remote_community, &f.context)
+    .await?;
+
+  // objects which are already known don't need any network requests, even if remote
+  for post in [local_post, remote_post] {
+    let res = resolve_object(
+      Query(query_for(&post.ap_id)),
+      TestRequest::default().to_http_request(),
+      f.context.reset_request_count(),
+      Some(f.user.clone()),
+    )
+    .await?
+    .0;
+    assert_eq!(Some(post.id), res.post.map(|p| p.post.id));
+    assert!(!res.resolved_remotely);
+  }
+
+  f.cleanup().await
+}
+
+async fn resolved_objects_of(
+  user: &LocalUserView,
+  context: &LemmyContext,
+) -> LemmyResult<Vec<ResolveObjectLog>> {
+  let resolved_objects = ResolveObjectLog::list(&mut context.pool(), None, None, None).await?;
+  Ok(
+    resolved_objects
+      .into_iter()
+      .filter(|r| r.person_id == user.person.id)
+      .collect(),
+  )
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolved_remotely_outdated() -> LemmyResult<()> {
+  // a remote server which serves the same person for every request
+  let remote = mock_remote(|base| {
+    include_str!("../../../../assets/lemmy/objects/person.json")
+      .replace("https://enterprise.lemmy.ml", base)
+  })
+  .await?;
+  let person_id = format!("{}/u/picard", remote.base);
+  let f = Fixture::with_context("resolve_outdated_user", remote.context().await?).await?;
+  let query = query_for(&person_id);
+
+  // the first lookup fetches a new object
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  let person = res.person.ok_or(LemmyErrorType::CouldntFindPerson)?.person;
+  assert_eq!(person_id, person.actor_id.to_string());
+  assert!(res.resolved_remotely);
+  let resolved_objects = resolved_objects_of(&f.user, &f.context).await?;
+  let [resolved_object] = resolved_objects.as_slice() else {
+    Err(LemmyErrorType::CouldntFindObject)?
+  };
+  assert_eq!(person_id, resolved_object.query);
+  assert_eq!(Some(ResolveObjectType::Person), resolved_object.object_type);
+
+  // refetching the outdated object also makes a request, but it isn't new
+  let form = PersonUpdateForm {
+    last_refreshed_at: Some(Utc::now() - Days::new(7)),
+    ..Default::default()
+  };
+  Person::update(&mut f.context.pool(), person.id, &form).await?;
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(person.id), res.person.map(|p| p.person.id));
+  assert!(!res.resolved_remotely);
+  let refetched = Person::read(&mut f.context.pool(), person.id)
+    .await?
+    .ok_or(LemmyErrorType::CouldntFindPerson)?;
+  assert!(refetched.last_refreshed_at > Utc::now() - Days::new(1));
+
+  // neither the refetch nor resolving a local object is logged
+  f.resolve(&query_for(&f.user.person.actor_id), Some(&f.user))
+    .await?;
+  assert_eq!(1, resolved_objects_of(&f.user, &f.context).await?.len());
+
+  Instance::delete(&mut f.context.pool(), person.instance_id).await?;
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_through_proxy() -> LemmyResult<()> {
+  // The proxy answers every request with the person, and remembers the requested urls
+  let person_id = "http://proxied.example/u/picard";
+  let remote = mock_remote(|_| {
+    include_str!("../../../../assets/lemmy/objects/person.json")
+      .replace("https://enterprise.lemmy.ml", "http://proxied.example")
+  })
+  .await?;
+  let mut settings = LemmyContext::init_test_context().await.settings().clone();
+  settings.resolve_object.proxy = Some(Url::parse(&format!("http://{}", remote.addr))?);
+  let client = resolve_client_builder(&settings)?.build()?;
+  let context = mock_remote_context(Some(client.into())).await?;
+  let f = Fixture::with_context("resolve_proxy_user", context).await?;
+
+  // the domain doesn't exist, so the person can only be fetched through the proxy
+  let res = f.resolve(&query_for(person_id), Some(&f.user)).await?;
+  let person = res.person.ok_or(LemmyErrorType::CouldntFindPerson)?.person;
+  assert_eq!(person_id, person.actor_id.to_string());
+  // the site of the person's instance is fetched through the proxy as well
+  let requests = remote.requests().await;
+  assert_eq!(Some(&format!("GET {person_id} HTTP/1.1")), requests.first());
+  assert!(requests
+    .iter()
+    .all(|r| r.starts_with("GET http://proxied.example/")));
+
+  Instance::delete(&mut f.context.pool(), person.instance_id).await?;
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_response_size_limit() -> LemmyResult<()> {
+  // a remote server which serves the same person for every path. only /u/picard announces the
+  // body size, for the other paths it only becomes known while downloading.
+  let mut remote = MockRemote::bind().await?;
+  let base = remote.base.clone();
+  let body = include_str!("../../../../assets/lemmy/objects/person.json")
+    .replace("https://enterprise.lemmy.ml", &base);
+  remote.serve(move |request| {
+    let content_length = if request.starts_with("GET /u/picard ") {
+      format!("Content-Length: {}\r\n", body.len())
+    } else {
+      String::new()
+    };
+    Some(format!(
+      "HTTP/1.1 200 OK\r\nContent-Type: application/activity+json\r\n\
+       {content_length}Connection: close\r\n\r\n{body}"
+    ))
+  });
+  let test_context = LemmyContext::init_test_context().await;
+  let context_with_limit = |max_response_size: Option<usize>| {
+    let settings = test_context.settings().clone();
+    async move {
+      let mut client =
+        reqwest_middleware::ClientBuilder::new(resolve_client_builder(&settings)?.build()?);
+      if let Some(max_response_size) = max_response_size {
+        client = client.with(ResponseSizeLimit::new(max_response_size)?);
+      }
+      mock_remote_context(Some(client.build())).await
+    }
+  };
+  // the limit can only be lower than the one of the federation library
+  assert!(ResponseSizeLimit::new(204_800).is_err());
+  let f = Fixture::with_context("resolve_size_user", context_with_limit(Some(1000)).await?).await?;
+
+  // the person json is larger than the limit, so it isn't found, whether the size is announced
+  // or not
+  for name in ["picard", "riker"] {
+    let person_id = format!("{base}/u/{name}");
+    let res = f.resolve(&query_for(&person_id), Some(&f.user)).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+    let url: DbUrl = Url::parse(&person_id)?.into();
+    assert!(Person::read_from_apub_id(&mut f.context.pool(), &url)
+      .await?
+      .is_none());
+  }
+
+  // without a limit it is fetched
+  let context = context_with_limit(None).await?;
+  let query = query_for(format!("{base}/u/picard"));
+  let res = resolve(&query, Some(&f.user), f.ip_addr, &context).await?;
+  let person = res.person.ok_or(LemmyErrorType::CouldntFindPerson)?.person;
+  assert_eq!(format!("{base}/u/picard"), person.actor_id.to_string());
+
+  Instance::delete(&mut f.context.pool(), person.instance_id).await?;
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_raw_json() -> LemmyResult<()> {
+  // a remote server which serves a person with a field that Lemmy doesn't know
+  let remote = mock_remote(|base| {
+    let mut person: serde_json::Value = serde_json::from_str(
+      &include_str!("../../../../assets/lemmy/objects/person.json")
+        .replace("https://enterprise.lemmy.ml", base),
+    )
+    .unwrap_or_default();
+    if let Some(person) = person.as_object_mut() {
+      person.insert("customField".to_string(), "captain".into());
+    }
+    person.to_string()
+  })
+  .await?;
+  let f = Fixture::with_context("resolve_raw_user", remote.context().await?).await?;
+  let admin = f.create_user("resolve_raw_admin", true).await?;
+  let query = ResolveObject {
+    raw: Some(true),
+    ..query_for(format!("{}/u/picard", remote.base))
+  };
+
+  // only admins get the json
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  let person = res.person.ok_or(LemmyErrorType::CouldntFindPerson)?.person;
+  assert_eq!(None, res.raw_json);
+
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert!(res.person.is_some());
+  let raw_json: serde_json::Value =
+    serde_json::from_str(&res.raw_json.ok_or(LemmyErrorType::CouldntFindObject)?)?;
+  assert_eq!(
+    Some(person.actor_id.as_str()),
+    raw_json.get("id").and_then(|i| i.as_str())
+  );
+  assert_eq!(
+    Some("captain"),
+    raw_json.get("customField").and_then(|c| c.as_str())
+  );
+
+  // local objects have no remote json
+  let query = ResolveObject {
+    raw: Some(true),
+    ..query_for(&admin.person.actor_id)
+  };
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert!(res.person.is_some());
+  assert_eq!(None, res.raw_json);
+
+  Instance::delete(&mut f.context.pool(), person.instance_id).await?;
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_outbox_page() -> LemmyResult<()> {
+  // a remote server which serves a page of a mastodon outbox for every path
+  let remote = mock_remote(|base| {
+    include_str!("../../../../assets/mastodon/collections/outbox_page.json")
+      .replace("https://mastodon.madrid", base)
+  })
+  .await?;
+  let base = &remote.base;
+  let f = Fixture::with_context("resolve_page_user", remote.context().await?).await?;
+  let page_id = format!("{base}/users/felix/outbox?page=true");
+
+  // the ids of the contained objects are returned, without fetching them
+  let context = f.context.reset_request_count();
+  let res = resolve(&query_for(&page_id), Some(&f.user), f.ip_addr, &context).await?;
+  assert_eq!(1, context.request_count());
+  assert_eq!(
+    Some(page_id.as_str()),
+    res.ap_id.as_ref().map(|i| i.as_str())
+  );
+  let page = res
+    .collection_page
+    .ok_or(LemmyErrorType::CouldntFindObject)?;
+  assert_eq!(
+    Some(format!("{base}/users/felix/outbox")),
+    page.part_of.map(|p| p.to_string())
+  );
+  let items: Vec<_> = page.items.iter().map(ToString::to_string).collect();
+  assert_eq!(
+    vec![
+      format!("{base}/users/felix/statuses/110143255372493836"),
+      "https://lemmy.ml/post/1".to_string(),
+    ],
+    items
+  );
+  let url = Url::parse("https://lemmy.ml/post/1")?;
+  assert!(Post::read_from_apub_id(&mut f.context.pool(), url)
+    .await?
+    .is_none());
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_prefetch_posts() -> LemmyResult<()> {
+  // a remote server which serves a community with an empty outbox
+  let mut remote = MockRemote::bind().await?;
+  let base = remote.base.clone();
+  let group = include_str!("../../../../assets/lemmy/objects/group.json")
+    .replace("https://enterprise.lemmy.ml", &base);
+  let outbox = serde_json::json!({
+    "type": "OrderedCollection",
+    "id": format!("{base}/c/tenforward/outbox"),
+    "totalItems": 0,
+    "orderedItems": [],
+  })
+  .to_string();
+  remote.serve(move |request| {
+    if request.starts_with("GET /c/tenforward ") {
+      Some(json_response(&group))
+    } else if request.starts_with("GET /c/tenforward/outbox ") {
+      Some(json_response(&outbox))
+    } else {
+      let not_found = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+      Some(not_found.to_string())
+    }
+  });
+  let f = Fixture::with_context("resolve_prefetch_user", remote.context().await?).await?;
+  let query = ResolveObject {
+    prefetch_posts: Some(5),
+    ..query_for(format!("{base}/c/tenforward"))
+  };
+  let outbox_requests = |requests: Vec<String>| {
+    requests
+      .iter()
+      .filter(|r| r.starts_with("GET /c/tenforward/outbox "))
+      .count()
+  };
+
+  // the new community is returned once its outbox was received, which is only fetched once
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert!(res.resolved_remotely);
+  let community = res
+    .community
+    .ok_or(LemmyErrorType::CouldntFindCommunity)?
+    .community;
+  assert_eq!(1, outbox_requests(remote.requests().await));
+
+  // the known community is returned without waiting or fetching anything
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert!(!res.resolved_remotely);
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+  assert_eq!(1, outbox_requests(remote.requests().await));
+
+  Instance::delete(&mut f.context.pool(), community.instance_id).await?;
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_refresh() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_refresh_user").await?;
+  let admin = f.create_user("resolve_refresh_admin", true).await?;
+  let community = f
+    .remote
+    .create_community("resolve_refresh", &f.context)
+    .await?;
+  let mut query = query_for(&community.actor_id);
+
+  // by default the local copy is returned
+  let context = f.context.reset_request_count();
+  let res = resolve(&query, Some(&admin), f.ip_addr, &context).await?;
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+  assert_eq!(0, context.request_count());
+
+  // non-admins can't force a refresh
+  query.refresh = Some(true);
+  let context = f.context.reset_request_count();
+  let res = resolve(&query, Some(&f.user), f.ip_addr, &context).await?;
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+  assert_eq!(0, context.request_count());
+
+  // admins fetch the object again, which fails as the remote instance doesn't exist
+  let context = f.context.reset_request_count();
+  let res = resolve(&query, Some(&admin), f.ip_addr, &context).await;
+  assert!(res.is_err());
+  assert_eq!(1, context.request_count());
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_rate_limit() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_limit_user").await?;
+  // admins bypass the negative cache, so that each lookup makes a request
+  let admin = f.create_user("resolve_limit_admin", true).await?;
+  let creator = f
+    .remote
+    .create_person("resolve_limit_creator", &f.context)
+    .await?;
+  let community = f
+    .remote
+    .create_community("resolve_limit", &f.context)
+    .await?;
+  let known = f
+    .remote
+    .create_post("known", &creator, &community, &f.context)
+    .await?;
+  let query = query_for(f.remote.url("post/1")?);
+
+  // lookups of known objects don't use up the limit
+  let known_query = query_for(&known.ap_id);
+  for _ in 0..40 {
+    let res = f.resolve(&known_query, Some(&admin)).await?;
+    assert!(res.post.is_some());
+  }
+
+  // the test config allows 30 remote lookups in 10 minutes
+  for _ in 0..30 {
+    let context = f.context.reset_request_count();
+    let res = resolve(&query, Some(&admin), f.ip_addr, &context).await;
+    assert!(res.is_err());
+    assert_eq!(1, context.request_count());
+  }
+
+  // then it falls back to local lookups
+  let context = f.context.reset_request_count();
+  let res = resolve(&query, Some(&admin), f.ip_addr, &context).await;
+  assert!(res.is_err());
+  assert_eq!(0, context.request_count());
+
+  // other ips are unaffected
+  let context = f.context.reset_request_count();
+  let other_ip = Ipv4Addr::new(10, 0, 0, 2).into();
+  let res = resolve(&query, Some(&admin), other_ip, &context).await;
+  assert!(res.is_err());
+  assert_eq!(1, context.request_count());
+
+  // a forced refetch of a known object does, so it isn't fetched once the limit is reached
+  let refresh_query = ResolveObject {
+    refresh: Some(true),
+    ..known_query
+  };
+  let context = f.context.reset_request_count();
+  let res = resolve(&refresh_query, Some(&admin), f.ip_addr, &context).await?;
+  assert!(res.post.is_some());
+  assert_eq!(0, context.request_count());
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_remote_access() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_access_user").await?;
+  let admin = f.create_user("resolve_access_admin", true).await?;
+  let federation_form = LocalSiteFederationUpdateForm {
+    resolve_remote_access: Some(ResolveRemoteAccess::VerifiedEmail),
+    resolve_remote_min_account_age: Some(7),
+    ..Default::default()
+  };
+  LocalSiteFederation::update(&mut f.context.pool(), &federation_form).await?;
+  // unknown remote urls are only fetched if the user may resolve remotely. use a different url
+  // each time, so that the negative cache doesn't hide the request.
+  let request_count = |user: LocalUserView, path: &'static str| {
+    let context = f.context.reset_request_count();
+    let ip_addr = f.ip_addr;
+    async move {
+      let query = query_for(format!("https://access.example/post/{path}"));
+      let res = resolve(&query, Some(&user), ip_addr, &context).await;
+      assert!(res.is_err());
+      context.request_count()
+    }
+  };
+
+  // users below the threshold only get local results
+  assert_eq!(0, request_count(f.user.clone(), "unverified").await);
+  let mut verified = f.user.clone();
+  verified.local_user.email_verified = true;
+  assert_eq!(0, request_count(verified.clone(), "new_account").await);
+
+  // once the account is old enough and the email verified, remote objects are fetched
+  let published = Utc::now() - Days::new(8);
+  verified.person.published = published;
+  assert_eq!(1, request_count(verified.clone(), "verified").await);
+  let mut unverified = f.user.clone();
+  unverified.person.published = published;
+  assert_eq!(0, request_count(unverified, "old_account").await);
+
+  // admins may always resolve remotely
+  assert_eq!(1, request_count(admin.clone(), "admin").await);
+
+  // with admins only, not even verified users can
+  let federation_form = LocalSiteFederationUpdateForm {
+    resolve_remote_access: Some(ResolveRemoteAccess::Admins),
+    ..Default::default()
+  };
+  LocalSiteFederation::update(&mut f.context.pool(), &federation_form).await?;
+  assert_eq!(0, request_count(verified, "admins_only").await);
+
+  // if the settings can't be read, the resolve fails instead of allowing everyone
+  LocalSite::delete(&mut f.context.pool()).await?;
+  assert_eq!(0, request_count(admin, "without_settings").await);
+
+  f.cleanup().await
+}
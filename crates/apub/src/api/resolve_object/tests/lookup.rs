@@ -0,0 +1,212 @@
+use super::{error_type, query_for, Fixture};
+use crate::api::{
+  resolve_object::{resolve, resolve_object},
+  test::TestInstance,
+};
+use actix_web::{test::TestRequest, web::Query};
+use lemmy_api_common::{context::LemmyContext, site::ResolveObject};
+use lemmy_db_schema::ResolveObjectType;
+use lemmy_utils::error::{LemmyErrorType, LemmyResult};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use std::net::{IpAddr, Ipv4Addr};
+
+#[tokio::test]
+async fn test_resolve_negative_timeout() -> LemmyResult<()> {
+  let context = LemmyContext::init_test_context().await;
+  let query = ResolveObject {
+    timeout_ms: Some(-1),
+    ..query_for("https://remote.example/post/negative-timeout")
+  };
+  let ip_addr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+  let res = resolve(&query, None, ip_addr, &context).await;
+  assert_eq!(Some(LemmyErrorType::InvalidTimeout), error_type(res));
+  assert_eq!(0, context.request_count());
+  Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_all_matches() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_user").await?;
+  let local_community = f.local.create_community("news", &f.context).await?;
+  let remote_community = f.remote.create_community("news", &f.context).await?;
+
+  // all matching communities are returned, local first
+  let query = ResolveObject {
+    all_matches: Some(true),
+    ..query_for("!news")
+  };
+  let res = resolve_object(
+    Query(query),
+    TestRequest::default().to_http_request(),
+    f.context.reset_request_count(),
+    Some(f.user.clone()),
+  )
+  .await?
+  .0;
+  let matches = res.matches.unwrap_or_default();
+  let community_ids = matches
+    .iter()
+    .filter_map(|m| m.community.as_ref().map(|c| c.community.id))
+    .collect::<Vec<_>>();
+  assert_eq!(vec![local_community.id, remote_community.id], community_ids);
+
+  // by default only the first match is returned
+  let res = resolve_object(
+    Query(query_for("!news")),
+    TestRequest::default().to_http_request(),
+    f.context.reset_request_count(),
+    Some(f.user.clone()),
+  )
+  .await?
+  .0;
+  assert_eq!(
+    Some(local_community.id),
+    res.community.map(|c| c.community.id)
+  );
+  assert!(res.matches.is_none());
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_own_url() -> LemmyResult<()> {
+  // the local instance has the hostname of the settings, unlike the one of the fixture
+  let context = LemmyContext::init_test_context().await;
+  let local = TestInstance::builder(&context.settings().get_hostname_without_port()?)
+    .base_url(&context.settings().get_protocol_and_hostname())
+    .create(&context)
+    .await?;
+  let user = local
+    .create_user("resolve_own_url_user", false, &context)
+    .await?;
+  let community = local.create_community("resolve_own_url", &context).await?;
+  let ip_addr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+  // our own urls are resolved without any outgoing request, even when logged in
+  let mut query = query_for(&community.actor_id);
+  let res = resolve(&query, Some(&user), ip_addr, &context).await?;
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+  assert!(!res.resolved_remotely);
+  assert_eq!(0, context.request_count());
+
+  // the same for urls which don't exist
+  query.q = format!(
+    "{}/post/999999999",
+    context.settings().get_protocol_and_hostname()
+  );
+  let res = resolve(&query, Some(&user), ip_addr, &context).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+  assert_eq!(0, context.request_count());
+
+  local.cleanup(&context).await?;
+  Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_fuzzy() -> LemmyResult<()> {
+  let f = Fixture::new("fuzzyperson").await?;
+  let community = f.local.create_community("fuzzyresolve", &f.context).await?;
+  let longer_community = f
+    .local
+    .create_community("fuzzyresolve_extra", &f.context)
+    .await?;
+
+  // exact matches are preferred, even with fuzzy
+  let query = ResolveObject {
+    fuzzy: Some(true),
+    ..query_for("!fuzzyresolve_extra")
+  };
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(
+    Some(longer_community.id),
+    res.community.map(|c| c.community.id)
+  );
+
+  // partial names only match with fuzzy
+  let mut query = query_for("fuzzyresolv");
+  let res = f.resolve(&query, Some(&f.user)).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+  query.fuzzy = Some(true);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+
+  // the sigil restricts the actor type, also without authentication
+  query.q = "@fuzzyperso".to_string();
+  let res = f.resolve(&query, None).await?;
+  assert_eq!(Some(f.user.person.id), res.person.map(|p| p.person.id));
+  query.q = "!fuzzyperso".to_string();
+  let res = f.resolve(&query, None).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_expected_type() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_typed").await?;
+  let community = f
+    .local
+    .create_community("resolve_typed", &f.context)
+    .await?;
+
+  let mut query = ResolveObject {
+    expected_type: Some(ResolveObjectType::Community),
+    ..query_for(&community.actor_id)
+  };
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+
+  // a person with the same name isn't returned instead
+  query.expected_type = Some(ResolveObjectType::Person);
+  let res = f.resolve(&query, Some(&f.user)).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+
+  query.q = f.user.person.actor_id.to_string();
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(f.user.person.id), res.person.map(|p| p.person.id));
+
+  // with a person and a community of the same name, the one with the expected type is
+  // returned even though communities come first
+  query.q = "resolve_typed".to_string();
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(f.user.person.id), res.person.map(|p| p.person.id));
+  assert!(res.community.is_none());
+
+  // mismatching objects are also left out of all matches
+  query.expected_type = Some(ResolveObjectType::Post);
+  query.all_matches = Some(true);
+  let res = f.resolve(&query, Some(&f.user)).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_site() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_site_user").await?;
+
+  // the site can be resolved by its domain or actor id
+  for q in [
+    f.remote.instance.domain.clone(),
+    f.remote.site.actor_id.to_string(),
+  ] {
+    let res = resolve_object(
+      Query(query_for(q)),
+      TestRequest::default().to_http_request(),
+      f.context.reset_request_count(),
+      Some(f.user.clone()),
+    )
+    .await?
+    .0;
+    assert_eq!(Some(f.remote.site.id), res.site.map(|s| s.id));
+    assert!(!res.resolved_remotely);
+  }
+
+  f.cleanup().await
+}
@@ -0,0 +1,87 @@
+use crate::api::test::TestInstance;
+use activitypub_federation::config::Data;
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::{ResolveObject, ResolveObjectResponse},
+};
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::{LemmyErrorType, LemmyResult};
+use std::net::{IpAddr, Ipv4Addr};
+
+mod fetch;
+mod lookup;
+mod observability;
+mod response;
+mod visibility;
+
+/// The domain of the remote instance of [Fixture].
+const REMOTE_DOMAIN: &str = "remote.example";
+
+/// The local instance `example.com` with a regular user, and the remote instance
+/// [REMOTE_DOMAIN], which the objects of most tests belong to.
+pub(super) struct Fixture {
+  pub(super) context: Data<LemmyContext>,
+  pub(super) local: TestInstance,
+  pub(super) remote: TestInstance,
+  pub(super) user: LocalUserView,
+  /// The ip address of all resolves, each context has its own rate limit.
+  pub(super) ip_addr: IpAddr,
+}
+
+impl Fixture {
+  /// Creates the instances and the user with the given name, with a context which doesn't make
+  /// any requests.
+  pub(super) async fn new(user_name: &str) -> LemmyResult<Self> {
+    Self::with_context(user_name, LemmyContext::init_test_context().await).await
+  }
+
+  /// Same as [Fixture::new], but with a context which can fetch objects, eg from a
+  /// [MockRemote](crate::api::test::MockRemote).
+  pub(super) async fn with_context(
+    user_name: &str,
+    context: Data<LemmyContext>,
+  ) -> LemmyResult<Self> {
+    let (local, remote) = TestInstance::local_and_remote(REMOTE_DOMAIN, &context).await?;
+    let user = local.create_user(user_name, false, &context).await?;
+    Ok(Fixture {
+      context,
+      local,
+      remote,
+      user,
+      ip_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+    })
+  }
+
+  /// Creates another user of the local instance.
+  pub(super) async fn create_user(&self, name: &str, admin: bool) -> LemmyResult<LocalUserView> {
+    self.local.create_user(name, admin, &self.context).await
+  }
+
+  /// Resolves the query as the given user, or without login.
+  pub(super) async fn resolve(
+    &self,
+    query: &ResolveObject,
+    local_user_view: Option<&LocalUserView>,
+  ) -> LemmyResult<ResolveObjectResponse> {
+    super::resolve(query, local_user_view, self.ip_addr, &self.context).await
+  }
+
+  /// Deletes both instances with all their content.
+  pub(super) async fn cleanup(self) -> LemmyResult<()> {
+    self.remote.cleanup(&self.context).await?;
+    self.local.cleanup(&self.context).await
+  }
+}
+
+/// A query for `q` without any options.
+pub(super) fn query_for(q: impl ToString) -> ResolveObject {
+  ResolveObject {
+    q: q.to_string(),
+    ..Default::default()
+  }
+}
+
+/// The type of the error, for comparing results which should fail.
+pub(super) fn error_type<T>(res: LemmyResult<T>) -> Option<LemmyErrorType> {
+  res.err().map(|e| e.error_type)
+}
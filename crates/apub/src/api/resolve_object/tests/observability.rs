@@ -0,0 +1,99 @@
+use super::{query_for, Fixture};
+use crate::api::resolve_object::RESOLVE_DURATION;
+use lemmy_utils::error::LemmyResult;
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use std::sync::{Arc, Mutex};
+use tracing::{
+  field::{Field, Visit},
+  span::{Attributes, Id, Record},
+  Subscriber,
+};
+use tracing_subscriber::{
+  layer::{Context, SubscriberExt},
+  registry::LookupSpan,
+  Layer,
+};
+
+/// Collects the fields of all spans, as pairs of span name and `field=value`.
+#[derive(Clone, Default)]
+struct SpanFields(Arc<Mutex<Vec<(&'static str, String)>>>);
+
+struct SpanFieldVisitor<'a>(&'static str, &'a SpanFields);
+
+impl Visit for SpanFieldVisitor<'_> {
+  fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+    if let Ok(mut fields) = self.1 .0.lock() {
+      fields.push((self.0, format!("{}={value:?}", field.name())));
+    }
+  }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanFields {
+  fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+    attrs.record(&mut SpanFieldVisitor(attrs.metadata().name(), self));
+  }
+
+  fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+    if let Some(span) = ctx.span(id) {
+      values.record(&mut SpanFieldVisitor(span.name(), self));
+    }
+  }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_tracing_spans() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_spans_user").await?;
+  let community = f
+    .local
+    .create_community("resolve_spans", &f.context)
+    .await?;
+  let spans = SpanFields::default();
+  let subscriber = tracing_subscriber::registry().with(spans.clone());
+  let guard = tracing::subscriber::set_default(subscriber);
+
+  f.resolve(&query_for(&community.actor_id), Some(&f.user))
+    .await?;
+  drop(guard);
+
+  // the phases are traced with the object type, and the local community needs no network call
+  let fields = spans.0.lock().map(|s| s.clone()).unwrap_or_default();
+  let expected = [
+    ("resolve_object_fetch", "object_type=\"community\""),
+    ("resolve_object_fetch", "network=false"),
+    ("resolve_object_view", "object_type=\"community\""),
+    ("resolve_object_convert", "matches=1"),
+  ];
+  for (name, field) in expected {
+    assert!(
+      fields.contains(&(name, field.to_string())),
+      "{name} {field} not in {fields:?}"
+    );
+  }
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_duration_metric() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_metric_user").await?;
+  let community = f
+    .local
+    .create_community("resolve_metric", &f.context)
+    .await?;
+  let histogram = RESOLVE_DURATION.with_label_values(&["local", "community"]);
+  let samples = histogram.get_sample_count();
+
+  f.resolve(&query_for(&community.actor_id), Some(&f.user))
+    .await?;
+  assert_eq!(samples + 1, histogram.get_sample_count());
+
+  // failed resolves are not measured
+  let query = query_for("!resolve_metric_missing@example.com");
+  assert!(f.resolve(&query, Some(&f.user)).await.is_err());
+  assert_eq!(samples + 1, histogram.get_sample_count());
+
+  f.cleanup().await
+}
@@ -0,0 +1,585 @@
+use super::{error_type, query_for, Fixture};
+use crate::{
+  api::{
+    resolve_object::{convert_response, MAX_CONTEXT_PARENTS},
+    test::mock_remote,
+  },
+  fetcher::{search::SearchableObjects, user_or_community::UserOrCommunity},
+};
+use diesel_async::SimpleAsyncConnection;
+use lemmy_api_common::site::{
+  InstanceFederationStatus,
+  ModActionType,
+  PersonRelationship,
+  ResolveObject,
+  ResolvedModAction,
+  ResolvedTombstone,
+};
+use lemmy_db_schema::{
+  source::{
+    comment::{Comment, CommentInsertForm},
+    comment_edit::CommentEdit,
+    community::{
+      CommunityFollower,
+      CommunityFollowerForm,
+      CommunityModerator,
+      CommunityModeratorForm,
+    },
+    federation_blocklist::FederationBlockList,
+    person_block::{PersonBlock, PersonBlockForm},
+    post::{Post, PostInsertForm, PostRead, PostSaved, PostSavedForm},
+    resolve_object_log::ResolveObjectLog,
+  },
+  traits::{Blockable, Crud, Followable, Joinable, Saveable},
+  utils::DbPool,
+};
+use lemmy_utils::error::{LemmyErrorType, LemmyResult};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use std::collections::HashSet;
+use url::Url;
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_ap_id() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_ap_id_user").await?;
+  let admin = f.create_user("resolve_ap_id_admin", true).await?;
+  let community = f
+    .local
+    .create_community("resolve_ap_id", &f.context)
+    .await?;
+  let post = f
+    .local
+    .create_post("resolve_ap_id", &admin.person, &community, &f.context)
+    .await?;
+  let comment_form = CommentInsertForm::builder()
+    .content("resolve_ap_id".to_string())
+    .creator_id(admin.person.id)
+    .post_id(post.id)
+    .build();
+  let comment = Comment::create(&mut f.context.pool(), &comment_form, None).await?;
+  let mod_action = ResolvedModAction {
+    ap_id: f.local.url("activities/block/1")?,
+    kind: ModActionType::Block,
+    actor_id: admin.person.actor_id.clone(),
+    object_id: admin.person.actor_id.clone(),
+    target_id: None,
+    reason: None,
+  };
+  let tombstone = ResolvedTombstone {
+    ap_id: f.remote.url("post/1")?,
+    former_type: None,
+  };
+
+  // the canonical id is set for every type of object
+  let objects = [
+    (SearchableObjects::Post(post.clone().into()), &post.ap_id),
+    (
+      SearchableObjects::Comment(comment.clone().into()),
+      &comment.ap_id,
+    ),
+    (
+      SearchableObjects::PersonOrCommunity(Box::new(UserOrCommunity::User(
+        admin.person.clone().into(),
+      ))),
+      &admin.person.actor_id,
+    ),
+    (
+      SearchableObjects::PersonOrCommunity(Box::new(UserOrCommunity::Community(
+        community.clone().into(),
+      ))),
+      &community.actor_id,
+    ),
+    (
+      SearchableObjects::Site(f.local.site.clone().into()),
+      &f.local.site.actor_id,
+    ),
+    (
+      SearchableObjects::ModAction(mod_action.clone()),
+      &mod_action.ap_id,
+    ),
+    (
+      SearchableObjects::Tombstone(tombstone.clone()),
+      &tombstone.ap_id,
+    ),
+  ];
+  for (object, ap_id) in objects {
+    let res = convert_response(object, Some(&admin), false, &mut f.context.pool()).await?;
+    assert_eq!(Some(ap_id), res.ap_id.as_ref());
+  }
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_view_read_failure() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_read_failure_user").await?;
+  let community = f
+    .local
+    .create_community("resolve_read_failure", &f.context)
+    .await?;
+  let post = f
+    .local
+    .create_post(
+      "resolve_read_failure",
+      &f.user.person,
+      &community,
+      &f.context,
+    )
+    .await?;
+
+  // abort a transaction so that reading the view fails even though the post exists
+  let mut conn = f.context.inner_pool().get().await?;
+  conn.batch_execute("BEGIN").await?;
+  assert!(conn.batch_execute("SELECT 1/0").await.is_err());
+  let res = convert_response(
+    SearchableObjects::Post(post.into()),
+    None,
+    false,
+    &mut DbPool::Conn(&mut conn),
+  )
+  .await;
+  conn.batch_execute("ROLLBACK").await?;
+  assert_eq!(
+    Some(LemmyErrorType::CouldntReadResolvedObject),
+    error_type(res)
+  );
+  drop(conn);
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_saved_post() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_saved_user").await?;
+  let community = f
+    .local
+    .create_community("resolve_saved", &f.context)
+    .await?;
+  let post = f
+    .local
+    .create_post("resolve_saved", &f.user.person, &community, &f.context)
+    .await?;
+  let saved_form = PostSavedForm {
+    post_id: post.id,
+    person_id: f.user.person.id,
+  };
+  PostSaved::save(&mut f.context.pool(), &saved_form).await?;
+  PostRead::mark_as_read(
+    &mut f.context.pool(),
+    HashSet::from([post.id]),
+    f.user.person.id,
+  )
+  .await?;
+
+  // the flags of the viewer are set, also when all matches are requested
+  let mut query = query_for(&post.ap_id);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  let view = res.post.ok_or(LemmyErrorType::CouldntFindPost)?;
+  assert!(view.saved);
+  assert!(view.read);
+  query.all_matches = Some(true);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  let view = res
+    .matches
+    .unwrap_or_default()
+    .into_iter()
+    .find_map(|m| m.post)
+    .ok_or(LemmyErrorType::CouldntFindPost)?;
+  assert!(view.saved);
+  assert!(view.read);
+
+  // without login they are never set
+  query.all_matches = None;
+  let res = f.resolve(&query, None).await?;
+  let view = res.post.ok_or(LemmyErrorType::CouldntFindPost)?;
+  assert!(!view.saved);
+  assert!(!view.read);
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_comment_context() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_context_user").await?;
+  let community = f
+    .local
+    .create_community("resolve_context", &f.context)
+    .await?;
+  let post = f
+    .local
+    .create_post("resolve_context", &f.user.person, &community, &f.context)
+    .await?;
+
+  // a thread which is deeper than the context limit
+  let mut thread: Vec<Comment> = vec![];
+  for i in 0..MAX_CONTEXT_PARENTS + 2 {
+    let comment_form = CommentInsertForm::builder()
+      .content(format!("comment {i}"))
+      .creator_id(f.user.person.id)
+      .post_id(post.id)
+      .build();
+    let parent_path = thread.last().map(|c| c.path.clone());
+    let comment =
+      Comment::create(&mut f.context.pool(), &comment_form, parent_path.as_ref()).await?;
+    thread.push(comment);
+  }
+  let leaf = thread.last().ok_or(LemmyErrorType::CouldntFindComment)?;
+
+  // no context by default
+  let mut query = query_for(format!("comment:{}", leaf.id));
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(leaf.id), res.comment.map(|c| c.comment.id));
+  assert!(res.comment_post.is_none());
+  assert!(res.parent_comments.is_none());
+
+  // only the closest parents are returned, starting furthest up
+  query.include_context = Some(true);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(leaf.id), res.comment.map(|c| c.comment.id));
+  assert_eq!(Some(post.id), res.comment_post.map(|p| p.post.id));
+  let expected: Vec<_> = thread
+    .iter()
+    .skip(1)
+    .take(MAX_CONTEXT_PARENTS)
+    .map(|c| c.id)
+    .collect();
+  let parents: Vec<_> = res
+    .parent_comments
+    .unwrap_or_default()
+    .into_iter()
+    .map(|c| c.comment.id)
+    .collect();
+  assert_eq!(expected, parents);
+
+  // all matches include the same context
+  query.all_matches = Some(true);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  let matched = res
+    .matches
+    .unwrap_or_default()
+    .into_iter()
+    .next()
+    .ok_or(LemmyErrorType::CouldntFindComment)?;
+  assert_eq!(Some(post.id), matched.comment_post.map(|p| p.post.id));
+  assert_eq!(
+    Some(MAX_CONTEXT_PARENTS),
+    matched.parent_comments.map(|p| p.len())
+  );
+  query.all_matches = None;
+
+  // top level comments have no parents
+  let root = thread.first().ok_or(LemmyErrorType::CouldntFindComment)?;
+  query.q = format!("comment:{}", root.id);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(post.id), res.comment_post.map(|p| p.post.id));
+  assert_eq!(Some(0), res.parent_comments.map(|p| p.len()));
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_person_relationship() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_relationship_user").await?;
+  let person = f
+    .remote
+    .create_person("resolve_relationship_person", &f.context)
+    .await?;
+  let shared = f
+    .remote
+    .create_community("resolve_relationship", &f.context)
+    .await?;
+  for person_id in [f.user.person.id, person.id] {
+    let form = CommunityFollowerForm {
+      community_id: shared.id,
+      person_id,
+      pending: false,
+    };
+    CommunityFollower::follow(&mut f.context.pool(), &form).await?;
+  }
+
+  // only returned if requested
+  let mut query = query_for(&person.actor_id);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(None, res.person_relationship);
+
+  query.include_relationship = Some(true);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  let expected = PersonRelationship {
+    is_blocked: false,
+    shared_communities: 1,
+  };
+  assert_eq!(Some(expected), res.person_relationship);
+
+  let form = PersonBlockForm {
+    person_id: f.user.person.id,
+    target_id: person.id,
+  };
+  PersonBlock::block(&mut f.context.pool(), &form).await?;
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  let expected = PersonRelationship {
+    is_blocked: true,
+    shared_communities: 1,
+  };
+  assert_eq!(Some(expected), res.person_relationship);
+
+  // without login there is no relationship
+  let res = f.resolve(&query, None).await?;
+  assert_eq!(None, res.person_relationship);
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_as_user() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_as_user_user").await?;
+  let admin = f.create_user("resolve_as_user_admin", true).await?;
+  let person = f
+    .local
+    .create_person("resolve_as_user_blocked", &f.context)
+    .await?;
+  let form = PersonBlockForm {
+    person_id: f.user.person.id,
+    target_id: person.id,
+  };
+  PersonBlock::block(&mut f.context.pool(), &form).await?;
+  let mut query = ResolveObject {
+    include_relationship: Some(true),
+    ..query_for(&person.actor_id)
+  };
+
+  // the admin didn't block the person
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert_eq!(Some(false), res.person_relationship.map(|r| r.is_blocked));
+
+  // but resolving as the user shows their block
+  query.as_user = Some(f.user.local_user.id);
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert_eq!(Some(true), res.person_relationship.map(|r| r.is_blocked));
+  // which is logged for the admin, even though nothing was fetched
+  let log = ResolveObjectLog::list(&mut f.context.pool(), None, None, None).await?;
+  assert!(log.iter().any(|l| l.query == query.q
+    && l.person_id == admin.person.id
+    && l.impersonated_person_id == Some(f.user.person.id)));
+
+  // other users can't impersonate anyone
+  query.as_user = Some(admin.local_user.id);
+  let res = f.resolve(&query, Some(&f.user)).await;
+  assert_eq!(Some(LemmyErrorType::NotAnAdmin), error_type(res));
+  let res = f.resolve(&query, None).await;
+  assert_eq!(Some(LemmyErrorType::NotLoggedIn), error_type(res));
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_person_without_aggregates() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_new_user").await?;
+  let person = f
+    .remote
+    .create_person("resolve_new_person", &f.context)
+    .await?;
+  let admin = f.create_user("resolve_new_admin", true).await?.person;
+  // like persons whose aggregates weren't created yet
+  let mut conn = f.context.inner_pool().get().await?;
+  conn
+    .batch_execute(&format!(
+      "DELETE FROM person_aggregates WHERE person_id IN ({}, {})",
+      person.id.0, admin.id.0
+    ))
+    .await?;
+  drop(conn);
+
+  // the person is still returned, without counts
+  let res = f.resolve(&query_for(&person.actor_id), None).await?;
+  let view = res.person.ok_or(LemmyErrorType::CouldntFindPerson)?;
+  assert_eq!(person.id, view.person.id);
+  assert_eq!(person.id, view.counts.person_id);
+  assert_eq!(0, view.counts.post_count);
+  assert!(!view.is_admin);
+
+  // the admin flag still comes from the local user
+  let res = f.resolve(&query_for(&admin.actor_id), None).await?;
+  let view = res.person.ok_or(LemmyErrorType::CouldntFindPerson)?;
+  assert_eq!(admin.id, view.person.id);
+  assert_eq!(0, view.counts.post_count);
+  assert!(view.is_admin);
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_crossposts() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_crosspost_user").await?;
+  let url = Url::parse("https://news.example/article")?;
+  let mut posts = vec![];
+  for name in [
+    "resolve_crosspost_1",
+    "resolve_crosspost_2",
+    "resolve_crosspost_3",
+  ] {
+    let community = f.local.create_community(name, &f.context).await?;
+    let post_form = PostInsertForm::builder()
+      .name(name.to_string())
+      .creator_id(f.user.person.id)
+      .community_id(community.id)
+      .url(Some(url.clone().into()))
+      .build();
+    posts.push(Post::create(&mut f.context.pool(), &post_form).await?);
+  }
+  let [post, crosspost_1, crosspost_2] = posts.as_slice() else {
+    Err(LemmyErrorType::CouldntFindPost)?
+  };
+
+  // only returned if requested
+  let mut query = query_for(format!("post:{}", post.id));
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert!(res.crossposts.is_none());
+
+  // both other posts are returned, but not the resolved post itself
+  query.include_crossposts = Some(true);
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  let crosspost_ids = res
+    .crossposts
+    .unwrap_or_default()
+    .into_iter()
+    .map(|p| p.post.id)
+    .collect::<HashSet<_>>();
+  assert_eq!(
+    HashSet::from([crosspost_1.id, crosspost_2.id]),
+    crosspost_ids
+  );
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_federation_status() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_status_user").await?;
+  let admin = f.create_user("resolve_status_admin", true).await?;
+  let community = f.remote.create_community("fed_status", &f.context).await?;
+  let query = query_for(&community.actor_id);
+
+  // instances which aren't in any list federate normally
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert_eq!(
+    Some(InstanceFederationStatus::Unknown),
+    res.federation_status
+  );
+
+  // the status is only shown to admins
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+  assert_eq!(None, res.federation_status);
+
+  // blocking the instance is shown right away
+  FederationBlockList::replace(
+    &mut f.context.pool(),
+    Some(vec![f.remote.instance.domain.clone()]),
+  )
+  .await?;
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert_eq!(
+    Some(InstanceFederationStatus::Blocked),
+    res.federation_status
+  );
+
+  FederationBlockList::replace(&mut f.context.pool(), Some(vec![])).await?;
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_edit_history() -> LemmyResult<()> {
+  // a remote server which serves an edited version of a known comment
+  let remote = mock_remote(|base| {
+    include_str!("../../../../assets/lemmy/objects/note.json")
+      .replace("https://enterprise.lemmy.ml", base)
+  })
+  .await?;
+  let f = Fixture::with_context("edit_history_user", remote.context().await?).await?;
+  let admin = f.create_user("edit_history_admin", true).await?;
+  let moderator = f.create_user("edit_history_moderator", false).await?;
+
+  // the first version of the comment, as it was received before the edit
+  let remote_instance = remote.instance(&f.context).await?;
+  let person = remote_instance.create_person("picard", &f.context).await?;
+  let community = remote_instance
+    .create_community("tenforward", &f.context)
+    .await?;
+  let moderator_form = CommunityModeratorForm {
+    community_id: community.id,
+    person_id: moderator.person.id,
+  };
+  CommunityModerator::join(&mut f.context.pool(), &moderator_form).await?;
+  let post = remote_instance
+    .create_post("55143", &person, &community, &f.context)
+    .await?;
+  let comment_form = CommentInsertForm::builder()
+    .content("original comment".to_string())
+    .creator_id(person.id)
+    .post_id(post.id)
+    .ap_id(Some(remote_instance.url("comment/38741")?))
+    .local(Some(false))
+    .published(Some("2021-03-01T13:42:43Z".parse()?))
+    .build();
+  let comment = Comment::create(&mut f.context.pool(), &comment_form, None).await?;
+  let mut query = ResolveObject {
+    include_edit_history: Some(true),
+    ..query_for(&comment.ap_id)
+  };
+
+  // without an edit there is no history
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert_eq!(Some(vec![]), res.edit_history);
+
+  // refetching the edited comment keeps the previous content
+  query.refresh = Some(true);
+  let res = f.resolve(&query, Some(&admin)).await?;
+  let resolved = res
+    .comment
+    .ok_or(LemmyErrorType::CouldntFindComment)?
+    .comment;
+  assert_eq!("first comment!", resolved.content);
+  let history = res.edit_history.unwrap_or_default();
+  let [edit] = history.as_slice() else {
+    Err(LemmyErrorType::CouldntFindComment)?
+  };
+  assert_eq!("original comment", edit.content);
+  assert_eq!(comment.published, edit.published);
+
+  // refetching the same version again doesn't add another entry
+  f.resolve(&query, Some(&admin)).await?;
+  assert_eq!(
+    1,
+    CommentEdit::list(&mut f.context.pool(), comment.id)
+      .await?
+      .len()
+  );
+
+  // moderators of the community see the history, other users don't
+  query.refresh = None;
+  let res = f.resolve(&query, Some(&moderator)).await?;
+  assert_eq!(Some(1), res.edit_history.map(|h| h.len()));
+  let res = f.resolve(&query, Some(&f.user)).await?;
+  assert!(res.comment.is_some());
+  assert_eq!(None, res.edit_history);
+
+  // deleting the account of the creator also deletes the previous versions
+  Comment::permadelete_for_creator(&mut f.context.pool(), person.id).await?;
+  assert_eq!(
+    Vec::<CommentEdit>::new(),
+    CommentEdit::list(&mut f.context.pool(), comment.id).await?
+  );
+
+  remote_instance.cleanup(&f.context).await?;
+  f.cleanup().await
+}
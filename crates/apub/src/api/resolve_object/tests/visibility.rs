@@ -0,0 +1,560 @@
+use super::{error_type, query_for, Fixture};
+use crate::{
+  api::{
+    resolve_object::{convert_response, is_expected_type, resolve_object},
+    test::TestInstance,
+  },
+  fetcher::search::SearchableObjects,
+};
+use activitypub_federation::config::Data;
+use actix_web::{test::TestRequest, web::Query};
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::{ModActionType, ResolveObject, ResolvedTombstone},
+};
+use lemmy_db_schema::{
+  source::{
+    activity::{ActorType, SentActivity, SentActivityForm},
+    comment::{Comment, CommentInsertForm, CommentUpdateForm},
+    community::{Community, CommunityModerator, CommunityModeratorForm, CommunityUpdateForm},
+    instance_block::{InstanceBlock, InstanceBlockForm},
+    local_site_federation::{LocalSiteFederation, LocalSiteFederationUpdateForm},
+    local_user::{LocalUser, LocalUserUpdateForm},
+    post::{Post, PostInsertForm, PostUpdateForm},
+  },
+  traits::{Blockable, Crud, Joinable},
+  CommunityVisibility,
+  ResolveObjectType,
+};
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::{LemmyErrorType, LemmyResult};
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+use url::Url;
+
+#[tokio::test]
+#[serial]
+async fn test_object_visibility() -> LemmyResult<()> {
+  let f = Fixture::new("visibility_user").await?;
+  let creator = f.create_user("visibility_creator", false).await?;
+  let admin = f.create_user("visibility_admin", true).await?;
+  let community = f.local.create_community("visibility", &f.context).await?;
+  let post = f
+    .local
+    .create_post("visibility", &creator.person, &community, &f.context)
+    .await?;
+  let query = query_for(&post.ap_id);
+  let viewers = [None, Some(&f.user), Some(&creator), Some(&admin)];
+
+  // objects are visible to everyone, also without login
+  for viewer in viewers {
+    let res = f.resolve(&query, viewer).await?;
+    assert_eq!(Some(post.id), res.post.map(|p| p.post.id));
+  }
+
+  // removed objects only to admins, not even to their creator
+  let form = PostUpdateForm {
+    removed: Some(true),
+    ..Default::default()
+  };
+  Post::update(&mut f.context.pool(), post.id, &form).await?;
+  for viewer in [None, Some(&f.user), Some(&creator)] {
+    let res = f.resolve(&query, viewer).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+  }
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert_eq!(Some(post.id), res.post.map(|p| p.post.id));
+
+  // deleted objects to nobody
+  let form = PostUpdateForm {
+    removed: Some(false),
+    deleted: Some(true),
+    ..Default::default()
+  };
+  Post::update(&mut f.context.pool(), post.id, &form).await?;
+  for viewer in viewers {
+    let res = f.resolve(&query, viewer).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+  }
+
+  // the same applies to removed communities
+  let form = CommunityUpdateForm {
+    removed: Some(true),
+    ..Default::default()
+  };
+  Community::update(&mut f.context.pool(), community.id, &form).await?;
+  let query = query_for(&community.actor_id);
+  let res = f.resolve(&query, Some(&creator)).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+  let res = f.resolve(&query, Some(&admin)).await?;
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_deleted_local_id() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_deleted_user").await?;
+  let community = f
+    .local
+    .create_community("resolve_deleted", &f.context)
+    .await?;
+  let post = f
+    .local
+    .create_post("resolve_deleted", &f.user.person, &community, &f.context)
+    .await?;
+
+  // unauthenticated users can resolve local objects by id
+  let query = query_for(format!("post:{}", post.id));
+  let res = resolve_object(
+    Query(query.clone()),
+    TestRequest::default().to_http_request(),
+    f.context.reset_request_count(),
+    None,
+  )
+  .await?
+  .0;
+  assert_eq!(Some(post.id), res.post.map(|p| p.post.id));
+
+  // but not once they are deleted
+  let post_form = PostUpdateForm {
+    deleted: Some(true),
+    ..Default::default()
+  };
+  Post::update(&mut f.context.pool(), post.id, &post_form).await?;
+  let res = resolve_object(
+    Query(query),
+    TestRequest::default().to_http_request(),
+    f.context.reset_request_count(),
+    None,
+  )
+  .await;
+  assert!(res.is_err());
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_local_only_post() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_local_only_user").await?;
+  let admin = f.create_user("resolve_local_only_admin", true).await?;
+  let community = f
+    .local
+    .create_community("resolve_local_only", &f.context)
+    .await?;
+  let community_form = CommunityUpdateForm {
+    visibility: Some(CommunityVisibility::LocalOnly),
+    ..Default::default()
+  };
+  Community::update(&mut f.context.pool(), community.id, &community_form).await?;
+  let post = f
+    .local
+    .create_post("resolve_local_only", &f.user.person, &community, &f.context)
+    .await?;
+  let query = query_for(format!("post:{}", post.id));
+
+  // posts in local only communities are hidden from users who aren't logged in
+  let res = resolve_object(
+    Query(query.clone()),
+    TestRequest::default().to_http_request(),
+    f.context.reset_request_count(),
+    None,
+  )
+  .await;
+  assert!(res.is_err());
+
+  // but visible to local users and admins
+  for viewer in [f.user.clone(), admin] {
+    let res = resolve_object(
+      Query(query.clone()),
+      TestRequest::default().to_http_request(),
+      f.context.reset_request_count(),
+      Some(viewer),
+    )
+    .await?
+    .0;
+    assert_eq!(Some(post.id), res.post.map(|p| p.post.id));
+  }
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_hide_nsfw() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_nsfw_user").await?;
+  let community = f.local.create_community("resolve_nsfw", &f.context).await?;
+  let community_form = CommunityUpdateForm {
+    nsfw: Some(true),
+    ..Default::default()
+  };
+  Community::update(&mut f.context.pool(), community.id, &community_form).await?;
+  let post = f
+    .local
+    .create_post("resolve_nsfw", &f.user.person, &community, &f.context)
+    .await?;
+  let post_query = query_for(format!("post:{}", post.id));
+  let community_query = query_for(&community.actor_id);
+
+  // nsfw content is visible without login by default
+  let res = f.resolve(&post_query, None).await?;
+  assert_eq!(Some(post.id), res.post.map(|p| p.post.id));
+
+  // but hidden once the site requires it
+  let federation_form = LocalSiteFederationUpdateForm {
+    hide_nsfw_from_resolve: Some(true),
+    ..Default::default()
+  };
+  LocalSiteFederation::update(&mut f.context.pool(), &federation_form).await?;
+  for query in [&post_query, &community_query] {
+    let res = f.resolve(query, None).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+  }
+
+  // logged in users with nsfw enabled still see it
+  let local_user_form = LocalUserUpdateForm {
+    show_nsfw: Some(true),
+    ..Default::default()
+  };
+  LocalUser::update(
+    &mut f.context.pool(),
+    f.user.local_user.id,
+    &local_user_form,
+  )
+  .await?;
+  let res = f.resolve(&post_query, Some(&f.user)).await?;
+  assert_eq!(Some(post.id), res.post.map(|p| p.post.id));
+  let res = f.resolve(&community_query, Some(&f.user)).await?;
+  assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+
+  f.cleanup().await
+}
+
+/// Creates a community with the given name, containing a post and a removed comment by the user.
+async fn create_removed_comment(
+  instance: &TestInstance,
+  user: &LocalUserView,
+  name: &str,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<(Community, Comment)> {
+  let community = instance.create_community(name, context).await?;
+  let post = instance
+    .create_post(name, &user.person, &community, context)
+    .await?;
+  let comment_form = CommentInsertForm::builder()
+    .content(name.to_string())
+    .creator_id(user.person.id)
+    .post_id(post.id)
+    .build();
+  let comment = Comment::create(&mut context.pool(), &comment_form, None).await?;
+  let comment_form = CommentUpdateForm {
+    removed: Some(true),
+    ..Default::default()
+  };
+  let comment = Comment::update(&mut context.pool(), comment.id, &comment_form).await?;
+  Ok((community, comment))
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_removed_comment_as_mod() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_removed_user").await?;
+  let moderator = f.create_user("resolve_removed_mod", false).await?;
+  let (modded_community, modded_comment) =
+    create_removed_comment(&f.local, &f.user, "resolve_removed_modded", &f.context).await?;
+  let (_, other_comment) =
+    create_removed_comment(&f.local, &f.user, "resolve_removed_other", &f.context).await?;
+  let moderator_form = CommunityModeratorForm {
+    community_id: modded_community.id,
+    person_id: moderator.person.id,
+  };
+  CommunityModerator::join(&mut f.context.pool(), &moderator_form).await?;
+
+  // the moderator can resolve the removed comment in their community
+  let query = query_for(format!("comment:{}", modded_comment.id));
+  let res = resolve_object(
+    Query(query.clone()),
+    TestRequest::default().to_http_request(),
+    f.context.reset_request_count(),
+    Some(moderator.clone()),
+  )
+  .await?
+  .0;
+  assert_eq!(Some(modded_comment.id), res.comment.map(|c| c.comment.id));
+
+  // but other users can't
+  let res = resolve_object(
+    Query(query),
+    TestRequest::default().to_http_request(),
+    f.context.reset_request_count(),
+    Some(f.user.clone()),
+  )
+  .await;
+  assert!(res.is_err());
+
+  // and neither can the moderator in another community
+  let query = query_for(format!("comment:{}", other_comment.id));
+  let res = resolve_object(
+    Query(query),
+    TestRequest::default().to_http_request(),
+    f.context.reset_request_count(),
+    Some(moderator),
+  )
+  .await;
+  assert!(res.is_err());
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_verbose_errors() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_verbose_user").await?;
+  let admin = f.create_user("resolve_verbose_admin", true).await?;
+  let community = f
+    .local
+    .create_community("resolve_verbose", &f.context)
+    .await?;
+  let post_form = PostInsertForm::builder()
+    .name("deleted post".to_string())
+    .creator_id(f.user.person.id)
+    .community_id(community.id)
+    .deleted(Some(true))
+    .build();
+  let post = Post::create(&mut f.context.pool(), &post_form).await?;
+
+  let deleted_query = ResolveObject {
+    verbose: Some(true),
+    ..query_for(format!("post:{}", post.id))
+  };
+  let missing_query = ResolveObject {
+    verbose: Some(true),
+    ..query_for(format!("post:{}", post.id.0 + 1000))
+  };
+
+  // regular users can't tell deleted objects apart from missing ones
+  for query in [&deleted_query, &missing_query] {
+    let res = f.resolve(query, Some(&f.user)).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+  }
+
+  // admins see the precise reason in verbose mode
+  let res = f.resolve(&deleted_query, Some(&admin)).await;
+  assert_eq!(
+    Some(LemmyErrorType::ResolvedObjectAccessDenied),
+    error_type(res)
+  );
+  let res = f.resolve(&missing_query, Some(&admin)).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+
+  // but not by default
+  let query = ResolveObject {
+    verbose: None,
+    ..deleted_query
+  };
+  let res = f.resolve(&query, Some(&admin)).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_tombstone() -> LemmyResult<()> {
+  let f = Fixture::new("resolve_tombstone_user").await?;
+  let tombstone = ResolvedTombstone {
+    ap_id: f.remote.url("post/1")?,
+    former_type: Some(ResolveObjectType::Post),
+  };
+
+  let res = convert_response(
+    SearchableObjects::Tombstone(tombstone.clone()),
+    Some(&f.user),
+    false,
+    &mut f.context.pool(),
+  )
+  .await?;
+  assert_eq!(Some(tombstone.clone()), res.tombstone);
+
+  // the former type is used to check the expected type
+  let object = SearchableObjects::Tombstone(tombstone.clone());
+  assert!(is_expected_type(&object, Some(ResolveObjectType::Post)));
+  assert!(!is_expected_type(&object, Some(ResolveObjectType::Comment)));
+
+  // without login, deleted objects look like they never existed
+  let res = convert_response(
+    SearchableObjects::Tombstone(tombstone),
+    None,
+    false,
+    &mut f.context.pool(),
+  )
+  .await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+
+  f.cleanup().await
+}
+
+/// Stores an activity which this instance sent, so that it can be resolved by its id.
+async fn create_sent_activity(
+  kind: &str,
+  mut data: serde_json::Value,
+  actor: &LocalUserView,
+  sensitive: bool,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<String> {
+  let ap_id = format!(
+    "{}/activities/{}/{}",
+    context.settings().get_protocol_and_hostname(),
+    kind.to_lowercase(),
+    uuid::Uuid::new_v4()
+  );
+  if let Some(data) = data.as_object_mut() {
+    data.insert("id".to_string(), ap_id.clone().into());
+    data.insert("type".to_string(), kind.into());
+    data.insert(
+      "actor".to_string(),
+      actor.person.actor_id.to_string().into(),
+    );
+  }
+  let activity_form = SentActivityForm {
+    ap_id: Url::parse(&ap_id)?.into(),
+    data,
+    sensitive,
+    send_inboxes: vec![],
+    send_community_followers_of: None,
+    send_all_instances: false,
+    actor_type: ActorType::Person,
+    actor_apub_id: actor.person.actor_id.clone(),
+  };
+  SentActivity::create(&mut context.pool(), activity_form).await?;
+  Ok(ap_id)
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_mod_action() -> LemmyResult<()> {
+  let f = Fixture::new("mod_action_user").await?;
+  let moderator = f.create_user("mod_action_moderator", false).await?;
+  let community = f.local.create_community("mod_action", &f.context).await?;
+  let moderator_form = CommunityModeratorForm {
+    community_id: community.id,
+    person_id: moderator.person.id,
+  };
+  CommunityModerator::join(&mut f.context.pool(), &moderator_form).await?;
+  // a ban which was sent by this instance
+  let data = serde_json::json!({
+    "object": f.user.person.actor_id,
+    "target": community.actor_id,
+    "summary": "spam",
+  });
+  let ap_id = create_sent_activity("Block", data, &moderator, false, &f.context).await?;
+  let query = query_for(ap_id);
+
+  // moderators can see the ban
+  let res = f.resolve(&query, Some(&moderator)).await?;
+  let mod_action = res.mod_action.ok_or(LemmyErrorType::CouldntFindObject)?;
+  assert_eq!(ModActionType::Block, mod_action.kind);
+  assert_eq!(f.user.person.actor_id, mod_action.object_id);
+  assert_eq!(Some(community.actor_id.clone()), mod_action.target_id);
+  assert_eq!(Some("spam".to_string()), mod_action.reason);
+
+  // but regular users and anonymous visitors don't learn that it exists
+  for local_user_view in [Some(&f.user), None] {
+    let res = f.resolve(&query, local_user_view).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+  }
+
+  // reports are only visible to the moderators of their community
+  let other_moderator = f.create_user("mod_action_other_moderator", false).await?;
+  let other_community = f
+    .local
+    .create_community("mod_action_other", &f.context)
+    .await?;
+  let moderator_form = CommunityModeratorForm {
+    community_id: other_community.id,
+    person_id: other_moderator.person.id,
+  };
+  CommunityModerator::join(&mut f.context.pool(), &moderator_form).await?;
+  let data = serde_json::json!({
+    "object": moderator.person.actor_id,
+    "audience": community.actor_id,
+    "summary": "report reason",
+  });
+  let ap_id = create_sent_activity("Flag", data, &f.user, true, &f.context).await?;
+  let query = query_for(ap_id);
+  let res = f.resolve(&query, Some(&moderator)).await?;
+  assert_eq!(Some(ModActionType::Flag), res.mod_action.map(|m| m.kind));
+  let res = f.resolve(&query, Some(&other_moderator)).await;
+  assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+
+  f.cleanup().await
+}
+
+#[tokio::test]
+#[serial]
+async fn test_resolve_blocked_by_user() -> LemmyResult<()> {
+  let f = Fixture::new("instance_blocking_user").await?;
+  let blocking_user = &f.user;
+  let other_user = f.create_user("instance_other_user", false).await?;
+  let admin = f.create_user("instance_blocking_admin", true).await?;
+  let community = f
+    .remote
+    .create_community("user_blocked", &f.context)
+    .await?;
+  for user in [blocking_user, &admin] {
+    let block_form = InstanceBlockForm {
+      person_id: user.person.id,
+      instance_id: f.remote.instance.id,
+    };
+    InstanceBlock::block(&mut f.context.pool(), &block_form).await?;
+  }
+
+  // the instance is blocked for this user, by url and by webfinger identifier
+  for q in [
+    community.actor_id.to_string(),
+    format!("!user_blocked@{}", f.remote.instance.domain),
+  ] {
+    let query = query_for(q);
+    let res = f.resolve(&query, Some(blocking_user)).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+    assert_eq!(0, f.context.request_count());
+
+    // but other users and admins can still resolve it
+    for user in [&other_user, &admin] {
+      let res = f.resolve(&query, Some(user)).await?;
+      assert_eq!(Some(community.id), res.community.map(|c| c.community.id));
+    }
+  }
+
+  // objects which are only found after resolving are left out as well, like a bare name or a
+  // local post in a community of the blocked instance
+  let post = f
+    .local
+    .create_post("user_blocked", &other_user.person, &community, &f.context)
+    .await?;
+  for q in ["user_blocked".to_string(), post.ap_id.to_string()] {
+    let query = query_for(q);
+    let res = f.resolve(&query, Some(blocking_user)).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+    let res = f.resolve(&query, Some(&other_user)).await?;
+    assert!(res.community.is_some() || res.post.is_some());
+  }
+
+  // an admin resolving as the blocking user gets the same result as the user, while the block
+  // of the admin doesn't apply when resolving as another user
+  for q in [community.actor_id.to_string(), post.ap_id.to_string()] {
+    let mut query = ResolveObject {
+      as_user: Some(blocking_user.local_user.id),
+      ..query_for(q)
+    };
+    let res = f.resolve(&query, Some(&admin)).await;
+    assert_eq!(Some(LemmyErrorType::CouldntFindObject), error_type(res));
+    query.as_user = Some(other_user.local_user.id);
+    let res = f.resolve(&query, Some(&admin)).await?;
+    assert!(res.community.is_some() || res.post.is_some());
+  }
+
+  f.cleanup().await
+}
@@ -0,0 +1,133 @@
+use crate::api::resolve_object::resolve;
+use activitypub_federation::config::Data;
+use actix_web::{web::Json, HttpRequest};
+use futures::StreamExt;
+use lemmy_api_common::{
+  context::LemmyContext,
+  site::{ResolveObject, ResolveObjects, ResolveObjectsResponse},
+  utils::check_private_instance,
+};
+use lemmy_db_schema::source::local_site::LocalSite;
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::{
+  error::{LemmyErrorType, LemmyResult},
+  rate_limit::get_ip,
+};
+
+/// Maximum number of queries which can be resolved in a single batch.
+const MAX_QUERIES: usize = 50;
+const PARALLELISM: usize = 10;
+
+#[tracing::instrument(skip(context))]
+pub async fn resolve_objects(
+  data: Json<ResolveObjects>,
+  req: HttpRequest,
+  context: Data<LemmyContext>,
+  local_user_view: Option<LocalUserView>,
+) -> LemmyResult<Json<ResolveObjectsResponse>> {
+  let local_site = LocalSite::read(&mut context.pool()).await?;
+  check_private_instance(&local_user_view, &local_site)?;
+  if data.queries.len() > MAX_QUERIES {
+    Err(LemmyErrorType::TooManyItems)?
+  }
+  let ip_addr = get_ip(&req.connection_info());
+
+  let objects = futures::stream::iter(data.queries.iter().map(|q| {
+    let query = ResolveObject {
+      q: q.clone(),
+      ..Default::default()
+    };
+    // need to reset outgoing request count so that each query gets its own limit
+    let context = context.reset_request_count();
+    let local_user_view = local_user_view.as_ref();
+    async move {
+      resolve(&query, local_user_view, ip_addr, &context)
+        .await
+        .ok()
+    }
+  }))
+  .buffered(PARALLELISM)
+  .collect()
+  .await;
+
+  Ok(Json(ResolveObjectsResponse { objects }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::api::test::{create_local_site, create_user};
+  use actix_web::test::TestRequest;
+  use lemmy_db_schema::{
+    source::{
+      community::{Community, CommunityInsertForm},
+      instance::Instance,
+      post::{Post, PostInsertForm},
+    },
+    traits::Crud,
+  };
+  use pretty_assertions::assert_eq;
+  use serial_test::serial;
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_objects() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let user = create_user("resolve_batch_user".to_string(), None, false, &context).await?;
+    let instance_id = user.person.instance_id;
+    create_local_site(instance_id, &context).await?;
+
+    let community_form = CommunityInsertForm::builder()
+      .name("resolve_batch".to_string())
+      .title("resolve_batch".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(instance_id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+    let post_form = PostInsertForm::builder()
+      .name("batch post".to_string())
+      .creator_id(user.person.id)
+      .community_id(community.id)
+      .build();
+    let post = Post::create(&mut context.pool(), &post_form).await?;
+
+    let queries = vec![
+      post.ap_id.to_string(),
+      "https://batch.example/post/1".to_string(),
+      "!resolve_batch@example.com".to_string(),
+      "post:0".to_string(),
+    ];
+    let res = resolve_objects(
+      Json(ResolveObjects { queries }),
+      TestRequest::default().to_http_request(),
+      context.reset_request_count(),
+      Some(user),
+    )
+    .await?
+    .0;
+
+    // results keep the order of the queries, with failures as None
+    assert_eq!(4, res.objects.len());
+    let mut objects = res.objects.into_iter();
+    let post_res = objects.next().flatten().and_then(|o| o.post);
+    assert_eq!(Some(post.id), post_res.map(|p| p.post.id));
+    assert!(objects.next().flatten().is_none());
+    let community_res = objects.next().flatten().and_then(|o| o.community);
+    assert_eq!(Some(community.id), community_res.map(|c| c.community.id));
+    assert!(objects.next().flatten().is_none());
+
+    // too many queries are rejected
+    let queries = vec![post.ap_id.to_string(); MAX_QUERIES + 1];
+    let res = resolve_objects(
+      Json(ResolveObjects { queries }),
+      TestRequest::default().to_http_request(),
+      context.reset_request_count(),
+      None,
+    )
+    .await;
+    assert!(res.is_err());
+
+    Instance::delete(&mut context.pool(), instance_id).await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,398 @@
+use activitypub_federation::config::{Data, FederationConfig};
+use lemmy_api_common::context::LemmyContext;
+use lemmy_db_schema::{
+  newtypes::{DbUrl, InstanceId, SiteId},
+  source::{
+    community::{Community, CommunityInsertForm},
+    instance::Instance,
+    local_site::{LocalSite, LocalSiteInsertForm},
+    local_site_federation::{LocalSiteFederation, LocalSiteFederationInsertForm},
+    local_site_rate_limit::{LocalSiteRateLimit, LocalSiteRateLimitInsertForm},
+    local_user::{LocalUser, LocalUserInsertForm},
+    person::{Person, PersonInsertForm},
+    post::{Post, PostInsertForm},
+    site::{Site, SiteInsertForm},
+  },
+  traits::Crud,
+};
+use lemmy_db_views::structs::LocalUserView;
+use lemmy_utils::error::{LemmyErrorType, LemmyResult};
+use reqwest_middleware::ClientWithMiddleware;
+use std::{net::SocketAddr, sync::Arc};
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpListener,
+  sync::Mutex,
+  task::JoinHandle,
+};
+use url::Url;
+
+/// Creates a local user on instance `example.com`, for use in tests.
+pub(crate) async fn create_user(
+  name: String,
+  bio: Option<String>,
+  admin: bool,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<LocalUserView> {
+  create_user_with_images(name, bio, admin, None, None, context).await
+}
+
+/// Same as [create_user], but also sets the avatar and banner.
+pub(crate) async fn create_user_with_images(
+  name: String,
+  bio: Option<String>,
+  admin: bool,
+  avatar: Option<Url>,
+  banner: Option<Url>,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<LocalUserView> {
+  let instance = Instance::read_or_create(&mut context.pool(), "example.com".to_string()).await?;
+  let person_form = PersonInsertForm::builder()
+    .name(name.clone())
+    .display_name(Some(name.clone()))
+    .bio(bio)
+    .avatar(avatar.map(Into::into))
+    .banner(banner.map(Into::into))
+    .public_key("asd".to_string())
+    .instance_id(instance.id)
+    .build();
+  create_local_user(&person_form, admin, context).await
+}
+
+async fn create_local_user(
+  person_form: &PersonInsertForm,
+  admin: bool,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<LocalUserView> {
+  let person = Person::create(&mut context.pool(), person_form).await?;
+
+  let user_form = LocalUserInsertForm::builder()
+    .person_id(person.id)
+    .password_encrypted("pass".to_string())
+    .admin(Some(admin))
+    .build();
+  let local_user = LocalUser::create(&mut context.pool(), &user_form, vec![]).await?;
+
+  Ok(
+    LocalUserView::read(&mut context.pool(), local_user.id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindLocalUser)?,
+  )
+}
+
+/// Creates the site and local site for the given instance, for use in tests.
+pub(crate) async fn create_local_site(
+  instance_id: InstanceId,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<()> {
+  let site_form = SiteInsertForm::builder()
+    .name("test site".to_string())
+    .instance_id(instance_id)
+    .build();
+  let site = Site::create(&mut context.pool(), &site_form).await?;
+  create_local_site_for(site.id, context).await?;
+  Ok(())
+}
+
+/// Creates the local site with its settings for an existing site, as reading the local site view
+/// needs all of them.
+async fn create_local_site_for(
+  site_id: SiteId,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<LocalSite> {
+  let local_site_form = LocalSiteInsertForm::builder().site_id(site_id).build();
+  let local_site = LocalSite::create(&mut context.pool(), &local_site_form).await?;
+  let rate_limit_form = LocalSiteRateLimitInsertForm::builder()
+    .local_site_id(local_site.id)
+    .build();
+  LocalSiteRateLimit::create(&mut context.pool(), &rate_limit_form).await?;
+  let federation_form = LocalSiteFederationInsertForm::builder()
+    .local_site_id(local_site.id)
+    .build();
+  LocalSiteFederation::create(&mut context.pool(), &federation_form).await?;
+  Ok(local_site)
+}
+
+/// An instance with its site, for use in tests. The local instance also has a local site.
+pub(crate) struct TestInstance {
+  pub(crate) instance: Instance,
+  pub(crate) site: Site,
+  pub(crate) local_site: Option<LocalSite>,
+  base: String,
+}
+
+/// Builder for [TestInstance], see [TestInstance::builder].
+pub(crate) struct TestInstanceBuilder {
+  domain: String,
+  local: bool,
+  base: Option<String>,
+}
+
+impl TestInstanceBuilder {
+  /// Don't create a local site, so that the instance and its content are treated as remote.
+  pub(crate) fn remote(mut self) -> Self {
+    self.local = false;
+    self
+  }
+
+  /// Use this url instead of `https://<domain>` for the ids of the site and all created objects,
+  /// eg the url of a [MockRemote].
+  pub(crate) fn base_url(mut self, base: &str) -> Self {
+    self.base = Some(base.to_string());
+    self
+  }
+
+  pub(crate) async fn create(self, context: &Data<LemmyContext>) -> LemmyResult<TestInstance> {
+    let instance = Instance::read_or_create(&mut context.pool(), self.domain.clone()).await?;
+    let base = self
+      .base
+      .unwrap_or_else(|| format!("https://{}", self.domain));
+    let actor_id = Url::parse(&format!("{base}/"))?;
+    let site_form = SiteInsertForm::builder()
+      .name(self.domain.clone())
+      .instance_id(instance.id)
+      .actor_id(Some(actor_id.into()))
+      .build();
+    let site = Site::create(&mut context.pool(), &site_form).await?;
+    let local_site = if self.local {
+      Some(create_local_site_for(site.id, context).await?)
+    } else {
+      None
+    };
+    Ok(TestInstance {
+      instance,
+      site,
+      local_site,
+      base,
+    })
+  }
+}
+
+impl TestInstance {
+  /// Starts building a local instance with the given domain.
+  pub(crate) fn builder(domain: &str) -> TestInstanceBuilder {
+    TestInstanceBuilder {
+      domain: domain.to_string(),
+      local: true,
+      base: None,
+    }
+  }
+
+  /// Creates the local instance `example.com` and a remote instance with the given domain.
+  pub(crate) async fn local_and_remote(
+    remote_domain: &str,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<(TestInstance, TestInstance)> {
+    let local = TestInstance::builder("example.com").create(context).await?;
+    let remote = TestInstance::builder(remote_domain)
+      .remote()
+      .create(context)
+      .await?;
+    Ok((local, remote))
+  }
+
+  fn is_local(&self) -> bool {
+    self.local_site.is_some()
+  }
+
+  pub(crate) fn url(&self, path: &str) -> LemmyResult<DbUrl> {
+    Ok(Url::parse(&format!("{}/{path}", self.base))?.into())
+  }
+
+  /// Creates a person on this instance, with the actor_id it would have on that instance.
+  pub(crate) async fn create_person(
+    &self,
+    name: &str,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<Person> {
+    Ok(Person::create(&mut context.pool(), &self.person_form(name)?).await?)
+  }
+
+  /// Creates a person with a local user. Only the local instance has local users, for remote
+  /// instances use [TestInstance::create_person].
+  pub(crate) async fn create_user(
+    &self,
+    name: &str,
+    admin: bool,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<LocalUserView> {
+    if !self.is_local() {
+      Err(LemmyErrorType::CouldntFindLocalUser)?
+    }
+    create_local_user(&self.person_form(name)?, admin, context).await
+  }
+
+  fn person_form(&self, name: &str) -> LemmyResult<PersonInsertForm> {
+    Ok(
+      PersonInsertForm::builder()
+        .name(name.to_string())
+        .display_name(Some(name.to_string()))
+        .public_key("asd".to_string())
+        .instance_id(self.instance.id)
+        .local(Some(self.is_local()))
+        .actor_id(Some(self.url(&format!("u/{name}"))?))
+        .build(),
+    )
+  }
+
+  /// Creates a community on this instance, with the actor_id it would have on that instance.
+  pub(crate) async fn create_community(
+    &self,
+    name: &str,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<Community> {
+    let community_form = CommunityInsertForm::builder()
+      .name(name.to_string())
+      .title(name.to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(self.instance.id)
+      .local(Some(self.is_local()))
+      .actor_id(Some(self.url(&format!("c/{name}"))?))
+      .build();
+    Ok(Community::create(&mut context.pool(), &community_form).await?)
+  }
+
+  /// Creates a post in the community, with the ap_id it would have on this instance.
+  pub(crate) async fn create_post(
+    &self,
+    name: &str,
+    creator: &Person,
+    community: &Community,
+    context: &Data<LemmyContext>,
+  ) -> LemmyResult<Post> {
+    let post_form = PostInsertForm::builder()
+      .name(name.to_string())
+      .creator_id(creator.id)
+      .community_id(community.id)
+      .local(Some(self.is_local()))
+      .ap_id(Some(self.url(&format!("post/{name}"))?))
+      .build();
+    Ok(Post::create(&mut context.pool(), &post_form).await?)
+  }
+
+  /// Deletes the local site, site and instance, together with all content of the instance.
+  pub(crate) async fn cleanup(self, context: &Data<LemmyContext>) -> LemmyResult<()> {
+    if self.local_site.is_some() {
+      LocalSite::delete(&mut context.pool()).await?;
+    }
+    Site::delete(&mut context.pool(), self.site.id).await?;
+    Instance::delete(&mut context.pool(), self.instance.id).await?;
+    Ok(())
+  }
+}
+
+/// A fake remote instance on localhost, for tests which fetch objects. Each request is answered
+/// with the raw http response returned by the function passed to [MockRemote::serve], or not at
+/// all if it returns `None`. The server stops when this is dropped.
+pub(crate) struct MockRemote {
+  /// The url of the server like `http://localhost:1234`, which object ids should start with.
+  pub(crate) base: String,
+  pub(crate) addr: SocketAddr,
+  listener: Option<TcpListener>,
+  requests: Arc<Mutex<Vec<String>>>,
+  server: Option<JoinHandle<()>>,
+}
+
+impl MockRemote {
+  /// Binds the server to a free port, so that the response can be built from its url.
+  pub(crate) async fn bind() -> LemmyResult<Self> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    Ok(MockRemote {
+      base: format!("http://localhost:{}", addr.port()),
+      addr,
+      listener: Some(listener),
+      requests: Arc::new(Mutex::new(vec![])),
+      server: None,
+    })
+  }
+
+  /// Starts answering requests. `respond` gets the text of the request.
+  pub(crate) fn serve(&mut self, respond: impl Fn(&str) -> Option<String> + Send + 'static) {
+    let Some(listener) = self.listener.take() else {
+      return;
+    };
+    let requests = self.requests.clone();
+    self.server = Some(tokio::spawn(async move {
+      let mut unanswered = vec![];
+      while let Ok((mut stream, _)) = listener.accept().await {
+        let mut buf = [0; 4096];
+        let len = stream.read(&mut buf).await.unwrap_or_default();
+        let request = String::from_utf8_lossy(buf.get(..len).unwrap_or_default());
+        if let Some(line) = request.lines().next() {
+          requests.lock().await.push(line.to_string());
+        }
+        match respond(&request) {
+          Some(response) => {
+            let _ = stream.write_all(response.as_bytes()).await;
+          }
+          // Keep the connection open, so that the client waits for a response
+          None => unanswered.push(stream),
+        }
+      }
+    }));
+  }
+
+  /// The first line of each received request, like `GET /u/picard HTTP/1.1`.
+  pub(crate) async fn requests(&self) -> Vec<String> {
+    self.requests.lock().await.clone()
+  }
+
+  /// A context which can fetch from this server, as plain http to localhost needs debug mode.
+  pub(crate) async fn context(&self) -> LemmyResult<Data<LemmyContext>> {
+    mock_remote_context(None).await
+  }
+
+  /// A remote [TestInstance] for this server, whose objects can be fetched again.
+  pub(crate) async fn instance(&self, context: &Data<LemmyContext>) -> LemmyResult<TestInstance> {
+    TestInstance::builder("localhost")
+      .remote()
+      .base_url(&self.base)
+      .create(context)
+      .await
+  }
+}
+
+impl Drop for MockRemote {
+  fn drop(&mut self) {
+    if let Some(server) = &self.server {
+      server.abort();
+    }
+  }
+}
+
+/// Starts a [MockRemote] which serves the same json for every request. The json is built from
+/// the url of the server.
+pub(crate) async fn mock_remote(body: impl FnOnce(&str) -> String) -> LemmyResult<MockRemote> {
+  let mut remote = MockRemote::bind().await?;
+  let response = json_response(&body(&remote.base));
+  remote.serve(move |_| Some(response.clone()));
+  Ok(remote)
+}
+
+/// A successful http response with the activitypub json.
+pub(crate) fn json_response(body: &str) -> String {
+  format!(
+    "HTTP/1.1 200 OK\r\nContent-Type: application/activity+json\r\n\
+     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+    body.len()
+  )
+}
+
+/// A context which allows plain http requests, optionally with a custom client. The test context
+/// itself doesn't make any requests.
+pub(crate) async fn mock_remote_context(
+  client: Option<ClientWithMiddleware>,
+) -> LemmyResult<Data<LemmyContext>> {
+  let test_context = LemmyContext::init_test_context().await;
+  let mut config = FederationConfig::builder();
+  config
+    .domain(test_context.settings().hostname.clone())
+    .app_data(test_context.app_data().clone())
+    .debug(true)
+    .allow_http_urls(true);
+  if let Some(client) = client {
+    config.client(client);
+  }
+  Ok(config.build().await?.to_request_data())
+}
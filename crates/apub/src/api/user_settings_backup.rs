@@ -312,15 +312,15 @@ where
 #[allow(clippy::indexing_slicing)]
 mod tests {
 
-  use crate::api::user_settings_backup::{export_settings, import_settings, UserSettingsBackup};
-  use activitypub_federation::config::Data;
+  use crate::api::{
+    test::create_user,
+    user_settings_backup::{export_settings, import_settings, UserSettingsBackup},
+  };
   use lemmy_api_common::context::LemmyContext;
   use lemmy_db_schema::{
     source::{
       community::{Community, CommunityFollower, CommunityFollowerForm, CommunityInsertForm},
-      instance::Instance,
-      local_user::{LocalUser, LocalUserInsertForm},
-      person::{Person, PersonInsertForm},
+      local_user::LocalUser,
     },
     traits::{Crud, Followable},
   };
@@ -332,41 +332,18 @@ mod tests {
   use std::time::Duration;
   use tokio::time::sleep;
 
-  async fn create_user(
-    name: String,
-    bio: Option<String>,
-    context: &Data<LemmyContext>,
-  ) -> LemmyResult<LocalUserView> {
-    let instance = Instance::read_or_create(&mut context.pool(), "example.com".to_string()).await?;
-    let person_form = PersonInsertForm::builder()
-      .name(name.clone())
-      .display_name(Some(name.clone()))
-      .bio(bio)
-      .public_key("asd".to_string())
-      .instance_id(instance.id)
-      .build();
-    let person = Person::create(&mut context.pool(), &person_form).await?;
-
-    let user_form = LocalUserInsertForm::builder()
-      .person_id(person.id)
-      .password_encrypted("pass".to_string())
-      .build();
-    let local_user = LocalUser::create(&mut context.pool(), &user_form, vec![]).await?;
-
-    Ok(
-      LocalUserView::read(&mut context.pool(), local_user.id)
-        .await?
-        .ok_or(LemmyErrorType::CouldntFindLocalUser)?,
-    )
-  }
-
   #[tokio::test]
   #[serial]
   async fn test_settings_export_import() -> LemmyResult<()> {
     let context = LemmyContext::init_test_context().await;
 
-    let export_user =
-      create_user("hanna".to_string(), Some("my bio".to_string()), &context).await?;
+    let export_user = create_user(
+      "hanna".to_string(),
+      Some("my bio".to_string()),
+      false,
+      &context,
+    )
+    .await?;
 
     let community_form = CommunityInsertForm::builder()
       .name("testcom".to_string())
@@ -383,7 +360,7 @@ mod tests {
 
     let backup = export_settings(export_user.clone(), context.reset_request_count()).await?;
 
-    let import_user = create_user("charles".to_string(), None, &context).await?;
+    let import_user = create_user("charles".to_string(), None, false, &context).await?;
 
     import_settings(backup, import_user.clone(), context.reset_request_count()).await?;
 
@@ -415,8 +392,13 @@ mod tests {
   async fn test_settings_partial_import() -> LemmyResult<()> {
     let context = LemmyContext::init_test_context().await;
 
-    let export_user =
-      create_user("hanna".to_string(), Some("my bio".to_string()), &context).await?;
+    let export_user = create_user(
+      "hanna".to_string(),
+      Some("my bio".to_string()),
+      false,
+      &context,
+    )
+    .await?;
 
     let community_form = CommunityInsertForm::builder()
       .name("testcom".to_string())
@@ -433,7 +415,7 @@ mod tests {
 
     let backup = export_settings(export_user.clone(), context.reset_request_count()).await?;
 
-    let import_user = create_user("charles".to_string(), None, &context).await?;
+    let import_user = create_user("charles".to_string(), None, false, &context).await?;
 
     let backup2 = UserSettingsBackup {
       followed_communities: backup.followed_communities.clone(),
@@ -453,8 +435,13 @@ mod tests {
   async fn disallow_large_backup() -> LemmyResult<()> {
     let context = LemmyContext::init_test_context().await;
 
-    let export_user =
-      create_user("hanna".to_string(), Some("my bio".to_string()), &context).await?;
+    let export_user = create_user(
+      "hanna".to_string(),
+      Some("my bio".to_string()),
+      false,
+      &context,
+    )
+    .await?;
 
     let mut backup = export_settings(export_user.clone(), context.reset_request_count()).await?;
 
@@ -469,7 +456,7 @@ mod tests {
       backup.saved_comments.push("http://example4.com".parse()?);
     }
 
-    let import_user = create_user("charles".to_string(), None, &context).await?;
+    let import_user = create_user("charles".to_string(), None, false, &context).await?;
 
     let imported =
       import_settings(backup, import_user.clone(), context.reset_request_count()).await;
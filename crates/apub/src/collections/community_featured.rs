@@ -1,4 +1,5 @@
 use crate::{
+  collections::{fetch_permits, join_limited},
   objects::{community::ApubCommunity, post::ApubPost},
   protocol::collections::group_featured::GroupFeatured,
 };
@@ -8,7 +9,7 @@ use activitypub_federation::{
   protocol::verification::verify_domains_match,
   traits::{Collection, Object},
 };
-use futures::future::{join_all, try_join_all};
+use futures::future::try_join_all;
 use lemmy_api_common::{context::LemmyContext, utils::generate_featured_url};
 use lemmy_db_schema::{
   source::{community::Community, post::Post},
@@ -74,19 +75,20 @@ impl Collection for ApubCommunityFeatured {
 
     // process items in parallel, to avoid long delay from fetch_site_metadata() and other
     // processing
-    let stickied_posts: Vec<Post> = join_all(pages.into_iter().map(|page| {
+    let posts = pages.into_iter().map(|page| {
       async {
         // use separate request counter for each item, otherwise there will be problems with
         // parallel processing
         ApubPost::verify(&page, &apub.id, context).await?;
         ApubPost::from_json(page, context).await
       }
-    }))
-    .await
-    // ignore any failed or unparseable items
-    .into_iter()
-    .filter_map(|p| p.ok().map(|p| p.0))
-    .collect();
+    });
+    let stickied_posts: Vec<Post> = join_limited(posts, &fetch_permits())
+      .await
+      // ignore any failed or unparseable items
+      .into_iter()
+      .filter_map(|p| p.ok().map(|p| p.0))
+      .collect();
 
     Community::set_featured_posts(owner.id, stickied_posts, &mut context.pool()).await?;
 
@@ -1,5 +1,6 @@
 use crate::{
   activity_lists::AnnouncableActivities,
+  collections::{fetch_permits, join_limited},
   objects::community::ApubCommunity,
   protocol::{
     activities::{
@@ -16,14 +17,19 @@ use activitypub_federation::{
   protocol::verification::verify_domains_match,
   traits::{ActivityHandler, Collection},
 };
-use futures::future::join_all;
 use lemmy_api_common::{context::LemmyContext, utils::generate_outbox_url};
-use lemmy_db_schema::{utils::FETCH_LIMIT_MAX, SortType};
+use lemmy_db_schema::{newtypes::CommunityId, utils::FETCH_LIMIT_MAX, SortType};
 use lemmy_db_views::{post_view::PostQuery, structs::SiteView};
 use lemmy_utils::{
   error::{LemmyError, LemmyResult},
   LemmyErrorType,
 };
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+  sync::{watch, Mutex},
+  time::timeout,
+};
 use url::Url;
 
 #[derive(Clone, Debug)]
@@ -88,39 +94,86 @@ impl Collection for ApubCommunityOutbox {
   #[tracing::instrument(skip_all)]
   async fn from_json(
     apub: Self::Kind,
-    _owner: &Self::Owner,
+    owner: &Self::Owner,
     data: &Data<Self::DataType>,
   ) -> LemmyResult<Self> {
-    let mut outbox_activities = apub.ordered_items;
-    if outbox_activities.len() as i64 > FETCH_LIMIT_MAX {
-      outbox_activities = outbox_activities
-        .get(0..(FETCH_LIMIT_MAX as usize))
-        .unwrap_or_default()
-        .to_vec();
-    }
-
-    // We intentionally ignore errors here. This is because the outbox might contain posts from old
-    // Lemmy versions, or from other software which we cant parse. In that case, we simply skip the
-    // item and only parse the ones that work.
-    // process items in parallel, to avoid long delay from fetch_site_metadata() and other
-    // processing
-    join_all(outbox_activities.into_iter().map(|activity| {
-      async {
-        // Receiving announce requires at least one local community follower for anti spam purposes.
-        // This won't be the case for newly fetched communities, so we extract the inner activity
-        // and handle it directly to bypass this check.
-        let inner = activity.object.object(data).await.map(TryInto::try_into);
-        if let Ok(Ok(AnnouncableActivities::CreateOrUpdatePost(inner))) = inner {
-          let verify = inner.verify(data).await;
-          if verify.is_ok() {
-            inner.receive(data).await.ok();
-          }
-        }
-      }
-    }))
-    .await;
+    receive_outbox_posts(apub, owner, data).await;
 
     // This return value is unused, so just set an empty vec
     Ok(ApubCommunityOutbox(()))
   }
 }
+
+/// Outbox fetches of newly fetched communities which are still running, with the number of posts
+/// received so far. Resolving a community waits for these instead of fetching the outbox again.
+static OUTBOX_FETCHES: Lazy<Mutex<HashMap<CommunityId, Arc<watch::Sender<usize>>>>> =
+  Lazy::new(Mutex::default);
+
+/// A running outbox fetch of a community, see [wait_for_outbox_posts].
+pub(crate) struct OutboxFetch(CommunityId);
+
+impl OutboxFetch {
+  /// Must be called before the outbox is fetched, so that the fetch can be awaited right away.
+  pub(crate) async fn start(community_id: CommunityId) -> Self {
+    let (progress, _) = watch::channel(0);
+    OUTBOX_FETCHES
+      .lock()
+      .await
+      .insert(community_id, Arc::new(progress));
+    OutboxFetch(community_id)
+  }
+
+  pub(crate) async fn finish(self) {
+    OUTBOX_FETCHES.lock().await.remove(&self.0);
+  }
+}
+
+/// Waits until `limit` posts from the outbox of a newly fetched community were received, the
+/// outbox fetch finished, or `wait` elapsed. Returns right away if the outbox isn't being fetched.
+pub(crate) async fn wait_for_outbox_posts(community_id: CommunityId, limit: usize, wait: Duration) {
+  let progress = OUTBOX_FETCHES
+    .lock()
+    .await
+    .get(&community_id)
+    .map(|p| p.subscribe());
+  if let Some(mut progress) = progress {
+    // Fails once the fetch is finished and the sender dropped
+    timeout(wait, progress.wait_for(|received| *received >= limit))
+      .await
+      .ok();
+  }
+}
+
+async fn receive_outbox_posts(
+  outbox: GroupOutbox,
+  owner: &ApubCommunity,
+  data: &Data<LemmyContext>,
+) {
+  let mut outbox_activities = outbox.ordered_items;
+  outbox_activities.truncate(FETCH_LIMIT_MAX as usize);
+  let progress = OUTBOX_FETCHES.lock().await.get(&owner.id).cloned();
+
+  // We intentionally ignore errors here. This is because the outbox might contain posts from old
+  // Lemmy versions, or from other software which we cant parse. In that case, we simply skip the
+  // item and only parse the ones that work.
+  // process items in parallel, to avoid long delay from fetch_site_metadata() and other
+  // processing
+  let activities = outbox_activities.into_iter().map(|activity| {
+    let progress = progress.clone();
+    async move {
+      // Receiving announce requires at least one local community follower for anti spam purposes.
+      // This won't be the case for newly fetched communities, so we extract the inner activity
+      // and handle it directly to bypass this check.
+      let inner = activity.object.object(data).await.map(TryInto::try_into);
+      if let Ok(Ok(AnnouncableActivities::CreateOrUpdatePost(inner))) = inner {
+        let verify = inner.verify(data).await;
+        if verify.is_ok() && inner.receive(data).await.is_ok() {
+          if let Some(progress) = progress {
+            progress.send_modify(|received| *received += 1);
+          }
+        }
+      }
+    }
+  });
+  join_limited(activities, &fetch_permits()).await;
+}
@@ -1,4 +1,172 @@
+use futures::{stream::FuturesOrdered, Future, StreamExt};
+use lemmy_utils::settings::SETTINGS;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
 pub(crate) mod community_featured;
 pub(crate) mod community_follower;
 pub(crate) mod community_moderators;
 pub(crate) mod community_outbox;
+
+tokio::task_local! {
+  /// The permits of the query which is being resolved, see [fetch_permits]. Unset outside of
+  /// resolving.
+  static FETCH_PERMITS: Arc<Semaphore>;
+}
+
+/// Resolves a query with its own permits, so that all collections which are processed for it
+/// share the limit of concurrent fetches.
+pub(crate) async fn with_fetch_permits<F: Future>(future: F) -> F::Output {
+  FETCH_PERMITS.scope(new_fetch_permits(), future).await
+}
+
+/// Runs a future which was spawned while resolving with the same permits, so that collections
+/// which are processed in the background count towards the limit as well.
+pub(crate) fn keep_fetch_permits<F: Future>(future: F) -> impl Future<Output = F::Output> {
+  let permits = FETCH_PERMITS.try_with(Arc::clone).ok();
+  async move {
+    match permits {
+      Some(permits) => FETCH_PERMITS.scope(permits, future).await,
+      None => future.await,
+    }
+  }
+}
+
+/// The permits for processing the items of a fetched collection with [join_limited]. Outside of
+/// resolving, eg for a community which is fetched when an activity is received, each collection
+/// gets its own permits.
+pub(crate) fn fetch_permits() -> Arc<Semaphore> {
+  FETCH_PERMITS
+    .try_with(Arc::clone)
+    .unwrap_or_else(|_| new_fetch_permits())
+}
+
+fn new_fetch_permits() -> Arc<Semaphore> {
+  // The caller always processes one item itself, without a permit
+  let permits = SETTINGS
+    .resolve_object
+    .max_concurrent_fetches
+    .saturating_sub(1);
+  Arc::new(Semaphore::new(permits))
+}
+
+/// Runs the futures in parallel while permits are available, so that processing the items of
+/// fetched collections can't open an unbounded number of connections. The permits are shared by
+/// all collections of a resolved query, see [fetch_permits]. The caller always processes one item
+/// without a permit, so that nested collections can't wait for each other. The results are
+/// returned in order.
+pub(crate) async fn join_limited<F: Future>(
+  futures: impl IntoIterator<Item = F>,
+  permits: &Semaphore,
+) -> Vec<F::Output> {
+  let mut pending = futures.into_iter();
+  let mut running = FuturesOrdered::new();
+  let mut results = Vec::new();
+  loop {
+    loop {
+      let permit = if running.is_empty() {
+        None
+      } else {
+        match permits.try_acquire() {
+          Ok(permit) => Some(permit),
+          Err(_) => break,
+        }
+      };
+      let Some(future) = pending.next() else {
+        break;
+      };
+      running.push_back(async move {
+        let output = future.await;
+        drop(permit);
+        output
+      });
+    }
+    match running.next().await {
+      Some(output) => results.push(output),
+      None => return results,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use lemmy_utils::error::LemmyResult;
+  use pretty_assertions::assert_eq;
+  use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+  };
+  use tokio::time::{sleep, timeout};
+
+  async fn run_counted(running: &AtomicUsize, max_running: &AtomicUsize) {
+    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+    max_running.fetch_max(now_running, Ordering::SeqCst);
+    sleep(Duration::from_millis(10)).await;
+    running.fetch_sub(1, Ordering::SeqCst);
+  }
+
+  #[tokio::test]
+  async fn test_join_limited() -> LemmyResult<()> {
+    let permits = Semaphore::new(2);
+    let running = AtomicUsize::new(0);
+    let max_running = AtomicUsize::new(0);
+    let futures = (0..20).map(|i| {
+      let running = &running;
+      let max_running = &max_running;
+      async move {
+        run_counted(running, max_running).await;
+        i
+      }
+    });
+
+    // one item without a permit, and one for each permit
+    let res = join_limited(futures, &permits).await;
+    assert_eq!((0..20).collect::<Vec<_>>(), res);
+    assert_eq!(3, max_running.load(Ordering::SeqCst));
+    assert_eq!(2, permits.available_permits());
+
+    // without permits the items are processed one after another
+    let res = join_limited((0..2).map(|i| async move { i }), &Semaphore::new(0)).await;
+    assert_eq!(vec![0, 1], res);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_fetch_permits() -> LemmyResult<()> {
+    // the collections of a resolved query share its permits, also when spawned in the background
+    with_fetch_permits(async {
+      let permits = fetch_permits();
+      assert!(Arc::ptr_eq(&permits, &fetch_permits()));
+      let spawned = tokio::spawn(keep_fetch_permits(async { fetch_permits() })).await?;
+      assert!(Arc::ptr_eq(&permits, &spawned));
+      LemmyResult::Ok(())
+    })
+    .await?;
+
+    // outside of resolving each collection gets its own permits
+    assert!(!Arc::ptr_eq(&fetch_permits(), &fetch_permits()));
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_join_limited_nested() -> LemmyResult<()> {
+    let permits = Semaphore::new(2);
+    let running = AtomicUsize::new(0);
+    let max_running = AtomicUsize::new(0);
+    let outer = (0..5).map(|_| {
+      let (permits, running, max_running) = (&permits, &running, &max_running);
+      async move {
+        let inner = (0..5).map(|_| run_counted(running, max_running));
+        join_limited(inner, permits).await.len()
+      }
+    });
+
+    // nested collections share the permits, and don't wait for each other
+    let res = timeout(Duration::from_secs(5), join_limited(outer, &permits)).await?;
+    assert_eq!(vec![5; 5], res);
+    assert!(max_running.load(Ordering::SeqCst) <= 5);
+    assert_eq!(2, permits.available_permits());
+    Ok(())
+  }
+}
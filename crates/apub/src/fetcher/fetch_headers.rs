@@ -0,0 +1,154 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use lemmy_db_schema::source::resolve_fetch_validator::{
+  ResolveFetchValidator,
+  ResolveFetchValidatorForm,
+};
+use reqwest::{
+  header::{
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    ETAG,
+    IF_MODIFIED_SINCE,
+    IF_NONE_MATCH,
+    LAST_MODIFIED,
+  },
+  Request,
+  Response,
+  StatusCode,
+};
+use reqwest_middleware::{Middleware, Next};
+use std::{
+  future::Future,
+  sync::{Arc, Mutex},
+};
+use task_local_extensions::Extensions;
+use url::Url;
+
+tokio::task_local! {
+  static FETCH: Arc<Mutex<Fetch>>;
+}
+
+/// The fetch which runs in [with_fetch_response]. Other requests in the same task, like those for
+/// the objects which the fetched one references, are passed through unchanged.
+struct Fetch {
+  url: Url,
+  validator: Option<ResolveFetchValidator>,
+  response: Option<FetchResponse>,
+}
+
+/// What the federation library doesn't report about the response to a fetch, recorded by
+/// [FetchHeaders].
+#[derive(Clone, Debug)]
+pub(crate) struct FetchResponse {
+  /// The url after http redirects.
+  pub(crate) served_from: Url,
+  pub(crate) status: StatusCode,
+  pub(crate) etag: Option<String>,
+  pub(crate) last_modified: Option<String>,
+}
+
+impl FetchResponse {
+  /// Instances in secure mode deny fetches which aren't signed.
+  pub(crate) fn is_denied(&self) -> bool {
+    matches!(
+      self.status,
+      StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    )
+  }
+}
+
+/// The validators to store for a fetch of the url, which are empty if the response had neither
+/// header or wasn't recorded.
+pub(crate) fn fetch_validator(
+  url: &Url,
+  response: Option<&FetchResponse>,
+) -> ResolveFetchValidatorForm {
+  ResolveFetchValidatorForm {
+    ap_id: url.clone().into(),
+    etag: response.and_then(|r| r.etag.clone()),
+    last_modified: response.and_then(|r| r.last_modified.clone()),
+  }
+}
+
+/// Sends the validators of the last fetch with fetches which run in [with_fetch_response], and
+/// records their response, as the federation library can't send custom headers and only reports
+/// whether the status was 410 Gone. The response must be served from the host in the url, and not
+/// eg after an http redirect to another host. Added to the client of resolve_object.
+pub struct FetchHeaders;
+
+#[async_trait]
+impl Middleware for FetchHeaders {
+  async fn handle(
+    &self,
+    mut req: Request,
+    extensions: &mut Extensions,
+    next: Next<'_>,
+  ) -> reqwest_middleware::Result<Response> {
+    let fetch = FETCH
+      .try_with(Arc::clone)
+      .ok()
+      .filter(|fetch| fetch.lock().is_ok_and(|f| f.url == *req.url()));
+    let Some(fetch) = fetch else {
+      return next.run(req, extensions).await;
+    };
+    if let Some(validator) = fetch.lock().ok().and_then(|f| f.validator.clone()) {
+      let headers = req.headers_mut();
+      insert_header(headers, IF_NONE_MATCH, validator.etag.as_deref());
+      insert_header(
+        headers,
+        IF_MODIFIED_SINCE,
+        validator.last_modified.as_deref(),
+      );
+    }
+    let host = req.url().host_str().map(ToString::to_string);
+    let res = next.run(req, extensions).await?;
+    let header = |name: HeaderName| {
+      res
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(ToString::to_string)
+    };
+    let response = FetchResponse {
+      served_from: res.url().clone(),
+      status: res.status(),
+      etag: header(ETAG),
+      last_modified: header(LAST_MODIFIED),
+    };
+    if let Ok(mut fetch) = fetch.lock() {
+      fetch.response = Some(response);
+    }
+    if res.url().host_str() != host.as_deref() {
+      Err(reqwest_middleware::Error::Middleware(anyhow!(
+        "Object was served from another host"
+      )))?
+    }
+    Ok(res)
+  }
+}
+
+fn insert_header(headers: &mut HeaderMap, name: HeaderName, value: Option<&str>) {
+  if let Some(value) = value.and_then(|v| HeaderValue::from_str(v).ok()) {
+    headers.insert(name, value);
+  }
+}
+
+/// Runs a fetch of the url, and returns its result together with the response recorded by
+/// [FetchHeaders]. With a validator the fetch is conditional, so that the origin instance can
+/// respond with 304 Not Modified. Nothing is recorded if the client doesn't have the middleware.
+pub(crate) async fn with_fetch_response<T>(
+  url: &Url,
+  validator: Option<&ResolveFetchValidator>,
+  fetch: impl Future<Output = T>,
+) -> (T, Option<FetchResponse>) {
+  let scope = Arc::new(Mutex::new(Fetch {
+    url: url.clone(),
+    validator: validator.cloned(),
+    response: None,
+  }));
+  let res = FETCH.scope(scope.clone(), fetch).await;
+  let response = scope.lock().ok().and_then(|f| f.response.clone());
+  (res, response)
+}
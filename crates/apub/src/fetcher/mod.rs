@@ -10,7 +10,9 @@ use lemmy_db_schema::traits::ApubActor;
 use lemmy_db_views::structs::LocalUserView;
 use lemmy_utils::error::{LemmyError, LemmyResult};
 
+pub mod fetch_headers;
 pub mod post_or_comment;
+pub mod response_size_limit;
 pub mod search;
 pub mod site_or_community_or_user;
 pub mod user_or_community;
@@ -0,0 +1,73 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures::StreamExt;
+use lemmy_utils::error::LemmyResult;
+use reqwest::{Body, Request, Response, ResponseBuilderExt};
+use reqwest_middleware::{Middleware, Next};
+use std::io;
+use task_local_extensions::Extensions;
+
+/// Responses above this size are rejected by the federation library for all fetches.
+const FEDERATION_MAX_RESPONSE_SIZE: usize = 204_800;
+
+/// Aborts responses whose body is larger than the given number of bytes. The body is checked
+/// while it is streamed to the caller, so it isn't buffered here.
+pub struct ResponseSizeLimit(usize);
+
+impl ResponseSizeLimit {
+  /// The limit has to be below the one of the federation library, otherwise it has no effect.
+  pub fn new(max_response_size: usize) -> LemmyResult<Self> {
+    if max_response_size >= FEDERATION_MAX_RESPONSE_SIZE {
+      Err(anyhow!(
+        "Response size limit must be below {FEDERATION_MAX_RESPONSE_SIZE} bytes"
+      ))?
+    }
+    Ok(ResponseSizeLimit(max_response_size))
+  }
+}
+
+#[async_trait]
+impl Middleware for ResponseSizeLimit {
+  async fn handle(
+    &self,
+    req: Request,
+    extensions: &mut Extensions,
+    next: Next<'_>,
+  ) -> reqwest_middleware::Result<Response> {
+    let limit = self.0;
+    let res = next.run(req, extensions).await?;
+    // Fail early if the server announces the size, otherwise count the bytes as they arrive
+    if res
+      .content_length()
+      .is_some_and(|len| usize::try_from(len).map_or(true, |len| len > limit))
+    {
+      return Err(reqwest_middleware::Error::Middleware(anyhow!(
+        "Response body is larger than {limit} bytes"
+      )));
+    }
+    let mut builder = http::Response::builder()
+      .status(res.status())
+      .version(res.version())
+      .url(res.url().clone());
+    if let Some(headers) = builder.headers_mut() {
+      headers.extend(res.headers().clone());
+    }
+    let mut received = 0;
+    let body = res.bytes_stream().map(move |chunk| {
+      let chunk = chunk.map_err(io::Error::other)?;
+      received += chunk.len();
+      if received > limit {
+        return Err(io::Error::other(format!(
+          "Response body is larger than {limit} bytes"
+        )));
+      }
+      Ok(chunk)
+    });
+    Ok(
+      builder
+        .body(Body::wrap_stream(body))
+        .map_err(anyhow::Error::from)?
+        .into(),
+    )
+  }
+}
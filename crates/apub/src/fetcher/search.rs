@@ -1,54 +1,1058 @@
 use crate::{
-  fetcher::user_or_community::{PersonOrGroup, UserOrCommunity},
-  objects::{comment::ApubComment, community::ApubCommunity, person::ApubPerson, post::ApubPost},
-  protocol::objects::{note::Note, page::Page},
+  collections::with_fetch_permits,
+  fetcher::{
+    fetch_headers::{fetch_validator, with_fetch_response, FetchResponse},
+    user_or_community::{PersonOrGroup, UserOrCommunity},
+  },
+  local_site_data_cached,
+  objects::{
+    comment::ApubComment,
+    community::ApubCommunity,
+    instance::ApubSite,
+    person::ApubPerson,
+    post::ApubPost,
+  },
+  protocol::{
+    collections::collection_page::CollectionPage,
+    objects::{
+      instance::Instance,
+      mod_action::ModAction,
+      note::Note,
+      page::Page,
+      redirect::Redirect,
+      tombstone::Tombstone,
+    },
+  },
 };
 use activitypub_federation::{
   config::Data,
-  fetch::{object_id::ObjectId, webfinger::webfinger_resolve_actor},
-  traits::Object,
+  error::Error as ActivityPubError,
+  fetch::{fetch_object_http, object_id::ObjectId, webfinger::webfinger_resolve_actor},
+  protocol::verification::verify_domains_match,
+  traits::{Actor, Object},
 };
 use chrono::{DateTime, Utc};
-use lemmy_api_common::context::LemmyContext;
-use lemmy_utils::error::{LemmyError, LemmyResult};
-use serde::Deserialize;
+use itertools::Itertools;
+use lemmy_api_common::{
+  context::{LemmyContext, ResolveBackoff},
+  site::{ResolvedCollectionPage, ResolvedModAction, ResolvedTombstone},
+};
+use lemmy_db_schema::{
+  newtypes::{CommentId, CommunityId, DbUrl, PersonId, PostId},
+  source::{
+    activity::SentActivity,
+    actor_alias::ActorAlias,
+    comment::Comment,
+    community::Community,
+    content_hash::ContentHash,
+    instance::Instance as DbInstance,
+    instance_block::InstanceBlock,
+    local_site_federation::LocalSiteFederation,
+    person::Person,
+    post::Post,
+    resolve_fetch_validator::{ResolveFetchValidator, ResolveFetchValidatorForm},
+  },
+  traits::{ApubActor, Crud},
+  ResolveObjectType,
+};
+use lemmy_utils::error::{LemmyError, LemmyErrorExt2, LemmyErrorType, LemmyResult};
+use once_cell::sync::Lazy;
+use prometheus::{default_registry, IntCounterVec, Opts};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+  future::Future,
+  time::{Duration, Instant},
+};
 use url::Url;
 
-/// Converts search query to object id. The query can either be an URL, which will be treated as
+/// Converts search query to object ids. The query can either be an URL, which will be treated as
 /// ObjectId directly, or a webfinger identifier (@user@example.com or !community@example.com)
-/// which gets resolved to an URL.
+/// which gets resolved to an URL. A bare name without domain (news, !news or @news) matches all
+/// known actors with that name, local and remote. A bare domain (example.com) resolves to the
+/// site of that instance.
+///
+/// The returned objects are deduplicated by their ap_id.
+///
+/// If a timeout is given, the search is aborted with [LemmyErrorType::RequestTimeout] once it
+/// elapses. The timeout is capped at [MAX_RESOLVE_TIMEOUT].
+///
+/// Failed remote lookups are cached for a short time, so that repeated lookups of missing objects
+/// don't cause any network requests. The cache is only checked when the object isn't known
+/// locally, so objects which arrive through federation in the meantime are found right away.
+/// Queries which keep failing are retried less and less often, see [backoff_window]. Admins
+/// bypass this cache.
+///
+/// With `refresh`, remote objects are fetched again from their origin instance even if they are
+/// already known locally.
+///
+/// Admins can disable remote fetches of posts, comments or actors, see [RemoteKinds]. Moderation
+/// activities of this instance are only looked up with `mod_actions`.
 #[tracing::instrument(skip_all)]
 pub(crate) async fn search_query_to_object_id(
-  mut query: String,
+  query: String,
+  timeout: Option<Duration>,
+  is_admin: bool,
+  refresh: bool,
+  mod_actions: bool,
   context: &Data<LemmyContext>,
-) -> LemmyResult<SearchableObjects> {
-  Ok(match Url::parse(&query) {
-    Ok(url) => {
+) -> LemmyResult<Vec<SearchableObjects>> {
+  let cache_key = normalize_query(&query);
+  let search = async {
+    let res = if is_outside_allowlist(&query, context).await? {
+      // Federation with the instance isn't allowed, so only return objects which are known already
+      search_query_to_object_id_local(&query, context)
+        .await
+        .map(|o| vec![o])
+    } else {
+      let kinds = RemoteKinds::read(mod_actions, context).await;
+      let search = search_query_to_object_id_inner(query, is_admin, refresh, kinds, context);
+      RESOLVE_KINDS.scope(kinds, with_fetch_permits(search)).await
+    };
+    res.with_lemmy_type(LemmyErrorType::CouldntFindObject)
+  };
+  let request_count = context.request_count();
+  let res = with_timeout(timeout, search).await;
+  match &res {
+    Ok(_) => {
+      context
+        .resolve_negative_cache()
+        .invalidate(&cache_key)
+        .await;
+      context.resolve_backoff().invalidate(&cache_key).await
+    }
+    // The timeout is chosen by the client, so the object may well exist
+    Err(e) if e.error_type == LemmyErrorType::RequestTimeout => {}
+    // Only remember failed remote fetches, local misses are cheap and may be created any time
+    Err(_) if context.request_count() > request_count => {
+      record_failure(&cache_key, context).await;
+      context.resolve_negative_cache().insert(cache_key, ()).await
+    }
+    Err(_) => {}
+  }
+  res
+}
+
+/// Number of objects which were newly inserted into the database by resolving a search query.
+/// Objects which were already known are not counted, even if they were refetched.
+static INSERTED_OBJECTS: Lazy<IntCounterVec> = Lazy::new(|| {
+  let counter = IntCounterVec::new(
+    Opts::new(
+      "lemmy_resolve_object_inserted",
+      "Number of objects inserted by resolving a search query",
+    ),
+    &["object_type"],
+  )
+  .expect("create inserted objects counter");
+  default_registry()
+    .register(Box::new(counter.clone()))
+    .expect("register inserted objects counter");
+  counter
+});
+
+/// Fails if fetching the query failed recently. Only checked right before remote fetches, so that
+/// objects which are known locally are always found. Admins bypass this.
+async fn check_not_failed_recently(
+  query: &str,
+  is_admin: bool,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<()> {
+  if is_admin {
+    return Ok(());
+  }
+  let cache_key = normalize_query(query);
+  if context.resolve_negative_cache().contains_key(&cache_key) {
+    Err(LemmyErrorType::CouldntFindObject)?
+  }
+  if let Some(backoff) = context.resolve_backoff().get(&cache_key).await {
+    if Instant::now() < backoff.retry_at {
+      Err(LemmyErrorType::CouldntFindObject)?
+    }
+    // Once the backoff elapsed only a single lookup may try again, the others fail until its
+    // result is known
+    let backoff = ResolveBackoff {
+      retry_at: Instant::now() + backoff_window(backoff.failures, context),
+      ..backoff
+    };
+    context.resolve_backoff().insert(cache_key, backoff).await;
+  }
+  Ok(())
+}
+
+/// Remembers another consecutive failure to resolve the query, which doubles the time until it is
+/// fetched again.
+async fn record_failure(cache_key: &str, context: &Data<LemmyContext>) {
+  let failures = context
+    .resolve_backoff()
+    .get(cache_key)
+    .await
+    .map_or(0, |b| b.failures)
+    .saturating_add(1);
+  let backoff = ResolveBackoff {
+    failures,
+    retry_at: Instant::now() + backoff_window(failures, context),
+  };
+  context
+    .resolve_backoff()
+    .insert(cache_key.to_string(), backoff)
+    .await
+}
+
+/// Time until a query is fetched again after the given number of consecutive failures. Starts at
+/// the ttl of the negative cache, and doubles with each failure up to the maximum backoff.
+fn backoff_window(failures: u32, context: &Data<LemmyContext>) -> Duration {
+  let config = &context.settings().resolve_object;
+  let factor = 1u64
+    .checked_shl(failures.saturating_sub(1))
+    .unwrap_or(u64::MAX);
+  Duration::from_secs(
+    config
+      .negative_cache_ttl
+      .saturating_mul(factor)
+      .min(config.max_backoff),
+  )
+}
+
+/// Normalizes the search query for use as cache key, so that trivially different queries for
+/// the same object share a cache entry.
+fn normalize_query(query: &str) -> String {
+  match query_url(query) {
+    Some(url) => url.to_string(),
+    None => query.trim().to_lowercase(),
+  }
+}
+
+/// Returns true if the query refers to a remote instance which isn't in the allowlist. Lookups of
+/// bare names and local ids don't contact any instance, so they are never affected.
+async fn is_outside_allowlist(query: &str, context: &Data<LemmyContext>) -> LemmyResult<bool> {
+  let Some(domain) = query_domain(query) else {
+    return Ok(false);
+  };
+  if domain == context.settings().get_hostname_without_port()? {
+    return Ok(false);
+  }
+  let local_site_data = local_site_data_cached(&mut context.pool()).await?;
+  Ok(local_site_data.is_outside_allowlist(&domain))
+}
+
+/// Returns true if the query refers to an instance which the person has blocked. Like
+/// [is_outside_allowlist], bare names and local ids are never affected. This is checked before
+/// resolving, so that nothing is fetched from blocked instances.
+pub(crate) async fn is_blocked_by_person(
+  query: &str,
+  person_id: PersonId,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<bool> {
+  let Some(domain) = query_domain(query) else {
+    return Ok(false);
+  };
+  let Some(instance) = DbInstance::read_from_domain(&mut context.pool(), &domain).await? else {
+    return Ok(false);
+  };
+  Ok(InstanceBlock::read(&mut context.pool(), person_id, instance.id).await?)
+}
+
+/// Returns true if the resolved object is from an instance which the person has blocked. Unlike
+/// [is_blocked_by_person] this also covers bare names and redirects. Posts and comments are also
+/// blocked if their community is on a blocked instance.
+pub(crate) async fn is_object_blocked_by_person(
+  object: &SearchableObjects,
+  person_id: PersonId,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<bool> {
+  let pool = &mut context.pool();
+  let origin = match object.ap_id().domain() {
+    Some(domain) => DbInstance::read_from_domain(pool, domain)
+      .await?
+      .map(|i| i.id),
+    None => None,
+  };
+  let community_id = match object {
+    SearchableObjects::Post(p) => Some(p.community_id),
+    SearchableObjects::Comment(c) => Post::read(pool, c.post_id).await?.map(|p| p.community_id),
+    _ => None,
+  };
+  let community_instance = match community_id {
+    Some(community_id) => Community::read(pool, community_id)
+      .await?
+      .map(|c| c.instance_id),
+    None => None,
+  };
+  let actor_instance = match object {
+    SearchableObjects::PersonOrCommunity(pc) => Some(match pc.as_ref() {
+      UserOrCommunity::User(p) => p.instance_id,
+      UserOrCommunity::Community(c) => c.instance_id,
+    }),
+    SearchableObjects::Site(s) => Some(s.instance_id),
+    _ => None,
+  };
+  for instance_id in [origin, community_instance, actor_instance]
+    .into_iter()
+    .flatten()
+    .unique()
+  {
+    if InstanceBlock::read(pool, person_id, instance_id).await? {
+      return Ok(true);
+    }
+  }
+  Ok(false)
+}
+
+/// Returns true if the query is an url, webfinger identifier or bare domain of this instance. Such
+/// queries can only refer to local objects, so they never need a network request.
+pub(crate) fn is_local_query(query: &str, context: &Data<LemmyContext>) -> LemmyResult<bool> {
+  Ok(query_domain(query) == Some(context.settings().get_hostname_without_port()?))
+}
+
+/// Returns the domain which the query refers to, if any. Bare names and local ids have none.
+fn query_domain(query: &str) -> Option<String> {
+  let query = query.trim();
+  match Url::parse(query)
+    .ok()
+    .or_else(|| site_url_from_domain(query))
+  {
+    Some(url) => url.domain().map(ToString::to_string),
+    None => split_sigil(query)
+      .1
+      .split_once('@')
+      .map(|(_, domain)| domain.to_lowercase()),
+  }
+}
+
+/// Kinds of objects which may be fetched from remote instances, as configured in the site
+/// settings. Objects of other kinds are only resolved if they are known already, and are never
+/// refreshed.
+#[derive(Clone, Copy)]
+struct RemoteKinds {
+  posts: bool,
+  comments: bool,
+  actors: bool,
+  /// Whether moderation activities of this instance are looked up among the sent activities.
+  /// Unknown urls would otherwise need another query, even though most users can't view them.
+  mod_actions: bool,
+}
+
+tokio::task_local! {
+  /// The kinds which may be fetched for the query which is being resolved, so that objects which
+  /// are fetched along with the resolved one are checked as well. Unset outside of resolving.
+  static RESOLVE_KINDS: RemoteKinds;
+}
+
+/// A kind of object which may be disabled in [RemoteKinds].
+#[derive(Clone, Copy)]
+pub(crate) enum RemoteKind {
+  Post,
+  Comment,
+  Actor,
+}
+
+/// Checks whether an object may be fetched along with a resolved object, like the author of a
+/// fetched comment. Only new objects are rejected, known ones may still be updated. Outside of
+/// resolving, eg for received activities, all objects are allowed.
+pub(crate) async fn check_nested_fetch<Kind>(
+  id: &Url,
+  kind: RemoteKind,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<()>
+where
+  Kind: Object<DataType = LemmyContext, Error = LemmyError> + Send,
+{
+  let Ok(kinds) = RESOLVE_KINDS.try_with(|k| *k) else {
+    return Ok(());
+  };
+  if kinds.allows_kind(kind) || Kind::read_from_id(id.clone(), context).await?.is_some() {
+    return Ok(());
+  }
+  Err(LemmyErrorType::CouldntFindObject)?
+}
+
+/// Runs a future which was spawned while resolving with the same [RemoteKinds], so that fetches
+/// in the background are checked as well.
+pub(crate) fn keep_resolve_kinds<F: Future>(future: F) -> impl Future<Output = F::Output> {
+  let kinds = RESOLVE_KINDS.try_with(|k| *k).ok();
+  async move {
+    match kinds {
+      Some(kinds) => RESOLVE_KINDS.scope(kinds, future).await,
+      None => future.await,
+    }
+  }
+}
+
+impl RemoteKinds {
+  /// Without readable settings nothing may be fetched, as an admin may have disabled any kind.
+  async fn read(mod_actions: bool, context: &Data<LemmyContext>) -> Self {
+    match LocalSiteFederation::read(&mut context.pool()).await {
+      Ok(f) => RemoteKinds {
+        posts: f.resolve_remote_posts,
+        comments: f.resolve_remote_comments,
+        actors: f.resolve_remote_actors,
+        mod_actions,
+      },
+      Err(e) => {
+        tracing::warn!("Failed to read federation settings, not fetching remote objects: {e}");
+        RemoteKinds {
+          posts: false,
+          comments: false,
+          actors: false,
+          mod_actions,
+        }
+      }
+    }
+  }
+
+  fn allows_kind(&self, kind: RemoteKind) -> bool {
+    match kind {
+      RemoteKind::Post => self.posts,
+      RemoteKind::Comment => self.comments,
+      RemoteKind::Actor => self.actors,
+    }
+  }
+
+  /// Checks a fetched object before it is stored. The kind of a redirect target is only known
+  /// after following it, so redirects are only followed if all kinds may be fetched.
+  fn allows(&self, object: &SearchableKinds) -> bool {
+    match object {
+      SearchableKinds::Page(_) => self.posts,
+      SearchableKinds::Note(_) => self.comments,
+      SearchableKinds::PersonOrGroup(_) | SearchableKinds::Instance(_) => self.actors,
+      SearchableKinds::Redirect(_) => self.posts && self.comments && self.actors,
+      SearchableKinds::ModAction(_)
+      | SearchableKinds::CollectionPage(_)
+      | SearchableKinds::Tombstone(_) => true,
+    }
+  }
+
+  /// Checks whether a known object may be fetched again from its origin instance.
+  fn allows_refresh(&self, object: &SearchableObjects) -> bool {
+    match object {
+      SearchableObjects::Post(_) => self.posts,
+      SearchableObjects::Comment(_) => self.comments,
+      SearchableObjects::PersonOrCommunity(_) | SearchableObjects::Site(_) => self.actors,
+      SearchableObjects::ModAction(_)
+      | SearchableObjects::CollectionPage(_)
+      | SearchableObjects::Tombstone(_) => true,
+    }
+  }
+}
+
+/// Maximum time that a client can allow for resolving a remote object.
+pub(crate) const MAX_RESOLVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+async fn with_timeout<T>(
+  timeout: Option<Duration>,
+  fut: impl Future<Output = LemmyResult<T>>,
+) -> LemmyResult<T> {
+  match timeout {
+    Some(t) => tokio::time::timeout(t.min(MAX_RESOLVE_TIMEOUT), fut)
+      .await
+      .map_err(|_| LemmyErrorType::RequestTimeout)?,
+    None => fut.await,
+  }
+}
+
+async fn search_query_to_object_id_inner(
+  query: String,
+  is_admin: bool,
+  refresh: bool,
+  kinds: RemoteKinds,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Vec<SearchableObjects>> {
+  if let Some(object) = read_from_local_id(&query, context).await? {
+    return Ok(vec![refresh_object(object, refresh, kinds, context).await?]);
+  }
+  let objects = match query_url(&query) {
+    Some(url) => {
       // its already an url, just go with it
-      ObjectId::from(url).dereference(context).await?
+      let object_id = ObjectId::<SearchableObjects>::from(url.clone());
+      let known = object_id.dereference_local(context).await.ok();
+      let is_new = known.is_none();
+      if is_new {
+        check_not_failed_recently(&query, is_admin, context).await?;
+      }
+      let former_type = known.as_ref().and_then(SearchableObjects::resolve_type);
+      let res = match known {
+        None if !object_id.is_local(context) => fetch_from_origin(&url, kinds, context).await,
+        None => object_id.dereference(context).await,
+        Some(k) if refresh => refresh_object(k, true, kinds, context).await,
+        Some(k) => refresh_outdated(k, kinds, context).await,
+      };
+      let object = match res {
+        // The origin instance responded with 410 Gone
+        Err(e) if is_object_deleted(&e) => SearchableObjects::Tombstone(ResolvedTombstone {
+          ap_id: url.into(),
+          former_type,
+        }),
+        res => res?,
+      };
+      let is_stored = !matches!(
+        object,
+        SearchableObjects::ModAction(_)
+          | SearchableObjects::CollectionPage(_)
+          | SearchableObjects::Tombstone(_)
+      );
+      if is_new && is_stored {
+        INSERTED_OBJECTS
+          .with_label_values(&[object.object_type()])
+          .inc();
+      }
+      vec![object]
     }
-    Err(_) => {
-      // not an url, try to resolve via webfinger
-      if query.starts_with('!') || query.starts_with('@') {
-        query.remove(0);
+    None => {
+      let (sigil, identifier) = split_sigil(query.trim());
+      match identifier.split_once('@') {
+        Some((name, domain)) => {
+          let domain = domain.to_lowercase();
+          let object = match read_mention_from_db(sigil, name, &domain, context).await? {
+            Some(actor) => refresh_object(actor.into(), refresh, kinds, context).await?,
+            None if !kinds.actors => Err(LemmyErrorType::CouldntFindObject)?,
+            // not known locally, try to resolve via webfinger
+            None => {
+              check_not_failed_recently(&query, is_admin, context).await?;
+              let actor =
+                webfinger_resolve_mention(sigil, &format!("{name}@{domain}"), context).await?;
+              INSERTED_OBJECTS
+                .with_label_values(&[actor_type(&actor)])
+                .inc();
+              actor.into()
+            }
+          };
+          vec![object]
+        }
+        None => {
+          let mut objects = vec![];
+          for object in read_actors_from_name(identifier, sigil, context).await? {
+            objects.push(refresh_object(object, refresh, kinds, context).await?);
+          }
+          objects
+        }
       }
-      SearchableObjects::PersonOrCommunity(Box::new(
-        webfinger_resolve_actor::<LemmyContext, UserOrCommunity>(&query, context).await?,
-      ))
     }
+  };
+  Ok(
+    objects
+      .into_iter()
+      .unique_by(SearchableObjects::ap_id)
+      .collect(),
+  )
+}
+
+/// The json of a remote object, fetched by [fetch_from_host].
+struct Fetched<Kind> {
+  object: Kind,
+  /// The url which the object was served from.
+  served_from: Url,
+  /// The validators of the response, to fetch the object again conditionally.
+  validator: ResolveFetchValidatorForm,
+  /// Set if the fetch was retried as signed. The object is processed with this context then, so
+  /// that the objects it references on the same instance are fetched with a signature as well.
+  signed: Option<Data<LemmyContext>>,
+}
+
+/// Fetches the json of a remote object. It must be served from the host in the url, and not eg
+/// after an http redirect to another host, so that a server can't pass off its objects as those
+/// of another instance.
+///
+/// Instances in secure mode deny fetches which aren't signed with 401 or 403. If the site signs
+/// fetches, such a fetch is retried once as signed by the site actor. Otherwise fetches of
+/// resolve_object aren't signed, as their urls are entered by users.
+async fn fetch_from_host<Kind: DeserializeOwned>(
+  url: &Url,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Fetched<Kind>> {
+  fetch_from_host_if_modified(url, None, context)
+    .await?
+    .ok_or_else(|| LemmyErrorType::CouldntFindObject.into())
+}
+
+/// Like [fetch_from_host], but with the validators of the last fetch, so that the origin instance
+/// can respond with 304 Not Modified. Then `None` is returned.
+async fn fetch_from_host_if_modified<Kind: DeserializeOwned>(
+  url: &Url,
+  validator: Option<&ResolveFetchValidator>,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Option<Fetched<Kind>>> {
+  let (res, response) =
+    with_fetch_response(url, validator, fetch_object_http::<_, Kind>(url, context)).await;
+  let (res, response, signed) = match (res, context.signed_resolve_config()) {
+    // The federation library only checks for 410 Gone, so the error response fails like an invalid
+    // object
+    (Err(_), Some(signed)) if response.as_ref().is_some_and(FetchResponse::is_denied) => {
+      let signed = signed.to_request_data();
+      let (res, response) =
+        with_fetch_response(url, validator, fetch_object_http::<_, Kind>(url, &signed)).await;
+      (res, response, Some(signed))
+    }
+    (res, _) => (res, response, None),
+  };
+  // The resolve client rejects responses from other hosts before they are parsed
+  let served_from_host = |served_from: &Url| served_from.host_str() == url.host_str();
+  if response
+    .as_ref()
+    .is_some_and(|r| !served_from_host(&r.served_from))
+  {
+    Err(LemmyErrorType::CouldntFindObject)?
+  }
+  let not_modified = validator.is_some()
+    && response
+      .as_ref()
+      .is_some_and(|r| r.status == StatusCode::NOT_MODIFIED);
+  let res = match res {
+    // Like error responses, the empty response fails to parse
+    Err(_) if not_modified => return Ok(None),
+    res => res?,
+  };
+  if !served_from_host(&res.url) {
+    Err(LemmyErrorType::CouldntFindObject)?
+  }
+  Ok(Some(Fetched {
+    object: res.object,
+    validator: fetch_validator(url, response.as_ref()),
+    served_from: res.url,
+    signed,
+  }))
+}
+
+/// Fetches a remote object from its origin instance and stores it, like
+/// [ObjectId::dereference_forced] but checked with [fetch_from_host].
+async fn fetch_from_origin(
+  url: &Url,
+  kinds: RemoteKinds,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<SearchableObjects> {
+  let fetched = fetch_from_host::<SearchableKinds>(url, context).await?;
+  store_fetched(fetched, kinds, context).await
+}
+
+/// Fetches a known remote object again from its origin instance and updates it, like
+/// [fetch_from_origin]. The fetch is conditional, and `None` is returned if the object didn't
+/// change, without parsing or updating anything.
+async fn refetch_from_origin(
+  object: &SearchableObjects,
+  kinds: RemoteKinds,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Option<SearchableObjects>> {
+  let url = object.ap_id();
+  let validator = ResolveFetchValidator::read(&mut context.pool(), &url.clone().into()).await?;
+  let fetched =
+    fetch_from_host_if_modified::<SearchableKinds>(&url, validator.as_ref(), context).await?;
+  match fetched {
+    Some(fetched) => store_fetched(fetched, kinds, context).await.map(Some),
+    None => Ok(None),
+  }
+}
+
+/// Stores an object which was fetched from its origin instance, after checking whether objects of
+/// its kind may be fetched. The validators of the fetch are stored as well.
+async fn store_fetched(
+  fetched: Fetched<SearchableKinds>,
+  kinds: RemoteKinds,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<SearchableObjects> {
+  let context = fetched.signed.as_ref().unwrap_or(context);
+  if !kinds.allows(&fetched.object) {
+    Err(LemmyErrorType::CouldntFindObject)?
+  }
+  SearchableObjects::verify(&fetched.object, &fetched.served_from, context).await?;
+  let object = SearchableObjects::from_json(fetched.object, context).await?;
+  // Only after the object is updated, so that a failed update is retried with a full fetch. Objects
+  // which aren't stored, like moderation actions, are never fetched again.
+  if object.last_refreshed_at().is_some() {
+    ResolveFetchValidator::replace(&mut context.pool(), &fetched.validator).await?;
+  }
+  Ok(object)
+}
+
+/// Fetches the json of a remote object from its origin instance again, without converting it.
+/// The json is verified like when the object is stored, so it is only returned if the object
+/// could be resolved from it. Local objects and tombstones have no remote json.
+pub(crate) async fn fetch_raw_json(
+  object: &SearchableObjects,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Option<String>> {
+  if object.is_local(context) || matches!(object, SearchableObjects::Tombstone(_)) {
+    return Ok(None);
+  }
+  let fetched = fetch_from_host::<serde_json::Value>(&object.ap_id(), context).await?;
+  let json = fetched.object;
+  let kind: SearchableKinds = serde_json::from_value(json.clone())?;
+  SearchableObjects::verify(&kind, &fetched.served_from, context).await?;
+  Ok(Some(json.to_string()))
+}
+
+/// Returns true if fetching the object failed because it was deleted on its origin instance.
+fn is_object_deleted(error: &LemmyError) -> bool {
+  matches!(
+    error.inner.downcast_ref::<ActivityPubError>(),
+    Some(ActivityPubError::ObjectDeleted(_))
+  )
+}
+
+/// With `refresh`, fetches a remote object again from its origin instance, to update the local
+/// copy. Local objects and objects of kinds which may not be fetched are returned unchanged, as
+/// are objects which didn't change since they were last fetched.
+async fn refresh_object(
+  object: SearchableObjects,
+  refresh: bool,
+  kinds: RemoteKinds,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<SearchableObjects> {
+  if !refresh || object.is_local(context) || !kinds.allows_refresh(&object) {
+    return Ok(object);
+  }
+  let refetched = refetch_from_origin(&object, kinds, context).await?;
+  Ok(refetched.unwrap_or(object))
+}
+
+/// Fetches a known remote object again if it wasn't refreshed for a while, with
+/// [ObjectId::dereference] so that it is outdated after the same time as other objects. The fetch
+/// is conditional like in [refetch_from_origin], and retried as signed like in [fetch_from_host].
+/// If it fails or the object didn't change, the known object is returned, unless it was deleted
+/// on its origin instance.
+async fn refresh_outdated(
+  object: SearchableObjects,
+  kinds: RemoteKinds,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<SearchableObjects> {
+  if object.is_local(context) || !kinds.allows_refresh(&object) {
+    return Ok(object);
+  }
+  let url = object.ap_id();
+  let validator = ResolveFetchValidator::read(&mut context.pool(), &url.clone().into()).await?;
+  let refreshed_at = object.last_refreshed_at();
+  let object_id = ObjectId::<SearchableObjects>::from(url.clone());
+  let (res, response) =
+    with_fetch_response(&url, validator.as_ref(), object_id.dereference(context)).await;
+  let (res, response) = match context.signed_resolve_config() {
+    Some(signed) if response.as_ref().is_some_and(FetchResponse::is_denied) => {
+      let signed = signed.to_request_data();
+      with_fetch_response(&url, validator.as_ref(), object_id.dereference(&signed)).await
+    }
+    _ => (res, response),
+  };
+  let object = res?;
+  if object.last_refreshed_at() != refreshed_at {
+    let validator = fetch_validator(&url, response.as_ref());
+    ResolveFetchValidator::replace(&mut context.pool(), &validator).await?;
+  }
+  Ok(object)
+}
+
+/// Query params which are only added to urls for tracking, in addition to those starting with
+/// `utm_`. Other params may be part of an object id, so they are kept.
+const TRACKING_PARAMS: [&str; 3] = ["fbclid", "gclid", "mc_eid"];
+
+/// Parses the query as url, with a bare domain treated like the url of the instance's site. The
+/// fragment and tracking params are removed, as they are often part of urls copied from a browser
+/// but never of an object id.
+fn query_url(query: &str) -> Option<Url> {
+  let mut url = Url::parse(query.trim())
+    .ok()
+    .or_else(|| site_url_from_domain(query))?;
+  url.set_fragment(None);
+  if let Some(params) = url.query() {
+    // Filter the raw params instead of reencoding them, so that the remaining ones are unchanged
+    let params = params
+      .split('&')
+      .filter(|p| {
+        let name = p.split_once('=').map_or(*p, |(name, _)| name);
+        !name.starts_with("utm_") && !TRACKING_PARAMS.contains(&name)
+      })
+      .join("&");
+    url.set_query((!params.is_empty()).then_some(params.as_str()));
+  }
+  Some(url)
+}
+
+/// Converts a bare domain like `example.com` to the actor id of the instance's site. Returns
+/// `None` for anything else, as names of persons and communities can't contain a dot.
+fn site_url_from_domain(query: &str) -> Option<Url> {
+  let query = query.trim();
+  if !query.contains('.') || query.contains(['@', '!', '/', ':']) {
+    return None;
+  }
+  Url::parse(&format!("https://{}/", query.to_lowercase())).ok()
+}
+
+/// Splits the leading `!` (community) or `@` (person) from a mention like `!news@example.com`.
+fn split_sigil(query: &str) -> (Option<char>, &str) {
+  ['!', '@']
+    .into_iter()
+    .find_map(|c| query.strip_prefix(c).map(|rest| (Some(c), rest)))
+    .unwrap_or((None, query))
+}
+
+/// Reads the community (unless sigil is `@`) or person (unless sigil is `!`) with the given name
+/// and domain from the database.
+async fn read_mention_from_db(
+  sigil: Option<char>,
+  name: &str,
+  domain: &str,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Option<UserOrCommunity>> {
+  if sigil != Some('@') {
+    let community = Community::read_from_name_and_domain(&mut context.pool(), name, domain).await?;
+    if let Some(c) = community {
+      return Ok(Some(UserOrCommunity::Community(c.into())));
+    }
+  }
+  if sigil != Some('!') {
+    let person = Person::read_from_name_and_domain(&mut context.pool(), name, domain).await?;
+    if let Some(p) = person {
+      return Ok(Some(UserOrCommunity::User(p.into())));
+    }
+  }
+  Ok(None)
+}
+
+/// Resolves the mention via webfinger, only accepting the actor type given by the sigil.
+async fn webfinger_resolve_mention(
+  sigil: Option<char>,
+  identifier: &str,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<UserOrCommunity> {
+  Ok(match sigil {
+    Some('!') => UserOrCommunity::Community(
+      webfinger_resolve_actor::<LemmyContext, ApubCommunity>(identifier, context).await?,
+    ),
+    Some('@') => UserOrCommunity::User(
+      webfinger_resolve_actor::<LemmyContext, ApubPerson>(identifier, context).await?,
+    ),
+    _ => webfinger_resolve_actor::<LemmyContext, UserOrCommunity>(identifier, context).await?,
   })
 }
 
-/// Converts a search query to an object id.  The query MUST bbe a URL which will bbe treated
-/// as the ObjectId directly.  If the query is a webfinger identifier (@user@example.com or
-/// !community@example.com) this method will return an error.
+/// Reads all known communities (unless sigil is `@`) and persons (unless sigil is `!`) with the
+/// given name from the database.
+async fn read_actors_from_name(
+  name: &str,
+  sigil: Option<char>,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Vec<SearchableObjects>> {
+  let mut objects = vec![];
+  if sigil != Some('@') {
+    let communities = Community::list_from_name(&mut context.pool(), name).await?;
+    objects.extend(
+      communities
+        .into_iter()
+        .map(|c| UserOrCommunity::Community(c.into())),
+    );
+  }
+  if sigil != Some('!') {
+    let persons = Person::list_from_name(&mut context.pool(), name).await?;
+    objects.extend(persons.into_iter().map(|p| UserOrCommunity::User(p.into())));
+  }
+  if objects.is_empty() {
+    Err(LemmyErrorType::CouldntFindObject)?
+  }
+  Ok(
+    objects
+      .into_iter()
+      .map(|o| SearchableObjects::PersonOrCommunity(Box::new(o)))
+      .collect(),
+  )
+}
+
+/// Converts a search query to an object id, without making any network requests. The query can
+/// either be an URL, which will be treated as the ObjectId directly, a webfinger identifier
+/// (@user@example.com or !community@example.com) of an actor which is already known locally, a
+/// bare domain of a known instance, or a local database id like post:123.
 #[tracing::instrument(skip_all)]
 pub(crate) async fn search_query_to_object_id_local(
   query: &str,
   context: &Data<LemmyContext>,
 ) -> LemmyResult<SearchableObjects> {
-  let url = Url::parse(query)?;
-  ObjectId::from(url).dereference_local(context).await
+  if let Some(object) = read_from_local_id(query, context).await? {
+    return Ok(object);
+  }
+  match query_url(query) {
+    Some(url) => match ObjectId::from(url.clone()).dereference_local(context).await {
+      Ok(object) => Ok(object),
+      // Actors may also be known by another url, eg the one from before they moved
+      Err(e) => read_from_alias(&url.into(), context).await?.ok_or(e),
+    },
+    None => {
+      let (sigil, identifier) = split_sigil(query.trim());
+      let (name, domain) = identifier
+        .split_once('@')
+        .ok_or(LemmyErrorType::CouldntFindObject)?;
+      let actor = read_mention_from_db(sigil, name, &domain.to_lowercase(), context)
+        .await?
+        .ok_or(LemmyErrorType::CouldntFindObject)?;
+      Ok(SearchableObjects::PersonOrCommunity(Box::new(actor)))
+    }
+  }
+}
+
+/// Resolves a partial name (fedi, !fedi or @fedi) to the known community (unless sigil is `@`) or
+/// person (unless sigil is `!`) with the most similar name, without making any network requests.
+/// Communities win ties, like in exact name matches.
+#[tracing::instrument(skip(context))]
+pub(crate) async fn search_similar_actor_local(
+  query: &str,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<SearchableObjects> {
+  let (sigil, name) = split_sigil(query.trim());
+  if name.is_empty() {
+    Err(LemmyErrorType::CouldntFindObject)?
+  }
+  let community = match sigil {
+    Some('@') => None,
+    _ => Community::find_similar_name(&mut context.pool(), name).await?,
+  };
+  let person = match sigil {
+    Some('!') => None,
+    _ => Person::find_similar_name(&mut context.pool(), name).await?,
+  };
+  let actor = match (community, person) {
+    (Some((c, c_similarity)), Some((p, p_similarity))) => {
+      if p_similarity > c_similarity {
+        UserOrCommunity::User(p.into())
+      } else {
+        UserOrCommunity::Community(c.into())
+      }
+    }
+    (Some((c, _)), None) => UserOrCommunity::Community(c.into()),
+    (None, Some((p, _))) => UserOrCommunity::User(p.into()),
+    (None, None) => Err(LemmyErrorType::CouldntFindObject)?,
+  };
+  Ok(actor.into())
+}
+
+/// Reads an object by its database id, given as `<kind>:<id>` where kind is one of post,
+/// comment, person or community, by its local path, see [read_from_local_path], or by the hash of
+/// its content, see [read_from_content_hash]. Returns `None` if the query isn't in one of these
+/// forms.
+async fn read_from_local_id(
+  query: &str,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Option<SearchableObjects>> {
+  if let Some(path) = query.trim().strip_prefix('/') {
+    return read_from_local_path(path, context).await;
+  }
+  if let Some(hash) = query.trim().strip_prefix("hash:") {
+    return read_from_content_hash(hash, context).await;
+  }
+  let Some((kind, id)) = query.trim().split_once(':') else {
+    return Ok(None);
+  };
+  let Ok(id) = id.parse::<i32>() else {
+    return Ok(None);
+  };
+  let pool = &mut context.pool();
+  let object = match kind {
+    "post" => Post::read(pool, PostId(id))
+      .await?
+      .map(|p| SearchableObjects::Post(p.into())),
+    "comment" => Comment::read(pool, CommentId(id))
+      .await?
+      .map(|c| SearchableObjects::Comment(c.into())),
+    "person" => Person::read(pool, PersonId(id))
+      .await?
+      .map(|p| UserOrCommunity::User(p.into()).into()),
+    "community" => Community::read(pool, CommunityId(id))
+      .await?
+      .map(|c| UserOrCommunity::Community(c.into()).into()),
+    _ => return Ok(None),
+  };
+  Ok(Some(object.ok_or(LemmyErrorType::CouldntFindObject)?))
+}
+
+/// Reads the first received post or comment whose content has the given SHA-256 hash, given as
+/// hex string. Objects are never fetched by their hash, as only the url tells where to fetch from.
+async fn read_from_content_hash(
+  hash: &str,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Option<SearchableObjects>> {
+  let hash = hash.to_lowercase();
+  if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+    Err(LemmyErrorType::CouldntFindObject)?
+  }
+  let pool = &mut context.pool();
+  let content_hash = ContentHash::read(pool, &hash)
+    .await?
+    .ok_or(LemmyErrorType::CouldntFindObject)?;
+  let object = match (content_hash.post_id, content_hash.comment_id) {
+    (Some(post_id), _) => Post::read(pool, post_id)
+      .await?
+      .map(|p| SearchableObjects::Post(p.into())),
+    (None, Some(comment_id)) => Comment::read(pool, comment_id)
+      .await?
+      .map(|c| SearchableObjects::Comment(c.into())),
+    (None, None) => None,
+  };
+  Ok(Some(object.ok_or(LemmyErrorType::CouldntFindObject)?))
+}
+
+/// Reads an object by the path used for it in the frontend, like `c/news`, `u/alice`, `post/123`
+/// or `comment/123`. Actor names may include the domain of a remote actor, like
+/// `c/news@example.com`. Returns `None` if the path isn't in one of these forms.
+async fn read_from_local_path(
+  path: &str,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Option<SearchableObjects>> {
+  let Some((kind, id)) = path.trim_end_matches('/').split_once('/') else {
+    return Ok(None);
+  };
+  if id.is_empty() || id.contains('/') {
+    return Ok(None);
+  }
+  let pool = &mut context.pool();
+  let object = match kind {
+    "post" => {
+      let Ok(id) = id.parse::<i32>() else {
+        return Ok(None);
+      };
+      Post::read(pool, PostId(id))
+        .await?
+        .map(|p| SearchableObjects::Post(p.into()))
+    }
+    "comment" => {
+      let Ok(id) = id.parse::<i32>() else {
+        return Ok(None);
+      };
+      Comment::read(pool, CommentId(id))
+        .await?
+        .map(|c| SearchableObjects::Comment(c.into()))
+    }
+    "c" => match id.split_once('@') {
+      Some((name, domain)) => {
+        Community::read_from_name_and_domain(pool, name, &domain.to_lowercase()).await?
+      }
+      None => Community::read_from_name(pool, id, false).await?,
+    }
+    .map(|c| UserOrCommunity::Community(c.into()).into()),
+    "u" => match id.split_once('@') {
+      Some((name, domain)) => {
+        Person::read_from_name_and_domain(pool, name, &domain.to_lowercase()).await?
+      }
+      None => Person::read_from_name(pool, id, false).await?,
+    }
+    .map(|p| UserOrCommunity::User(p.into()).into()),
+    _ => return Ok(None),
+  };
+  Ok(Some(object.ok_or(LemmyErrorType::CouldntFindObject)?))
+}
+
+/// Reads the person or community which has the url as alias, see [ActorAlias]. Returns `None` if
+/// no actor is known by this url.
+async fn read_from_alias(
+  alias: &DbUrl,
+  context: &Data<LemmyContext>,
+) -> LemmyResult<Option<SearchableObjects>> {
+  let pool = &mut context.pool();
+  let Some(alias) = ActorAlias::read(pool, alias).await? else {
+    return Ok(None);
+  };
+  let actor = match (alias.person_id, alias.community_id) {
+    (Some(person_id), _) => Person::read(pool, person_id)
+      .await?
+      .map(|p| UserOrCommunity::User(p.into())),
+    (None, Some(community_id)) => Community::read(pool, community_id)
+      .await?
+      .map(|c| UserOrCommunity::Community(c.into())),
+    (None, None) => None,
+  };
+  Ok(actor.map(Into::into))
 }
 
 /// The types of ActivityPub objects that can be fetched directly by searching for their ID.
@@ -57,6 +1061,91 @@ pub(crate) enum SearchableObjects {
   Post(ApubPost),
   Comment(ApubComment),
   PersonOrCommunity(Box<UserOrCommunity>),
+  Site(ApubSite),
+  /// A moderation activity. Only those sent by this instance are known locally, remote ones are
+  /// never stored.
+  ModAction(ResolvedModAction),
+  /// A page of a remote collection like an outbox. This is never stored, and the contained
+  /// objects aren't fetched.
+  CollectionPage(ResolvedCollectionPage),
+  /// A remote object which was deleted on its origin instance. This is never stored.
+  Tombstone(ResolvedTombstone),
+}
+
+impl From<UserOrCommunity> for SearchableObjects {
+  fn from(value: UserOrCommunity) -> Self {
+    SearchableObjects::PersonOrCommunity(Box::new(value))
+  }
+}
+
+fn actor_type(actor: &UserOrCommunity) -> &'static str {
+  match actor {
+    UserOrCommunity::User(_) => "person",
+    UserOrCommunity::Community(_) => "community",
+  }
+}
+
+impl SearchableObjects {
+  /// Name of the object type, used as metrics label and in tracing spans.
+  pub(crate) fn object_type(&self) -> &'static str {
+    match self {
+      SearchableObjects::Post(_) => "post",
+      SearchableObjects::Comment(_) => "comment",
+      SearchableObjects::PersonOrCommunity(pc) => actor_type(pc),
+      SearchableObjects::Site(_) => "site",
+      SearchableObjects::ModAction(_) => "mod_action",
+      SearchableObjects::CollectionPage(_) => "collection_page",
+      SearchableObjects::Tombstone(_) => "tombstone",
+    }
+  }
+
+  /// The type of the object as given in resolve requests, if it has one.
+  pub(crate) fn resolve_type(&self) -> Option<ResolveObjectType> {
+    match self {
+      SearchableObjects::Post(_) => Some(ResolveObjectType::Post),
+      SearchableObjects::Comment(_) => Some(ResolveObjectType::Comment),
+      SearchableObjects::PersonOrCommunity(pc) => match pc.as_ref() {
+        UserOrCommunity::User(_) => Some(ResolveObjectType::Person),
+        UserOrCommunity::Community(_) => Some(ResolveObjectType::Community),
+      },
+      SearchableObjects::Site(_)
+      | SearchableObjects::ModAction(_)
+      | SearchableObjects::CollectionPage(_) => None,
+      SearchableObjects::Tombstone(t) => t.former_type,
+    }
+  }
+
+  /// Returns true if the object was created on this instance.
+  fn is_local(&self, context: &Data<LemmyContext>) -> bool {
+    // The hostname setting may include a port, which isn't part of the domain
+    let hostname = context.settings().get_hostname_without_port().ok();
+    let is_local_url = |url: &Url| url.domain().is_some() && url.domain() == hostname.as_deref();
+    match self {
+      SearchableObjects::Post(p) => p.local,
+      SearchableObjects::Comment(c) => c.local,
+      SearchableObjects::PersonOrCommunity(pc) => match pc.as_ref() {
+        UserOrCommunity::User(p) => p.local,
+        UserOrCommunity::Community(c) => c.local,
+      },
+      SearchableObjects::Site(s) => is_local_url(s.actor_id.inner()),
+      SearchableObjects::ModAction(m) => is_local_url(m.ap_id.inner()),
+      SearchableObjects::CollectionPage(c) => is_local_url(c.ap_id.inner()),
+      SearchableObjects::Tombstone(_) => false,
+    }
+  }
+
+  /// The ActivityPub id of the object.
+  pub(crate) fn ap_id(&self) -> Url {
+    match self {
+      SearchableObjects::Post(p) => p.ap_id.clone().into(),
+      SearchableObjects::Comment(c) => c.ap_id.clone().into(),
+      SearchableObjects::PersonOrCommunity(pc) => pc.id(),
+      SearchableObjects::Site(s) => s.actor_id.clone().into(),
+      SearchableObjects::ModAction(m) => m.ap_id.clone().into(),
+      SearchableObjects::CollectionPage(c) => c.ap_id.clone().into(),
+      SearchableObjects::Tombstone(t) => t.ap_id.clone().into(),
+    }
+  }
 }
 
 #[derive(Deserialize)]
@@ -65,6 +1154,11 @@ pub(crate) enum SearchableKinds {
   Page(Box<Page>),
   Note(Note),
   PersonOrGroup(Box<PersonOrGroup>),
+  Instance(Box<Instance>),
+  ModAction(Box<ModAction>),
+  CollectionPage(Box<CollectionPage>),
+  Redirect(Redirect),
+  Tombstone(Tombstone),
 }
 
 #[async_trait::async_trait]
@@ -78,12 +1172,16 @@ impl Object for SearchableObjects {
       SearchableObjects::Post(p) => p.last_refreshed_at(),
       SearchableObjects::Comment(c) => c.last_refreshed_at(),
       SearchableObjects::PersonOrCommunity(p) => p.last_refreshed_at(),
+      SearchableObjects::Site(s) => s.last_refreshed_at(),
+      SearchableObjects::ModAction(_)
+      | SearchableObjects::CollectionPage(_)
+      | SearchableObjects::Tombstone(_) => None,
     }
   }
 
   // TODO: this is inefficient, because if the object is not in local db, it will run 4 db queries
-  //       before finally returning an error. it would be nice if we could check all 4 tables in
-  //       a single query.
+  //       (5 if moderation activities are resolved) before finally returning an error. it would be
+  //       nice if we could check all tables in a single query.
   //       we could skip this and always return an error, but then it would always fetch objects
   //       over http, and not be able to mark objects as deleted that were deleted by remote server.
   #[tracing::instrument(skip_all)]
@@ -99,11 +1197,23 @@ impl Object for SearchableObjects {
     if let Some(p) = p {
       return Ok(Some(SearchableObjects::Post(p)));
     }
-    let c = ApubComment::read_from_id(object_id, context).await?;
+    let c = ApubComment::read_from_id(object_id.clone(), context).await?;
     if let Some(c) = c {
       return Ok(Some(SearchableObjects::Comment(c)));
     }
-    Ok(None)
+    let s = ApubSite::read_from_id(object_id.clone(), context).await?;
+    if let Some(s) = s {
+      return Ok(Some(SearchableObjects::Site(s)));
+    }
+    // Only look up moderation activities if they were asked for. Outside of resolving, eg for
+    // local lookups, they are always included.
+    if !RESOLVE_KINDS.try_with(|k| k.mod_actions).unwrap_or(true) {
+      return Ok(None);
+    }
+    // Activities of other types or which can't be parsed are not resolved
+    let activity = SentActivity::read_from_apub_id(&mut context.pool(), &object_id.into()).await?;
+    let m = activity.and_then(|a| serde_json::from_value::<ModAction>(a.data).ok());
+    Ok(m.map(|m| SearchableObjects::ModAction(m.into())))
   }
 
   #[tracing::instrument(skip_all)]
@@ -115,6 +1225,11 @@ impl Object for SearchableObjects {
         UserOrCommunity::User(p) => p.delete(data).await,
         UserOrCommunity::Community(c) => c.delete(data).await,
       },
+      // sites and activities can't be deleted, and collection pages and tombstones aren't stored
+      SearchableObjects::Site(_)
+      | SearchableObjects::ModAction(_)
+      | SearchableObjects::CollectionPage(_)
+      | SearchableObjects::Tombstone(_) => Ok(()),
     }
   }
 
@@ -135,6 +1250,21 @@ impl Object for SearchableObjects {
         PersonOrGroup::Person(a) => ApubPerson::verify(a, expected_domain, data).await,
         PersonOrGroup::Group(a) => ApubCommunity::verify(a, expected_domain, data).await,
       },
+      SearchableKinds::Instance(a) => ApubSite::verify(a, expected_domain, data).await,
+      SearchableKinds::Redirect(r) => {
+        verify_domains_match(&r.id, expected_domain)?;
+        if r.target == r.id {
+          Err(LemmyErrorType::TooManyRedirects)?
+        }
+        Ok(())
+      }
+      SearchableKinds::ModAction(m) => {
+        verify_domains_match(&m.id, expected_domain)?;
+        Ok(verify_domains_match(&m.actor, expected_domain)?)
+      }
+      // The items may belong to other instances, and are only returned as ids
+      SearchableKinds::CollectionPage(c) => Ok(verify_domains_match(&c.id, expected_domain)?),
+      SearchableKinds::Tombstone(t) => Ok(verify_domains_match(&t.id, expected_domain)?),
     }
   }
 
@@ -148,6 +1278,1288 @@ impl Object for SearchableObjects {
       SAT::PersonOrGroup(pg) => {
         SO::PersonOrCommunity(Box::new(UserOrCommunity::from_json(*pg, context).await?))
       }
+      SAT::Instance(i) => SO::Site(ApubSite::from_json(*i, context).await?),
+      SAT::Redirect(r) => {
+        if let Some(object) = SO::read_from_id(r.target.clone(), context).await? {
+          return Ok(object);
+        }
+        // Only a single redirect is followed, so that redirect loops can't cause endless fetching
+        let fetched = fetch_from_host::<SAT>(&r.target, context).await?;
+        let context = fetched.signed.as_ref().unwrap_or(context);
+        if let SAT::Redirect(_) = fetched.object {
+          Err(LemmyErrorType::TooManyRedirects)?
+        }
+        SO::verify(&fetched.object, &fetched.served_from, context).await?;
+        SO::from_json(fetched.object, context).await?
+      }
+      SAT::ModAction(m) => SO::ModAction((*m).into()),
+      SAT::CollectionPage(c) => SO::CollectionPage((*c).into()),
+      // Some platforms respond with a tombstone instead of 410 Gone
+      SAT::Tombstone(t) => SO::Tombstone(ResolvedTombstone {
+        ap_id: t.id.into(),
+        former_type: None,
+      }),
     })
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    api::test::{create_user, json_response, mock_remote_context, MockRemote, TestInstance},
+    fetcher::fetch_headers::FetchHeaders,
+    objects::{
+      community::tests::parse_lemmy_community,
+      instance::tests::parse_lemmy_instance,
+      person::tests::parse_lemmy_person,
+    },
+    protocol::{objects::person::Person as PersonObject, tests::file_to_json_object},
+    VerifyUrlData,
+  };
+  use activitypub_federation::{config::FederationConfig, http_signatures::generate_actor_keypair};
+  use lemmy_api_common::site::ModActionType;
+  use lemmy_db_schema::source::{
+    activity::{ActorType, SentActivityForm},
+    comment::CommentInsertForm,
+    community::CommunityInsertForm,
+    federation_allowlist::FederationAllowList,
+    federation_blocklist::FederationBlockList,
+    instance::Instance,
+    local_site_federation::LocalSiteFederationUpdateForm,
+    person::PersonUpdateForm,
+    post::PostInsertForm,
+    site::{Site, SiteUpdateForm},
+  };
+  use lemmy_utils::CACHE_DURATION_FEDERATION;
+  use pretty_assertions::assert_eq;
+  use serial_test::serial;
+  use sha2::{Digest, Sha256};
+  use std::time::Instant;
+  use tokio::time::sleep;
+
+  #[tokio::test]
+  async fn test_resolve_timeout() -> LemmyResult<()> {
+    // simulates a remote server which takes a long time to respond
+    let slow_fetch = async {
+      sleep(Duration::from_secs(5)).await;
+      Ok(())
+    };
+    let res = with_timeout(Some(Duration::from_millis(50)), slow_fetch).await;
+    assert_eq!(
+      Some(LemmyErrorType::RequestTimeout),
+      res.err().map(|e| e.error_type)
+    );
+
+    // fast responses are unaffected
+    let res = with_timeout(Some(Duration::from_secs(1)), async { Ok(1) }).await?;
+    assert_eq!(1, res);
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_timeout_slow_remote() -> LemmyResult<()> {
+    // a remote server which accepts the connection, but never responds
+    let mut remote = MockRemote::bind().await?;
+    remote.serve(|_| None);
+    let context = remote.context().await?;
+
+    let start = Instant::now();
+    let query = format!("{}/post/1", remote.base);
+    let timeout = Some(Duration::from_millis(200));
+    let res = search_query_to_object_id(query, timeout, true, false, true, &context).await;
+    assert_eq!(
+      Some(LemmyErrorType::RequestTimeout),
+      res.err().map(|e| e.error_type)
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(1, context.request_count());
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_negative_cache() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let query = "https://missing.example/post/1".to_string();
+
+    // the first lookup tries to fetch the object
+    let context_ = context.reset_request_count();
+    let res = search_query_to_object_id(query.clone(), None, false, false, true, &context_).await;
+    assert!(res.is_err());
+    assert_eq!(1, context_.request_count());
+
+    // repeated lookups fail immediately, also if the query is written slightly different
+    let context_ = context.reset_request_count();
+    let res =
+      search_query_to_object_id(format!(" {query} "), None, false, false, true, &context_).await;
+    assert!(res.is_err());
+    assert_eq!(0, context_.request_count());
+
+    // admins bypass the cache
+    let context_ = context.reset_request_count();
+    let res = search_query_to_object_id(query, None, true, false, true, &context_).await;
+    assert!(res.is_err());
+    assert_eq!(1, context_.request_count());
+
+    // local misses aren't cached, so that the object can be found once it exists
+    let instance =
+      Instance::read_or_create(&mut context.pool(), "negative-cache.tld".to_string()).await?;
+    let query = "negative_cache_community".to_string();
+    let context_ = context.reset_request_count();
+    let res = search_query_to_object_id(query.clone(), None, false, false, true, &context_).await;
+    assert!(res.is_err());
+    assert_eq!(0, context_.request_count());
+    let community_form = CommunityInsertForm::builder()
+      .name(query.clone())
+      .title(query.clone())
+      .public_key("pubkey".to_string())
+      .instance_id(instance.id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+    let res = search_query_to_object_id(query, None, false, false, true, &context).await?;
+    assert_eq!(vec![community.actor_id.inner().clone()], ap_ids(&res));
+
+    // objects which arrive through federation after a failed fetch are found right away
+    let query = "https://missing.example/c/negative_remote".to_string();
+    let context_ = context.reset_request_count();
+    let res = search_query_to_object_id(query.clone(), None, false, false, true, &context_).await;
+    assert!(res.is_err());
+    assert_eq!(1, context_.request_count());
+    let remote_instance =
+      Instance::read_or_create(&mut context.pool(), "missing.example".to_string()).await?;
+    let community_form = CommunityInsertForm::builder()
+      .name("negative_remote".to_string())
+      .title("negative_remote".to_string())
+      .public_key("pubkey".to_string())
+      .actor_id(Some(Url::parse(&query)?.into()))
+      .local(Some(false))
+      .instance_id(remote_instance.id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+    let context_ = context.reset_request_count();
+    let res = search_query_to_object_id(query.clone(), None, false, false, true, &context_).await?;
+    assert_eq!(vec![community.actor_id.inner().clone()], ap_ids(&res));
+    assert_eq!(0, context_.request_count());
+    // and the failed fetch is forgotten
+    assert!(!context
+      .resolve_negative_cache()
+      .contains_key(&normalize_query(&query)));
+
+    Instance::delete(&mut context.pool(), remote_instance.id).await?;
+    Instance::delete(&mut context.pool(), instance.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_backoff() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let query = "https://backoff.example/post/1".to_string();
+    let cache_key = normalize_query(&query);
+    let mut windows = vec![];
+    for _ in 0..3 {
+      // each lookup after the backoff elapsed tries to fetch the object once more
+      let context_ = context.reset_request_count();
+      let res = search_query_to_object_id(query.clone(), None, false, false, true, &context_).await;
+      assert!(res.is_err());
+      assert_eq!(1, context_.request_count());
+      let backoff = context
+        .resolve_backoff()
+        .get(&cache_key)
+        .await
+        .ok_or(LemmyErrorType::CouldntFindObject)?;
+      windows.push(backoff.retry_at - Instant::now());
+
+      // until then, lookups fail without any request, also once the negative cache expired
+      context
+        .resolve_negative_cache()
+        .invalidate(&cache_key)
+        .await;
+      let context_ = context.reset_request_count();
+      let res = search_query_to_object_id(query.clone(), None, false, false, true, &context_).await;
+      assert!(res.is_err());
+      assert_eq!(0, context_.request_count());
+
+      // simulate that the backoff elapsed
+      let elapsed = ResolveBackoff {
+        retry_at: Instant::now(),
+        ..backoff
+      };
+      context
+        .resolve_backoff()
+        .insert(cache_key.clone(), elapsed)
+        .await;
+    }
+    // the backoff grows with each consecutive failure
+    assert!(windows.iter().tuple_windows().all(|(a, b)| a < b));
+    assert!(windows.last() > Some(&backoff_window(2, &context)));
+
+    // only a single lookup may try again once the backoff elapsed
+    let context_ = context.reset_request_count();
+    let _ = search_query_to_object_id(query.clone(), None, false, false, true, &context_).await;
+    let _ = search_query_to_object_id(query.clone(), None, false, false, true, &context_).await;
+    assert_eq!(1, context_.request_count());
+
+    context
+      .resolve_negative_cache()
+      .invalidate(&cache_key)
+      .await;
+    context.resolve_backoff().invalidate(&cache_key).await;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_blocked_instance() -> LemmyResult<()> {
+    // The test context accepts all urls, so the blocklist needs the verifier of the server
+    let test_context = LemmyContext::init_test_context().await;
+    let context = FederationConfig::builder()
+      .domain(test_context.settings().hostname.clone())
+      .app_data(test_context.app_data().clone())
+      .http_fetch_limit(0)
+      .url_verifier(Box::new(VerifyUrlData(test_context.inner_pool().clone())))
+      .build()
+      .await?
+      .to_request_data();
+    let query = "https://blocked.example/post/1".to_string();
+    FederationBlockList::replace(&mut context.pool(), Some(vec!["blocked.example".into()])).await?;
+    // wait for the cached blocklist to expire
+    sleep(CACHE_DURATION_FEDERATION * 2).await;
+
+    // urls of blocked instances are rejected before any request is made, also for admins
+    let context_ = context.reset_request_count();
+    let res = search_query_to_object_id(query.clone(), None, true, false, true, &context_).await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindObject),
+      res.err().map(|e| e.error_type)
+    );
+    assert_eq!(0, context_.request_count());
+
+    // same for webfinger lookups
+    let context_ = context.reset_request_count();
+    let res = search_query_to_object_id(
+      "!news@blocked.example".to_string(),
+      None,
+      true,
+      false,
+      true,
+      &context_,
+    )
+    .await;
+    assert!(res.is_err());
+    assert_eq!(0, context_.request_count());
+
+    FederationBlockList::replace(&mut context.pool(), Some(vec![])).await?;
+    let instance = Instance::read_or_create(&mut context.pool(), "blocked.example".into()).await?;
+    Instance::delete(&mut context.pool(), instance.id).await?;
+    sleep(CACHE_DURATION_FEDERATION * 2).await;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_outside_allowlist() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let remote = TestInstance::builder("not-allowed.example")
+      .remote()
+      .create(&context)
+      .await?;
+    let community = remote
+      .create_community("outside_allowlist", &context)
+      .await?;
+    FederationAllowList::replace(&mut context.pool(), Some(vec!["allowed.example".into()])).await?;
+    // wait for the cached allowlist to expire
+    sleep(CACHE_DURATION_FEDERATION * 2).await;
+
+    // known objects are returned from the database, even when a refresh is requested
+    let context_ = context.reset_request_count();
+    let res = search_query_to_object_id(
+      community.actor_id.to_string(),
+      None,
+      true,
+      true,
+      true,
+      &context_,
+    )
+    .await?;
+    assert_eq!(1, res.len());
+    assert_eq!(0, context_.request_count());
+    let mention = format!("!outside_allowlist@{}", remote.instance.domain);
+    let res = search_query_to_object_id(mention, None, true, true, true, &context_).await?;
+    assert_eq!(1, res.len());
+    assert_eq!(0, context_.request_count());
+
+    // unknown objects aren't fetched
+    let res = search_query_to_object_id(
+      "https://not-allowed.example/post/1".to_string(),
+      None,
+      true,
+      false,
+      true,
+      &context_,
+    )
+    .await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindObject),
+      res.err().map(|e| e.error_type)
+    );
+    assert_eq!(0, context_.request_count());
+
+    FederationAllowList::replace(&mut context.pool(), Some(vec![])).await?;
+    let instance = Instance::read_or_create(&mut context.pool(), "allowed.example".into()).await?;
+    Instance::delete(&mut context.pool(), instance.id).await?;
+    remote.cleanup(&context).await?;
+    sleep(CACHE_DURATION_FEDERATION * 2).await;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_remote_kinds() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (local, remote) = TestInstance::local_and_remote("remote-kinds.example", &context).await?;
+    let person = remote.create_person("remote_kinds_user", &context).await?;
+    let community = remote.create_community("remote_kinds", &context).await?;
+    let post_form = PostInsertForm::builder()
+      .name("remote kinds post".to_string())
+      .creator_id(person.id)
+      .community_id(community.id)
+      .ap_id(Some(
+        Url::parse("https://remote-kinds.example/post/1")?.into(),
+      ))
+      .local(Some(false))
+      .build();
+    let post = Post::create(&mut context.pool(), &post_form).await?;
+    let comment_form = CommentInsertForm::builder()
+      .content("remote kinds comment".to_string())
+      .creator_id(person.id)
+      .post_id(post.id)
+      .ap_id(Some(
+        Url::parse("https://remote-kinds.example/comment/1")?.into(),
+      ))
+      .local(Some(false))
+      .build();
+    let comment = Comment::create(&mut context.pool(), &comment_form, None).await?;
+
+    let disabled = |posts: bool, comments: bool, actors: bool| LocalSiteFederationUpdateForm {
+      resolve_remote_posts: Some(!posts),
+      resolve_remote_comments: Some(!comments),
+      resolve_remote_actors: Some(!actors),
+      ..Default::default()
+    };
+    let cases = [
+      (disabled(true, false, false), &post.ap_id, &comment.ap_id),
+      (disabled(false, true, false), &comment.ap_id, &post.ap_id),
+      (
+        disabled(false, false, true),
+        &community.actor_id,
+        &post.ap_id,
+      ),
+    ];
+    for (form, disabled_id, enabled_id) in cases {
+      LocalSiteFederation::update(&mut context.pool(), &form).await?;
+
+      // known objects of the disabled kind are returned without refetching them
+      let context_ = context.reset_request_count();
+      let res =
+        search_query_to_object_id(disabled_id.to_string(), None, true, true, true, &context_)
+          .await?;
+      assert_eq!(vec![disabled_id.inner().clone()], ap_ids(&res));
+      assert_eq!(0, context_.request_count());
+
+      // while other kinds are still fetched
+      let context_ = context.reset_request_count();
+      let _ =
+        search_query_to_object_id(enabled_id.to_string(), None, true, true, true, &context_).await;
+      assert_eq!(1, context_.request_count());
+    }
+
+    // unknown actors aren't looked up via webfinger
+    let context_ = context.reset_request_count();
+    let mention = "!unknown@remote-kinds.example".to_string();
+    let res = search_query_to_object_id(mention, None, true, false, true, &context_).await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindObject),
+      res.err().map(|e| e.error_type)
+    );
+    assert_eq!(0, context_.request_count());
+
+    remote.cleanup(&context).await?;
+    local.cleanup(&context).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_remote_kinds_nested() -> LemmyResult<()> {
+    let mut remote = MockRemote::bind().await?;
+    let base = remote.base.clone();
+    let object =
+      move |json: &str| json_response(&json.replace("https://enterprise.lemmy.ml", &base));
+    let note = object(include_str!("../../assets/lemmy/objects/note.json"));
+    let page = object(include_str!("../../assets/lemmy/objects/page.json"));
+    let group = object(include_str!("../../assets/lemmy/objects/group.json"));
+    let person = object(include_str!("../../assets/lemmy/objects/person.json"));
+    remote.serve(move |request| {
+      let path = request.split_whitespace().nth(1)?;
+      match path {
+        "/comment/38741" => Some(note.clone()),
+        "/post/55143" => Some(page.clone()),
+        "/c/tenforward" => Some(group.clone()),
+        "/u/picard" => Some(person.clone()),
+        _ => None,
+      }
+    });
+    let context = remote.context().await?;
+    let local = TestInstance::builder("example.com")
+      .create(&context)
+      .await?;
+    LocalSiteFederation::update(
+      &mut context.pool(),
+      &LocalSiteFederationUpdateForm {
+        resolve_remote_actors: Some(false),
+        ..Default::default()
+      },
+    )
+    .await?;
+
+    // the comment and its post are allowed, but they need the unknown community and author,
+    // which are actors
+    let query = format!("{}/comment/38741", remote.base);
+    let res = search_query_to_object_id(query, None, true, false, true, &context).await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindObject),
+      res.err().map(|e| e.error_type)
+    );
+    let community_id = Url::parse(&format!("{}/c/tenforward", remote.base))?;
+    assert!(ApubCommunity::read_from_id(community_id, &context)
+      .await?
+      .is_none());
+    let person_id = Url::parse(&format!("{}/u/picard", remote.base))?;
+    assert!(ApubPerson::read_from_id(person_id, &context)
+      .await?
+      .is_none());
+
+    local.cleanup(&context).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_mention() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let user = create_user("mention_user".to_string(), None, false, &context).await?;
+    let community_form = CommunityInsertForm::builder()
+      .name("mention_community".to_string())
+      .title("mention_community".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(user.person.instance_id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+
+    for query in [
+      "!mention_community@example.com",
+      "!Mention_Community@EXAMPLE.com",
+      "mention_community@example.com",
+    ] {
+      let res = search_query_to_object_id_local(query, &context).await?;
+      assert_eq!(community.actor_id.inner(), &res.ap_id());
+      let context_ = context.reset_request_count();
+      let res =
+        search_query_to_object_id(query.to_string(), None, false, false, true, &context_).await?;
+      assert_eq!(vec![community.actor_id.inner().clone()], ap_ids(&res));
+      assert_eq!(0, context_.request_count());
+    }
+
+    for query in ["@mention_user@example.com", "@Mention_User@Example.com"] {
+      let res = search_query_to_object_id_local(query, &context).await?;
+      assert_eq!(user.person.actor_id.inner(), &res.ap_id());
+      let res =
+        search_query_to_object_id(query.to_string(), None, false, false, true, &context).await?;
+      assert_eq!(vec![user.person.actor_id.inner().clone()], ap_ids(&res));
+    }
+
+    // the sigil restricts the actor type
+    let res = search_query_to_object_id_local("!mention_user@example.com", &context).await;
+    assert!(res.is_err());
+    let res = search_query_to_object_id_local("@mention_community@example.com", &context).await;
+    assert!(res.is_err());
+
+    Instance::delete(&mut context.pool(), user.person.instance_id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_alias() -> LemmyResult<()> {
+    let mut remote = MockRemote::bind().await?;
+    let picard = "https://enterprise.lemmy.ml/u/picard";
+    let old = format!("{}/u/old_picard", remote.base);
+    let liar = format!("{}/u/liar", remote.base);
+    let base = remote.base.clone();
+    remote.serve(move |request| {
+      // only the old account lists the person back
+      let (name, also_known_as) = if request.starts_with("GET /u/old_picard ") {
+        ("old_picard", vec![picard])
+      } else {
+        ("liar", vec![])
+      };
+      let json = serde_json::json!({
+        "id": format!("{base}/u/{name}"),
+        "type": "Person",
+        "alsoKnownAs": also_known_as,
+      });
+      Some(json_response(&json.to_string()))
+    });
+    let context = remote.context().await?;
+    let site = parse_lemmy_instance(&context).await?;
+    let same_domain = "https://enterprise.lemmy.ml/u/picard_alt";
+    let mut json: PersonObject = file_to_json_object("assets/lemmy/objects/person.json")?;
+    json.also_known_as = Some(vec![
+      Url::parse(same_domain)?,
+      Url::parse(&old)?,
+      Url::parse(&liar)?,
+    ]);
+    let person = ApubPerson::from_json(json.clone(), &context).await?;
+    assert_eq!(2, remote.requests().await.len());
+
+    // the verified aliases resolve to the person, like its canonical id
+    for query in [person.actor_id.as_str(), same_domain, &old] {
+      let res = search_query_to_object_id_local(query, &context).await?;
+      assert_eq!(person.actor_id.inner(), &res.ap_id());
+    }
+    // while the claim which isn't confirmed by the other actor is ignored
+    let res = search_query_to_object_id_local(&liar, &context).await;
+    assert!(res.is_err());
+
+    // aliases which were verified before aren't fetched again
+    ApubPerson::from_json(json, &context).await?;
+    assert_eq!(3, remote.requests().await.len());
+
+    // once the person doesn't list the alias anymore, it isn't resolved
+    let json = file_to_json_object("assets/lemmy/objects/person.json")?;
+    ApubPerson::from_json(json, &context).await?;
+    let res = search_query_to_object_id_local(same_domain, &context).await;
+    assert!(res.is_err());
+
+    Person::delete(&mut context.pool(), person.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_inserted_objects_counter() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let user = create_user("inserted_user".to_string(), None, false, &context).await?;
+    let community_form = CommunityInsertForm::builder()
+      .name("inserted_community".to_string())
+      .title("inserted_community".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(user.person.instance_id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+    let inserted_communities = INSERTED_OBJECTS.with_label_values(&["community"]);
+    let inserted_posts = INSERTED_OBJECTS.with_label_values(&["post"]);
+    let communities_before = inserted_communities.get();
+    let posts_before = inserted_posts.get();
+
+    // resolving an object which is already known doesn't count as inserted
+    let res = search_query_to_object_id(
+      community.actor_id.to_string(),
+      None,
+      false,
+      false,
+      true,
+      &context,
+    )
+    .await?;
+    assert_eq!(vec![community.actor_id.inner().clone()], ap_ids(&res));
+    assert_eq!(communities_before, inserted_communities.get());
+
+    // neither does a failed fetch
+    let query = "https://missing.example/post/2".to_string();
+    let res = search_query_to_object_id(query, None, true, false, true, &context).await;
+    assert!(res.is_err());
+    assert_eq!(posts_before, inserted_posts.get());
+
+    Instance::delete(&mut context.pool(), user.person.instance_id).await?;
+    Ok(())
+  }
+
+  #[test]
+  fn test_query_url() -> LemmyResult<()> {
+    let cases = [
+      (
+        "https://example.com/post/1#comments",
+        "https://example.com/post/1",
+      ),
+      (
+        "https://example.com/post/1?utm_source=share&utm_medium=web",
+        "https://example.com/post/1",
+      ),
+      (
+        "https://example.com/view?fbclid=abc&id=1%202&page=2",
+        "https://example.com/view?id=1%202&page=2",
+      ),
+      // params which merely look similar are kept
+      (
+        "https://example.com/view?utm=1&fbclid_id=2",
+        "https://example.com/view?utm=1&fbclid_id=2",
+      ),
+      ("Example.com", "https://example.com/"),
+    ];
+    for (query, expected) in cases {
+      assert_eq!(Some(Url::parse(expected)?), query_url(query));
+    }
+    assert_eq!(None, query_url("!news@example.com"));
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_stripped_url() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let user = create_user("stripped_url_user".to_string(), None, false, &context).await?;
+    let community_form = CommunityInsertForm::builder()
+      .name("stripped_url_community".to_string())
+      .title("stripped_url_community".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(user.person.instance_id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+    let remote_instance =
+      Instance::read_or_create(&mut context.pool(), "stripped-url.example".to_string()).await?;
+    let post_form = PostInsertForm::builder()
+      .name("stripped url post".to_string())
+      .creator_id(user.person.id)
+      .community_id(community.id)
+      .ap_id(Some(
+        Url::parse("https://stripped-url.example/post/1")?.into(),
+      ))
+      .local(Some(false))
+      .build();
+    let post = Post::create(&mut context.pool(), &post_form).await?;
+
+    // urls copied from a browser still resolve the known post, without any fetch
+    for query in [
+      "https://stripped-url.example/post/1#comment-3",
+      "https://stripped-url.example/post/1?utm_source=share&utm_medium=web",
+    ] {
+      let res = search_query_to_object_id_local(query, &context).await?;
+      assert_eq!(post.ap_id.inner(), &res.ap_id());
+      let context_ = context.reset_request_count();
+      let res =
+        search_query_to_object_id(query.to_string(), None, false, false, true, &context_).await?;
+      assert_eq!(vec![post.ap_id.inner().clone()], ap_ids(&res));
+      assert_eq!(0, context_.request_count());
+    }
+
+    Instance::delete(&mut context.pool(), remote_instance.id).await?;
+    Instance::delete(&mut context.pool(), user.person.instance_id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_redirect() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let user = create_user("redirect_user".to_string(), None, false, &context).await?;
+    let community_form = CommunityInsertForm::builder()
+      .name("redirect_community".to_string())
+      .title("redirect_community".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(user.person.instance_id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+    let post_form = PostInsertForm::builder()
+      .name("redirect post".to_string())
+      .creator_id(user.person.id)
+      .community_id(community.id)
+      .build();
+    let post = Post::create(&mut context.pool(), &post_form).await?;
+
+    // a redirect resolves to its target
+    let old_url = Url::parse("https://old.example/post/1")?;
+    let json = serde_json::json!({
+      "id": old_url,
+      "type": "Move",
+      "target": post.ap_id,
+    });
+    let redirect: SearchableKinds = serde_json::from_value(json)?;
+    SearchableObjects::verify(&redirect, &old_url, &context).await?;
+    let context_ = context.reset_request_count();
+    let res = SearchableObjects::from_json(redirect, &context_).await?;
+    assert_eq!(post.ap_id.inner(), &res.ap_id());
+    assert_eq!(0, context_.request_count());
+
+    // a redirect to itself is rejected
+    let json = serde_json::json!({
+      "id": old_url,
+      "type": "Move",
+      "target": old_url,
+    });
+    let redirect: SearchableKinds = serde_json::from_value(json)?;
+    let res = SearchableObjects::verify(&redirect, &old_url, &context).await;
+    assert_eq!(
+      Some(LemmyErrorType::TooManyRedirects),
+      res.err().map(|e| e.error_type)
+    );
+
+    Instance::delete(&mut context.pool(), user.person.instance_id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_rejects_other_origin() -> LemmyResult<()> {
+    // a server for two domains, which redirects requests for the first one to the second one
+    // where it serves a person
+    let mut remote = MockRemote::bind().await?;
+    let port = remote.addr.port();
+    let origin = format!("http://other.example:{port}");
+    let person_response = json_response(
+      &include_str!("../../assets/lemmy/objects/person.json")
+        .replace("https://enterprise.lemmy.ml", &origin),
+    );
+    let redirect_response = format!(
+      "HTTP/1.1 302 Found\r\nLocation: {origin}/u/picard\r\n\
+       Content-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    remote.serve(move |request| {
+      if request.to_lowercase().contains("host: queried.example") {
+        Some(redirect_response.clone())
+      } else {
+        Some(person_response.clone())
+      }
+    });
+    let client = reqwest::Client::builder()
+      .resolve("queried.example", remote.addr)
+      .resolve("other.example", remote.addr)
+      .build()?;
+    let context = mock_remote_context(Some(client.into())).await?;
+    let local = TestInstance::builder("example.com")
+      .create(&context)
+      .await?;
+
+    // the person is returned for the queried url, but is served by another host
+    let query = format!("http://queried.example:{port}/u/picard");
+    let res = search_query_to_object_id(query, None, true, false, true, &context).await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindObject),
+      res.err().map(|e| e.error_type)
+    );
+    let actor_id = Url::parse(&format!("{origin}/u/picard"))?;
+    assert!(SearchableObjects::read_from_id(actor_id.clone(), &context)
+      .await?
+      .is_none());
+
+    // fetched directly from its origin, the same person is accepted
+    let res =
+      search_query_to_object_id(actor_id.to_string(), None, true, false, true, &context).await?;
+    assert_eq!(vec![actor_id], ap_ids(&res));
+
+    // the target of a redirect is checked the same way
+    let json = serde_json::json!({
+      "id": format!("http://queried.example:{port}/u/data"),
+      "type": "Move",
+      "target": format!("http://queried.example:{port}/u/riker"),
+    });
+    let redirect: SearchableKinds = serde_json::from_value(json)?;
+    let res = SearchableObjects::from_json(redirect, &context).await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindObject),
+      res.err().map(|e| e.error_type)
+    );
+
+    // as is a known object which is refreshed, which stays unchanged
+    let queried = TestInstance::builder("queried.example")
+      .remote()
+      .base_url(&format!("http://queried.example:{port}"))
+      .create(&context)
+      .await?;
+    let known = queried.create_person("riker", &context).await?;
+    let known_id = known.actor_id.to_string();
+    let res = search_query_to_object_id(known_id.clone(), None, true, true, true, &context).await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindObject),
+      res.err().map(|e| e.error_type)
+    );
+    let res = search_query_to_object_id(known_id, None, true, false, true, &context).await?;
+    assert_eq!(vec![known.actor_id.inner().clone()], ap_ids(&res));
+
+    let instance =
+      Instance::read_or_create(&mut context.pool(), "other.example".to_string()).await?;
+    Instance::delete(&mut context.pool(), instance.id).await?;
+    queried.cleanup(&context).await?;
+    local.cleanup(&context).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_signed_fetch() -> LemmyResult<()> {
+    // an instance in secure mode, which only serves the person to signed fetches
+    let mut remote = MockRemote::bind().await?;
+    let person_response = json_response(
+      &include_str!("../../assets/lemmy/objects/person.json")
+        .replace("https://enterprise.lemmy.ml", &remote.base),
+    );
+    let body = r#"{"error":"Request not signed"}"#;
+    let unauthorized_response = format!(
+      "HTTP/1.1 401 Unauthorized\r\nContent-Type: application/json\r\n\
+       Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+      body.len()
+    );
+    remote.serve(move |request| {
+      if request.starts_with("GET /u/broken ") {
+        Some(json_response("{"))
+      } else if request.to_lowercase().contains("\r\nsignature: ") {
+        Some(person_response.clone())
+      } else {
+        Some(unauthorized_response.clone())
+      }
+    });
+    // the status of responses is only known with the middleware of the resolve client
+    let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+      .with(FetchHeaders)
+      .build();
+    let context = mock_remote_context(Some(client.clone())).await?;
+    let local = TestInstance::builder("example.com")
+      .create(&context)
+      .await?;
+    let query = format!("{}/u/picard", remote.base);
+
+    // without a key to sign fetches, the person can't be resolved
+    let res = search_query_to_object_id(query.clone(), None, true, false, true, &context).await;
+    assert!(res.is_err());
+    assert_eq!(1, remote.requests().await.len());
+
+    // with the key of the site, the denied fetch is retried once as signed
+    let keypair = generate_actor_keypair()?;
+    let form = SiteUpdateForm {
+      public_key: Some(keypair.public_key),
+      private_key: Some(Some(keypair.private_key)),
+      ..Default::default()
+    };
+    let site: ApubSite = Site::update(&mut context.pool(), local.site.id, &form)
+      .await?
+      .into();
+    let mut config = FederationConfig::builder();
+    config
+      .domain(context.settings().hostname.clone())
+      .debug(true)
+      .allow_http_urls(true)
+      .client(client);
+    let signed_config = config
+      .clone()
+      .app_data(context.app_data().clone())
+      .signed_fetch_actor(&site)
+      .build()
+      .await?;
+    let context = config
+      .app_data(
+        context
+          .app_data()
+          .clone()
+          .with_signed_resolve_config(signed_config),
+      )
+      .build()
+      .await?
+      .to_request_data();
+    let res = search_query_to_object_id(query.clone(), None, true, false, true, &context).await?;
+    assert_eq!(vec![Url::parse(&query)?], ap_ids(&res));
+    let requests = remote.requests().await;
+    let picard_requests = requests
+      .iter()
+      .filter(|r| r.starts_with("GET /u/picard "))
+      .count();
+    assert_eq!(3, picard_requests);
+
+    // fetches which fail for other reasons than a denied request aren't retried
+    let query = format!("{}/u/broken", remote.base);
+    let res = search_query_to_object_id(query, None, true, false, true, &context).await;
+    assert!(res.is_err());
+    let requests = remote.requests().await;
+    let broken_requests = requests
+      .iter()
+      .filter(|r| r.starts_with("GET /u/broken "))
+      .count();
+    assert_eq!(1, broken_requests);
+
+    let instance = Instance::read_or_create(&mut context.pool(), "localhost".to_string()).await?;
+    Instance::delete(&mut context.pool(), instance.id).await?;
+    local.cleanup(&context).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_conditional_fetch() -> LemmyResult<()> {
+    // a remote instance which only responds with the person if its etag isn't known
+    let mut remote = MockRemote::bind().await?;
+    let body = include_str!("../../assets/lemmy/objects/person.json")
+      .replace("https://enterprise.lemmy.ml", &remote.base);
+    let person_response = format!(
+      "HTTP/1.1 200 OK\r\nContent-Type: application/activity+json\r\nETag: \"v1\"\r\n\
+       Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+      body.len()
+    );
+    let not_modified_response =
+      "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n".to_string();
+    remote.serve(move |request| {
+      if request
+        .to_lowercase()
+        .contains("\r\nif-none-match: \"v1\"\r\n")
+      {
+        Some(not_modified_response.clone())
+      } else {
+        Some(person_response.clone())
+      }
+    });
+    let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+      .with(FetchHeaders)
+      .build();
+    let context = mock_remote_context(Some(client)).await?;
+    let query = format!("{}/u/picard", remote.base);
+    let picard_requests = |requests: Vec<String>| {
+      requests
+        .iter()
+        .filter(|r| r.starts_with("GET /u/picard "))
+        .count()
+    };
+
+    // the etag of the first fetch is stored
+    let res = search_query_to_object_id(query.clone(), None, true, false, true, &context).await?;
+    let refreshed_at = res.first().and_then(SearchableObjects::last_refreshed_at);
+    assert!(refreshed_at.is_some());
+    assert_eq!(1, picard_requests(remote.requests().await));
+    let ap_id: DbUrl = Url::parse(&query)?.into();
+    let validator = ResolveFetchValidator::read(&mut context.pool(), &ap_id).await?;
+    assert_eq!(Some("\"v1\"".to_string()), validator.and_then(|v| v.etag));
+
+    // so that refreshing the person fetches it with its etag, and it isn't updated
+    let res = search_query_to_object_id(query.clone(), None, true, true, true, &context).await?;
+    assert_eq!(vec![Url::parse(&query)?], ap_ids(&res));
+    assert_eq!(
+      refreshed_at,
+      res.first().and_then(SearchableObjects::last_refreshed_at)
+    );
+    assert_eq!(2, picard_requests(remote.requests().await));
+
+    // the same when the person is outdated
+    let outdated = Utc::now() - chrono::TimeDelta::try_days(2).expect("TimeDelta out of bounds");
+    let form = PersonUpdateForm {
+      last_refreshed_at: Some(outdated),
+      ..Default::default()
+    };
+    let person = Person::read_from_apub_id(&mut context.pool(), &ap_id)
+      .await?
+      .ok_or(LemmyErrorType::CouldntFindPerson)?;
+    Person::update(&mut context.pool(), person.id, &form).await?;
+    let res = search_query_to_object_id(query.clone(), None, true, false, true, &context).await?;
+    assert_eq!(
+      Some(outdated),
+      res.first().and_then(SearchableObjects::last_refreshed_at)
+    );
+    assert_eq!(3, picard_requests(remote.requests().await));
+
+    let removed = ResolveFetchValidatorForm {
+      ap_id,
+      etag: None,
+      last_modified: None,
+    };
+    ResolveFetchValidator::replace(&mut context.pool(), &removed).await?;
+    let instance = Instance::read_or_create(&mut context.pool(), "localhost".to_string()).await?;
+    Instance::delete(&mut context.pool(), instance.id).await?;
+    Ok(())
+  }
+
+  fn ap_ids(objects: &[SearchableObjects]) -> Vec<Url> {
+    objects.iter().map(SearchableObjects::ap_id).collect()
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_local_id() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let user = create_user("local_id_user".to_string(), None, false, &context).await?;
+    let community_form = CommunityInsertForm::builder()
+      .name("local_id_community".to_string())
+      .title("local_id_community".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(user.person.instance_id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+    let post_form = PostInsertForm::builder()
+      .name("local id post".to_string())
+      .creator_id(user.person.id)
+      .community_id(community.id)
+      .build();
+    let post = Post::create(&mut context.pool(), &post_form).await?;
+    let comment_form = CommentInsertForm::builder()
+      .content("local id comment".to_string())
+      .creator_id(user.person.id)
+      .post_id(post.id)
+      .build();
+    let comment = Comment::create(&mut context.pool(), &comment_form, None).await?;
+
+    let cases = [
+      (format!("post:{}", post.id), post.ap_id),
+      (format!("comment:{}", comment.id), comment.ap_id),
+      (format!("person:{}", user.person.id.0), user.person.actor_id),
+      (format!("community:{}", community.id.0), community.actor_id),
+    ];
+    for (query, ap_id) in cases {
+      let res = search_query_to_object_id_local(&query, &context).await?;
+      assert_eq!(ap_id.inner(), &res.ap_id());
+      let context_ = context.reset_request_count();
+      let res = search_query_to_object_id(query, None, false, false, true, &context_).await?;
+      assert_eq!(vec![ap_id.inner().clone()], ap_ids(&res));
+      assert_eq!(0, context_.request_count());
+    }
+
+    // unknown ids and kinds are rejected
+    let res = search_query_to_object_id_local("post:0", &context).await;
+    assert!(res.is_err());
+    let res = search_query_to_object_id_local(&format!("poll:{}", post.id), &context).await;
+    assert!(res.is_err());
+
+    Instance::delete(&mut context.pool(), user.person.instance_id).await?;
+    Ok(())
+  }
+
+  /// The hash which is stored by the database for received content.
+  fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content))
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_content_hash() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let community = parse_lemmy_community(&context).await?;
+    let page: Page = file_to_json_object("assets/lemmy/objects/page.json")?;
+    let hash = content_hash(page.content.as_deref().unwrap_or_default());
+    let post = ApubPost::from_json(page, &context).await?;
+    let note: Note = file_to_json_object("assets/lemmy/objects/note.json")?;
+    let comment_hash = content_hash(&note.content);
+    let comment = ApubComment::from_json(note, &context).await?;
+
+    let cases = [
+      (format!("hash:{hash}"), &post.ap_id),
+      (format!("hash:{}", hash.to_uppercase()), &post.ap_id),
+      (format!("hash:{comment_hash}"), &comment.ap_id),
+    ];
+    for (query, ap_id) in cases {
+      let res = search_query_to_object_id_local(&query, &context).await?;
+      assert_eq!(ap_id.inner(), &res.ap_id());
+      let context_ = context.reset_request_count();
+      let res = search_query_to_object_id(query, None, false, false, true, &context_).await?;
+      assert_eq!(vec![ap_id.inner().clone()], ap_ids(&res));
+      assert_eq!(0, context_.request_count());
+    }
+
+    // unknown and invalid hashes aren't found
+    let res = search_query_to_object_id_local(&format!("hash:{}", "0".repeat(64)), &context).await;
+    assert!(res.is_err());
+    let res = search_query_to_object_id_local("hash:post", &context).await;
+    assert!(res.is_err());
+
+    // the hash follows edits of the content
+    let mut page: Page = file_to_json_object("assets/lemmy/objects/page.json")?;
+    page.content = Some("<p>edited</p>".to_string());
+    page.updated = Some(Utc::now());
+    ApubPost::from_json(page, &context).await?;
+    let res = search_query_to_object_id_local(&format!("hash:{hash}"), &context).await;
+    assert!(res.is_err());
+    let res =
+      search_query_to_object_id_local(&format!("hash:{}", content_hash("<p>edited</p>")), &context)
+        .await?;
+    assert_eq!(post.ap_id.inner(), &res.ap_id());
+
+    Comment::delete(&mut context.pool(), comment.id).await?;
+    Post::delete(&mut context.pool(), post.id).await?;
+    Person::delete(&mut context.pool(), person.id).await?;
+    Community::delete(&mut context.pool(), community.id).await?;
+    Site::delete(&mut context.pool(), site.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_local_path() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let user = create_user("local_path_user".to_string(), None, false, &context).await?;
+    let community_form = CommunityInsertForm::builder()
+      .name("local_path_community".to_string())
+      .title("local_path_community".to_string())
+      .public_key("pubkey".to_string())
+      .instance_id(user.person.instance_id)
+      .build();
+    let community = Community::create(&mut context.pool(), &community_form).await?;
+    let post_form = PostInsertForm::builder()
+      .name("local path post".to_string())
+      .creator_id(user.person.id)
+      .community_id(community.id)
+      .build();
+    let post = Post::create(&mut context.pool(), &post_form).await?;
+    let comment_form = CommentInsertForm::builder()
+      .content("local path comment".to_string())
+      .creator_id(user.person.id)
+      .post_id(post.id)
+      .build();
+    let comment = Comment::create(&mut context.pool(), &comment_form, None).await?;
+
+    let cases = [
+      (format!("/post/{}", post.id), post.ap_id),
+      (format!("/comment/{}/", comment.id), comment.ap_id),
+      ("/u/local_path_user".to_string(), user.person.actor_id),
+      ("/c/local_path_community".to_string(), community.actor_id),
+    ];
+    for (query, ap_id) in cases {
+      let res = search_query_to_object_id_local(&query, &context).await?;
+      assert_eq!(ap_id.inner(), &res.ap_id());
+      let context_ = context.reset_request_count();
+      let res = search_query_to_object_id(query, None, false, false, true, &context_).await?;
+      assert_eq!(vec![ap_id.inner().clone()], ap_ids(&res));
+      assert_eq!(0, context_.request_count());
+    }
+
+    // unknown objects are rejected, malformed paths are handled like any other query
+    let res = search_query_to_object_id_local("/post/0", &context).await;
+    assert_eq!(
+      Some(LemmyErrorType::CouldntFindObject),
+      res.err().map(|e| e.error_type)
+    );
+    let context_ = context.reset_request_count();
+    for query in [
+      "/post/abc",
+      "/c/",
+      "/c/local_path_community/extra",
+      "/poll/1",
+    ] {
+      let res = read_from_local_id(query, &context_).await?;
+      assert!(res.is_none());
+    }
+
+    Instance::delete(&mut context.pool(), user.person.instance_id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_tombstone() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let ap_id = Url::parse("https://lemmy.ml/comment/110273")?;
+
+    // a tombstone in place of the object is returned as such, without storing anything
+    let json: SearchableKinds = file_to_json_object("assets/lemmy/objects/tombstone.json")?;
+    SearchableObjects::verify(&json, &ap_id, &context).await?;
+    let object = SearchableObjects::from_json(json, &context).await?;
+    assert_eq!(ap_id, object.ap_id());
+    assert_eq!(None, object.resolve_type());
+    assert!(matches!(object, SearchableObjects::Tombstone(_)));
+    assert!(SearchableObjects::read_from_id(ap_id.clone(), &context)
+      .await?
+      .is_none());
+
+    // a tombstone from another domain is rejected
+    let json: SearchableKinds = file_to_json_object("assets/lemmy/objects/tombstone.json")?;
+    let other = Url::parse("https://example.com/comment/1")?;
+    assert!(SearchableObjects::verify(&json, &other, &context)
+      .await
+      .is_err());
+
+    // 410 Gone responses are recognized
+    let error: LemmyError = ActivityPubError::ObjectDeleted(ap_id).into();
+    assert!(is_object_deleted(&error));
+    assert!(!is_object_deleted(
+      &LemmyErrorType::CouldntFindObject.into()
+    ));
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_resolve_mod_action() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let ap_id = Url::parse(
+      "http://enterprise.lemmy.ml/activities/block/5d42fffb-0903-4625-86d4-0b39bb344fc2",
+    )?;
+
+    // a remote ban is returned without storing anything
+    let json: SearchableKinds =
+      file_to_json_object("assets/lemmy/activities/block/block_user.json")?;
+    SearchableObjects::verify(&json, &ap_id, &context).await?;
+    let object = SearchableObjects::from_json(json, &context).await?;
+    let SearchableObjects::ModAction(mod_action) = object else {
+      Err(LemmyErrorType::CouldntFindObject)?
+    };
+    assert_eq!(ModActionType::Block, mod_action.kind);
+    assert_eq!(Some("spam post".to_string()), mod_action.reason);
+    let community_id = Url::parse("http://enterprise.lemmy.ml/c/main")?;
+    assert_eq!(Some(community_id.into()), mod_action.target_id);
+    assert!(SearchableObjects::read_from_id(ap_id, &context)
+      .await?
+      .is_none());
+
+    // also reports, which are addressed to the community instead
+    let json: SearchableKinds =
+      file_to_json_object("assets/lemmy/activities/community/report_page.json")?;
+    assert!(matches!(json, SearchableKinds::ModAction(_)));
+
+    // a ban from another domain is rejected
+    let json: SearchableKinds =
+      file_to_json_object("assets/lemmy/activities/block/block_user.json")?;
+    let other = Url::parse("https://example.com/activities/block/1")?;
+    assert!(SearchableObjects::verify(&json, &other, &context)
+      .await
+      .is_err());
+
+    // a ban sent by this instance is found among the sent activities, while resolving only if
+    // moderation activities were asked for
+    let ap_id = Url::parse(&format!(
+      "{}/activities/block/{}",
+      context.settings().get_protocol_and_hostname(),
+      uuid::Uuid::new_v4()
+    ))?;
+    let actor_id = Url::parse("http://enterprise.lemmy.ml/u/picard")?;
+    let data = serde_json::json!({
+      "id": ap_id,
+      "type": "Block",
+      "actor": actor_id,
+      "object": "http://enterprise.lemmy.ml/u/riker",
+      "target": "http://enterprise.lemmy.ml/c/main",
+    });
+    let activity_form = SentActivityForm {
+      ap_id: ap_id.clone().into(),
+      data,
+      sensitive: false,
+      send_inboxes: vec![],
+      send_community_followers_of: None,
+      send_all_instances: false,
+      actor_type: ActorType::Person,
+      actor_apub_id: actor_id.into(),
+    };
+    SentActivity::create(&mut context.pool(), activity_form).await?;
+    let read = || SearchableObjects::read_from_id(ap_id.clone(), &context);
+    assert!(matches!(
+      read().await?,
+      Some(SearchableObjects::ModAction(_))
+    ));
+    let kinds = |mod_actions| RemoteKinds {
+      posts: true,
+      comments: true,
+      actors: true,
+      mod_actions,
+    };
+    assert!(RESOLVE_KINDS.scope(kinds(false), read()).await?.is_none());
+    assert!(RESOLVE_KINDS.scope(kinds(true), read()).await?.is_some());
+    Ok(())
+  }
+}
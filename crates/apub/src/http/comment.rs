@@ -6,6 +6,7 @@ use crate::{
     redirect_remote_object,
   },
   objects::comment::ApubComment,
+  protocol::collections::vote_collection::VoteCollection,
 };
 use activitypub_federation::{config::Data, traits::Object};
 use actix_web::{web::Path, HttpResponse};
@@ -31,10 +32,10 @@ pub(crate) async fn get_apub_comment(
 ) -> LemmyResult<HttpResponse> {
   let id = CommentId(info.comment_id.parse::<i32>()?);
   // Can't use CommentView here because it excludes deleted/removed/local-only items
-  let comment: ApubComment = Comment::read(&mut context.pool(), id)
+  let (comment, counts) = Comment::read_with_counts(&mut context.pool(), id)
     .await?
-    .ok_or(LemmyErrorType::CouldntFindComment)?
-    .into();
+    .ok_or(LemmyErrorType::CouldntFindComment)?;
+  let comment: ApubComment = comment.into();
   let post = Post::read(&mut context.pool(), comment.post_id)
     .await?
     .ok_or(LemmyErrorType::CouldntFindPost)?;
@@ -46,7 +47,13 @@ pub(crate) async fn get_apub_comment(
   if !comment.local {
     Ok(redirect_remote_object(&comment.ap_id))
   } else if !comment.deleted && !comment.removed {
-    create_apub_response(&comment.into_json(&context).await?)
+    let mut note = comment.into_json(&context).await?;
+    // Lets other instances correct their vote counts, see vote reconciliation
+    if context.settings().publish_vote_counts {
+      note.likes = Some(VoteCollection::new(counts.upvotes));
+      note.dislikes = Some(VoteCollection::new(counts.downvotes));
+    }
+    create_apub_response(&note)
   } else {
     create_apub_tombstone_response(comment.ap_id.clone())
   }
@@ -133,6 +133,7 @@ pub(crate) mod tests {
       community::CommunityInsertForm,
       instance::Instance,
       local_site::{LocalSite, LocalSiteInsertForm},
+      local_site_federation::{LocalSiteFederation, LocalSiteFederationInsertForm},
       local_site_rate_limit::{LocalSiteRateLimit, LocalSiteRateLimitInsertForm},
       site::{Site, SiteInsertForm},
     },
@@ -182,6 +183,10 @@ pub(crate) mod tests {
       .build();
 
     LocalSiteRateLimit::create(&mut context.pool(), &local_site_rate_limit_form).await?;
+    let local_site_federation_form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .build();
+    LocalSiteFederation::create(&mut context.pool(), &local_site_federation_form).await?;
     Ok(())
   }
 
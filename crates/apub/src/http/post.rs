@@ -6,6 +6,7 @@ use crate::{
     redirect_remote_object,
   },
   objects::post::ApubPost,
+  protocol::collections::vote_collection::VoteCollection,
 };
 use activitypub_federation::{config::Data, traits::Object};
 use actix_web::{web, HttpResponse};
@@ -31,10 +32,10 @@ pub(crate) async fn get_apub_post(
 ) -> LemmyResult<HttpResponse> {
   let id = PostId(info.post_id.parse::<i32>()?);
   // Can't use PostView here because it excludes deleted/removed/local-only items
-  let post: ApubPost = Post::read(&mut context.pool(), id)
+  let (post, counts) = Post::read_with_counts(&mut context.pool(), id)
     .await?
-    .ok_or(LemmyErrorType::CouldntFindPost)?
-    .into();
+    .ok_or(LemmyErrorType::CouldntFindPost)?;
+  let post: ApubPost = post.into();
   let community = Community::read(&mut context.pool(), post.community_id)
     .await?
     .ok_or(LemmyErrorType::CouldntFindCommunity)?;
@@ -43,7 +44,13 @@ pub(crate) async fn get_apub_post(
   if !post.local {
     Ok(redirect_remote_object(&post.ap_id))
   } else if !post.deleted && !post.removed {
-    create_apub_response(&post.into_json(&context).await?)
+    let mut page = post.into_json(&context).await?;
+    // Lets other instances correct their vote counts, see vote reconciliation
+    if context.settings().publish_vote_counts {
+      page.likes = Some(VoteCollection::new(counts.upvotes));
+      page.dislikes = Some(VoteCollection::new(counts.downvotes));
+    }
+    create_apub_response(&page)
   } else {
     create_apub_tombstone_response(post.ap_id.clone())
   }
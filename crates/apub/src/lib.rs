@@ -102,13 +102,7 @@ fn check_apub_id_valid(apub_id: &Url, local_site_data: &LocalSiteData) -> LemmyR
     Err(LemmyErrorType::DomainBlocked(domain.clone()))?
   }
 
-  // Only check this if there are instances in the allowlist
-  if !local_site_data.allowed_instances.is_empty()
-    && !local_site_data
-      .allowed_instances
-      .iter()
-      .any(|i| domain.to_lowercase().eq(&i.domain.to_lowercase()))
-  {
+  if local_site_data.is_outside_allowlist(&domain) {
     Err(LemmyErrorType::DomainNotInAllowList(domain))?
   }
 
@@ -122,6 +116,19 @@ pub(crate) struct LocalSiteData {
   blocked_instances: Vec<Instance>,
 }
 
+impl LocalSiteData {
+  /// Returns true if federation is limited to the instances in the allowlist, and the domain isn't
+  /// one of them.
+  pub(crate) fn is_outside_allowlist(&self, domain: &str) -> bool {
+    // Only check this if there are instances in the allowlist
+    !self.allowed_instances.is_empty()
+      && !self
+        .allowed_instances
+        .iter()
+        .any(|i| domain.to_lowercase().eq(&i.domain.to_lowercase()))
+  }
+}
+
 pub(crate) async fn local_site_data_cached(
   pool: &mut DbPool<'_>,
 ) -> LemmyResult<Arc<LocalSiteData>> {
@@ -213,3 +220,17 @@ async fn insert_received_activity(ap_id: &Url, data: &Data<LemmyContext>) -> Lem
   ReceivedActivity::create(&mut data.pool(), &ap_id.clone().into()).await?;
   Ok(())
 }
+
+/// Like [insert_received_activity], but returns false instead of an error if the activity was
+/// received before. Then the caller can acknowledge it without doing any work.
+#[tracing::instrument(skip(data))]
+async fn insert_new_received_activity(ap_id: &Url, data: &Data<LemmyContext>) -> LemmyResult<bool> {
+  Ok(ReceivedActivity::insert_if_new(&mut data.pool(), &ap_id.clone().into()).await?)
+}
+
+/// Returns true if the activity was received before, so that a retried delivery can be
+/// acknowledged in `verify` already, without dereferencing anything.
+#[tracing::instrument(skip(data))]
+async fn is_received_activity(ap_id: &Url, data: &Data<LemmyContext>) -> LemmyResult<bool> {
+  Ok(ReceivedActivity::exists(&mut data.pool(), &ap_id.clone().into()).await?)
+}
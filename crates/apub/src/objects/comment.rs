@@ -1,6 +1,7 @@
 use crate::{
   activities::{verify_is_public, verify_person_in_community},
   check_apub_id_valid_with_strictness,
+  fetcher::search::{check_nested_fetch, RemoteKind},
   mentions::collect_non_local_mentions,
   objects::{read_from_string_or_source, verify_is_remote_object},
   protocol::{
@@ -23,6 +24,7 @@ use lemmy_api_common::{
 use lemmy_db_schema::{
   source::{
     comment::{Comment, CommentInsertForm, CommentUpdateForm},
+    comment_edit::{CommentEdit, CommentEditForm},
     community::Community,
     local_site::LocalSite,
     person::Person,
@@ -131,6 +133,9 @@ impl Object for ApubComment {
       distinguished: Some(self.distinguished),
       language,
       audience: Some(community.actor_id.into()),
+      // The vote counts are only included when the object is served over http
+      likes: None,
+      dislikes: None,
     };
 
     Ok(note)
@@ -142,6 +147,7 @@ impl Object for ApubComment {
     expected_domain: &Url,
     context: &Data<LemmyContext>,
   ) -> LemmyResult<()> {
+    check_nested_fetch::<Self>(note.id.inner(), RemoteKind::Comment, context).await?;
     verify_domains_match(note.id.inner(), expected_domain)?;
     verify_domains_match(note.attributed_to.inner(), note.id.inner())?;
     verify_is_public(&note.to, &note.cc)?;
@@ -179,6 +185,11 @@ impl Object for ApubComment {
     let content = process_markdown(&content, slur_regex, &url_blocklist, context).await?;
     let language_id =
       LanguageTag::to_language_id_single(note.language, &mut context.pool()).await?;
+    // If the note declares an edit, keep the version which it replaces
+    let previous = match note.updated {
+      Some(_) => Comment::read_from_apub_id(&mut context.pool(), note.id.inner().clone()).await?,
+      None => None,
+    };
 
     let form = CommentInsertForm {
       creator_id: creator.id,
@@ -200,8 +211,17 @@ impl Object for ApubComment {
       Some(timestamp),
       &form,
       parent_comment_path.as_ref(),
+      Some(&note.content),
     )
     .await?;
+    if let Some(previous) = previous.filter(|p| p.content != comment.content) {
+      let form = CommentEditForm {
+        comment_id: comment.id,
+        published: previous.updated.unwrap_or(previous.published),
+        content: previous.content,
+      };
+      CommentEdit::create(&mut context.pool(), &form).await?;
+    }
     Ok(comment.into())
   }
 }
@@ -1,8 +1,14 @@
 use crate::{
   activities::GetActorType,
   check_apub_id_valid,
+  collections::{community_outbox::OutboxFetch, keep_fetch_permits},
+  fetcher::search::{check_nested_fetch, keep_resolve_kinds, RemoteKind},
   local_site_data_cached,
-  objects::{instance::fetch_instance_actor_for_object, read_from_string_or_source_opt},
+  objects::{
+    instance::fetch_instance_actor_for_object,
+    read_from_string_or_source_opt,
+    verified_actor_aliases,
+  },
   protocol::{
     objects::{group::Group, Endpoints, LanguageTag},
     ImageObject,
@@ -31,6 +37,7 @@ use lemmy_db_schema::{
   sensitive::SensitiveString,
   source::{
     activity::ActorType,
+    actor_alias::ActorAlias,
     actor_language::CommunityLanguage,
     community::{Community, CommunityInsertForm, CommunityUpdateForm},
     local_site::LocalSite,
@@ -124,6 +131,7 @@ impl Object for ApubCommunity {
       updated: self.updated,
       posting_restricted_to_mods: Some(self.posting_restricted_to_mods),
       attributed_to: Some(generate_moderators_url(&self.actor_id)?.into()),
+      also_known_as: None,
     };
     Ok(group)
   }
@@ -134,6 +142,7 @@ impl Object for ApubCommunity {
     expected_domain: &Url,
     context: &Data<Self::DataType>,
   ) -> LemmyResult<()> {
+    check_nested_fetch::<Self>(group.id.inner(), RemoteKind::Actor, context).await?;
     group.verify(expected_domain, context).await
   }
 
@@ -180,14 +189,27 @@ impl Object for ApubCommunity {
     let timestamp = group.updated.or(group.published).unwrap_or_else(naive_now);
     let community = Community::insert_apub(&mut context.pool(), timestamp, &form).await?;
     CommunityLanguage::update(&mut context.pool(), languages, community.id).await?;
+    let stored = ActorAlias::list_for_community(&mut context.pool(), community.id).await?;
+    let aliases = verified_actor_aliases(
+      group.also_known_as,
+      community.actor_id.inner(),
+      &stored,
+      context,
+    )
+    .await;
+    if let Some(aliases) = aliases {
+      ActorAlias::update_community(&mut context.pool(), aliases, community.id).await?;
+    }
 
     let community: ApubCommunity = community.into();
 
     // These collections are not necessary for Lemmy to work, so ignore errors.
     let community_ = community.clone();
     let context_ = context.reset_request_count();
-    spawn_try_task(async move {
+    let outbox_fetch = OutboxFetch::start(community.id).await;
+    spawn_try_task(keep_resolve_kinds(keep_fetch_permits(async move {
       group.outbox.dereference(&community_, &context_).await.ok();
+      outbox_fetch.finish().await;
       if let Some(followers) = group.followers {
         followers.dereference(&community_, &context_).await.ok();
       }
@@ -198,7 +220,7 @@ impl Object for ApubCommunity {
         moderators.dereference(&community_, &context_).await.ok();
       }
       Ok(())
-    });
+    })));
 
     Ok(community)
   }
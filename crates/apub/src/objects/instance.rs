@@ -2,6 +2,7 @@ use super::verify_is_remote_object;
 use crate::{
   activities::GetActorType,
   check_apub_id_valid_with_strictness,
+  fetcher::search::{check_nested_fetch, RemoteKind},
   local_site_data_cached,
   objects::read_from_string_or_source_opt,
   protocol::{
@@ -125,6 +126,7 @@ impl Object for ApubSite {
     expected_domain: &Url,
     data: &Data<Self::DataType>,
   ) -> LemmyResult<()> {
+    check_nested_fetch::<Self>(apub.id.inner(), RemoteKind::Actor, data).await?;
     check_apub_id_valid_with_strictness(apub.id.inner(), true, data).await?;
     verify_domains_match(expected_domain, apub.id.inner())?;
     verify_is_remote_object(&apub.id, data)?;
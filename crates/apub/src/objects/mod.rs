@@ -1,16 +1,19 @@
 use crate::protocol::Source;
 use activitypub_federation::{
   config::Data,
-  fetch::object_id::ObjectId,
-  protocol::values::MediaTypeMarkdownOrHtml,
+  fetch::{fetch_object_http, object_id::ObjectId},
+  protocol::{helpers::deserialize_skip_error, values::MediaTypeMarkdownOrHtml},
   traits::Object,
 };
 use anyhow::anyhow;
 use html2md::parse_html;
 use lemmy_api_common::context::LemmyContext;
+use lemmy_db_schema::newtypes::DbUrl;
 use lemmy_utils::error::LemmyResult;
 use serde::Deserialize;
 use std::fmt::Debug;
+use tracing::debug;
+use url::Url;
 
 pub mod comment;
 pub mod community;
@@ -46,6 +49,67 @@ pub(crate) fn read_from_string_or_source_opt(
     .map(|content| read_from_string_or_source(content, media_type, source))
 }
 
+/// Maximum number of aliases which are stored for a remote actor.
+const MAX_ACTOR_ALIASES: usize = 10;
+
+/// Other urls of an actor, as listed in its json.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlsoKnownAs {
+  #[serde(deserialize_with = "deserialize_skip_error", default)]
+  also_known_as: Option<Vec<Url>>,
+}
+
+/// Returns the `alsoKnownAs` urls of a remote actor which can be stored as its aliases, or `None`
+/// if they are the same as the `stored` ones. The actor's own id and urls of this instance are
+/// skipped, as they can't refer to the remote actor.
+///
+/// Any actor can claim the url of another one, so an alias on a different domain is only accepted
+/// if the actor at that url lists this one in its `alsoKnownAs` as well. Aliases which were
+/// accepted before aren't fetched again.
+pub(crate) async fn verified_actor_aliases(
+  also_known_as: Option<Vec<Url>>,
+  id: &Url,
+  stored: &[DbUrl],
+  context: &Data<LemmyContext>,
+) -> Option<Vec<DbUrl>> {
+  let mut claimed: Vec<Url> = vec![];
+  for url in also_known_as.unwrap_or_default() {
+    if &url == id || url.domain() == Some(context.domain()) || claimed.contains(&url) {
+      continue;
+    }
+    claimed.push(url);
+  }
+  claimed.truncate(MAX_ACTOR_ALIASES);
+
+  let mut aliases: Vec<DbUrl> = vec![];
+  for url in claimed {
+    let alias: DbUrl = url.clone().into();
+    if url.domain() == id.domain()
+      || stored.contains(&alias)
+      || lists_alias(&url, id, context).await
+    {
+      aliases.push(alias);
+    }
+  }
+  if aliases.len() == stored.len() && aliases.iter().all(|a| stored.contains(a)) {
+    None
+  } else {
+    Some(aliases)
+  }
+}
+
+/// Checks if the actor at `url` lists `id` in its `alsoKnownAs`.
+async fn lists_alias(url: &Url, id: &Url, context: &Data<LemmyContext>) -> bool {
+  match fetch_object_http::<_, AlsoKnownAs>(url, context).await {
+    Ok(res) => res.object.also_known_as.unwrap_or_default().contains(id),
+    Err(e) => {
+      debug!("Failed to verify alias {url} of {id}: {e}");
+      false
+    }
+  }
+}
+
 /// When for example a Post is made in a remote community, the community will send it back,
 /// wrapped in Announce. If we simply receive this like any other federated object, overwrite the
 /// existing, local Post. In particular, it will set the field local = false, so that the object
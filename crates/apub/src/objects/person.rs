@@ -2,8 +2,13 @@ use super::verify_is_remote_object;
 use crate::{
   activities::GetActorType,
   check_apub_id_valid_with_strictness,
+  fetcher::search::{check_nested_fetch, RemoteKind},
   local_site_data_cached,
-  objects::{instance::fetch_instance_actor_for_object, read_from_string_or_source_opt},
+  objects::{
+    instance::fetch_instance_actor_for_object,
+    read_from_string_or_source_opt,
+    verified_actor_aliases,
+  },
   protocol::{
     objects::{
       person::{Person, UserTypes},
@@ -33,6 +38,7 @@ use lemmy_db_schema::{
   sensitive::SensitiveString,
   source::{
     activity::ActorType,
+    actor_alias::ActorAlias,
     local_site::LocalSite,
     person::{Person as DbPerson, PersonInsertForm, PersonUpdateForm},
   },
@@ -123,6 +129,7 @@ impl Object for ApubPerson {
       public_key: self.public_key(),
       updated: self.updated,
       inbox: self.inbox_url.clone().into(),
+      also_known_as: None,
     };
     Ok(person)
   }
@@ -133,6 +140,7 @@ impl Object for ApubPerson {
     expected_domain: &Url,
     context: &Data<Self::DataType>,
   ) -> LemmyResult<()> {
+    check_nested_fetch::<Self>(person.id.inner(), RemoteKind::Actor, context).await?;
     let local_site_data = local_site_data_cached(&mut context.pool()).await?;
     let slur_regex = &local_site_opt_to_slur_regex(&local_site_data.local_site);
     check_slurs(&person.preferred_username, slur_regex)?;
@@ -162,6 +170,7 @@ impl Object for ApubPerson {
     // Some Mastodon users have `name: ""` (empty string), need to convert that to `None`
     // https://github.com/mastodon/mastodon/issues/25233
     let display_name = person.name.filter(|n| !n.is_empty());
+    let also_known_as = person.also_known_as;
 
     let person_form = PersonInsertForm {
       name: person.preferred_username,
@@ -186,6 +195,12 @@ impl Object for ApubPerson {
       instance_id,
     };
     let person = DbPerson::upsert(&mut context.pool(), &person_form).await?;
+    let stored = ActorAlias::list_for_person(&mut context.pool(), person.id).await?;
+    let aliases =
+      verified_actor_aliases(also_known_as, person.actor_id.inner(), &stored, context).await;
+    if let Some(aliases) = aliases {
+      ActorAlias::update_person(&mut context.pool(), aliases, person.id).await?;
+    }
 
     Ok(person.into())
   }
@@ -223,6 +238,7 @@ impl GetActorType for ApubPerson {
 pub(crate) mod tests {
   use super::*;
   use crate::{
+    api::test::create_user_with_images,
     objects::instance::{tests::parse_lemmy_instance, ApubSite},
     protocol::{objects::instance::Instance, tests::file_to_json_object},
   };
@@ -283,6 +299,73 @@ pub(crate) mod tests {
     Ok(())
   }
 
+  #[tokio::test]
+  #[serial]
+  async fn test_person_avatar_and_banner() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let avatar = Url::parse("https://example.com/pictrs/image/avatar.png")?;
+    let banner = Url::parse("https://example.com/pictrs/image/banner.png")?;
+    let user = create_user_with_images(
+      "avatar_user".to_string(),
+      None,
+      false,
+      Some(avatar.clone()),
+      Some(banner.clone()),
+      &context,
+    )
+    .await?;
+
+    let json = ApubPerson::from(user.person.clone())
+      .into_json(&context)
+      .await?;
+    assert_eq!(Some(avatar), json.icon.map(|i| i.url));
+    assert_eq!(Some(banner), json.image.map(|i| i.url));
+
+    DbPerson::delete(&mut context.pool(), user.person.id).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_parse_person_profile_fields() -> LemmyResult<()> {
+    let context = LemmyContext::init_test_context().await;
+    let (person, site) = parse_lemmy_person(&context).await?;
+    let url = Url::parse("https://enterprise.lemmy.ml/u/picard")?;
+
+    // all profile fields of the remote person are stored
+    assert_eq!(Some("Jean-Luc Picard".to_string()), person.display_name);
+    assert!(person.bio.is_some());
+    assert!(person.avatar.is_some());
+    assert!(person.banner.is_some());
+    assert_eq!(
+      Some("@picard:matrix.org".to_string()),
+      person.matrix_user_id
+    );
+    assert!(!person.bot_account);
+
+    // once they are removed on the remote instance, they are cleared locally as well
+    let mut json: Person = file_to_json_object("assets/lemmy/objects/person.json")?;
+    json.kind = UserTypes::Service;
+    json.name = None;
+    json.summary = None;
+    json.source = None;
+    json.icon = None;
+    json.image = None;
+    json.matrix_user_id = None;
+    ApubPerson::verify(&json, &url, &context).await?;
+    let updated = ApubPerson::from_json(json, &context).await?;
+    assert_eq!(person.id, updated.id);
+    assert_eq!(None, updated.display_name);
+    assert_eq!(None, updated.bio);
+    assert_eq!(None, updated.avatar);
+    assert_eq!(None, updated.banner);
+    assert_eq!(None, updated.matrix_user_id);
+    assert!(updated.bot_account);
+
+    cleanup((updated, site), &context).await?;
+    Ok(())
+  }
+
   async fn cleanup(data: (ApubPerson, ApubSite), context: &LemmyContext) -> LemmyResult<()> {
     DbPerson::delete(&mut context.pool(), data.0.id).await?;
     Site::delete(&mut context.pool(), data.1.id).await?;
@@ -1,6 +1,7 @@
 use crate::{
   activities::{verify_is_public, verify_person_in_community},
   check_apub_id_valid_with_strictness,
+  fetcher::search::{check_nested_fetch, RemoteKind},
   local_site_data_cached,
   objects::{read_from_string_or_source_opt, verify_is_remote_object},
   protocol::{
@@ -149,6 +150,9 @@ impl Object for ApubPost {
       audience: Some(community.actor_id.into()),
       in_reply_to: None,
       tag: vec![hashtag],
+      // The vote counts are only included when the object is served over http
+      likes: None,
+      dislikes: None,
     };
     Ok(page)
   }
@@ -159,6 +163,7 @@ impl Object for ApubPost {
     expected_domain: &Url,
     context: &Data<Self::DataType>,
   ) -> LemmyResult<()> {
+    check_nested_fetch::<Self>(page.id.inner(), RemoteKind::Post, context).await?;
     verify_domains_match(page.id.inner(), expected_domain)?;
     verify_is_remote_object(&page.id, context)?;
 
@@ -248,7 +253,13 @@ impl Object for ApubPost {
       .build();
 
     let timestamp = page.updated.or(page.published).unwrap_or_else(naive_now);
-    let post = Post::insert_apub(&mut context.pool(), timestamp, &form).await?;
+    let post = Post::insert_apub(
+      &mut context.pool(),
+      timestamp,
+      &form,
+      page.content.as_deref(),
+    )
+    .await?;
     let post_ = post.clone();
     let context_ = context.reset_request_count();
 
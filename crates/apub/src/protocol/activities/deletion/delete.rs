@@ -43,6 +43,10 @@ pub struct Delete {
   /// Nonstandard field, only valid if object refers to a Person. If present, all content from the
   /// user should be deleted along with the account
   pub(crate) remove_data: Option<bool>,
+  /// Nonstandard field, only valid if object refers to a Community. If true, the community was
+  /// purged on its instance and receivers should also purge their copy of it, instead of only
+  /// marking it as removed.
+  pub(crate) purge: Option<bool>,
 }
 
 #[async_trait::async_trait]
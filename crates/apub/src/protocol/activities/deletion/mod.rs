@@ -27,4 +27,17 @@ mod tests {
     test_parse_lemmy_item::<DeleteUser>("assets/lemmy/activities/deletion/delete_user.json")?;
     Ok(())
   }
+
+  #[test]
+  fn test_parse_lemmy_purge_community() -> LemmyResult<()> {
+    let purge =
+      test_parse_lemmy_item::<Delete>("assets/lemmy/activities/deletion/purge_community.json")?;
+    assert_eq!(Some(true), purge.purge);
+
+    // a normal removal doesn't include the flag
+    let remove =
+      test_parse_lemmy_item::<Delete>("assets/lemmy/activities/deletion/remove_note.json")?;
+    assert_eq!(None, remove.purge);
+    Ok(())
+  }
 }
@@ -4,11 +4,14 @@ use crate::{
   protocol::{activities::voting::vote::Vote, InCommunity},
 };
 use activitypub_federation::{config::Data, fetch::object_id::ObjectId, kinds::activity::UndoType};
+use chrono::{DateTime, Utc};
 use lemmy_api_common::context::LemmyContext;
 use lemmy_utils::error::LemmyResult;
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 use url::Url;
 
+#[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UndoVote {
@@ -18,6 +21,8 @@ pub struct UndoVote {
   pub(crate) kind: UndoType,
   pub(crate) id: Url,
   pub(crate) audience: Option<ObjectId<ApubCommunity>>,
+  /// Used to order votes and undos which arrive out of order. Not sent by older versions.
+  pub(crate) published: Option<DateTime<Utc>>,
 }
 
 #[async_trait::async_trait]
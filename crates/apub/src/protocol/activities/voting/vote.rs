@@ -5,12 +5,15 @@ use crate::{
   protocol::InCommunity,
 };
 use activitypub_federation::{config::Data, fetch::object_id::ObjectId};
+use chrono::{DateTime, Utc};
 use lemmy_api_common::context::LemmyContext;
 use lemmy_utils::error::{LemmyError, LemmyErrorType, LemmyResult};
 use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
 use strum_macros::Display;
 use url::Url;
 
+#[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Vote {
@@ -20,6 +23,8 @@ pub struct Vote {
   pub(crate) kind: VoteType,
   pub(crate) id: Url,
   pub(crate) audience: Option<ObjectId<ApubCommunity>>,
+  /// Used to order votes and undos which arrive out of order. Not sent by older versions.
+  pub(crate) published: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Debug, Display, Deserialize, Serialize, PartialEq, Eq)]
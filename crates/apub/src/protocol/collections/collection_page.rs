@@ -0,0 +1,62 @@
+use activitypub_federation::kinds::collection::OrderedCollectionPageType;
+use itertools::Itertools;
+use lemmy_api_common::site::ResolvedCollectionPage;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use url::Url;
+
+/// Maximum number of ids which are returned for a resolved collection page.
+pub(crate) const MAX_COLLECTION_PAGE_ITEMS: usize = 50;
+
+/// A single page of a remote collection like an outbox, when it is resolved by its id. The items
+/// are only parsed as far as needed to get their ids.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionPage {
+  pub(crate) id: Url,
+  #[serde(rename = "type")]
+  pub(crate) kind: OrderedCollectionPageType,
+  pub(crate) part_of: Option<Url>,
+  pub(crate) next: Option<Url>,
+  #[serde(default)]
+  pub(crate) ordered_items: Vec<CollectionItem>,
+}
+
+/// An item of a collection page, which can be an activity, an object or only its id.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum CollectionItem {
+  Activity { object: Box<CollectionItem> },
+  Object { id: Url },
+  Id(Url),
+}
+
+impl CollectionItem {
+  /// The id of the item, or for activities like the `Create` of an outbox the id of their object.
+  fn object_id(&self) -> &Url {
+    match self {
+      CollectionItem::Activity { object } => object.object_id(),
+      CollectionItem::Object { id } | CollectionItem::Id(id) => id,
+    }
+  }
+}
+
+impl From<CollectionPage> for ResolvedCollectionPage {
+  fn from(value: CollectionPage) -> Self {
+    let items = value
+      .ordered_items
+      .iter()
+      .map(CollectionItem::object_id)
+      .unique()
+      .take(MAX_COLLECTION_PAGE_ITEMS)
+      .map(|id| id.clone().into())
+      .collect();
+    ResolvedCollectionPage {
+      ap_id: value.id.into(),
+      part_of: value.part_of.map(Into::into),
+      next: value.next.map(Into::into),
+      items,
+    }
+  }
+}
@@ -1,13 +1,16 @@
+pub(crate) mod collection_page;
 pub(crate) mod empty_outbox;
 pub(crate) mod group_featured;
 pub(crate) mod group_followers;
 pub(crate) mod group_moderators;
 pub(crate) mod group_outbox;
+pub(crate) mod vote_collection;
 
 #[cfg(test)]
 mod tests {
   use crate::protocol::{
     collections::{
+      collection_page::CollectionPage,
       empty_outbox::EmptyOutbox,
       group_featured::GroupFeatured,
       group_followers::GroupFollowers,
@@ -16,6 +19,7 @@ mod tests {
     },
     tests::{test_json, test_parse_lemmy_item},
   };
+  use lemmy_api_common::site::ResolvedCollectionPage;
   use lemmy_utils::error::LemmyResult;
   use pretty_assertions::assert_eq;
 
@@ -34,6 +38,16 @@ mod tests {
   #[test]
   fn test_parse_mastodon_collections() -> LemmyResult<()> {
     test_json::<GroupFeatured>("assets/mastodon/collections/featured.json")?;
+    let page = test_json::<CollectionPage>("assets/mastodon/collections/outbox_page.json")?;
+    let page = ResolvedCollectionPage::from(page.inner().clone());
+    let items: Vec<_> = page.items.iter().map(|i| i.as_str()).collect();
+    assert_eq!(
+      vec![
+        "https://mastodon.madrid/users/felix/statuses/110143255372493836",
+        "https://lemmy.ml/post/1"
+      ],
+      items
+    );
     Ok(())
   }
 }
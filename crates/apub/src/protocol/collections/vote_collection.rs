@@ -0,0 +1,20 @@
+use activitypub_federation::kinds::collection::CollectionType;
+use serde::{Deserialize, Serialize};
+
+/// Number of likes or dislikes of a post or comment, embedded in the object. The votes
+/// themselves are not included.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VoteCollection {
+  pub(crate) r#type: CollectionType,
+  pub(crate) total_items: i64,
+}
+
+impl VoteCollection {
+  pub(crate) fn new(total_items: i64) -> Self {
+    VoteCollection {
+      r#type: CollectionType::Collection,
+      total_items,
+    }
+  }
+}
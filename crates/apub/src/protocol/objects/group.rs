@@ -66,6 +66,9 @@ pub struct Group {
   pub(crate) outbox: CollectionId<ApubCommunityOutbox>,
   pub(crate) endpoints: Option<Endpoints>,
   pub(crate) featured: Option<CollectionId<ApubCommunityFeatured>>,
+  /// Other urls of the actor, eg the one it had before moving
+  #[serde(deserialize_with = "deserialize_skip_error", default)]
+  pub(crate) also_known_as: Option<Vec<Url>>,
   #[serde(default)]
   pub(crate) language: Vec<LanguageTag>,
   pub(crate) published: Option<DateTime<Utc>>,
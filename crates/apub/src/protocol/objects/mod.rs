@@ -11,9 +11,11 @@ use url::Url;
 pub(crate) mod chat_message;
 pub(crate) mod group;
 pub(crate) mod instance;
+pub(crate) mod mod_action;
 pub(crate) mod note;
 pub(crate) mod page;
 pub(crate) mod person;
+pub(crate) mod redirect;
 pub(crate) mod tombstone;
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -0,0 +1,37 @@
+use activitypub_federation::protocol::helpers::deserialize_one;
+use lemmy_api_common::site::{ModActionType, ResolvedModAction};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use url::Url;
+
+/// A moderation activity like a ban or a report, when it is resolved by its id. Only the parts
+/// which are shown to moderators are parsed.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModAction {
+  pub(crate) id: Url,
+  #[serde(rename = "type")]
+  pub(crate) kind: ModActionType,
+  pub(crate) actor: Url,
+  #[serde(deserialize_with = "deserialize_one")]
+  pub(crate) object: [Url; 1],
+  pub(crate) target: Option<Url>,
+  /// Reports are addressed to their community with `audience` instead of `target`
+  pub(crate) audience: Option<Url>,
+  pub(crate) summary: Option<String>,
+}
+
+impl From<ModAction> for ResolvedModAction {
+  fn from(value: ModAction) -> Self {
+    let [object] = value.object;
+    ResolvedModAction {
+      ap_id: value.id.into(),
+      kind: value.kind,
+      actor_id: value.actor.into(),
+      object_id: object.into(),
+      target_id: value.target.or(value.audience).map(Into::into),
+      reason: value.summary,
+    }
+  }
+}
@@ -3,7 +3,12 @@ use crate::{
   fetcher::post_or_comment::PostOrComment,
   mentions::MentionOrValue,
   objects::{comment::ApubComment, community::ApubCommunity, person::ApubPerson, post::ApubPost},
-  protocol::{objects::LanguageTag, InCommunity, Source},
+  protocol::{
+    collections::vote_collection::VoteCollection,
+    objects::LanguageTag,
+    InCommunity,
+    Source,
+  },
 };
 use activitypub_federation::{
   config::Data,
@@ -51,6 +56,11 @@ pub struct Note {
   pub(crate) distinguished: Option<bool>,
   pub(crate) language: Option<LanguageTag>,
   pub(crate) audience: Option<ObjectId<ApubCommunity>>,
+  /// Vote counts on the origin instance, used to correct local counts which drifted
+  #[serde(deserialize_with = "deserialize_skip_error", default)]
+  pub(crate) likes: Option<VoteCollection>,
+  #[serde(deserialize_with = "deserialize_skip_error", default)]
+  pub(crate) dislikes: Option<VoteCollection>,
 }
 
 impl Note {
@@ -2,7 +2,13 @@ use crate::{
   activities::verify_community_matches,
   fetcher::user_or_community::{PersonOrGroupType, UserOrCommunity},
   objects::{community::ApubCommunity, person::ApubPerson, post::ApubPost},
-  protocol::{objects::LanguageTag, ImageObject, InCommunity, Source},
+  protocol::{
+    collections::vote_collection::VoteCollection,
+    objects::LanguageTag,
+    ImageObject,
+    InCommunity,
+    Source,
+  },
 };
 use activitypub_federation::{
   config::Data,
@@ -67,6 +73,11 @@ pub struct Page {
   pub(crate) audience: Option<ObjectId<ApubCommunity>>,
   #[serde(deserialize_with = "deserialize_skip_error", default)]
   pub(crate) tag: Vec<Hashtag>,
+  /// Vote counts on the origin instance, used to correct local counts which drifted
+  #[serde(deserialize_with = "deserialize_skip_error", default)]
+  pub(crate) likes: Option<VoteCollection>,
+  #[serde(deserialize_with = "deserialize_skip_error", default)]
+  pub(crate) dislikes: Option<VoteCollection>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -44,6 +44,9 @@ pub struct Person {
   pub(crate) image: Option<ImageObject>,
   pub(crate) matrix_user_id: Option<String>,
   pub(crate) endpoints: Option<Endpoints>,
+  /// Other urls of the actor, eg the one it had before moving
+  #[serde(deserialize_with = "deserialize_skip_error", default)]
+  pub(crate) also_known_as: Option<Vec<Url>>,
   pub(crate) published: Option<DateTime<Utc>>,
   pub(crate) updated: Option<DateTime<Utc>>,
 }
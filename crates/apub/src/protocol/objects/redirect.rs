@@ -0,0 +1,14 @@
+use activitypub_federation::kinds::activity::MoveType;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Pointer from the old url of an object to its new canonical url, sent by some platforms in place
+/// of an object which has moved.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Redirect {
+  pub(crate) id: Url,
+  #[serde(rename = "type")]
+  pub(crate) kind: MoveType,
+  pub(crate) target: Url,
+}
@@ -32,7 +32,8 @@ LANGUAGE plpgsql
 AS $a$
 BEGIN
     EXECUTE replace($b$
-        -- When a thing gets a vote, update its aggregates and its creator's aggregates
+        -- When a thing gets a vote, update its aggregates and its creator's aggregates. Votes with a
+        -- score of 0 are stored, but not counted.
         CALL r.create_triggers ('thing_like', $$
             BEGIN
                 WITH thing_diff AS ( UPDATE
@@ -41,7 +42,7 @@ BEGIN
                         score = a.score + diff.upvotes - diff.downvotes, upvotes = a.upvotes + diff.upvotes, downvotes = a.downvotes + diff.downvotes, controversy_rank = r.controversy_rank ((a.upvotes + diff.upvotes)::numeric, (a.downvotes + diff.downvotes)::numeric)
                     FROM (
                         SELECT
-                            (thing_like).thing_id, coalesce(sum(count_diff) FILTER (WHERE (thing_like).score = 1), 0) AS upvotes, coalesce(sum(count_diff) FILTER (WHERE (thing_like).score != 1), 0) AS downvotes FROM select_old_and_new_rows AS old_and_new_rows GROUP BY (thing_like).thing_id) AS diff
+                            (thing_like).thing_id, coalesce(sum(count_diff) FILTER (WHERE (thing_like).score = 1), 0) AS upvotes, coalesce(sum(count_diff) FILTER (WHERE (thing_like).score = -1), 0) AS downvotes FROM select_old_and_new_rows AS old_and_new_rows GROUP BY (thing_like).thing_id) AS diff
             WHERE
                 a.thing_id = diff.thing_id
                     AND (diff.upvotes, diff.downvotes) != (0, 0)
@@ -69,6 +70,58 @@ CALL r.post_or_comment ('post');
 
 CALL r.post_or_comment ('comment');
 
+-- Corrections of vote counts are counted like votes, so that the counts are always the stored
+-- votes plus all corrections
+CALL r.create_triggers ('vote_correction', $$
+BEGIN
+    WITH post_diff AS ( UPDATE
+            post_aggregates AS a
+        SET
+            score = a.score + diff.upvotes - diff.downvotes, upvotes = a.upvotes + diff.upvotes, downvotes = a.downvotes + diff.downvotes, controversy_rank = r.controversy_rank ((a.upvotes + diff.upvotes)::numeric, (a.downvotes + diff.downvotes)::numeric)
+        FROM (
+            SELECT
+                (vote_correction).post_id, coalesce(sum(count_diff * (vote_correction).upvotes), 0) AS upvotes, coalesce(sum(count_diff * (vote_correction).downvotes), 0) AS downvotes FROM select_old_and_new_rows AS old_and_new_rows
+            WHERE (vote_correction).post_id IS NOT NULL GROUP BY (vote_correction).post_id) AS diff
+    WHERE
+        a.post_id = diff.post_id
+            AND (diff.upvotes, diff.downvotes) != (0, 0)
+        RETURNING
+            a.creator_id, diff.upvotes - diff.downvotes AS score)
+    UPDATE
+        person_aggregates AS a
+    SET
+        post_score = a.post_score + diff.score FROM (
+            SELECT
+                creator_id, sum(score) AS score FROM post_diff GROUP BY creator_id) AS diff
+        WHERE
+            a.person_id = diff.creator_id
+            AND diff.score != 0;
+    WITH comment_diff AS ( UPDATE
+            comment_aggregates AS a
+        SET
+            score = a.score + diff.upvotes - diff.downvotes, upvotes = a.upvotes + diff.upvotes, downvotes = a.downvotes + diff.downvotes, controversy_rank = r.controversy_rank ((a.upvotes + diff.upvotes)::numeric, (a.downvotes + diff.downvotes)::numeric)
+        FROM (
+            SELECT
+                (vote_correction).comment_id, coalesce(sum(count_diff * (vote_correction).upvotes), 0) AS upvotes, coalesce(sum(count_diff * (vote_correction).downvotes), 0) AS downvotes FROM select_old_and_new_rows AS old_and_new_rows
+            WHERE (vote_correction).comment_id IS NOT NULL GROUP BY (vote_correction).comment_id) AS diff
+    WHERE
+        a.comment_id = diff.comment_id
+            AND (diff.upvotes, diff.downvotes) != (0, 0)
+        RETURNING
+            r.creator_id_from_comment_aggregates (a.*) AS creator_id, diff.upvotes - diff.downvotes AS score)
+    UPDATE
+        person_aggregates AS a
+    SET
+        comment_score = a.comment_score + diff.score FROM (
+            SELECT
+                creator_id, sum(score) AS score FROM comment_diff GROUP BY creator_id) AS diff
+        WHERE
+            a.person_id = diff.creator_id
+            AND diff.score != 0;
+    RETURN NULL;
+END;
+$$);
+
 -- Create triggers that update counts in parent aggregates
 CREATE FUNCTION r.parent_comment_ids (path ltree)
     RETURNS SETOF int
@@ -3,6 +3,7 @@ use crate::{
   diesel::OptionalExtension,
   newtypes::CommentId,
   schema::comment_aggregates,
+  source::vote_correction::{VoteCorrection, VoteCorrectionForm},
   utils::{functions::hot_rank, get_conn, DbPool},
 };
 use diesel::{result::Error, ExpressionMethods, QueryDsl};
@@ -32,6 +33,44 @@ impl CommentAggregates {
       .get_result::<Self>(conn)
       .await
   }
+
+  /// Sets the vote counts, for example to the counts of the origin instance if the local ones
+  /// drifted. The difference is stored as [VoteCorrection], which the triggers apply to the counts
+  /// and to the comment score of the creator. The hot rank is updated separately.
+  pub async fn update_votes(
+    pool: &mut DbPool<'_>,
+    comment_id: CommentId,
+    upvotes: i64,
+    downvotes: i64,
+  ) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          let (old_upvotes, old_downvotes) = comment_aggregates::table
+            .find(comment_id)
+            .select((comment_aggregates::upvotes, comment_aggregates::downvotes))
+            .for_update()
+            .first::<(i64, i64)>(conn)
+            .await?;
+          if (upvotes, downvotes) != (old_upvotes, old_downvotes) {
+            let form = VoteCorrectionForm {
+              post_id: None,
+              comment_id: Some(comment_id),
+              upvotes: upvotes - old_upvotes,
+              downvotes: downvotes - old_downvotes,
+            };
+            VoteCorrection::create(conn, &form).await?;
+          }
+          comment_aggregates::table
+            .find(comment_id)
+            .first::<Self>(conn)
+            .await
+        }) as _
+      })
+      .await
+  }
 }
 
 #[cfg(test)]
@@ -121,6 +160,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: inserted_person.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     CommentLike::like(pool, &comment_like).await.unwrap();
@@ -140,6 +180,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: another_inserted_person.id,
       score: -1,
+      activity_ap_id: None,
     };
 
     CommentLike::like(pool, &comment_dislike).await.unwrap();
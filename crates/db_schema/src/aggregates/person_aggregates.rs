@@ -86,6 +86,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: inserted_person.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     let _inserted_post_like = PostLike::like(pool, &post_like).await.unwrap();
@@ -103,6 +104,7 @@ mod tests {
       person_id: inserted_person.id,
       post_id: inserted_post.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     let _inserted_comment_like = CommentLike::like(pool, &comment_like).await.unwrap();
@@ -123,6 +125,7 @@ mod tests {
       person_id: another_inserted_person.id,
       post_id: inserted_post.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     let _inserted_child_comment_like = CommentLike::like(pool, &child_comment_like).await.unwrap();
@@ -3,6 +3,7 @@ use crate::{
   diesel::OptionalExtension,
   newtypes::PostId,
   schema::{community_aggregates, post, post_aggregates},
+  source::vote_correction::{VoteCorrection, VoteCorrectionForm},
   utils::{
     functions::{hot_rank, scaled_rank},
     get_conn,
@@ -51,6 +52,44 @@ impl PostAggregates {
       .get_result::<Self>(conn)
       .await
   }
+
+  /// Sets the vote counts, for example to the counts of the origin instance if the local ones
+  /// drifted. The difference is stored as [VoteCorrection], which the triggers apply to the counts
+  /// and to the post score of the creator. The hot ranks are updated separately.
+  pub async fn update_votes(
+    pool: &mut DbPool<'_>,
+    post_id: PostId,
+    upvotes: i64,
+    downvotes: i64,
+  ) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          let (old_upvotes, old_downvotes) = post_aggregates::table
+            .find(post_id)
+            .select((post_aggregates::upvotes, post_aggregates::downvotes))
+            .for_update()
+            .first::<(i64, i64)>(conn)
+            .await?;
+          if (upvotes, downvotes) != (old_upvotes, old_downvotes) {
+            let form = VoteCorrectionForm {
+              post_id: Some(post_id),
+              comment_id: None,
+              upvotes: upvotes - old_upvotes,
+              downvotes: downvotes - old_downvotes,
+            };
+            VoteCorrection::create(conn, &form).await?;
+          }
+          post_aggregates::table
+            .find(post_id)
+            .first::<Self>(conn)
+            .await
+        }) as _
+      })
+      .await
+  }
 }
 
 #[cfg(test)]
@@ -59,7 +98,7 @@ impl PostAggregates {
 mod tests {
 
   use crate::{
-    aggregates::post_aggregates::PostAggregates,
+    aggregates::{person_aggregates::PersonAggregates, post_aggregates::PostAggregates},
     source::{
       comment::{Comment, CommentInsertForm, CommentUpdateForm},
       community::{Community, CommunityInsertForm},
@@ -139,6 +178,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: inserted_person.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     PostLike::like(pool, &post_like).await.unwrap();
@@ -158,6 +198,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: another_inserted_person.id,
       score: -1,
+      activity_ap_id: None,
     };
 
     PostLike::like(pool, &post_dislike).await.unwrap();
@@ -338,4 +379,122 @@ mod tests {
       .unwrap();
     Instance::delete(pool, inserted_instance.id).await.unwrap();
   }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_uncounted_vote() {
+    let pool = &build_db_pool_for_tests().await;
+    let pool = &mut pool.into();
+
+    let inserted_instance = Instance::read_or_create(pool, "my_domain.tld".to_string())
+      .await
+      .unwrap();
+
+    let new_person = PersonInsertForm::builder()
+      .name("thommy_uncounted_agg".into())
+      .public_key("pubkey".to_string())
+      .instance_id(inserted_instance.id)
+      .build();
+
+    let inserted_person = Person::create(pool, &new_person).await.unwrap();
+
+    let young_person = PersonInsertForm::builder()
+      .name("young_uncounted_agg".into())
+      .public_key("pubkey".to_string())
+      .instance_id(inserted_instance.id)
+      .build();
+
+    let inserted_young_person = Person::create(pool, &young_person).await.unwrap();
+
+    let new_community = CommunityInsertForm::builder()
+      .name("TIL_uncounted_agg".into())
+      .title("nada".to_owned())
+      .public_key("pubkey".to_string())
+      .instance_id(inserted_instance.id)
+      .build();
+
+    let inserted_community = Community::create(pool, &new_community).await.unwrap();
+
+    let new_post = PostInsertForm::builder()
+      .name("A test post".into())
+      .creator_id(inserted_person.id)
+      .community_id(inserted_community.id)
+      .build();
+
+    let inserted_post = Post::create(pool, &new_post).await.unwrap();
+
+    let votes = |post_aggregates: PostAggregates| {
+      (
+        post_aggregates.score,
+        post_aggregates.upvotes,
+        post_aggregates.downvotes,
+      )
+    };
+
+    // A vote with a score of 0, like the ones of young accounts, is stored without being counted
+    let mut young_like = PostLikeForm {
+      post_id: inserted_post.id,
+      person_id: inserted_young_person.id,
+      score: 0,
+      activity_ap_id: None,
+    };
+    PostLike::like(pool, &young_like).await.unwrap();
+
+    let after_uncounted_vote = PostAggregates::read(pool, inserted_post.id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!((0, 0, 0), votes(after_uncounted_vote));
+    let creator_aggregates = PersonAggregates::read(pool, inserted_person.id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(0, creator_aggregates.post_score);
+
+    // Changing it to a counted vote adds it
+    young_like.score = 1;
+    PostLike::like(pool, &young_like).await.unwrap();
+
+    let after_counted_vote = PostAggregates::read(pool, inserted_post.id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!((1, 1, 0), votes(after_counted_vote));
+
+    // And changing it back to 0 takes it away again
+    young_like.score = 0;
+    PostLike::like(pool, &young_like).await.unwrap();
+
+    let after_uncounted_again = PostAggregates::read(pool, inserted_post.id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!((0, 0, 0), votes(after_uncounted_again));
+
+    // Removing an uncounted vote doesn't change anything either
+    PostLike::remove(pool, inserted_young_person.id, inserted_post.id)
+      .await
+      .unwrap();
+
+    let after_uncounted_remove = PostAggregates::read(pool, inserted_post.id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!((0, 0, 0), votes(after_uncounted_remove));
+    let creator_aggregates = PersonAggregates::read(pool, inserted_person.id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(0, creator_aggregates.post_score);
+
+    Post::delete(pool, inserted_post.id).await.unwrap();
+    Person::delete(pool, inserted_young_person.id)
+      .await
+      .unwrap();
+    Person::delete(pool, inserted_person.id).await.unwrap();
+    Community::delete(pool, inserted_community.id)
+      .await
+      .unwrap();
+    Instance::delete(pool, inserted_instance.id).await.unwrap();
+  }
 }
@@ -5,8 +5,9 @@ use crate::{
   utils::{get_conn, DbPool},
 };
 use diesel::{
-  dsl::insert_into,
+  dsl::{exists, insert_into},
   result::{DatabaseErrorKind, Error, Error::DatabaseError},
+  select,
   ExpressionMethods,
   QueryDsl,
 };
@@ -43,15 +44,7 @@ impl SentActivity {
 
 impl ReceivedActivity {
   pub async fn create(pool: &mut DbPool<'_>, ap_id_: &DbUrl) -> Result<(), Error> {
-    use crate::schema::received_activity::dsl::{ap_id, received_activity};
-    let conn = &mut get_conn(pool).await?;
-    let rows_affected = insert_into(received_activity)
-      .values(ap_id.eq(ap_id_))
-      .on_conflict_do_nothing()
-      .execute(conn)
-      .await
-      .optional()?;
-    if rows_affected == Some(1) {
+    if Self::insert_if_new(pool, ap_id_).await? {
       // new activity inserted successfully
       Ok(())
     } else {
@@ -62,6 +55,38 @@ impl ReceivedActivity {
       ))
     }
   }
+
+  /// Stores the activity id, and returns false if it was received before. The insert and the check
+  /// are a single statement, so concurrent deliveries of the same activity can't both succeed.
+  pub async fn insert_if_new(pool: &mut DbPool<'_>, ap_id_: &DbUrl) -> Result<bool, Error> {
+    use crate::schema::received_activity::dsl::{ap_id, received_activity};
+    let conn = &mut get_conn(pool).await?;
+    let rows_affected = insert_into(received_activity)
+      .values(ap_id.eq(ap_id_))
+      .on_conflict_do_nothing()
+      .execute(conn)
+      .await
+      .optional()?;
+    Ok(rows_affected == Some(1))
+  }
+
+  /// Returns true if the activity was received before.
+  pub async fn exists(pool: &mut DbPool<'_>, ap_id_: &DbUrl) -> Result<bool, Error> {
+    use crate::schema::received_activity::dsl::{ap_id, received_activity};
+    let conn = &mut get_conn(pool).await?;
+    select(exists(received_activity.filter(ap_id.eq(ap_id_))))
+      .get_result(conn)
+      .await
+  }
+
+  /// Forgets that the activity was received, so that it can be processed again.
+  pub async fn delete(pool: &mut DbPool<'_>, ap_id_: &DbUrl) -> Result<usize, Error> {
+    use crate::schema::received_activity::dsl::{ap_id, received_activity};
+    let conn = &mut get_conn(pool).await?;
+    diesel::delete(received_activity.filter(ap_id.eq(ap_id_)))
+      .execute(conn)
+      .await
+  }
 }
 
 #[cfg(test)]
@@ -0,0 +1,106 @@
+use crate::{
+  diesel::{OptionalExtension, PgExpressionMethods},
+  newtypes::{CommunityId, DbUrl, PersonId},
+  schema::actor_alias,
+  source::actor_alias::ActorAlias,
+  utils::{get_conn, DbPool},
+};
+use diesel::{delete, insert_into, result::Error, upsert::excluded, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+
+impl ActorAlias {
+  pub async fn read(pool: &mut DbPool<'_>, alias: &DbUrl) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    actor_alias::table
+      .find(alias)
+      .first::<Self>(conn)
+      .await
+      .optional()
+  }
+
+  /// Returns the aliases of the person.
+  pub async fn list_for_person(
+    pool: &mut DbPool<'_>,
+    person_id: PersonId,
+  ) -> Result<Vec<DbUrl>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    actor_alias::table
+      .filter(actor_alias::person_id.eq(person_id))
+      .select(actor_alias::alias)
+      .load(conn)
+      .await
+  }
+
+  /// Returns the aliases of the community.
+  pub async fn list_for_community(
+    pool: &mut DbPool<'_>,
+    community_id: CommunityId,
+  ) -> Result<Vec<DbUrl>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    actor_alias::table
+      .filter(actor_alias::community_id.eq(community_id))
+      .select(actor_alias::alias)
+      .load(conn)
+      .await
+  }
+
+  /// Replaces the aliases of the person.
+  pub async fn update_person(
+    pool: &mut DbPool<'_>,
+    aliases: Vec<DbUrl>,
+    person_id: PersonId,
+  ) -> Result<(), Error> {
+    Self::update(pool, aliases, Some(person_id), None).await
+  }
+
+  /// Replaces the aliases of the community.
+  pub async fn update_community(
+    pool: &mut DbPool<'_>,
+    aliases: Vec<DbUrl>,
+    community_id: CommunityId,
+  ) -> Result<(), Error> {
+    Self::update(pool, aliases, None, Some(community_id)).await
+  }
+
+  /// Aliases which belonged to another actor are moved to the given one. The aliases need to be
+  /// verified by the caller, so that an actor can't take over the url of another one.
+  async fn update(
+    pool: &mut DbPool<'_>,
+    aliases: Vec<DbUrl>,
+    person_id: Option<PersonId>,
+    community_id: Option<CommunityId>,
+  ) -> Result<(), Error> {
+    let conn = &mut get_conn(pool).await?;
+    let forms = aliases
+      .into_iter()
+      .map(|alias| ActorAlias {
+        alias,
+        person_id,
+        community_id,
+      })
+      .collect::<Vec<_>>();
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          delete(actor_alias::table)
+            .filter(actor_alias::person_id.is_not_distinct_from(person_id))
+            .filter(actor_alias::community_id.is_not_distinct_from(community_id))
+            .execute(conn)
+            .await?;
+          insert_into(actor_alias::table)
+            .values(forms)
+            .on_conflict(actor_alias::alias)
+            .do_update()
+            .set((
+              actor_alias::person_id.eq(excluded(actor_alias::person_id)),
+              actor_alias::community_id.eq(excluded(actor_alias::community_id)),
+            ))
+            .execute(conn)
+            .await?;
+          Ok(())
+        }) as _
+      })
+      .await
+  }
+}
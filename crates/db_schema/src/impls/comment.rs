@@ -1,21 +1,26 @@
 use crate::{
+  aggregates::structs::CommentAggregates,
   diesel::{DecoratableTarget, OptionalExtension},
-  newtypes::{CommentId, DbUrl, PersonId},
-  schema::comment,
-  source::comment::{
-    Comment,
-    CommentInsertForm,
-    CommentLike,
-    CommentLikeForm,
-    CommentSaved,
-    CommentSavedForm,
-    CommentUpdateForm,
+  newtypes::{CommentId, CommunityId, DbUrl, PersonId},
+  schema::{admin_purge_comment, comment, comment_aggregates, comment_edit, post},
+  source::{
+    comment::{
+      Comment,
+      CommentInsertForm,
+      CommentLike,
+      CommentLikeForm,
+      CommentSaved,
+      CommentSavedForm,
+      CommentUpdateForm,
+    },
+    content_hash::ContentHash,
+    moderator::AdminPurgeCommentForm,
   },
   traits::{Crud, Likeable, Saveable},
   utils::{functions::coalesce, get_conn, naive_now, DbPool, DELETED_REPLACEMENT_TEXT},
 };
 use chrono::{DateTime, Utc};
-use diesel::{dsl::insert_into, result::Error, ExpressionMethods, QueryDsl};
+use diesel::{dsl::insert_into, result::Error, ExpressionMethods, QueryDsl, SelectableHelper};
 use diesel_async::RunQueryDsl;
 use diesel_ltree::Ltree;
 use url::Url;
@@ -26,14 +31,29 @@ impl Comment {
     for_creator_id: PersonId,
   ) -> Result<Vec<Self>, Error> {
     let conn = &mut get_conn(pool).await?;
-
-    diesel::update(comment::table.filter(comment::creator_id.eq(for_creator_id)))
-      .set((
-        comment::content.eq(DELETED_REPLACEMENT_TEXT),
-        comment::deleted.eq(true),
-        comment::updated.eq(naive_now()),
-      ))
-      .get_results::<Self>(conn)
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          // Previous versions would still contain the deleted content
+          let creator_comments = comment::table
+            .filter(comment::creator_id.eq(for_creator_id))
+            .select(comment::id);
+          diesel::delete(
+            comment_edit::table.filter(comment_edit::comment_id.eq_any(creator_comments)),
+          )
+          .execute(conn)
+          .await?;
+          diesel::update(comment::table.filter(comment::creator_id.eq(for_creator_id)))
+            .set((
+              comment::content.eq(DELETED_REPLACEMENT_TEXT),
+              comment::deleted.eq(true),
+              comment::updated.eq(naive_now()),
+            ))
+            .get_results::<Self>(conn)
+            .await
+        }) as _
+      })
       .await
   }
 
@@ -57,33 +77,47 @@ impl Comment {
     comment_form: &CommentInsertForm,
     parent_path: Option<&Ltree>,
   ) -> Result<Comment, Error> {
-    Self::insert_apub(pool, None, comment_form, parent_path).await
+    Self::insert_apub(pool, None, comment_form, parent_path, None).await
   }
 
+  /// Stores the comment. For received comments, the hash of their `content` as received is stored
+  /// with it.
   pub async fn insert_apub(
     pool: &mut DbPool<'_>,
     timestamp: Option<DateTime<Utc>>,
     comment_form: &CommentInsertForm,
     parent_path: Option<&Ltree>,
+    content: Option<&str>,
   ) -> Result<Comment, Error> {
     let conn = &mut get_conn(pool).await?;
     let comment_form = (comment_form, parent_path.map(|p| comment::path.eq(p)));
 
-    if let Some(timestamp) = timestamp {
-      insert_into(comment::table)
-        .values(comment_form)
-        .on_conflict(comment::ap_id)
-        .filter_target(coalesce(comment::updated, comment::published).lt(timestamp))
-        .do_update()
-        .set(comment_form)
-        .get_result::<Self>(conn)
-        .await
-    } else {
-      insert_into(comment::table)
-        .values(comment_form)
-        .get_result::<Self>(conn)
-        .await
-    }
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          let comment = if let Some(timestamp) = timestamp {
+            insert_into(comment::table)
+              .values(comment_form)
+              .on_conflict(comment::ap_id)
+              .filter_target(coalesce(comment::updated, comment::published).lt(timestamp))
+              .do_update()
+              .set(comment_form)
+              .get_result::<Self>(conn)
+              .await?
+          } else {
+            insert_into(comment::table)
+              .values(comment_form)
+              .get_result::<Self>(conn)
+              .await?
+          };
+          if let Some(content) = content {
+            ContentHash::update_comment(conn, content, comment.id).await?;
+          }
+          Ok(comment)
+        }) as _
+      })
+      .await
   }
 
   pub async fn read_from_apub_id(
@@ -99,6 +133,95 @@ impl Comment {
       .optional()
   }
 
+  /// Reads the comment together with its vote counts, in a single query.
+  pub async fn read_with_counts(
+    pool: &mut DbPool<'_>,
+    comment_id: CommentId,
+  ) -> Result<Option<(Self, CommentAggregates)>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    comment::table
+      .inner_join(comment_aggregates::table)
+      .filter(comment::id.eq(comment_id))
+      .select((comment::all_columns, CommentAggregates::as_select()))
+      .first(conn)
+      .await
+      .optional()
+  }
+
+  /// Ids and ap_ids of the newest remote comments which were published since the given time, and
+  /// which aren't deleted or removed.
+  pub async fn list_recent_remote(
+    pool: &mut DbPool<'_>,
+    since: DateTime<Utc>,
+    limit: i64,
+  ) -> Result<Vec<(CommentId, DbUrl)>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    comment::table
+      .filter(comment::local.eq(false))
+      .filter(comment::deleted.eq(false))
+      .filter(comment::removed.eq(false))
+      .filter(comment::published.ge(since))
+      .order_by(comment::published.desc())
+      .limit(limit)
+      .select((comment::id, comment::ap_id))
+      .load::<(CommentId, DbUrl)>(conn)
+      .await
+  }
+
+  /// Comments in posts of the community which were published within the given time range,
+  /// including its bounds. The posts themselves can be older.
+  pub async fn list_for_community_in_range(
+    pool: &mut DbPool<'_>,
+    community_id: CommunityId,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+  ) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    comment::table
+      .inner_join(post::table)
+      .filter(post::community_id.eq(community_id))
+      .filter(comment::published.between(start, end))
+      .order_by(comment::published.asc())
+      .select(comment::all_columns)
+      .load::<Self>(conn)
+      .await
+  }
+
+  /// Deletes the comments and logs each of them as `AdminPurgeComment`. This runs in a single
+  /// transaction, so that a failure can't leave purged comments without a modlog entry.
+  pub async fn purge_many(
+    pool: &mut DbPool<'_>,
+    comments: &[Self],
+    admin_person_id: PersonId,
+    reason: Option<String>,
+  ) -> Result<(), Error> {
+    let conn = &mut get_conn(pool).await?;
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          let comment_ids = comments.iter().map(|c| c.id).collect::<Vec<_>>();
+          diesel::delete(comment::table.filter(comment::id.eq_any(comment_ids)))
+            .execute(conn)
+            .await?;
+          let forms = comments
+            .iter()
+            .map(|c| AdminPurgeCommentForm {
+              admin_person_id,
+              post_id: c.post_id,
+              reason: reason.clone(),
+            })
+            .collect::<Vec<_>>();
+          insert_into(admin_purge_comment::table)
+            .values(forms)
+            .execute(conn)
+            .await?;
+          Ok(())
+        }) as _
+      })
+      .await
+  }
+
   pub fn parent_comment_id(&self) -> Option<CommentId> {
     let mut ltree_split: Vec<&str> = self.path.0.split('.').collect();
     ltree_split.remove(0); // The first is always 0
@@ -135,6 +258,39 @@ impl Crud for Comment {
   }
 }
 
+impl CommentLike {
+  /// Removes the existing votes for the given person and comment pairs, then inserts the new votes.
+  /// Everything is written in a single transaction, so this is more efficient than separate calls
+  /// to [Likeable::remove] and [Likeable::like] for many votes.
+  pub async fn replace_many(
+    pool: &mut DbPool<'_>,
+    remove: &[(PersonId, CommentId)],
+    likes: &[CommentLikeForm],
+  ) -> Result<(), Error> {
+    use crate::schema::comment_like::dsl::comment_like;
+    let conn = &mut get_conn(pool).await?;
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          for &(person_id, comment_id) in remove {
+            diesel::delete(comment_like.find((person_id, comment_id)))
+              .execute(conn)
+              .await?;
+          }
+          if !likes.is_empty() {
+            insert_into(comment_like)
+              .values(likes)
+              .execute(conn)
+              .await?;
+          }
+          Ok(())
+        }) as _
+      })
+      .await
+  }
+}
+
 #[async_trait]
 impl Likeable for CommentLike {
   type Form = CommentLikeForm;
@@ -161,6 +317,19 @@ impl Likeable for CommentLike {
       .execute(conn)
       .await
   }
+  async fn read(
+    pool: &mut DbPool<'_>,
+    person_id: PersonId,
+    comment_id: CommentId,
+  ) -> Result<Option<Self>, Error> {
+    use crate::schema::comment_like::dsl::comment_like;
+    let conn = &mut get_conn(pool).await?;
+    comment_like
+      .find((person_id, comment_id))
+      .first::<Self>(conn)
+      .await
+      .optional()
+  }
 }
 
 #[async_trait]
@@ -299,6 +468,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: inserted_person.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     let inserted_comment_like = CommentLike::like(pool, &comment_like_form).await.unwrap();
@@ -309,6 +479,7 @@ mod tests {
       person_id: inserted_person.id,
       published: inserted_comment_like.published,
       score: 1,
+      activity_ap_id: None,
     };
 
     // Comment Saved
@@ -0,0 +1,39 @@
+use crate::{
+  newtypes::CommentId,
+  schema::comment_edit,
+  source::comment_edit::{CommentEdit, CommentEditForm},
+  utils::{get_conn, DbPool},
+};
+use diesel::{insert_into, result::Error, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+
+impl CommentEdit {
+  pub async fn create(pool: &mut DbPool<'_>, form: &CommentEditForm) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    insert_into(comment_edit::table)
+      .values(form)
+      .get_result::<Self>(conn)
+      .await
+  }
+
+  /// Previous versions of the comment, oldest first.
+  pub async fn list(pool: &mut DbPool<'_>, comment_id: CommentId) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    comment_edit::table
+      .filter(comment_edit::comment_id.eq(comment_id))
+      .order_by((comment_edit::published, comment_edit::id))
+      .load::<Self>(conn)
+      .await
+  }
+
+  /// Removes all previous versions of the comment, so that deleted content isn't kept.
+  pub async fn delete_for_comment(
+    pool: &mut DbPool<'_>,
+    comment_id: CommentId,
+  ) -> Result<usize, Error> {
+    let conn = &mut get_conn(pool).await?;
+    diesel::delete(comment_edit::table.filter(comment_edit::comment_id.eq(comment_id)))
+      .execute(conn)
+      .await
+  }
+}
@@ -1,6 +1,6 @@
 use crate::{
   diesel::{DecoratableTarget, OptionalExtension},
-  newtypes::{CommunityId, DbUrl, PersonId},
+  newtypes::{CommunityId, DbUrl, InstanceId, PersonId},
   schema::{community, community_follower, instance},
   source::{
     actor_language::CommunityLanguage,
@@ -13,13 +13,18 @@ use crate::{
       CommunityModeratorForm,
       CommunityPersonBan,
       CommunityPersonBanForm,
+      CommunityPurgeOptions,
       CommunityUpdateForm,
     },
+    community_purge_progress::CommunityPurgeProgress,
+    moderator::{AdminPurgeCommunity, AdminPurgeCommunityForm},
     post::Post,
+    report_archive::ReportArchive,
   },
   traits::{ApubActor, Bannable, Crud, Followable, Joinable},
   utils::{
-    functions::{coalesce, lower},
+    functions::{coalesce, lower, similarity},
+    fuzzy_search,
     get_conn,
     DbPool,
   },
@@ -38,6 +43,7 @@ use diesel::{
   BoolExpressionMethods,
   ExpressionMethods,
   NullableExpressionMethods,
+  PgTextExpressionMethods,
   QueryDsl,
   Queryable,
 };
@@ -141,6 +147,46 @@ impl Community {
     Ok(community_)
   }
 
+  /// Lists all known communities with the given name, both local and remote. Local communities
+  /// come first.
+  pub async fn list_from_name(
+    pool: &mut DbPool<'_>,
+    community_name: &str,
+  ) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    community::table
+      .filter(lower(community::name).eq(community_name.to_lowercase()))
+      .filter(community::deleted.eq(false))
+      .filter(community::removed.eq(false))
+      .order_by((community::local.desc(), community::id))
+      .load::<Self>(conn)
+      .await
+  }
+
+  /// Returns the known community whose name is most similar to the given partial name, along
+  /// with the similarity. Uses the trigram index, ties are broken by preferring local and older
+  /// communities.
+  pub async fn find_similar_name(
+    pool: &mut DbPool<'_>,
+    partial_name: &str,
+  ) -> Result<Option<(Self, f32)>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let name_similarity = similarity(community::name, partial_name);
+    community::table
+      .filter(community::name.ilike(fuzzy_search(partial_name)))
+      .filter(community::deleted.eq(false))
+      .filter(community::removed.eq(false))
+      .select((community::all_columns, name_similarity))
+      .order_by((
+        name_similarity.desc(),
+        community::local.desc(),
+        community::id,
+      ))
+      .first::<(Self, f32)>(conn)
+      .await
+      .optional()
+  }
+
   /// Get the community which has a given moderators or featured url, also return the collection
   /// type
   pub async fn get_by_collection_url(
@@ -193,6 +239,84 @@ impl Community {
       .await?;
     Ok(())
   }
+
+  /// Lists all communities of the given instance, including deleted and removed ones.
+  pub async fn list_for_instance(
+    pool: &mut DbPool<'_>,
+    for_instance_id: InstanceId,
+  ) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    community::table
+      .filter(community::instance_id.eq(for_instance_id))
+      .order_by(community::id)
+      .load::<Self>(conn)
+      .await
+  }
+
+  /// Deletes the community and logs it as [AdminPurgeCommunity]. This runs in a single
+  /// transaction, so that a failure can't leave a purged community without a modlog entry.
+  pub async fn purge(
+    pool: &mut DbPool<'_>,
+    community_id: CommunityId,
+    admin_person_id: PersonId,
+    reason: Option<String>,
+    options: CommunityPurgeOptions,
+  ) -> Result<(), Error> {
+    let conn = &mut get_conn(pool).await?;
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          let snapshot = if options.store_snapshot {
+            Some(Post::purge_snapshot_for_community(&mut conn.into(), community_id).await?)
+          } else {
+            None
+          };
+          // Reports are deleted together with the reported content, so they have to be copied
+          // first
+          if options.archive_reports {
+            ReportArchive::archive_for_community(&mut conn.into(), community_id).await?;
+          }
+          let community = Self::read(&mut conn.into(), community_id).await?;
+          Self::delete(&mut conn.into(), community_id).await?;
+          let form = AdminPurgeCommunityForm {
+            admin_person_id,
+            reason,
+            snapshot,
+            community_actor_id: community.map(|c| c.actor_id),
+            federated: options.federated,
+          };
+          AdminPurgeCommunity::create(&mut conn.into(), &form).await?;
+          if let Some(progress_id) = options.progress_id {
+            CommunityPurgeProgress::add_purged(&mut conn.into(), progress_id).await?;
+          }
+          Ok(())
+        }) as _
+      })
+      .await
+  }
+
+  /// Returns the number of posts and comments in the community, including deleted and removed
+  /// ones.
+  pub async fn count_posts_and_comments(
+    pool: &mut DbPool<'_>,
+    community_id: CommunityId,
+  ) -> Result<(i64, i64), Error> {
+    use crate::schema::{comment, post};
+    let conn = &mut get_conn(pool).await?;
+    let posts = post::table
+      .filter(post::community_id.eq(community_id))
+      .count()
+      .get_result::<i64>(conn)
+      .await?;
+    let comments = comment::table
+      .inner_join(post::table)
+      .filter(post::community_id.eq(community_id))
+      .count()
+      .get_result::<i64>(conn)
+      .await?;
+    Ok((posts, comments))
+  }
 }
 
 impl CommunityModerator {
@@ -285,6 +409,27 @@ impl CommunityFollower {
     community_follower::pending.nullable()
   }
 
+  /// Returns the number of communities which are followed by both persons.
+  pub async fn count_shared(
+    pool: &mut DbPool<'_>,
+    person_a: PersonId,
+    person_b: PersonId,
+  ) -> Result<i64, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let follower_b = diesel::alias!(community_follower as follower_b);
+    let communities_b = follower_b
+      .select(follower_b.field(community_follower::community_id))
+      .filter(follower_b.field(community_follower::person_id).eq(person_b))
+      .filter(follower_b.field(community_follower::pending).eq(false));
+    community_follower::table
+      .filter(community_follower::person_id.eq(person_a))
+      .filter(community_follower::pending.eq(false))
+      .filter(community_follower::community_id.eq_any(communities_b))
+      .count()
+      .get_result(conn)
+      .await
+  }
+
   /// Check if a remote instance has any followers on local instance. For this it is enough to check
   /// if any follow relation is stored. Dont use this for local community.
   pub async fn has_local_followers(
@@ -421,6 +566,7 @@ mod tests {
     utils::build_db_pool_for_tests,
     CommunityVisibility,
   };
+  use lemmy_utils::{error::LemmyResult, LemmyErrorType};
   use pretty_assertions::assert_eq;
   use serial_test::serial;
 
@@ -477,6 +623,12 @@ mod tests {
       posting_restricted_to_mods: false,
       instance_id: inserted_instance.id,
       visibility: CommunityVisibility::Public,
+      post_upvotes: None,
+      post_downvotes: None,
+      comment_upvotes: None,
+      comment_downvotes: None,
+      allow_bot_votes: false,
+      vote_mode_updated: None,
     };
 
     let community_follower_form = CommunityFollowerForm {
@@ -568,4 +720,72 @@ mod tests {
     // assert_eq!(2, loaded_count);
     assert_eq!(1, num_deleted);
   }
+
+  #[tokio::test]
+  #[serial]
+  async fn test_count_shared() -> LemmyResult<()> {
+    let pool = &build_db_pool_for_tests().await;
+    let pool = &mut pool.into();
+    let instance = Instance::read_or_create(pool, "my_domain.tld".to_string()).await?;
+
+    let mut persons = vec![];
+    for name in ["shared_a", "shared_b"] {
+      let person_form = PersonInsertForm::builder()
+        .name(name.into())
+        .public_key("pubkey".to_string())
+        .instance_id(instance.id)
+        .build();
+      persons.push(Person::create(pool, &person_form).await?.id);
+    }
+    let mut communities = vec![];
+    for name in ["shared_both", "shared_only_a", "shared_pending"] {
+      let community_form = CommunityInsertForm::builder()
+        .name(name.into())
+        .title(name.to_owned())
+        .public_key("pubkey".to_string())
+        .instance_id(instance.id)
+        .build();
+      communities.push(Community::create(pool, &community_form).await?.id);
+    }
+    let [person_a, person_b] = persons.as_slice() else {
+      Err(LemmyErrorType::CouldntFindPerson)?
+    };
+    let [both, only_a, pending] = communities.as_slice() else {
+      Err(LemmyErrorType::CouldntFindCommunity)?
+    };
+
+    // both follow the first community, only a follows the second, and the follow of b in the
+    // third is still pending
+    let follows = [
+      (both, person_a, false),
+      (both, person_b, false),
+      (only_a, person_a, false),
+      (pending, person_a, false),
+      (pending, person_b, true),
+    ];
+    for (&community_id, &person_id, pending) in follows {
+      let form = CommunityFollowerForm {
+        community_id,
+        person_id,
+        pending,
+      };
+      CommunityFollower::follow(pool, &form).await?;
+    }
+
+    assert_eq!(
+      1,
+      CommunityFollower::count_shared(pool, *person_a, *person_b).await?
+    );
+    assert_eq!(
+      1,
+      CommunityFollower::count_shared(pool, *person_b, *person_a).await?
+    );
+    assert_eq!(
+      3,
+      CommunityFollower::count_shared(pool, *person_a, *person_a).await?
+    );
+
+    Instance::delete(pool, instance.id).await?;
+    Ok(())
+  }
 }
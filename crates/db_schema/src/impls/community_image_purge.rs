@@ -0,0 +1,81 @@
+use crate::{
+  diesel::OptionalExtension,
+  newtypes::CommunityId,
+  schema::community_image_purge,
+  source::community_image_purge::{CommunityImagePurge, CommunityImagePurgeForm},
+  utils::{get_conn, naive_now, DbPool},
+};
+use chrono::{DateTime, Utc};
+use diesel::{dsl::insert_into, result::Error, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+
+impl CommunityImagePurge {
+  /// Starts tracking a purge, resetting the progress of any previous purge of the community.
+  pub async fn create(
+    pool: &mut DbPool<'_>,
+    form: &CommunityImagePurgeForm,
+  ) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    insert_into(community_image_purge::table)
+      .values(form)
+      .on_conflict(community_image_purge::community_id)
+      .do_update()
+      .set((
+        form,
+        community_image_purge::purged.eq(0),
+        community_image_purge::failed.eq(0),
+        community_image_purge::published.eq(naive_now()),
+        community_image_purge::finished.eq(None::<DateTime<Utc>>),
+      ))
+      .get_result::<Self>(conn)
+      .await
+  }
+
+  pub async fn read(
+    pool: &mut DbPool<'_>,
+    community_id: CommunityId,
+  ) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    community_image_purge::table
+      .find(community_id)
+      .first::<Self>(conn)
+      .await
+      .optional()
+  }
+
+  /// Counts a single image as purged or failed.
+  pub async fn add_result(
+    pool: &mut DbPool<'_>,
+    community_id: CommunityId,
+    purged: bool,
+  ) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let target = community_image_purge::table.find(community_id);
+    if purged {
+      diesel::update(target)
+        .set(community_image_purge::purged.eq(community_image_purge::purged + 1))
+        .get_result::<Self>(conn)
+        .await
+    } else {
+      diesel::update(target)
+        .set(community_image_purge::failed.eq(community_image_purge::failed + 1))
+        .get_result::<Self>(conn)
+        .await
+    }
+  }
+
+  pub async fn finish(pool: &mut DbPool<'_>, community_id: CommunityId) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    diesel::update(community_image_purge::table.find(community_id))
+      .set(community_image_purge::finished.eq(naive_now()))
+      .get_result::<Self>(conn)
+      .await
+  }
+
+  pub async fn delete(pool: &mut DbPool<'_>, community_id: CommunityId) -> Result<usize, Error> {
+    let conn = &mut get_conn(pool).await?;
+    diesel::delete(community_image_purge::table.find(community_id))
+      .execute(conn)
+      .await
+  }
+}
@@ -0,0 +1,66 @@
+use crate::{
+  diesel::OptionalExtension,
+  schema::community_purge_progress,
+  source::community_purge_progress::{CommunityPurgeProgress, CommunityPurgeProgressForm},
+  utils::{get_conn, naive_now, DbPool},
+};
+use diesel::{dsl::insert_into, result::Error, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+
+impl CommunityPurgeProgress {
+  pub async fn create(
+    pool: &mut DbPool<'_>,
+    form: &CommunityPurgeProgressForm,
+  ) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    insert_into(community_purge_progress::table)
+      .values(form)
+      .get_result::<Self>(conn)
+      .await
+  }
+
+  pub async fn read(pool: &mut DbPool<'_>, id: i32) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    community_purge_progress::table
+      .find(id)
+      .first::<Self>(conn)
+      .await
+      .optional()
+  }
+
+  /// Returns the most recently started purge.
+  pub async fn read_latest(pool: &mut DbPool<'_>) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    community_purge_progress::table
+      .order_by(community_purge_progress::id.desc())
+      .first::<Self>(conn)
+      .await
+      .optional()
+  }
+
+  /// Counts a single community as purged.
+  pub async fn add_purged(pool: &mut DbPool<'_>, id: i32) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    diesel::update(community_purge_progress::table.find(id))
+      .set(community_purge_progress::purged.eq(community_purge_progress::purged + 1))
+      .get_result::<Self>(conn)
+      .await
+  }
+
+  /// Counts a single community as failed, its purge was rolled back.
+  pub async fn add_failed(pool: &mut DbPool<'_>, id: i32) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    diesel::update(community_purge_progress::table.find(id))
+      .set(community_purge_progress::failed.eq(community_purge_progress::failed + 1))
+      .get_result::<Self>(conn)
+      .await
+  }
+
+  pub async fn finish(pool: &mut DbPool<'_>, id: i32) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    diesel::update(community_purge_progress::table.find(id))
+      .set(community_purge_progress::finished.eq(naive_now()))
+      .get_result::<Self>(conn)
+      .await
+  }
+}
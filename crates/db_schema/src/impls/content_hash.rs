@@ -0,0 +1,88 @@
+use crate::{
+  diesel::OptionalExtension,
+  newtypes::{CommentId, PostId},
+  schema::content_hash,
+  source::content_hash::ContentHash,
+  utils::{
+    functions::{convert_to, encode, sha256},
+    get_conn,
+    DbPool,
+  },
+};
+use diesel::{
+  delete,
+  dsl::insert_into,
+  result::Error,
+  upsert::excluded,
+  ExpressionMethods,
+  QueryDsl,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The SHA-256 hash of the content as hex string, calculated by the database.
+fn sha256_hex(
+  content: &str,
+) -> encode::HelperType<sha256::HelperType<convert_to::HelperType<&str, &str>>, &str> {
+  encode(sha256(convert_to(content, "UTF8")), "hex")
+}
+
+impl ContentHash {
+  /// Reads the first stored post or comment with the given hash.
+  pub async fn read(pool: &mut DbPool<'_>, hash: &str) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    content_hash::table
+      .filter(content_hash::hash.eq(hash))
+      .order_by(content_hash::id)
+      .first::<Self>(conn)
+      .await
+      .optional()
+  }
+
+  /// Replaces the hash of the post, or removes it if the post has no content anymore. Runs in the
+  /// transaction which stores the post.
+  pub(crate) async fn update_post(
+    conn: &mut AsyncPgConnection,
+    content: Option<&str>,
+    post_id: PostId,
+  ) -> Result<(), Error> {
+    match content {
+      Some(content) => {
+        insert_into(content_hash::table)
+          .values((
+            content_hash::hash.eq(sha256_hex(content)),
+            content_hash::post_id.eq(post_id),
+          ))
+          .on_conflict(content_hash::post_id)
+          .do_update()
+          .set(content_hash::hash.eq(excluded(content_hash::hash)))
+          .execute(conn)
+          .await?;
+      }
+      None => {
+        delete(content_hash::table.filter(content_hash::post_id.eq(post_id)))
+          .execute(conn)
+          .await?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Replaces the hash of the comment. Runs in the transaction which stores the comment.
+  pub(crate) async fn update_comment(
+    conn: &mut AsyncPgConnection,
+    content: &str,
+    comment_id: CommentId,
+  ) -> Result<(), Error> {
+    insert_into(content_hash::table)
+      .values((
+        content_hash::hash.eq(sha256_hex(content)),
+        content_hash::comment_id.eq(comment_id),
+      ))
+      .on_conflict(content_hash::comment_id)
+      .do_update()
+      .set(content_hash::hash.eq(excluded(content_hash::hash)))
+      .execute(conn)
+      .await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,36 @@
+use crate::{
+  schema::federated_vote_rejection,
+  source::federated_vote_rejection::{FederatedVoteRejection, FederatedVoteRejectionForm},
+  utils::{get_conn, limit_and_offset, DbPool},
+};
+use diesel::{dsl::insert_into, result::Error, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+
+impl FederatedVoteRejection {
+  pub async fn create(
+    pool: &mut DbPool<'_>,
+    form: &FederatedVoteRejectionForm,
+  ) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    insert_into(federated_vote_rejection::table)
+      .values(form)
+      .get_result::<Self>(conn)
+      .await
+  }
+
+  /// Lists rejected votes, most recent first.
+  pub async fn list(
+    pool: &mut DbPool<'_>,
+    page: Option<i64>,
+    limit: Option<i64>,
+  ) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let (limit, offset) = limit_and_offset(page, limit)?;
+    federated_vote_rejection::table
+      .order_by(federated_vote_rejection::published.desc())
+      .limit(limit)
+      .offset(offset)
+      .load::<Self>(conn)
+      .await
+  }
+}
@@ -31,6 +31,18 @@ use diesel::{
   SelectableHelper,
 };
 use diesel_async::RunQueryDsl;
+use lemmy_utils::{error::LemmyResult, CACHE_DURATION_FEDERATION};
+use moka::future::Cache;
+use once_cell::sync::Lazy;
+
+/// Vote rate limits of remote instances, read for every federated vote. Invalidated whenever the
+/// limit of an instance is changed.
+static VOTE_RATE_LIMITS: Lazy<Cache<InstanceId, Option<i32>>> = Lazy::new(|| {
+  Cache::builder()
+    .max_capacity(10_000)
+    .time_to_live(CACHE_DURATION_FEDERATION)
+    .build()
+});
 
 impl Instance {
   /// Attempt to read Instance column for the given domain. If it doesn't exist, insert a new one.
@@ -67,6 +79,60 @@ impl Instance {
       }
     }
   }
+  /// Read the instance with the given domain, without inserting it if it doesn't exist.
+  pub async fn read_from_domain(
+    pool: &mut DbPool<'_>,
+    domain_: &str,
+  ) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    instance::table
+      .filter(lower(instance::domain).eq(domain_.to_lowercase()))
+      .first(conn)
+      .await
+      .optional()
+  }
+  pub async fn read(pool: &mut DbPool<'_>, instance_id: InstanceId) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    instance::table
+      .find(instance_id)
+      .first(conn)
+      .await
+      .optional()
+  }
+  /// Reads the vote rate limit of the instance, `None` if the site default applies.
+  pub async fn read_vote_rate_limit(
+    pool: &mut DbPool<'_>,
+    instance_id: InstanceId,
+  ) -> LemmyResult<Option<i32>> {
+    Ok(
+      VOTE_RATE_LIMITS
+        .try_get_with(instance_id, async {
+          let conn = &mut get_conn(pool).await?;
+          instance::table
+            .find(instance_id)
+            .select(instance::vote_rate_limit)
+            .first::<Option<i32>>(conn)
+            .await
+            .optional()
+            .map(Option::flatten)
+        })
+        .await?,
+    )
+  }
+  /// Sets the vote rate limit of the instance, `None` means that the site default applies.
+  pub async fn set_vote_rate_limit(
+    pool: &mut DbPool<'_>,
+    instance_id: InstanceId,
+    vote_rate_limit: Option<i32>,
+  ) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let instance = diesel::update(instance::table.find(instance_id))
+      .set(instance::vote_rate_limit.eq(vote_rate_limit))
+      .get_result(conn)
+      .await?;
+    VOTE_RATE_LIMITS.invalidate(&instance_id).await;
+    Ok(instance)
+  }
   pub async fn update(
     pool: &mut DbPool<'_>,
     instance_id: InstanceId,
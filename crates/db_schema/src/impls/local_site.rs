@@ -1,6 +1,9 @@
 use crate::{
   schema::local_site,
-  source::local_site::{LocalSite, LocalSiteInsertForm, LocalSiteUpdateForm},
+  source::{
+    local_site::{LocalSite, LocalSiteInsertForm, LocalSiteUpdateForm},
+    local_site_federation::LocalSiteFederation,
+  },
   utils::{get_conn, DbPool},
 };
 use diesel::{dsl::insert_into, result::Error};
@@ -9,21 +12,26 @@ use lemmy_utils::{error::LemmyResult, CACHE_DURATION_API};
 use moka::future::Cache;
 use once_cell::sync::Lazy;
 
+/// The local site is read for many requests and federated activities, but rarely changes. It is
+/// invalidated whenever the local site is written.
+static CACHE: Lazy<Cache<(), LocalSite>> = Lazy::new(|| {
+  Cache::builder()
+    .max_capacity(1)
+    .time_to_live(CACHE_DURATION_API)
+    .build()
+});
+
 impl LocalSite {
   pub async fn create(pool: &mut DbPool<'_>, form: &LocalSiteInsertForm) -> Result<Self, Error> {
     let conn = &mut get_conn(pool).await?;
-    insert_into(local_site::table)
+    let local_site = insert_into(local_site::table)
       .values(form)
       .get_result::<Self>(conn)
-      .await
+      .await?;
+    CACHE.invalidate(&()).await;
+    Ok(local_site)
   }
   pub async fn read(pool: &mut DbPool<'_>) -> LemmyResult<Self> {
-    static CACHE: Lazy<Cache<(), LocalSite>> = Lazy::new(|| {
-      Cache::builder()
-        .max_capacity(1)
-        .time_to_live(CACHE_DURATION_API)
-        .build()
-    });
     Ok(
       CACHE
         .try_get_with((), async {
@@ -35,13 +43,73 @@ impl LocalSite {
   }
   pub async fn update(pool: &mut DbPool<'_>, form: &LocalSiteUpdateForm) -> Result<Self, Error> {
     let conn = &mut get_conn(pool).await?;
-    diesel::update(local_site::table)
+    let local_site = diesel::update(local_site::table)
       .set(form)
       .get_result::<Self>(conn)
-      .await
+      .await?;
+    CACHE.invalidate(&()).await;
+    Ok(local_site)
   }
   pub async fn delete(pool: &mut DbPool<'_>) -> Result<usize, Error> {
     let conn = &mut get_conn(pool).await?;
-    diesel::delete(local_site::table).execute(conn).await
+    let deleted = diesel::delete(local_site::table).execute(conn).await?;
+    CACHE.invalidate(&()).await;
+    LocalSiteFederation::invalidate_cache().await;
+    Ok(deleted)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    source::{
+      instance::Instance,
+      site::{Site, SiteInsertForm},
+    },
+    traits::Crud,
+    utils::build_db_pool_for_tests,
+  };
+  use diesel::ExpressionMethods;
+  use serial_test::serial;
+
+  #[tokio::test]
+  #[serial]
+  async fn test_local_site_cache() -> LemmyResult<()> {
+    let pool = &build_db_pool_for_tests().await;
+    let pool = &mut pool.into();
+    let instance = Instance::read_or_create(pool, "my_domain.tld".to_string()).await?;
+    let site_form = SiteInsertForm::builder()
+      .name("test site".to_string())
+      .instance_id(instance.id)
+      .build();
+    let site = Site::create(pool, &site_form).await?;
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    LocalSite::create(pool, &local_site_form).await?;
+    assert!(!LocalSite::read(pool).await?.reports_email_admins);
+
+    // changes which bypass LocalSite::update are only seen once the cache is invalidated
+    {
+      let conn = &mut get_conn(pool).await?;
+      diesel::update(local_site::table)
+        .set(local_site::reports_email_admins.eq(true))
+        .execute(conn)
+        .await?;
+    }
+    assert!(!LocalSite::read(pool).await?.reports_email_admins);
+    CACHE.invalidate(&()).await;
+    assert!(LocalSite::read(pool).await?.reports_email_admins);
+
+    // updating the local site invalidates the cache
+    let form = LocalSiteUpdateForm {
+      store_purge_snapshots: Some(true),
+      ..Default::default()
+    };
+    LocalSite::update(pool, &form).await?;
+    assert!(LocalSite::read(pool).await?.store_purge_snapshots);
+
+    LocalSite::delete(pool).await?;
+    Instance::delete(pool, instance.id).await?;
+    Ok(())
   }
 }
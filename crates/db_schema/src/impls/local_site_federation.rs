@@ -0,0 +1,143 @@
+use crate::{
+  schema::local_site_federation,
+  source::local_site_federation::{
+    LocalSiteFederation,
+    LocalSiteFederationInsertForm,
+    LocalSiteFederationUpdateForm,
+  },
+  utils::{get_conn, DbPool},
+};
+use diesel::{dsl::insert_into, result::Error};
+use diesel_async::RunQueryDsl;
+use lemmy_utils::{error::LemmyResult, CACHE_DURATION_API};
+use moka::future::Cache;
+use once_cell::sync::Lazy;
+
+/// Read for every federated vote and resolve_object call. Like the local site, it is invalidated
+/// whenever it is written.
+static CACHE: Lazy<Cache<(), LocalSiteFederation>> = Lazy::new(|| {
+  Cache::builder()
+    .max_capacity(1)
+    .time_to_live(CACHE_DURATION_API)
+    .build()
+});
+
+impl LocalSiteFederation {
+  pub async fn read(pool: &mut DbPool<'_>) -> LemmyResult<Self> {
+    Ok(
+      CACHE
+        .try_get_with((), async {
+          let conn = &mut get_conn(pool).await?;
+          local_site_federation::table.first(conn).await
+        })
+        .await?,
+    )
+  }
+
+  pub async fn create(
+    pool: &mut DbPool<'_>,
+    form: &LocalSiteFederationInsertForm,
+  ) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let federation = insert_into(local_site_federation::table)
+      .values(form)
+      .get_result::<Self>(conn)
+      .await?;
+    Self::invalidate_cache().await;
+    Ok(federation)
+  }
+
+  pub async fn update(
+    pool: &mut DbPool<'_>,
+    form: &LocalSiteFederationUpdateForm,
+  ) -> Result<(), Error> {
+    // avoid error "There are no changes to save. This query cannot be built"
+    if form.is_empty() {
+      return Ok(());
+    }
+    let conn = &mut get_conn(pool).await?;
+    diesel::update(local_site_federation::table)
+      .set(form)
+      .get_result::<Self>(conn)
+      .await?;
+    Self::invalidate_cache().await;
+    Ok(())
+  }
+
+  /// Also needed when the local site is deleted, which deletes this row as well.
+  pub(crate) async fn invalidate_cache() {
+    CACHE.invalidate(&()).await;
+  }
+}
+
+impl LocalSiteFederationUpdateForm {
+  /// Compared with the default form, so that new fields are covered without changing this.
+  fn is_empty(&self) -> bool {
+    *self == Self::default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    source::{
+      instance::Instance,
+      local_site::{LocalSite, LocalSiteInsertForm},
+      site::{Site, SiteInsertForm},
+    },
+    traits::Crud,
+    utils::build_db_pool_for_tests,
+  };
+  use diesel::ExpressionMethods;
+  use serial_test::serial;
+
+  #[tokio::test]
+  #[serial]
+  async fn test_local_site_federation_cache() -> LemmyResult<()> {
+    let pool = &build_db_pool_for_tests().await;
+    let pool = &mut pool.into();
+    let instance = Instance::read_or_create(pool, "my_domain.tld".to_string()).await?;
+    let site_form = SiteInsertForm::builder()
+      .name("test site".to_string())
+      .instance_id(instance.id)
+      .build();
+    let site = Site::create(pool, &site_form).await?;
+    let local_site_form = LocalSiteInsertForm::builder().site_id(site.id).build();
+    let local_site = LocalSite::create(pool, &local_site_form).await?;
+    let form = LocalSiteFederationInsertForm::builder()
+      .local_site_id(local_site.id)
+      .build();
+    LocalSiteFederation::create(pool, &form).await?;
+    assert!(!LocalSiteFederation::read(pool).await?.log_rejected_votes);
+
+    // changes which bypass LocalSiteFederation::update are only seen once the cache is invalidated
+    {
+      let conn = &mut get_conn(pool).await?;
+      diesel::update(local_site_federation::table)
+        .set(local_site_federation::read_only.eq(true))
+        .execute(conn)
+        .await?;
+    }
+    assert!(!LocalSiteFederation::read(pool).await?.read_only);
+    LocalSiteFederation::invalidate_cache().await;
+    assert!(LocalSiteFederation::read(pool).await?.read_only);
+
+    let form = LocalSiteFederationUpdateForm {
+      log_rejected_votes: Some(true),
+      ..Default::default()
+    };
+    LocalSiteFederation::update(pool, &form).await?;
+    assert!(LocalSiteFederation::read(pool).await?.log_rejected_votes);
+
+    // an update without changes is skipped instead of failing
+    LocalSiteFederation::update(pool, &Default::default()).await?;
+
+    // deleting the local site also deletes its federation settings
+    LocalSite::delete(pool).await?;
+    assert!(LocalSiteFederation::read(pool).await.is_err());
+
+    Instance::delete(pool, instance.id).await?;
+    Ok(())
+  }
+}
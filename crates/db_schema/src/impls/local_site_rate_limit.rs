@@ -58,6 +58,8 @@ impl LocalSiteRateLimitUpdateForm {
       && self.comment_per_second.is_none()
       && self.search.is_none()
       && self.search_per_second.is_none()
+      && self.resolve_object.is_none()
+      && self.resolve_object_per_second.is_none()
       && self.updated.is_none()
   }
 }
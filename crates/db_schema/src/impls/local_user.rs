@@ -142,6 +142,19 @@ impl LocalUser {
     .await
   }
 
+  /// Whether the person is a local admin.
+  pub async fn is_admin(pool: &mut DbPool<'_>, person_id: PersonId) -> Result<bool, Error> {
+    use diesel::dsl::{exists, select};
+    let conn = &mut get_conn(pool).await?;
+    select(exists(
+      local_user::table
+        .filter(local_user::person_id.eq(person_id))
+        .filter(local_user::admin),
+    ))
+    .get_result(conn)
+    .await
+  }
+
   // TODO: maybe move this and pass in LocalUserView
   pub async fn export_backup(
     pool: &mut DbPool<'_>,
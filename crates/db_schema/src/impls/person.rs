@@ -10,9 +10,23 @@ use crate::{
     PersonUpdateForm,
   },
   traits::{ApubActor, Crud, Followable},
-  utils::{functions::lower, get_conn, naive_now, DbPool},
+  utils::{
+    functions::{lower, similarity},
+    fuzzy_search,
+    get_conn,
+    naive_now,
+    DbPool,
+  },
+};
+use diesel::{
+  dsl::insert_into,
+  result::Error,
+  CombineDsl,
+  ExpressionMethods,
+  JoinOnDsl,
+  PgTextExpressionMethods,
+  QueryDsl,
 };
-use diesel::{dsl::insert_into, result::Error, CombineDsl, ExpressionMethods, JoinOnDsl, QueryDsl};
 use diesel_async::RunQueryDsl;
 
 #[async_trait]
@@ -56,14 +70,33 @@ impl Person {
   /// Update or insert the person.
   ///
   /// This is necessary for federation, because Activitypub doesn't distinguish between these
-  /// actions.
+  /// actions. Profile fields which are missing in the form are cleared, because they were removed
+  /// on the remote instance.
   pub async fn upsert(pool: &mut DbPool<'_>, form: &PersonInsertForm) -> Result<Self, Error> {
     let conn = &mut get_conn(pool).await?;
+    // The changeset skips missing fields, so the profile fields are set separately
+    let without_profile = PersonInsertForm {
+      display_name: None,
+      avatar: None,
+      banner: None,
+      bio: None,
+      matrix_user_id: None,
+      shared_inbox_url: None,
+      ..form.clone()
+    };
     insert_into(person::table)
       .values(form)
       .on_conflict(person::actor_id)
       .do_update()
-      .set(form)
+      .set((
+        &without_profile,
+        person::display_name.eq(&form.display_name),
+        person::avatar.eq(&form.avatar),
+        person::banner.eq(&form.banner),
+        person::bio.eq(&form.bio),
+        person::matrix_user_id.eq(&form.matrix_user_id),
+        person::shared_inbox_url.eq(&form.shared_inbox_url),
+      ))
       .get_result::<Self>(conn)
       .await
   }
@@ -90,6 +123,37 @@ impl Person {
       .await
   }
 
+  /// Lists all known persons with the given name, both local and remote. Local persons come
+  /// first.
+  pub async fn list_from_name(pool: &mut DbPool<'_>, from_name: &str) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    person::table
+      .filter(lower(person::name).eq(from_name.to_lowercase()))
+      .filter(person::deleted.eq(false))
+      .order_by((person::local.desc(), person::id))
+      .load::<Self>(conn)
+      .await
+  }
+
+  /// Returns the known person whose name is most similar to the given partial name, along with
+  /// the similarity. Uses the trigram index, ties are broken by preferring local and older
+  /// persons.
+  pub async fn find_similar_name(
+    pool: &mut DbPool<'_>,
+    partial_name: &str,
+  ) -> Result<Option<(Self, f32)>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let name_similarity = similarity(person::name, partial_name);
+    person::table
+      .filter(person::name.ilike(fuzzy_search(partial_name)))
+      .filter(person::deleted.eq(false))
+      .select((person::all_columns, name_similarity))
+      .order_by((name_similarity.desc(), person::local.desc(), person::id))
+      .first::<(Self, f32)>(conn)
+      .await
+      .optional()
+  }
+
   /// Lists local community ids for all posts and comments for a given creator.
   pub async fn list_local_community_ids(
     pool: &mut DbPool<'_>,
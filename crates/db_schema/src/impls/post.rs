@@ -1,19 +1,24 @@
 use crate::{
+  aggregates::structs::PostAggregates,
   diesel::OptionalExtension,
   newtypes::{CommunityId, DbUrl, PersonId, PostId},
-  schema::{post, post_hide, post_like, post_read, post_saved},
-  source::post::{
-    Post,
-    PostHide,
-    PostHideForm,
-    PostInsertForm,
-    PostLike,
-    PostLikeForm,
-    PostRead,
-    PostReadForm,
-    PostSaved,
-    PostSavedForm,
-    PostUpdateForm,
+  schema::{admin_purge_post, post, post_aggregates, post_hide, post_like, post_read, post_saved},
+  source::{
+    content_hash::ContentHash,
+    moderator::AdminPurgePostForm,
+    post::{
+      Post,
+      PostHide,
+      PostHideForm,
+      PostInsertForm,
+      PostLike,
+      PostLikeForm,
+      PostRead,
+      PostReadForm,
+      PostSaved,
+      PostSavedForm,
+      PostUpdateForm,
+    },
   },
   traits::{Crud, Likeable, Saveable},
   utils::{
@@ -35,9 +40,11 @@ use diesel::{
   DecoratableTarget,
   ExpressionMethods,
   QueryDsl,
+  SelectableHelper,
   TextExpressionMethods,
 };
 use diesel_async::RunQueryDsl;
+use serde_json::{json, Value};
 use std::collections::HashSet;
 
 #[async_trait]
@@ -68,19 +75,30 @@ impl Crud for Post {
 }
 
 impl Post {
+  /// Stores a received post, together with the hash of its `content` as received.
   pub async fn insert_apub(
     pool: &mut DbPool<'_>,
     timestamp: DateTime<Utc>,
     form: &PostInsertForm,
+    content: Option<&str>,
   ) -> Result<Self, Error> {
     let conn = &mut get_conn(pool).await?;
-    insert_into(post::table)
-      .values(form)
-      .on_conflict(post::ap_id)
-      .filter_target(coalesce(post::updated, post::published).lt(timestamp))
-      .do_update()
-      .set(form)
-      .get_result::<Self>(conn)
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          let post = insert_into(post::table)
+            .values(form)
+            .on_conflict(post::ap_id)
+            .filter_target(coalesce(post::updated, post::published).lt(timestamp))
+            .do_update()
+            .set(form)
+            .get_result::<Self>(conn)
+            .await?;
+          ContentHash::update_post(conn, content, post.id).await?;
+          Ok(post)
+        }) as _
+      })
       .await
   }
 
@@ -100,6 +118,58 @@ impl Post {
       .await
   }
 
+  /// Posts of the community which were published within the given time range, including its
+  /// bounds.
+  pub async fn list_for_community_in_range(
+    pool: &mut DbPool<'_>,
+    the_community_id: CommunityId,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+  ) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    post::table
+      .filter(post::community_id.eq(the_community_id))
+      .filter(post::published.between(start, end))
+      .order_by(post::published.asc())
+      .load::<Self>(conn)
+      .await
+  }
+
+  /// Deletes the posts and logs each of them as `AdminPurgePost`. This runs in a single
+  /// transaction, so that a failure can't leave purged posts without a modlog entry.
+  pub async fn purge_many(
+    pool: &mut DbPool<'_>,
+    posts: &[Self],
+    admin_person_id: PersonId,
+    reason: Option<String>,
+  ) -> Result<(), Error> {
+    let conn = &mut get_conn(pool).await?;
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          let post_ids = posts.iter().map(|p| p.id).collect::<Vec<_>>();
+          diesel::delete(post::table.filter(post::id.eq_any(post_ids)))
+            .execute(conn)
+            .await?;
+          let forms = posts
+            .iter()
+            .map(|p| AdminPurgePostForm {
+              admin_person_id,
+              community_id: p.community_id,
+              reason: reason.clone(),
+            })
+            .collect::<Vec<_>>();
+          insert_into(admin_purge_post::table)
+            .values(forms)
+            .execute(conn)
+            .await?;
+          Ok(())
+        }) as _
+      })
+      .await
+  }
+
   pub async fn list_for_sitemap(
     pool: &mut DbPool<'_>,
   ) -> Result<Vec<(DbUrl, chrono::DateTime<Utc>)>, Error> {
@@ -222,25 +292,60 @@ impl Post {
       .await
   }
 
-  /// Sets the url and thumbnails fields to None
-  pub async fn remove_pictrs_post_images_and_thumbnails_for_community(
+  /// Reads the post together with its vote counts, in a single query.
+  pub async fn read_with_counts(
     pool: &mut DbPool<'_>,
-    for_community_id: CommunityId,
-  ) -> Result<Vec<Self>, Error> {
+    post_id: PostId,
+  ) -> Result<Option<(Self, PostAggregates)>, Error> {
     let conn = &mut get_conn(pool).await?;
-    let pictrs_search = "%pictrs/image%";
+    post::table
+      .inner_join(post_aggregates::table)
+      .filter(post::id.eq(post_id))
+      .select((post::all_columns, PostAggregates::as_select()))
+      .first(conn)
+      .await
+      .optional()
+  }
 
-    diesel::update(
-      post::table
-        .filter(post::community_id.eq(for_community_id))
-        .filter(post::url.like(pictrs_search)),
+  /// Ids and ap_ids of the newest remote posts which were published since the given time, and
+  /// which aren't deleted or removed.
+  pub async fn list_recent_remote(
+    pool: &mut DbPool<'_>,
+    since: DateTime<Utc>,
+    limit: i64,
+  ) -> Result<Vec<(PostId, DbUrl)>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    post::table
+      .filter(post::local.eq(false))
+      .filter(post::deleted.eq(false))
+      .filter(post::removed.eq(false))
+      .filter(post::published.ge(since))
+      .order_by(post::published.desc())
+      .limit(limit)
+      .select((post::id, post::ap_id))
+      .load::<(PostId, DbUrl)>(conn)
+      .await
+  }
+
+  /// Names and ap_ids of all posts in the community, to keep a record of them when the community
+  /// is purged.
+  pub async fn purge_snapshot_for_community(
+    pool: &mut DbPool<'_>,
+    for_community_id: CommunityId,
+  ) -> Result<Value, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let posts = post::table
+      .filter(post::community_id.eq(for_community_id))
+      .order_by(post::published)
+      .select((post::name, post::ap_id))
+      .load::<(String, DbUrl)>(conn)
+      .await?;
+    Ok(
+      posts
+        .into_iter()
+        .map(|(name, ap_id)| json!({ "name": name, "ap_id": ap_id }))
+        .collect(),
     )
-    .set((
-      post::url.eq::<Option<String>>(None),
-      post::thumbnail_url.eq::<Option<String>>(None),
-    ))
-    .get_results::<Self>(conn)
-    .await
   }
 }
 
@@ -268,6 +373,50 @@ impl Likeable for PostLike {
       .execute(conn)
       .await
   }
+  async fn read(
+    pool: &mut DbPool<'_>,
+    person_id: PersonId,
+    post_id: PostId,
+  ) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    post_like::table
+      .find((person_id, post_id))
+      .first::<Self>(conn)
+      .await
+      .optional()
+  }
+}
+
+impl PostLike {
+  /// Removes the existing votes for the given person and post pairs, then inserts the new votes.
+  /// Everything is written in a single transaction, so this is more efficient than separate calls
+  /// to [Likeable::remove] and [Likeable::like] for many votes.
+  pub async fn replace_many(
+    pool: &mut DbPool<'_>,
+    remove: &[(PersonId, PostId)],
+    likes: &[PostLikeForm],
+  ) -> Result<(), Error> {
+    let conn = &mut get_conn(pool).await?;
+    conn
+      .build_transaction()
+      .run(|conn| {
+        Box::pin(async move {
+          for &(person_id, post_id) in remove {
+            diesel::delete(post_like::table.find((person_id, post_id)))
+              .execute(conn)
+              .await?;
+          }
+          if !likes.is_empty() {
+            insert_into(post_like::table)
+              .values(likes)
+              .execute(conn)
+              .await?;
+          }
+          Ok(())
+        }) as _
+      })
+      .await
+  }
 }
 
 #[async_trait]
@@ -464,6 +613,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: inserted_person.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     let inserted_post_like = PostLike::like(pool, &post_like_form).await.unwrap();
@@ -473,6 +623,7 @@ mod tests {
       person_id: inserted_person.id,
       published: inserted_post_like.published,
       score: 1,
+      activity_ap_id: None,
     };
 
     // Post Save
@@ -0,0 +1,37 @@
+use crate::{
+  schema::received_vote_action,
+  source::received_vote_action::ReceivedVoteAction,
+  utils::{get_conn, DbPool},
+};
+use diesel::{
+  dsl::{insert_into, update},
+  result::Error,
+  ExpressionMethods,
+};
+use diesel_async::RunQueryDsl;
+
+impl ReceivedVoteAction {
+  /// Stores the time of a received vote or undo, unless a later one was already stored for the
+  /// same person and object. Returns false in that case, the action is outdated and shouldn't be
+  /// applied.
+  pub async fn update_if_newer(pool: &mut DbPool<'_>, action: &Self) -> Result<bool, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let updated = update(received_vote_action::table)
+      .filter(received_vote_action::person_id.eq(action.person_id))
+      .filter(received_vote_action::object_id.eq(&action.object_id))
+      .filter(received_vote_action::published.lt(action.published))
+      .set(received_vote_action::published.eq(action.published))
+      .execute(conn)
+      .await?;
+    if updated > 0 {
+      return Ok(true);
+    }
+    // Nothing was updated, either because there is no stored action yet or because it is newer
+    let inserted = insert_into(received_vote_action::table)
+      .values(action)
+      .on_conflict_do_nothing()
+      .execute(conn)
+      .await?;
+    Ok(inserted > 0)
+  }
+}
@@ -0,0 +1,86 @@
+use crate::{
+  newtypes::{CommunityId, DbUrl},
+  schema::{comment, comment_report, community, person, post, post_report, report_archive},
+  source::report_archive::ReportArchive,
+  utils::{get_conn, limit_and_offset, DbPool},
+};
+use diesel::{dsl::insert_into, result::Error, ExpressionMethods, JoinOnDsl, QueryDsl};
+use diesel_async::RunQueryDsl;
+
+impl ReportArchive {
+  /// Copies the unresolved post and comment reports of the community to the archive, so that
+  /// they are kept once the community is purged. Returns the number of archived reports.
+  pub async fn archive_for_community(
+    pool: &mut DbPool<'_>,
+    community_id: CommunityId,
+  ) -> Result<usize, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let columns = (
+      report_archive::community_actor_id,
+      report_archive::object_ap_id,
+      report_archive::reporter_actor_id,
+      report_archive::original_content,
+      report_archive::reason,
+      report_archive::reported,
+    );
+    let post_reports = post_report::table
+      .inner_join(post::table.inner_join(community::table))
+      .inner_join(person::table.on(post_report::creator_id.eq(person::id)))
+      .filter(post::community_id.eq(community_id))
+      .filter(post_report::resolved.eq(false))
+      .select((
+        community::actor_id,
+        post::ap_id,
+        person::actor_id,
+        post_report::original_post_name,
+        post_report::reason,
+        post_report::published,
+      ));
+    let archived_posts = insert_into(report_archive::table)
+      .values(post_reports)
+      .into_columns(columns)
+      .execute(conn)
+      .await?;
+    let comment_reports = comment_report::table
+      .inner_join(comment::table.inner_join(post::table.inner_join(community::table)))
+      .inner_join(person::table.on(comment_report::creator_id.eq(person::id)))
+      .filter(post::community_id.eq(community_id))
+      .filter(comment_report::resolved.eq(false))
+      .select((
+        community::actor_id,
+        comment::ap_id,
+        person::actor_id,
+        comment_report::original_comment_text,
+        comment_report::reason,
+        comment_report::published,
+      ));
+    let archived_comments = insert_into(report_archive::table)
+      .values(comment_reports)
+      .into_columns(columns)
+      .execute(conn)
+      .await?;
+    Ok(archived_posts + archived_comments)
+  }
+
+  /// Lists the archived reports, oldest first. With `community_actor_id`, only the reports of
+  /// that purged community are returned.
+  pub async fn list(
+    pool: &mut DbPool<'_>,
+    community_actor_id: Option<&DbUrl>,
+    page: Option<i64>,
+    limit: Option<i64>,
+  ) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let (limit, offset) = limit_and_offset(page, limit)?;
+    let mut query = report_archive::table.into_boxed();
+    if let Some(community_actor_id) = community_actor_id {
+      query = query.filter(report_archive::community_actor_id.eq(community_actor_id));
+    }
+    query
+      .order_by((report_archive::reported, report_archive::id))
+      .limit(limit)
+      .offset(offset)
+      .load::<Self>(conn)
+      .await
+  }
+}
@@ -0,0 +1,48 @@
+use crate::{
+  diesel::OptionalExtension,
+  newtypes::DbUrl,
+  schema::resolve_fetch_validator,
+  source::resolve_fetch_validator::{ResolveFetchValidator, ResolveFetchValidatorForm},
+  utils::{get_conn, now, DbPool},
+};
+use diesel::{delete, insert_into, result::Error, upsert::excluded, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+
+impl ResolveFetchValidator {
+  pub async fn read(pool: &mut DbPool<'_>, ap_id: &DbUrl) -> Result<Option<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    resolve_fetch_validator::table
+      .find(ap_id)
+      .first::<Self>(conn)
+      .await
+      .optional()
+  }
+
+  /// Replaces the validators of the object, or removes them if the last response had neither
+  /// header.
+  pub async fn replace(
+    pool: &mut DbPool<'_>,
+    form: &ResolveFetchValidatorForm,
+  ) -> Result<(), Error> {
+    let conn = &mut get_conn(pool).await?;
+    if form.etag.is_none() && form.last_modified.is_none() {
+      delete(resolve_fetch_validator::table.find(&form.ap_id))
+        .execute(conn)
+        .await?;
+    } else {
+      insert_into(resolve_fetch_validator::table)
+        .values(form)
+        .on_conflict(resolve_fetch_validator::ap_id)
+        .do_update()
+        .set((
+          resolve_fetch_validator::etag.eq(excluded(resolve_fetch_validator::etag)),
+          resolve_fetch_validator::last_modified
+            .eq(excluded(resolve_fetch_validator::last_modified)),
+          resolve_fetch_validator::updated.eq(now()),
+        ))
+        .execute(conn)
+        .await?;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,40 @@
+use crate::{
+  schema::resolve_object_log,
+  source::resolve_object_log::{ResolveObjectLog, ResolveObjectLogForm},
+  utils::{get_conn, limit_and_offset, DbPool},
+};
+use chrono::{DateTime, Utc};
+use diesel::{dsl::insert_into, result::Error, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+
+impl ResolveObjectLog {
+  pub async fn create(pool: &mut DbPool<'_>, form: &ResolveObjectLogForm) -> Result<Self, Error> {
+    let conn = &mut get_conn(pool).await?;
+    insert_into(resolve_object_log::table)
+      .values(form)
+      .get_result::<Self>(conn)
+      .await
+  }
+
+  /// Lists resolved objects, most recent first. With `since`, only objects resolved after that
+  /// time are returned.
+  pub async fn list(
+    pool: &mut DbPool<'_>,
+    since: Option<DateTime<Utc>>,
+    page: Option<i64>,
+    limit: Option<i64>,
+  ) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    let (limit, offset) = limit_and_offset(page, limit)?;
+    let mut query = resolve_object_log::table.into_boxed();
+    if let Some(since) = since {
+      query = query.filter(resolve_object_log::published.gt(since));
+    }
+    query
+      .order_by(resolve_object_log::published.desc())
+      .limit(limit)
+      .offset(offset)
+      .load::<Self>(conn)
+      .await
+  }
+}
@@ -0,0 +1,45 @@
+use crate::{
+  newtypes::{CommentId, PostId},
+  schema::vote_correction,
+  source::vote_correction::{VoteCorrection, VoteCorrectionForm},
+  utils::{get_conn, DbPool},
+};
+use diesel::{dsl::insert_into, result::Error, ExpressionMethods, QueryDsl};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+impl VoteCorrection {
+  /// All corrections of the post, oldest first.
+  pub async fn list_for_post(pool: &mut DbPool<'_>, post_id: PostId) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    vote_correction::table
+      .filter(vote_correction::post_id.eq(post_id))
+      .order_by(vote_correction::id)
+      .load(conn)
+      .await
+  }
+
+  /// All corrections of the comment, oldest first.
+  pub async fn list_for_comment(
+    pool: &mut DbPool<'_>,
+    comment_id: CommentId,
+  ) -> Result<Vec<Self>, Error> {
+    let conn = &mut get_conn(pool).await?;
+    vote_correction::table
+      .filter(vote_correction::comment_id.eq(comment_id))
+      .order_by(vote_correction::id)
+      .load(conn)
+      .await
+  }
+
+  /// Stores a correction, which the triggers apply to the vote counts of the post or comment and
+  /// the score of its creator. Runs in the transaction which reads the counts to correct.
+  pub(crate) async fn create(
+    conn: &mut AsyncPgConnection,
+    form: &VoteCorrectionForm,
+  ) -> Result<Self, Error> {
+    insert_into(vote_correction::table)
+      .values(form)
+      .get_result(conn)
+      .await
+  }
+}
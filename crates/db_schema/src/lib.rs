@@ -177,6 +177,44 @@ pub enum SearchType {
   Url,
 }
 
+#[derive(EnumString, Display, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "full", derive(DbEnum, TS))]
+#[cfg_attr(
+  feature = "full",
+  ExistingTypePath = "crate::schema::sql_types::ResolveObjectTypeEnum"
+)]
+#[cfg_attr(feature = "full", DbValueStyle = "verbatim")]
+#[cfg_attr(feature = "full", ts(export))]
+/// The type of object expected from a resolve object request.
+pub enum ResolveObjectType {
+  Post,
+  Comment,
+  Person,
+  Community,
+}
+
+#[derive(
+  EnumString, Display, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Hash,
+)]
+#[cfg_attr(feature = "full", derive(DbEnum, TS))]
+#[cfg_attr(
+  feature = "full",
+  ExistingTypePath = "crate::schema::sql_types::ResolveRemoteAccessEnum"
+)]
+#[cfg_attr(feature = "full", DbValueStyle = "verbatim")]
+#[cfg_attr(feature = "full", ts(export))]
+/// Which users may fetch remote objects with resolve_object. Others can only resolve objects which
+/// are known already. Admins may always fetch remote objects.
+pub enum ResolveRemoteAccess {
+  /// All logged in users.
+  #[default]
+  All,
+  /// Users with a verified email address.
+  VerifiedEmail,
+  /// Only admins.
+  Admins,
+}
+
 #[derive(EnumString, Display, Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy, Hash)]
 #[cfg_attr(feature = "full", derive(TS))]
 #[cfg_attr(feature = "full", ts(export))]
@@ -245,6 +283,29 @@ pub enum CommunityVisibility {
   LocalOnly,
 }
 
+#[derive(
+  EnumString, Display, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Hash,
+)]
+#[cfg_attr(feature = "full", derive(DbEnum, TS))]
+#[cfg_attr(
+  feature = "full",
+  ExistingTypePath = "crate::schema::sql_types::FederationModeEnum"
+)]
+#[cfg_attr(feature = "full", DbValueStyle = "verbatim")]
+#[cfg_attr(feature = "full", ts(export))]
+/// Which votes are accepted from federation.
+pub enum FederationMode {
+  /// Accept votes from local and remote users.
+  #[default]
+  All,
+  /// Only accept votes from local users.
+  Local,
+  /// Only accept votes from users whose instance follows the community.
+  Followers,
+  /// Don't accept any votes.
+  Disable,
+}
+
 /// Wrapper for assert_eq! macro. Checks that vec matches the given length, and prints the
 /// vec on failure.
 #[macro_export]
@@ -9,6 +9,10 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "community_visibility"))]
     pub struct CommunityVisibility;
 
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "federation_mode_enum"))]
+    pub struct FederationModeEnum;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "listing_type_enum"))]
     pub struct ListingTypeEnum;
@@ -25,11 +29,28 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "registration_mode_enum"))]
     pub struct RegistrationModeEnum;
 
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "resolve_object_type_enum"))]
+    pub struct ResolveObjectTypeEnum;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "resolve_remote_access_enum"))]
+    pub struct ResolveRemoteAccessEnum;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "sort_type_enum"))]
     pub struct SortTypeEnum;
 }
 
+diesel::table! {
+    actor_alias (alias) {
+        #[max_length = 255]
+        alias -> Varchar,
+        person_id -> Nullable<Int4>,
+        community_id -> Nullable<Int4>,
+    }
+}
+
 diesel::table! {
     admin_purge_comment (id) {
         id -> Int4,
@@ -46,6 +67,10 @@ diesel::table! {
         admin_person_id -> Int4,
         reason -> Nullable<Text>,
         when_ -> Timestamptz,
+        snapshot -> Nullable<Jsonb>,
+        #[max_length = 255]
+        community_actor_id -> Nullable<Varchar>,
+        federated -> Bool,
     }
 }
 
@@ -111,6 +136,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    comment_edit (id) {
+        id -> Int4,
+        comment_id -> Int4,
+        content -> Text,
+        published -> Timestamptz,
+    }
+}
+
 diesel::table! {
     comment_like (person_id, comment_id) {
         person_id -> Int4,
@@ -118,6 +152,8 @@ diesel::table! {
         post_id -> Int4,
         score -> Int2,
         published -> Timestamptz,
+        #[max_length = 255]
+        activity_ap_id -> Nullable<Varchar>,
     }
 }
 
@@ -156,6 +192,7 @@ diesel::table! {
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::CommunityVisibility;
+    use super::sql_types::FederationModeEnum;
 
     community (id) {
         id -> Int4,
@@ -191,6 +228,12 @@ diesel::table! {
         #[max_length = 255]
         featured_url -> Nullable<Varchar>,
         visibility -> CommunityVisibility,
+        post_upvotes -> Nullable<FederationModeEnum>,
+        post_downvotes -> Nullable<FederationModeEnum>,
+        comment_upvotes -> Nullable<FederationModeEnum>,
+        comment_downvotes -> Nullable<FederationModeEnum>,
+        allow_bot_votes -> Bool,
+        vote_mode_updated -> Nullable<Timestamptz>,
     }
 }
 
@@ -227,6 +270,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    community_image_purge (community_id) {
+        community_id -> Int4,
+        total -> Int4,
+        purged -> Int4,
+        failed -> Int4,
+        published -> Timestamptz,
+        finished -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     community_language (community_id, language_id) {
         community_id -> Int4,
@@ -251,6 +305,27 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    community_purge_progress (id) {
+        id -> Int4,
+        total -> Int4,
+        purged -> Int4,
+        published -> Timestamptz,
+        finished -> Nullable<Timestamptz>,
+        failed -> Int4,
+    }
+}
+
+diesel::table! {
+    content_hash (id) {
+        id -> Int4,
+        #[max_length = 64]
+        hash -> Varchar,
+        post_id -> Nullable<Int4>,
+        comment_id -> Nullable<Int4>,
+    }
+}
+
 diesel::table! {
     custom_emoji (id) {
         id -> Int4,
@@ -283,6 +358,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    federated_vote_rejection (id) {
+        id -> Int4,
+        #[max_length = 255]
+        actor_id -> Varchar,
+        #[max_length = 255]
+        object_id -> Varchar,
+        score -> Int2,
+        published -> Timestamptz,
+    }
+}
+
 diesel::table! {
     federation_allowlist (instance_id) {
         instance_id -> Int4,
@@ -320,6 +407,7 @@ diesel::table! {
         software -> Nullable<Varchar>,
         #[max_length = 255]
         version -> Nullable<Varchar>,
+        vote_rate_limit -> Nullable<Int4>,
     }
 }
 
@@ -384,6 +472,34 @@ diesel::table! {
         federation_signed_fetch -> Bool,
         default_post_listing_mode -> PostListingModeEnum,
         default_sort_type -> SortTypeEnum,
+        purge_confirmation_post_threshold -> Int4,
+        store_purge_snapshots -> Bool,
+        vote_mode_updated -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ResolveRemoteAccessEnum;
+
+    local_site_federation (local_site_id) {
+        local_site_id -> Int4,
+        log_rejected_votes -> Bool,
+        published -> Timestamptz,
+        updated -> Nullable<Timestamptz>,
+        min_account_age_for_full_vote -> Int4,
+        instance_vote_rate_limit -> Int4,
+        hide_nsfw_from_resolve -> Bool,
+        resolve_remote_posts -> Bool,
+        resolve_remote_comments -> Bool,
+        resolve_remote_actors -> Bool,
+        min_community_age_for_remote_votes -> Int4,
+        read_only -> Bool,
+        resolve_remote_access -> ResolveRemoteAccessEnum,
+        resolve_remote_min_account_age -> Int4,
+        vote_score_change_limit -> Int4,
+        accept_remote_community_purges -> Bool,
+        vote_mode_grace_period -> Int4,
     }
 }
 
@@ -406,6 +522,8 @@ diesel::table! {
         updated -> Nullable<Timestamptz>,
         import_user_settings -> Int4,
         import_user_settings_per_second -> Int4,
+        resolve_object -> Int4,
+        resolve_object_per_second -> Int4,
     }
 }
 
@@ -764,6 +882,8 @@ diesel::table! {
         person_id -> Int4,
         score -> Int2,
         published -> Timestamptz,
+        #[max_length = 255]
+        activity_ap_id -> Nullable<Varchar>,
     }
 }
 
@@ -837,6 +957,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    received_vote_action (person_id, object_id) {
+        person_id -> Int4,
+        #[max_length = 255]
+        object_id -> Varchar,
+        published -> Timestamptz,
+    }
+}
+
 diesel::table! {
     registration_application (id) {
         id -> Int4,
@@ -856,6 +985,46 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    report_archive (id) {
+        id -> Int4,
+        #[max_length = 255]
+        community_actor_id -> Varchar,
+        #[max_length = 255]
+        object_ap_id -> Varchar,
+        #[max_length = 255]
+        reporter_actor_id -> Varchar,
+        original_content -> Text,
+        reason -> Text,
+        reported -> Timestamptz,
+        archived -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    resolve_fetch_validator (ap_id) {
+        #[max_length = 255]
+        ap_id -> Varchar,
+        etag -> Nullable<Text>,
+        last_modified -> Nullable<Text>,
+        updated -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ResolveObjectTypeEnum;
+
+    resolve_object_log (id) {
+        id -> Int4,
+        query -> Text,
+        person_id -> Int4,
+        object_type -> Nullable<ResolveObjectTypeEnum>,
+        published -> Timestamptz,
+        impersonated_person_id -> Nullable<Int4>,
+    }
+}
+
 diesel::table! {
     secret (id) {
         id -> Int4,
@@ -936,6 +1105,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    vote_correction (id) {
+        id -> Int4,
+        post_id -> Nullable<Int4>,
+        comment_id -> Nullable<Int4>,
+        upvotes -> Int8,
+        downvotes -> Int8,
+        published -> Timestamptz,
+    }
+}
+
+diesel::joinable!(actor_alias -> community (community_id));
+diesel::joinable!(actor_alias -> person (person_id));
 diesel::joinable!(admin_purge_comment -> person (admin_person_id));
 diesel::joinable!(admin_purge_comment -> post (post_id));
 diesel::joinable!(admin_purge_community -> person (admin_person_id));
@@ -946,6 +1128,7 @@ diesel::joinable!(comment -> language (language_id));
 diesel::joinable!(comment -> person (creator_id));
 diesel::joinable!(comment -> post (post_id));
 diesel::joinable!(comment_aggregates -> comment (comment_id));
+diesel::joinable!(comment_edit -> comment (comment_id));
 diesel::joinable!(comment_like -> comment (comment_id));
 diesel::joinable!(comment_like -> person (person_id));
 diesel::joinable!(comment_like -> post (post_id));
@@ -966,6 +1149,8 @@ diesel::joinable!(community_moderator -> community (community_id));
 diesel::joinable!(community_moderator -> person (person_id));
 diesel::joinable!(community_person_ban -> community (community_id));
 diesel::joinable!(community_person_ban -> person (person_id));
+diesel::joinable!(content_hash -> comment (comment_id));
+diesel::joinable!(content_hash -> post (post_id));
 diesel::joinable!(custom_emoji -> local_site (local_site_id));
 diesel::joinable!(custom_emoji_keyword -> custom_emoji (custom_emoji_id));
 diesel::joinable!(email_verification -> local_user (local_user_id));
@@ -976,6 +1161,7 @@ diesel::joinable!(instance_block -> instance (instance_id));
 diesel::joinable!(instance_block -> person (person_id));
 diesel::joinable!(local_image -> local_user (local_user_id));
 diesel::joinable!(local_site -> site (site_id));
+diesel::joinable!(local_site_federation -> local_site (local_site_id));
 diesel::joinable!(local_site_rate_limit -> local_site (local_site_id));
 diesel::joinable!(local_user -> person (person_id));
 diesel::joinable!(local_user_language -> language (language_id));
@@ -1022,15 +1208,20 @@ diesel::joinable!(post_report -> post (post_id));
 diesel::joinable!(post_saved -> person (person_id));
 diesel::joinable!(post_saved -> post (post_id));
 diesel::joinable!(private_message_report -> private_message (private_message_id));
+diesel::joinable!(received_vote_action -> person (person_id));
 diesel::joinable!(registration_application -> local_user (local_user_id));
 diesel::joinable!(registration_application -> person (admin_id));
+diesel::joinable!(resolve_object_log -> person (person_id));
 diesel::joinable!(site -> instance (instance_id));
 diesel::joinable!(site_aggregates -> site (site_id));
 diesel::joinable!(site_language -> language (language_id));
 diesel::joinable!(site_language -> site (site_id));
 diesel::joinable!(tagline -> local_site (local_site_id));
+diesel::joinable!(vote_correction -> comment (comment_id));
+diesel::joinable!(vote_correction -> post (post_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    actor_alias,
     admin_purge_comment,
     admin_purge_community,
     admin_purge_person,
@@ -1038,6 +1229,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     captcha_answer,
     comment,
     comment_aggregates,
+    comment_edit,
     comment_like,
     comment_reply,
     comment_report,
@@ -1046,12 +1238,16 @@ diesel::allow_tables_to_appear_in_same_query!(
     community_aggregates,
     community_block,
     community_follower,
+    community_image_purge,
     community_language,
     community_moderator,
     community_person_ban,
+    community_purge_progress,
+    content_hash,
     custom_emoji,
     custom_emoji_keyword,
     email_verification,
+    federated_vote_rejection,
     federation_allowlist,
     federation_blocklist,
     federation_queue_state,
@@ -1060,6 +1256,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     language,
     local_image,
     local_site,
+    local_site_federation,
     local_site_rate_limit,
     local_site_url_blocklist,
     local_user,
@@ -1095,12 +1292,17 @@ diesel::allow_tables_to_appear_in_same_query!(
     private_message,
     private_message_report,
     received_activity,
+    received_vote_action,
     registration_application,
     remote_image,
+    report_archive,
+    resolve_fetch_validator,
+    resolve_object_log,
     secret,
     sent_activity,
     site,
     site_aggregates,
     site_language,
     tagline,
+    vote_correction,
 );
@@ -0,0 +1,20 @@
+use crate::newtypes::{CommunityId, DbUrl, PersonId};
+#[cfg(feature = "full")]
+use crate::schema::actor_alias;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(
+  feature = "full",
+  derive(Queryable, Selectable, Identifiable, Insertable)
+)]
+#[cfg_attr(feature = "full", diesel(table_name = actor_alias))]
+#[cfg_attr(feature = "full", diesel(primary_key(alias)))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+/// Another url of a remote person or community, from its `alsoKnownAs` field. Exactly one of
+/// `person_id` and `community_id` is set.
+pub struct ActorAlias {
+  pub alias: DbUrl,
+  pub person_id: Option<PersonId>,
+  pub community_id: Option<CommunityId>,
+}
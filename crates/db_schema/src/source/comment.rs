@@ -102,6 +102,8 @@ pub struct CommentLike {
   pub post_id: PostId, // TODO this is redundant
   pub score: i16,
   pub published: DateTime<Utc>,
+  /// Id of the federated vote activity, if the vote was received over federation.
+  pub activity_ap_id: Option<DbUrl>,
 }
 
 #[derive(Clone)]
@@ -112,6 +114,8 @@ pub struct CommentLikeForm {
   pub comment_id: CommentId,
   pub post_id: PostId, // TODO this is redundant
   pub score: i16,
+  /// Id of the federated vote activity, if the vote was received over federation.
+  pub activity_ap_id: Option<DbUrl>,
 }
 
 #[derive(PartialEq, Eq, Debug)]
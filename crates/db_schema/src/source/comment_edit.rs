@@ -0,0 +1,31 @@
+use crate::newtypes::CommentId;
+#[cfg(feature = "full")]
+use crate::schema::comment_edit;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "full")]
+use ts_rs::TS;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable, TS))]
+#[cfg_attr(feature = "full", diesel(table_name = comment_edit))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+#[cfg_attr(feature = "full", ts(export))]
+/// A previous version of a received comment, which was replaced by an edit.
+pub struct CommentEdit {
+  pub id: i32,
+  pub comment_id: CommentId,
+  /// The content of the comment before the edit.
+  pub content: String,
+  /// When this version was published or last edited.
+  pub published: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "full", derive(Insertable))]
+#[cfg_attr(feature = "full", diesel(table_name = comment_edit))]
+pub struct CommentEditForm {
+  pub comment_id: CommentId,
+  pub content: String,
+  pub published: DateTime<Utc>,
+}
@@ -5,6 +5,7 @@ use crate::{
   sensitive::SensitiveString,
   source::placeholder_apub_url,
   CommunityVisibility,
+  FederationMode,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -69,6 +70,18 @@ pub struct Community {
   #[serde(skip)]
   pub featured_url: Option<DbUrl>,
   pub visibility: CommunityVisibility,
+  /// Overrides the site setting for federated upvotes on posts.
+  pub post_upvotes: Option<FederationMode>,
+  /// Overrides the site setting for federated downvotes on posts.
+  pub post_downvotes: Option<FederationMode>,
+  /// Overrides the site setting for federated upvotes on comments.
+  pub comment_upvotes: Option<FederationMode>,
+  /// Overrides the site setting for federated downvotes on comments.
+  pub comment_downvotes: Option<FederationMode>,
+  /// Whether federated votes from bot accounts are accepted.
+  pub allow_bot_votes: bool,
+  /// When the vote federation modes were last changed.
+  pub vote_mode_updated: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, TypedBuilder, Default)]
@@ -103,6 +116,11 @@ pub struct CommunityInsertForm {
   #[builder(!default)]
   pub instance_id: InstanceId,
   pub visibility: Option<CommunityVisibility>,
+  pub post_upvotes: Option<FederationMode>,
+  pub post_downvotes: Option<FederationMode>,
+  pub comment_upvotes: Option<FederationMode>,
+  pub comment_downvotes: Option<FederationMode>,
+  pub allow_bot_votes: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -131,6 +149,11 @@ pub struct CommunityUpdateForm {
   pub hidden: Option<bool>,
   pub posting_restricted_to_mods: Option<bool>,
   pub visibility: Option<CommunityVisibility>,
+  pub post_upvotes: Option<Option<FederationMode>>,
+  pub post_downvotes: Option<Option<FederationMode>>,
+  pub comment_upvotes: Option<Option<FederationMode>>,
+  pub comment_downvotes: Option<Option<FederationMode>>,
+  pub allow_bot_votes: Option<bool>,
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -214,3 +237,16 @@ pub struct CommunityFollowerForm {
   pub person_id: PersonId,
   pub pending: bool,
 }
+
+/// Options for [Community::purge].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommunityPurgeOptions {
+  /// Stores the posts of the community in the modlog entry.
+  pub store_snapshot: bool,
+  /// Counts the community as purged in this `CommunityPurgeProgress`.
+  pub progress_id: Option<i32>,
+  /// Only recorded in the modlog, to show whether the removal is sent to other instances.
+  pub federated: bool,
+  /// Copies the unresolved reports of the community to the `ReportArchive` before deleting them.
+  pub archive_reports: bool,
+}
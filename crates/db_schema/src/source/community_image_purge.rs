@@ -0,0 +1,38 @@
+use crate::newtypes::CommunityId;
+#[cfg(feature = "full")]
+use crate::schema::community_image_purge;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::fmt::Debug;
+#[cfg(feature = "full")]
+use ts_rs::TS;
+
+#[skip_serializing_none]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable, TS))]
+#[cfg_attr(feature = "full", diesel(table_name = community_image_purge))]
+#[cfg_attr(feature = "full", diesel(primary_key(community_id)))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Progress of removing the images of a purged community from pictrs.
+pub struct CommunityImagePurge {
+  pub community_id: CommunityId,
+  /// The number of images to purge.
+  pub total: i32,
+  /// The number of images which were purged.
+  pub purged: i32,
+  /// The number of images which couldn't be purged, even after retrying.
+  pub failed: i32,
+  pub published: DateTime<Utc>,
+  /// Set once all images were handled.
+  pub finished: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "full", derive(Insertable, AsChangeset))]
+#[cfg_attr(feature = "full", diesel(table_name = community_image_purge))]
+pub struct CommunityImagePurgeForm {
+  pub community_id: CommunityId,
+  pub total: i32,
+}
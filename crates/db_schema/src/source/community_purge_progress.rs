@@ -0,0 +1,35 @@
+#[cfg(feature = "full")]
+use crate::schema::community_purge_progress;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::fmt::Debug;
+#[cfg(feature = "full")]
+use ts_rs::TS;
+
+#[skip_serializing_none]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable, TS))]
+#[cfg_attr(feature = "full", diesel(table_name = community_purge_progress))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Progress of purging multiple communities at once.
+pub struct CommunityPurgeProgress {
+  pub id: i32,
+  /// The number of communities to purge.
+  pub total: i32,
+  /// The number of communities which were purged.
+  pub purged: i32,
+  pub published: DateTime<Utc>,
+  /// Set once all communities were handled.
+  pub finished: Option<DateTime<Utc>>,
+  /// The number of communities which couldn't be purged.
+  pub failed: i32,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "full", derive(Insertable, AsChangeset))]
+#[cfg_attr(feature = "full", diesel(table_name = community_purge_progress))]
+pub struct CommunityPurgeProgressForm {
+  pub total: i32,
+}
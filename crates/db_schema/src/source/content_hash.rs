@@ -0,0 +1,17 @@
+use crate::newtypes::{CommentId, PostId};
+#[cfg(feature = "full")]
+use crate::schema::content_hash;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable))]
+#[cfg_attr(feature = "full", diesel(table_name = content_hash))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+/// The SHA-256 hash of the content of a received post or comment, as hex string. Exactly one of
+/// `post_id` and `comment_id` is set.
+pub struct ContentHash {
+  pub id: i32,
+  pub hash: String,
+  pub post_id: Option<PostId>,
+  pub comment_id: Option<CommentId>,
+}
@@ -0,0 +1,36 @@
+use crate::newtypes::DbUrl;
+#[cfg(feature = "full")]
+use crate::schema::federated_vote_rejection;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+#[cfg(feature = "full")]
+use ts_rs::TS;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable, TS))]
+#[cfg_attr(feature = "full", diesel(table_name = federated_vote_rejection))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+#[cfg_attr(feature = "full", ts(export))]
+/// A federated vote which was rejected because of the vote federation mode.
+pub struct FederatedVoteRejection {
+  pub id: i32,
+  /// The person who sent the vote.
+  #[cfg_attr(feature = "full", ts(type = "string"))]
+  pub actor_id: DbUrl,
+  /// The post or comment which was voted on.
+  #[cfg_attr(feature = "full", ts(type = "string"))]
+  pub object_id: DbUrl,
+  /// 1 for an upvote, -1 for a downvote.
+  pub score: i16,
+  pub published: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "full", derive(Insertable))]
+#[cfg_attr(feature = "full", diesel(table_name = federated_vote_rejection))]
+pub struct FederatedVoteRejectionForm {
+  pub actor_id: DbUrl,
+  pub object_id: DbUrl,
+  pub score: i16,
+}
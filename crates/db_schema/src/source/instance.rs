@@ -23,6 +23,9 @@ pub struct Instance {
   pub updated: Option<DateTime<Utc>>,
   pub software: Option<String>,
   pub version: Option<String>,
+  /// Maximum number of federated votes accepted from this instance per minute, overriding the
+  /// site's `instance_vote_rate_limit`. 0 disables the limit.
+  pub vote_rate_limit: Option<i32>,
 }
 
 #[derive(Clone, TypedBuilder)]
@@ -70,6 +70,12 @@ pub struct LocalSite {
   pub default_post_listing_mode: PostListingMode,
   /// Default value for [LocalUser.post_listing_mode]
   pub default_sort_type: SortType,
+  /// Purging a community with more posts than this requires passing `confirmed`.
+  pub purge_confirmation_post_threshold: i32,
+  /// Whether to store the names and ap_ids of posts when purging a community.
+  pub store_purge_snapshots: bool,
+  /// When downvotes were last enabled or disabled.
+  pub vote_mode_updated: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, TypedBuilder)]
@@ -101,6 +107,8 @@ pub struct LocalSiteInsertForm {
   pub federation_signed_fetch: Option<bool>,
   pub default_post_listing_mode: Option<PostListingMode>,
   pub default_sort_type: Option<SortType>,
+  pub purge_confirmation_post_threshold: Option<i32>,
+  pub store_purge_snapshots: Option<bool>,
 }
 
 #[derive(Clone, Default)]
@@ -130,4 +138,6 @@ pub struct LocalSiteUpdateForm {
   pub federation_signed_fetch: Option<bool>,
   pub default_post_listing_mode: Option<PostListingMode>,
   pub default_sort_type: Option<SortType>,
+  pub purge_confirmation_post_threshold: Option<i32>,
+  pub store_purge_snapshots: Option<bool>,
 }
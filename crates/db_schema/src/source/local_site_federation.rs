@@ -0,0 +1,110 @@
+#[cfg(feature = "full")]
+use crate::schema::local_site_federation;
+use crate::{newtypes::LocalSiteId, ResolveRemoteAccess};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+#[cfg(feature = "full")]
+use ts_rs::TS;
+use typed_builder::TypedBuilder;
+
+#[skip_serializing_none]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable, TS))]
+#[cfg_attr(feature = "full", diesel(table_name = local_site_federation))]
+#[cfg_attr(feature = "full", diesel(primary_key(local_site_id)))]
+#[cfg_attr(
+  feature = "full",
+  diesel(belongs_to(crate::source::local_site::LocalSite))
+)]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+#[cfg_attr(feature = "full", ts(export))]
+/// Federation settings of the local site: how incoming federated votes are checked and limited,
+/// which remote objects resolve_object may fetch, for whom, and whether remote community purges
+/// are applied.
+pub struct LocalSiteFederation {
+  pub local_site_id: LocalSiteId,
+  /// Whether to record federated votes which were rejected by the vote federation mode.
+  pub log_rejected_votes: bool,
+  pub published: DateTime<Utc>,
+  pub updated: Option<DateTime<Utc>>,
+  /// Federated votes from accounts younger than this many days don't count towards the score.
+  /// 0 disables the check.
+  pub min_account_age_for_full_vote: i32,
+  /// Maximum number of federated votes accepted from each remote instance per minute. 0 disables
+  /// the limit.
+  pub instance_vote_rate_limit: i32,
+  /// Hide NSFW posts, comments and communities from resolve_object for users who aren't logged
+  /// in.
+  pub hide_nsfw_from_resolve: bool,
+  /// Whether resolve_object may fetch posts from remote instances. Otherwise only posts which
+  /// are known already are resolved.
+  pub resolve_remote_posts: bool,
+  /// Whether resolve_object may fetch comments from remote instances.
+  pub resolve_remote_comments: bool,
+  /// Whether resolve_object may fetch users, communities and sites from remote instances.
+  pub resolve_remote_actors: bool,
+  /// Federated votes on posts and comments in communities younger than this many days are
+  /// rejected. 0 disables the check.
+  pub min_community_age_for_remote_votes: i32,
+  /// Read-only mode for maintenance. Incoming federated votes are acknowledged, but not stored.
+  pub read_only: bool,
+  /// Which users may fetch remote objects with resolve_object.
+  pub resolve_remote_access: ResolveRemoteAccess,
+  /// Users whose account is younger than this many days can't fetch remote objects with
+  /// resolve_object, regardless of `resolve_remote_access`. 0 disables the check.
+  pub resolve_remote_min_account_age: i32,
+  /// Maximum change of the score of a post or comment by federated votes per minute. Further
+  /// votes are queued and applied gradually. 0 disables the limit.
+  pub vote_score_change_limit: i32,
+  /// Whether communities of other instances are purged when an admin of their instance purges
+  /// them. Otherwise they are only removed, and their content is kept.
+  pub accept_remote_community_purges: bool,
+  /// Federated votes which were sent less than this many minutes before their vote federation
+  /// mode was changed are still accepted under the previous mode. 0 disables the grace period.
+  pub vote_mode_grace_period: i32,
+}
+
+#[derive(Clone, TypedBuilder)]
+#[builder(field_defaults(default))]
+#[cfg_attr(feature = "full", derive(Insertable))]
+#[cfg_attr(feature = "full", diesel(table_name = local_site_federation))]
+pub struct LocalSiteFederationInsertForm {
+  #[builder(!default)]
+  pub local_site_id: LocalSiteId,
+  pub log_rejected_votes: Option<bool>,
+  pub min_account_age_for_full_vote: Option<i32>,
+  pub instance_vote_rate_limit: Option<i32>,
+  pub hide_nsfw_from_resolve: Option<bool>,
+  pub resolve_remote_posts: Option<bool>,
+  pub resolve_remote_comments: Option<bool>,
+  pub resolve_remote_actors: Option<bool>,
+  pub min_community_age_for_remote_votes: Option<i32>,
+  pub read_only: Option<bool>,
+  pub resolve_remote_access: Option<ResolveRemoteAccess>,
+  pub resolve_remote_min_account_age: Option<i32>,
+  pub vote_score_change_limit: Option<i32>,
+  pub accept_remote_community_purges: Option<bool>,
+  pub vote_mode_grace_period: Option<i32>,
+}
+
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(feature = "full", derive(AsChangeset))]
+#[cfg_attr(feature = "full", diesel(table_name = local_site_federation))]
+pub struct LocalSiteFederationUpdateForm {
+  pub log_rejected_votes: Option<bool>,
+  pub updated: Option<Option<DateTime<Utc>>>,
+  pub min_account_age_for_full_vote: Option<i32>,
+  pub instance_vote_rate_limit: Option<i32>,
+  pub hide_nsfw_from_resolve: Option<bool>,
+  pub resolve_remote_posts: Option<bool>,
+  pub resolve_remote_comments: Option<bool>,
+  pub resolve_remote_actors: Option<bool>,
+  pub min_community_age_for_remote_votes: Option<i32>,
+  pub read_only: Option<bool>,
+  pub resolve_remote_access: Option<ResolveRemoteAccess>,
+  pub resolve_remote_min_account_age: Option<i32>,
+  pub vote_score_change_limit: Option<i32>,
+  pub accept_remote_community_purges: Option<bool>,
+  pub vote_mode_grace_period: Option<i32>,
+}
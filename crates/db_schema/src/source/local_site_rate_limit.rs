@@ -38,6 +38,8 @@ pub struct LocalSiteRateLimit {
   pub updated: Option<DateTime<Utc>>,
   pub import_user_settings: i32,
   pub import_user_settings_per_second: i32,
+  pub resolve_object: i32,
+  pub resolve_object_per_second: i32,
 }
 
 #[derive(Clone, TypedBuilder)]
@@ -61,6 +63,8 @@ pub struct LocalSiteRateLimitInsertForm {
   pub search_per_second: Option<i32>,
   pub import_user_settings: Option<i32>,
   pub import_user_settings_per_second: Option<i32>,
+  pub resolve_object: Option<i32>,
+  pub resolve_object_per_second: Option<i32>,
 }
 
 #[derive(Clone, Default)]
@@ -81,5 +85,7 @@ pub struct LocalSiteRateLimitUpdateForm {
   pub search_per_second: Option<i32>,
   pub import_user_settings: Option<i32>,
   pub import_user_settings_per_second: Option<i32>,
+  pub resolve_object: Option<i32>,
+  pub resolve_object_per_second: Option<i32>,
   pub updated: Option<Option<DateTime<Utc>>>,
 }
@@ -3,16 +3,22 @@ use url::Url;
 
 #[cfg(feature = "full")]
 pub mod activity;
+pub mod actor_alias;
 pub mod actor_language;
 pub mod captcha_answer;
 pub mod comment;
+pub mod comment_edit;
 pub mod comment_reply;
 pub mod comment_report;
 pub mod community;
 pub mod community_block;
+pub mod community_image_purge;
+pub mod community_purge_progress;
+pub mod content_hash;
 pub mod custom_emoji;
 pub mod custom_emoji_keyword;
 pub mod email_verification;
+pub mod federated_vote_rejection;
 pub mod federation_allowlist;
 pub mod federation_blocklist;
 pub mod federation_queue_state;
@@ -21,6 +27,7 @@ pub mod instance;
 pub mod instance_block;
 pub mod language;
 pub mod local_site;
+pub mod local_site_federation;
 pub mod local_site_rate_limit;
 pub mod local_site_url_blocklist;
 pub mod local_user;
@@ -35,10 +42,15 @@ pub mod post;
 pub mod post_report;
 pub mod private_message;
 pub mod private_message_report;
+pub mod received_vote_action;
 pub mod registration_application;
+pub mod report_archive;
+pub mod resolve_fetch_validator;
+pub mod resolve_object_log;
 pub mod secret;
 pub mod site;
 pub mod tagline;
+pub mod vote_correction;
 
 /// Default value for columns like [community::Community.inbox_url] which are marked as serde(skip).
 ///
@@ -1,4 +1,4 @@
-use crate::newtypes::{CommentId, CommunityId, PersonId, PostId};
+use crate::newtypes::{CommentId, CommunityId, DbUrl, PersonId, PostId};
 #[cfg(feature = "full")]
 use crate::schema::{
   admin_purge_comment,
@@ -19,6 +19,7 @@ use crate::schema::{
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use serde_with::skip_serializing_none;
 #[cfg(feature = "full")]
 use ts_rs::TS;
@@ -326,6 +327,16 @@ pub struct AdminPurgeCommunity {
   pub admin_person_id: PersonId,
   pub reason: Option<String>,
   pub when_: DateTime<Utc>,
+  /// Names and ap_ids of the posts in the community before it was purged. Only stored if
+  /// enabled in the site settings, and only visible to admins.
+  #[cfg_attr(feature = "full", ts(type = "unknown"))]
+  pub snapshot: Option<Value>,
+  /// The ap_id of the purged community, only visible to admins. Not set for purges before it
+  /// was stored.
+  pub community_actor_id: Option<DbUrl>,
+  /// False if the removal was deliberately not sent to other instances, for example because the
+  /// content has to be kept under a legal hold on remote instances.
+  pub federated: bool,
 }
 
 #[cfg_attr(feature = "full", derive(Insertable, AsChangeset))]
@@ -333,6 +344,9 @@ pub struct AdminPurgeCommunity {
 pub struct AdminPurgeCommunityForm {
   pub admin_person_id: PersonId,
   pub reason: Option<String>,
+  pub snapshot: Option<Value>,
+  pub community_actor_id: Option<DbUrl>,
+  pub federated: bool,
 }
 
 #[skip_serializing_none]
@@ -132,6 +132,8 @@ pub struct PostLike {
   pub person_id: PersonId,
   pub score: i16,
   pub published: DateTime<Utc>,
+  /// Id of the federated vote activity, if the vote was received over federation.
+  pub activity_ap_id: Option<DbUrl>,
 }
 
 #[derive(Clone)]
@@ -141,6 +143,8 @@ pub struct PostLikeForm {
   pub post_id: PostId,
   pub person_id: PersonId,
   pub score: i16,
+  /// Id of the federated vote activity, if the vote was received over federation.
+  pub activity_ap_id: Option<DbUrl>,
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -0,0 +1,19 @@
+use crate::newtypes::{DbUrl, PersonId};
+#[cfg(feature = "full")]
+use crate::schema::received_vote_action;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Insertable))]
+#[cfg_attr(feature = "full", diesel(table_name = received_vote_action))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+/// The time of the latest federated vote or undo which was applied for a person and a post or
+/// comment.
+pub struct ReceivedVoteAction {
+  pub person_id: PersonId,
+  /// The post or comment which was voted on.
+  pub object_id: DbUrl,
+  /// The `published` time of the activity.
+  pub published: DateTime<Utc>,
+}
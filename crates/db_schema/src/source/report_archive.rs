@@ -0,0 +1,28 @@
+use crate::newtypes::DbUrl;
+#[cfg(feature = "full")]
+use crate::schema::report_archive;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+#[cfg(feature = "full")]
+use ts_rs::TS;
+
+#[skip_serializing_none]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable, TS))]
+#[cfg_attr(feature = "full", diesel(table_name = report_archive))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+#[cfg_attr(feature = "full", ts(export))]
+/// An open post or comment report, which was kept when its community was purged.
+pub struct ReportArchive {
+  pub id: i32,
+  pub community_actor_id: DbUrl,
+  /// The reported post or comment.
+  pub object_ap_id: DbUrl,
+  pub reporter_actor_id: DbUrl,
+  /// The post title or comment text at the time of the report.
+  pub original_content: String,
+  pub reason: String,
+  pub reported: DateTime<Utc>,
+  pub archived: DateTime<Utc>,
+}
@@ -0,0 +1,28 @@
+use crate::newtypes::DbUrl;
+#[cfg(feature = "full")]
+use crate::schema::resolve_fetch_validator;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable))]
+#[cfg_attr(feature = "full", diesel(table_name = resolve_fetch_validator))]
+#[cfg_attr(feature = "full", diesel(primary_key(ap_id)))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+/// The `ETag` and `Last-Modified` headers of the last fetch of a remote object by resolve_object,
+/// which are sent back to fetch it again only if it changed.
+pub struct ResolveFetchValidator {
+  pub ap_id: DbUrl,
+  pub etag: Option<String>,
+  pub last_modified: Option<String>,
+  pub updated: DateTime<Utc>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "full", derive(Insertable))]
+#[cfg_attr(feature = "full", diesel(table_name = resolve_fetch_validator))]
+pub struct ResolveFetchValidatorForm {
+  pub ap_id: DbUrl,
+  pub etag: Option<String>,
+  pub last_modified: Option<String>,
+}
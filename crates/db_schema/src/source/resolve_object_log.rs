@@ -0,0 +1,39 @@
+#[cfg(feature = "full")]
+use crate::schema::resolve_object_log;
+use crate::{newtypes::PersonId, ResolveObjectType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+#[cfg(feature = "full")]
+use ts_rs::TS;
+
+#[skip_serializing_none]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable, TS))]
+#[cfg_attr(feature = "full", diesel(table_name = resolve_object_log))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+#[cfg_attr(feature = "full", ts(export))]
+/// A remote object which was fetched by a user with resolve_object, or any object which an admin
+/// resolved as another user.
+pub struct ResolveObjectLog {
+  pub id: i32,
+  /// The search query which was resolved.
+  pub query: String,
+  /// The user who resolved the object.
+  pub person_id: PersonId,
+  /// The type of the resolved object, empty for other objects like sites.
+  pub object_type: Option<ResolveObjectType>,
+  pub published: DateTime<Utc>,
+  /// The local user as whom an admin resolved the object.
+  pub impersonated_person_id: Option<PersonId>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "full", derive(Insertable))]
+#[cfg_attr(feature = "full", diesel(table_name = resolve_object_log))]
+pub struct ResolveObjectLogForm {
+  pub query: String,
+  pub person_id: PersonId,
+  pub object_type: Option<ResolveObjectType>,
+  pub impersonated_person_id: Option<PersonId>,
+}
@@ -0,0 +1,31 @@
+use crate::newtypes::{CommentId, PostId};
+#[cfg(feature = "full")]
+use crate::schema::vote_correction;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "full", derive(Queryable, Selectable, Identifiable))]
+#[cfg_attr(feature = "full", diesel(table_name = vote_correction))]
+#[cfg_attr(feature = "full", diesel(check_for_backend(diesel::pg::Pg)))]
+/// A correction of the vote counts of a post or comment with the counts of the origin instance,
+/// by how much each count changed. The counts are always the stored votes plus all corrections.
+/// Exactly one of `post_id` and `comment_id` is set.
+pub struct VoteCorrection {
+  pub id: i32,
+  pub post_id: Option<PostId>,
+  pub comment_id: Option<CommentId>,
+  pub upvotes: i64,
+  pub downvotes: i64,
+  pub published: DateTime<Utc>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "full", derive(Insertable))]
+#[cfg_attr(feature = "full", diesel(table_name = vote_correction))]
+pub struct VoteCorrectionForm {
+  pub post_id: Option<PostId>,
+  pub comment_id: Option<CommentId>,
+  pub upvotes: i64,
+  pub downvotes: i64,
+}
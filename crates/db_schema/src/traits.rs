@@ -107,6 +107,14 @@ pub trait Likeable {
   ) -> Result<usize, Error>
   where
     Self: Sized;
+  /// Returns the vote of the person on the item, if any.
+  async fn read(
+    pool: &mut DbPool<'_>,
+    person_id: PersonId,
+    item_id: Self::IdType,
+  ) -> Result<Option<Self>, Error>
+  where
+    Self: Sized;
 }
 
 #[async_trait]
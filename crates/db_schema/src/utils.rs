@@ -474,7 +474,7 @@ static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
 });
 
 pub mod functions {
-  use diesel::sql_types::{BigInt, Text, Timestamptz};
+  use diesel::sql_types::{BigInt, Bytea, Text, Timestamptz};
 
   sql_function! {
     #[sql_name = "r.hot_rank"]
@@ -488,13 +488,22 @@ pub mod functions {
 
   sql_function! {
     #[sql_name = "r.controversy_rank"]
-    fn controversy_rank(upvotes: BigInt, downvotes: BigInt, score: BigInt) -> Double;
+    fn controversy_rank(upvotes: BigInt, downvotes: BigInt) -> Double;
   }
 
   sql_function!(fn reverse_timestamp_sort(time: Timestamptz) -> BigInt);
 
+  sql_function!(fn sha256(x: Bytea) -> Bytea);
+
+  sql_function!(fn convert_to(x: Text, encoding: Text) -> Bytea);
+
+  sql_function!(fn encode(x: Bytea, format: Text) -> Text);
+
   sql_function!(fn lower(x: Text) -> Text);
 
+  // provided by the pg_trgm extension
+  sql_function!(fn similarity(x: Text, y: Text) -> Float);
+
   // really this function is variadic, this just adds the two-argument version
   sql_function!(fn coalesce<T: diesel::sql_types::SqlType + diesel::sql_types::SingleValue>(x: diesel::sql_types::Nullable<T>, y: T) -> T);
 }
@@ -440,6 +440,12 @@ mod tests {
         featured_url: inserted_community.featured_url,
         instance_id: inserted_instance.id,
         visibility: CommunityVisibility::Public,
+        post_upvotes: None,
+        post_downvotes: None,
+        comment_upvotes: None,
+        comment_downvotes: None,
+        allow_bot_votes: false,
+        vote_mode_updated: None,
       },
       creator: Person {
         id: inserted_jessica.id,
@@ -100,7 +100,8 @@ fn queries<'a>() -> Queries<
       .filter(
         comment::id
           .eq(comment_like::comment_id)
-          .and(comment_like::person_id.eq(person_id)),
+          .and(comment_like::person_id.eq(person_id))
+          .and(comment_like::score.ne(0)),
       )
       .select(comment_like::score.nullable())
       .single_value()
@@ -628,6 +629,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: inserted_timmy_person.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     let _inserted_comment_like = CommentLike::like(pool, &comment_like_form).await?;
@@ -728,6 +730,7 @@ mod tests {
       post_id: data.inserted_post.id,
       person_id: data.timmy_local_user_view.person.id,
       score: 1,
+      activity_ap_id: None,
     };
     CommentLike::like(pool, &comment_like_form).await?;
 
@@ -1140,6 +1143,12 @@ mod tests {
         moderators_url: data.inserted_community.moderators_url.clone(),
         featured_url: data.inserted_community.featured_url.clone(),
         visibility: CommunityVisibility::Public,
+        post_upvotes: None,
+        post_downvotes: None,
+        comment_upvotes: None,
+        comment_downvotes: None,
+        allow_bot_votes: false,
+        vote_mode_updated: None,
       },
       counts: CommentAggregates {
         comment_id: data.inserted_comment_0.id,
@@ -146,7 +146,8 @@ fn queries<'a>() -> Queries<
       .filter(
         post_aggregates::post_id
           .eq(post_like::post_id)
-          .and(post_like::person_id.eq(person_id)),
+          .and(post_like::person_id.eq(person_id))
+          .and(post_like::score.ne(0)),
       )
       .select(post_like::score.nullable())
       .single_value()
@@ -1082,6 +1083,7 @@ mod tests {
       post_id: data.inserted_post.id,
       person_id: data.local_user_view.person.id,
       score: 1,
+      activity_ap_id: None,
     };
 
     let inserted_post_like = PostLike::like(pool, &post_like_form).await?;
@@ -1091,6 +1093,7 @@ mod tests {
       person_id: data.local_user_view.person.id,
       published: inserted_post_like.published,
       score: 1,
+      activity_ap_id: None,
     };
     assert_eq!(expected_post_like, inserted_post_like);
 
@@ -1124,6 +1127,26 @@ mod tests {
     .await?;
     assert_eq!(vec![expected_post_with_upvote], read_post_listing);
 
+    // a vote with a score of 0 isn't counted, and isn't shown as a vote
+    let uncounted_like_form = PostLikeForm {
+      score: 0,
+      ..post_like_form
+    };
+    PostLike::like(pool, &uncounted_like_form).await?;
+
+    let post_listing_with_uncounted_vote = PostView::read(
+      pool,
+      data.inserted_post.id,
+      Some(data.local_user_view.person.id),
+      false,
+    )
+    .await?
+    .ok_or(LemmyErrorType::CouldntFindPost)?;
+    assert_eq!(
+      expected_post_view(&data, pool).await?,
+      post_listing_with_uncounted_vote
+    );
+
     let like_removed =
       PostLike::remove(pool, data.local_user_view.person.id, data.inserted_post.id).await?;
     assert_eq!(1, like_removed);
@@ -1143,6 +1166,7 @@ mod tests {
       post_id: data.inserted_post.id,
       person_id: data.local_user_view.person.id,
       score: 1,
+      activity_ap_id: None,
     };
     PostLike::like(pool, &post_like_form).await?;
 
@@ -1150,6 +1174,7 @@ mod tests {
       post_id: data.inserted_bot_post.id,
       person_id: data.local_user_view.person.id,
       score: 1,
+      activity_ap_id: None,
     };
     PostLike::like(pool, &bot_post_like_form).await?;
 
@@ -1698,6 +1723,12 @@ mod tests {
         moderators_url: inserted_community.moderators_url.clone(),
         featured_url: inserted_community.featured_url.clone(),
         visibility: CommunityVisibility::Public,
+        post_upvotes: None,
+        post_downvotes: None,
+        comment_upvotes: None,
+        comment_downvotes: None,
+        allow_bot_votes: false,
+        vote_mode_updated: None,
       },
       counts: PostAggregates {
         post_id: inserted_post.id,
@@ -2,7 +2,7 @@ use crate::structs::SiteView;
 use diesel::{result::Error, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
 use lemmy_db_schema::{
-  schema::{local_site, local_site_rate_limit, site, site_aggregates},
+  schema::{local_site, local_site_federation, local_site_rate_limit, site, site_aggregates},
   utils::{get_conn, DbPool},
 };
 
@@ -14,11 +14,15 @@ impl SiteView {
       .inner_join(
         local_site_rate_limit::table.on(local_site::id.eq(local_site_rate_limit::local_site_id)),
       )
+      .inner_join(
+        local_site_federation::table.on(local_site::id.eq(local_site_federation::local_site_id)),
+      )
       .inner_join(site_aggregates::table)
       .select((
         site::all_columns,
         local_site::all_columns,
         local_site_rate_limit::all_columns,
+        local_site_federation::all_columns,
         site_aggregates::all_columns,
       ))
       .first(conn)
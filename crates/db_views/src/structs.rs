@@ -10,6 +10,7 @@ use lemmy_db_schema::{
     custom_emoji_keyword::CustomEmojiKeyword,
     images::LocalImage,
     local_site::LocalSite,
+    local_site_federation::LocalSiteFederation,
     local_site_rate_limit::LocalSiteRateLimit,
     local_user::LocalUser,
     local_user_vote_display_mode::LocalUserVoteDisplayMode,
@@ -192,6 +193,7 @@ pub struct SiteView {
   pub site: Site,
   pub local_site: LocalSite,
   pub local_site_rate_limit: LocalSiteRateLimit,
+  pub local_site_federation: LocalSiteFederation,
   pub counts: SiteAggregates,
 }
 
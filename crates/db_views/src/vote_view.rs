@@ -36,6 +36,8 @@ impl VoteView {
         ),
       )
       .filter(post_like::post_id.eq(post_id))
+      // Votes with a score of 0 aren't counted, so they aren't listed either
+      .filter(post_like::score.ne(0))
       .select((
         person::all_columns,
         community_person_ban::community_id.nullable().is_not_null(),
@@ -69,6 +71,8 @@ impl VoteView {
         ),
       )
       .filter(comment_like::comment_id.eq(comment_id))
+      // Votes with a score of 0 aren't counted, so they aren't listed either
+      .filter(comment_like::score.ne(0))
       .select((
         person::all_columns,
         community_person_ban::community_id.nullable().is_not_null(),
@@ -128,6 +132,14 @@ mod tests {
 
     let inserted_sara = Person::create(pool, &new_person_2).await.unwrap();
 
+    let new_person_3 = PersonInsertForm::builder()
+      .name("young_vv".into())
+      .public_key("pubkey".to_string())
+      .instance_id(inserted_instance.id)
+      .build();
+
+    let inserted_young = Person::create(pool, &new_person_3).await.unwrap();
+
     let new_community = CommunityInsertForm::builder()
       .name("test community vv".to_string())
       .title("nada".to_owned())
@@ -158,6 +170,7 @@ mod tests {
       post_id: inserted_post.id,
       person_id: inserted_timmy.id,
       score: 1,
+      activity_ap_id: None,
     };
     PostLike::like(pool, &timmy_post_vote_form).await.unwrap();
 
@@ -166,9 +179,19 @@ mod tests {
       post_id: inserted_post.id,
       person_id: inserted_sara.id,
       score: -1,
+      activity_ap_id: None,
     };
     PostLike::like(pool, &sara_post_vote_form).await.unwrap();
 
+    // The vote of a young account isn't counted, so it isn't listed
+    let young_post_vote_form = PostLikeForm {
+      post_id: inserted_post.id,
+      person_id: inserted_young.id,
+      score: 0,
+      activity_ap_id: None,
+    };
+    PostLike::like(pool, &young_post_vote_form).await.unwrap();
+
     let expected_post_vote_views = [
       VoteView {
         creator: inserted_sara.clone(),
@@ -193,6 +216,7 @@ mod tests {
       comment_id: inserted_comment.id,
       person_id: inserted_timmy.id,
       score: -1,
+      activity_ap_id: None,
     };
     CommentLike::like(pool, &timmy_comment_vote_form)
       .await
@@ -204,11 +228,24 @@ mod tests {
       comment_id: inserted_comment.id,
       person_id: inserted_sara.id,
       score: 1,
+      activity_ap_id: None,
     };
     CommentLike::like(pool, &sara_comment_vote_form)
       .await
       .unwrap();
 
+    // Neither is the one on the comment
+    let young_comment_vote_form = CommentLikeForm {
+      post_id: inserted_post.id,
+      comment_id: inserted_comment.id,
+      person_id: inserted_young.id,
+      score: 0,
+      activity_ap_id: None,
+    };
+    CommentLike::like(pool, &young_comment_vote_form)
+      .await
+      .unwrap();
+
     let expected_comment_vote_views = [
       VoteView {
         creator: inserted_timmy.clone(),
@@ -1,8 +1,9 @@
 use crate::structs::CommunityFollowerView;
 use chrono::Utc;
 use diesel::{
-  dsl::{count_star, not},
+  dsl::{count_star, exists, not},
   result::Error,
+  select,
   ExpressionMethods,
   QueryDsl,
 };
@@ -75,6 +76,24 @@ impl CommunityFollowerView {
     Ok(res)
   }
 
+  /// Check if any user of the given instance has an accepted follow for the community.
+  pub async fn is_followed_by_instance(
+    pool: &mut DbPool<'_>,
+    community_id: CommunityId,
+    instance_id: InstanceId,
+  ) -> Result<bool, Error> {
+    let conn = &mut get_conn(pool).await?;
+    select(exists(
+      community_follower::table
+        .inner_join(person::table)
+        .filter(community_follower::community_id.eq(community_id))
+        .filter(community_follower::pending.eq(false))
+        .filter(person::instance_id.eq(instance_id)),
+    ))
+    .get_result(conn)
+    .await
+  }
+
   pub async fn for_person(pool: &mut DbPool<'_>, person_id: PersonId) -> Result<Vec<Self>, Error> {
     let conn = &mut get_conn(pool).await?;
     community_follower::table
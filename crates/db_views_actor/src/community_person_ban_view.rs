@@ -22,4 +22,17 @@ impl CommunityPersonBanView {
     .get_result::<bool>(conn)
     .await
   }
+
+  /// Returns true if anyone is banned from the community.
+  pub async fn any_in_community(
+    pool: &mut DbPool<'_>,
+    for_community_id: CommunityId,
+  ) -> Result<bool, Error> {
+    let conn = &mut get_conn(pool).await?;
+    select(exists(
+      community_person_ban::table.filter(community_person_ban::community_id.eq(for_community_id)),
+    ))
+    .get_result::<bool>(conn)
+    .await
+  }
 }
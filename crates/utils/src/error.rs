@@ -53,6 +53,8 @@ pub enum LemmyErrorType {
   CouldntFindCommentReply,
   CouldntFindPrivateMessage,
   CouldntFindActivity,
+  CouldntFindPurgeProgress,
+  CouldntFindPurge,
   PersonIsBlocked,
   CommunityIsBlocked,
   InstanceIsBlocked,
@@ -98,6 +100,7 @@ pub enum LemmyErrorType {
   PostIsLocked,
   PersonIsBannedFromSite(String),
   InvalidVoteValue,
+  InvalidVoteRateLimit,
   PageDoesNotSpecifyCreator,
   NoEmailSetup,
   LocalSiteNotSetup,
@@ -151,6 +154,9 @@ pub enum LemmyErrorType {
   EmailSendFailed,
   Slurs,
   CouldntFindObject,
+  CouldntReadResolvedObject,
+  ResolvedObjectAccessDenied,
+  TooManyRedirects,
   RegistrationDenied(Option<String>),
   FederationDisabled,
   DomainBlocked(String),
@@ -176,6 +182,26 @@ pub enum LemmyErrorType {
   CantBlockLocalInstance,
   UrlWithoutDomain,
   InboxTimeout,
+  RequestTimeout,
+  /// A negative timeout was given.
+  InvalidTimeout,
+  /// A minimum age site setting is negative or too large.
+  InvalidMinAge,
+  PurgeCantBeResent,
+  /// Only purges of local communities can be federated.
+  CantResendPurgeOfRemoteCommunity,
+  /// The purge was only done locally, and needs to be confirmed to be sent to other instances.
+  PurgeWasntFederated,
+  PurgeRequiresConfirmation {
+    posts: i64,
+    comments: i64,
+    images: i64,
+  },
+  CantPurgeLocalInstance,
+  /// The given activity json couldn't be parsed.
+  InvalidActivity,
+  /// The start of a time range is after its end.
+  InvalidTimeRange,
   Unknown(String),
 }
 
@@ -48,7 +48,7 @@ macro_rules! location_info {
 /// * attaches the spawned task to the tracing span of the caller for better logging
 pub fn spawn_try_task(
   task: impl futures::Future<Output = Result<(), error::LemmyError>> + Send + 'static,
-) {
+) -> tokio::task::JoinHandle<()> {
   use tracing::Instrument;
   tokio::spawn(
     async {
@@ -58,5 +58,5 @@ pub fn spawn_try_task(
     }
     .in_current_span(), /* this makes sure the inner tracing gets the same context as where
                          * spawn was called */
-  );
+  )
 }
@@ -87,6 +87,10 @@ impl RateLimitCell {
     self.new_checker(ActionType::ImportUserSettings)
   }
 
+  pub fn resolve_object(&self) -> RateLimitChecker {
+    self.new_checker(ActionType::ResolveObject)
+  }
+
   fn new_checker(&self, action_type: ActionType) -> RateLimitChecker {
     RateLimitChecker {
       state: self.state.clone(),
@@ -124,6 +128,10 @@ impl RateLimitCell {
         capacity: 1,
         secs_to_refill: 24 * 60 * 60,
       },
+      ActionType::ResolveObject => BucketConfig {
+        capacity: 30,
+        secs_to_refill: 600,
+      },
     })
   }
 }
@@ -201,7 +209,7 @@ where
   }
 }
 
-fn get_ip(conn_info: &ConnectionInfo) -> IpAddr {
+pub fn get_ip(conn_info: &ConnectionInfo) -> IpAddr {
   conn_info
     .realip_remote_addr()
     .and_then(parse_ip)
@@ -75,6 +75,7 @@ pub enum ActionType {
   Comment,
   Search,
   ImportUserSettings,
+  ResolveObject,
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -41,10 +41,29 @@ impl Settings {
   pub(crate) fn init() -> LemmyResult<Self> {
     let config = from_str::<Settings>(&Self::read_config_file()?)?;
     if config.hostname == "unset" {
-      Err(anyhow!("Hostname variable is not set!").into())
-    } else {
-      Ok(config)
+      Err(anyhow!("Hostname variable is not set!"))?
+    }
+    // Only http proxies can be used, reqwest is built without socks support
+    if let Some(proxy) = &config.resolve_object.proxy {
+      match proxy.scheme() {
+        "http" | "https" => {}
+        "socks4" | "socks4a" | "socks5" | "socks5h" => Err(anyhow!(
+          "resolve_object.proxy doesn't support socks proxies, use a http proxy instead"
+        ))?,
+        scheme => Err(anyhow!(
+          "resolve_object.proxy must be a http or https url, not {scheme}"
+        ))?,
+      }
+    }
+    // Reconciling without any pause would refetch remote objects continuously
+    if config
+      .vote_reconciliation
+      .as_ref()
+      .is_some_and(|r| r.interval == 0)
+    {
+      Err(anyhow!("vote_reconciliation.interval must be at least 1"))?
     }
+    Ok(config)
   }
 
   pub fn get_database_url(&self) -> String {
@@ -49,10 +49,30 @@ pub struct Settings {
   /// The number of activitypub federation retry workers that can be in-flight concurrently
   #[default(0)]
   pub retry_count: usize,
+  /// Collect incoming federated votes for this many milliseconds, and write them to the database
+  /// together. This reduces the number of transactions on instances which receive many votes.
+  /// Votes are acknowledged to the sending instance before they are written, so votes which are
+  /// still waiting are lost if Lemmy crashes. They are written on a normal shutdown. Votes which
+  /// still can't be written after 5 attempts, eg because the database is unreachable, are
+  /// dropped. Disabled with 0.
+  #[default(0)]
+  pub vote_batch_window: u64,
   // Prometheus configuration.
   #[default(None)]
   #[doku(example = "Some(Default::default())")]
   pub prometheus: Option<PrometheusConfig>,
+  /// Options for resolving remote objects through the resolve_object API
+  #[default(Default::default())]
+  pub resolve_object: ResolveObjectConfig,
+  /// Periodically refetch recent remote posts and comments, and correct their vote counts if they
+  /// differ from the counts on the origin instance. Disabled if not set.
+  #[default(None)]
+  #[doku(example = "Some(Default::default())")]
+  pub vote_reconciliation: Option<VoteReconciliationConfig>,
+  /// Include the vote counts of local posts and comments when serving them over ActivityPub, so
+  /// that other instances can reconcile their counts. This makes the counts public.
+  #[default(false)]
+  pub publish_vote_counts: bool,
   /// Sets a response Access-Control-Allow-Origin CORS header
   /// https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Access-Control-Allow-Origin
   #[default(None)]
@@ -237,3 +257,61 @@ pub struct PrometheusConfig {
   #[doku(example = "10002")]
   pub port: i32,
 }
+
+#[derive(Debug, Deserialize, Serialize, Clone, SmartDefault, Document)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResolveObjectConfig {
+  /// How long to remember failed remote lookups (in seconds). Repeated lookups of the same
+  /// query fail immediately during this time, without making any network requests. Admins
+  /// bypass this cache.
+  #[default(60)]
+  pub negative_cache_ttl: u64,
+  /// Each consecutive failure to resolve the same query doubles the time until it is fetched
+  /// again, starting at negative_cache_ttl and up to this many seconds. Admins bypass this as
+  /// well.
+  #[default(3600)]
+  pub max_backoff: u64,
+  /// Maximum number of items which are fetched at the same time, when processing the outbox or
+  /// featured posts of a newly fetched community. The limit is shared by all collections which are
+  /// processed while resolving one query.
+  #[default(10)]
+  pub max_concurrent_fetches: usize,
+  /// Send the requests for resolving remote objects through this http or https proxy. Other
+  /// federation requests and link previews are not affected. Socks proxies are rejected at
+  /// startup, for Tor point this to the HTTPTunnelPort of the Tor daemon instead.
+  #[default(None)]
+  #[doku(example = "http://127.0.0.1:8118")]
+  pub proxy: Option<Url>,
+  /// Maximum size in bytes of a response to a resolve request. Larger responses are aborted
+  /// while downloading, and the object isn't found. Must be below 204800, the limit which the
+  /// federation library applies to all fetches. Disabled if not set.
+  #[default(None)]
+  #[doku(example = "102400")]
+  pub max_response_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, SmartDefault, Document)]
+#[serde(default, deny_unknown_fields)]
+pub struct VoteReconciliationConfig {
+  /// How often to run the reconciliation (in minutes), at least 1
+  #[default(60)]
+  #[doku(example = "60")]
+  pub interval: u64,
+  /// Only posts and comments which were published within this many hours are checked
+  #[default(24)]
+  #[doku(example = "24")]
+  pub max_age: u64,
+  /// Maximum number of posts and comments which are refetched in each run
+  #[default(100)]
+  #[doku(example = "100")]
+  pub max_objects: u32,
+  /// Counts which differ from the local ones by more than this many votes are considered bogus,
+  /// and the object is skipped
+  #[default(100)]
+  #[doku(example = "100")]
+  pub max_correction: u32,
+  /// Time to wait between two fetches (in milliseconds), to avoid overloading remote instances
+  #[default(500)]
+  #[doku(example = "500")]
+  pub fetch_delay: u64,
+}
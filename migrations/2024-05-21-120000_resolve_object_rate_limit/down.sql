@@ -0,0 +1,6 @@
+ALTER TABLE local_site_rate_limit
+    DROP COLUMN resolve_object;
+
+ALTER TABLE local_site_rate_limit
+    DROP COLUMN resolve_object_per_second;
+
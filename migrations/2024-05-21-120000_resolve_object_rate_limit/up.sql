@@ -0,0 +1,6 @@
+ALTER TABLE local_site_rate_limit
+    ADD COLUMN resolve_object int NOT NULL DEFAULT 30;
+
+ALTER TABLE local_site_rate_limit
+    ADD COLUMN resolve_object_per_second int NOT NULL DEFAULT 600;
+
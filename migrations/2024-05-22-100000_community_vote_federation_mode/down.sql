@@ -0,0 +1,8 @@
+ALTER TABLE community
+    DROP COLUMN post_upvotes,
+    DROP COLUMN post_downvotes,
+    DROP COLUMN comment_upvotes,
+    DROP COLUMN comment_downvotes;
+
+DROP TYPE federation_mode_enum;
+
@@ -0,0 +1,13 @@
+CREATE TYPE federation_mode_enum AS enum (
+    'All',
+    'Local',
+    'Disable'
+);
+
+-- Null means the site setting is used
+ALTER TABLE community
+    ADD COLUMN post_upvotes federation_mode_enum,
+    ADD COLUMN post_downvotes federation_mode_enum,
+    ADD COLUMN comment_upvotes federation_mode_enum,
+    ADD COLUMN comment_downvotes federation_mode_enum;
+
@@ -0,0 +1,52 @@
+UPDATE
+    community
+SET
+    post_upvotes = NULL
+WHERE
+    post_upvotes = 'Followers';
+
+UPDATE
+    community
+SET
+    post_downvotes = NULL
+WHERE
+    post_downvotes = 'Followers';
+
+UPDATE
+    community
+SET
+    comment_upvotes = NULL
+WHERE
+    comment_upvotes = 'Followers';
+
+UPDATE
+    community
+SET
+    comment_downvotes = NULL
+WHERE
+    comment_downvotes = 'Followers';
+
+-- rename the old enum
+ALTER TYPE federation_mode_enum RENAME TO federation_mode_enum__;
+
+-- create the new enum
+CREATE TYPE federation_mode_enum AS ENUM (
+    'All',
+    'Local',
+    'Disable'
+);
+
+-- alter all your enum columns
+ALTER TABLE community
+    ALTER COLUMN post_upvotes TYPE federation_mode_enum
+    USING post_upvotes::text::federation_mode_enum,
+    ALTER COLUMN post_downvotes TYPE federation_mode_enum
+    USING post_downvotes::text::federation_mode_enum,
+    ALTER COLUMN comment_upvotes TYPE federation_mode_enum
+    USING comment_upvotes::text::federation_mode_enum,
+    ALTER COLUMN comment_downvotes TYPE federation_mode_enum
+    USING comment_downvotes::text::federation_mode_enum;
+
+-- drop the old enum
+DROP TYPE federation_mode_enum__;
+
@@ -0,0 +1,3 @@
+ALTER TYPE federation_mode_enum
+    ADD VALUE 'Followers' BEFORE 'Disable';
+
@@ -0,0 +1,4 @@
+DROP TABLE local_site_federation;
+
+DROP TABLE federated_vote_rejection;
+
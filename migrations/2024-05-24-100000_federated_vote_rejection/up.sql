@@ -0,0 +1,25 @@
+-- Settings for federated votes, one row per local site like local_site_rate_limit
+CREATE TABLE local_site_federation (
+    local_site_id int PRIMARY KEY REFERENCES local_site ON UPDATE CASCADE ON DELETE CASCADE,
+    log_rejected_votes boolean DEFAULT FALSE NOT NULL,
+    published timestamptz DEFAULT now() NOT NULL,
+    updated timestamptz
+);
+
+INSERT INTO local_site_federation (local_site_id)
+SELECT
+    id
+FROM
+    local_site;
+
+-- Federated votes which were rejected because of the vote federation mode
+CREATE TABLE federated_vote_rejection (
+    id serial PRIMARY KEY,
+    actor_id varchar(255) NOT NULL,
+    object_id varchar(255) NOT NULL,
+    score smallint NOT NULL,
+    published timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_federated_vote_rejection_published ON federated_vote_rejection (published DESC);
+
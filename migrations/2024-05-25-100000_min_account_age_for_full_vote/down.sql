@@ -0,0 +1,3 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN min_account_age_for_full_vote;
+
@@ -0,0 +1,5 @@
+-- Federated votes from accounts younger than this many days are stored with a score of 0, which
+-- doesn't count towards aggregates. 0 disables the check.
+ALTER TABLE local_site_federation
+    ADD COLUMN min_account_age_for_full_vote int DEFAULT 0 NOT NULL;
+
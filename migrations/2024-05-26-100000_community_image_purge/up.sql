@@ -0,0 +1,11 @@
+-- Progress of removing the images of a purged community from pictrs. There is no foreign key,
+-- because the community is already deleted while its images are purged.
+CREATE TABLE community_image_purge (
+    community_id int PRIMARY KEY,
+    total int NOT NULL,
+    purged int NOT NULL DEFAULT 0,
+    failed int NOT NULL DEFAULT 0,
+    published timestamptz NOT NULL DEFAULT now(),
+    finished timestamptz
+);
+
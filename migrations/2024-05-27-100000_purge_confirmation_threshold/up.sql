@@ -0,0 +1,4 @@
+-- Purging a community with more posts than this requires a confirmation by the admin.
+ALTER TABLE local_site
+    ADD COLUMN purge_confirmation_post_threshold int DEFAULT 1000 NOT NULL;
+
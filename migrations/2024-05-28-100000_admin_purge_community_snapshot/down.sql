@@ -0,0 +1,6 @@
+ALTER TABLE admin_purge_community
+    DROP COLUMN snapshot;
+
+ALTER TABLE local_site
+    DROP COLUMN store_purge_snapshots;
+
@@ -0,0 +1,7 @@
+-- Optionally store the names and ap_ids of posts in a purged community, for auditing.
+ALTER TABLE admin_purge_community
+    ADD COLUMN snapshot jsonb;
+
+ALTER TABLE local_site
+    ADD COLUMN store_purge_snapshots boolean DEFAULT FALSE NOT NULL;
+
@@ -0,0 +1,6 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN instance_vote_rate_limit;
+
+ALTER TABLE instance
+    DROP COLUMN vote_rate_limit;
+
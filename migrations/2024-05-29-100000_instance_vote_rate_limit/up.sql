@@ -0,0 +1,9 @@
+-- Maximum number of federated votes accepted from each remote instance per minute. 0 disables the
+-- limit.
+ALTER TABLE local_site_federation
+    ADD COLUMN instance_vote_rate_limit int DEFAULT 0 NOT NULL;
+
+-- Overrides the site's instance_vote_rate_limit for a single instance.
+ALTER TABLE instance
+    ADD COLUMN vote_rate_limit int;
+
@@ -0,0 +1,4 @@
+-- Store which community was purged, so that the purge can be federated again.
+ALTER TABLE admin_purge_community
+    ADD COLUMN community_actor_id varchar(255);
+
@@ -0,0 +1,3 @@
+ALTER TABLE community
+    ADD COLUMN allow_bot_votes boolean DEFAULT FALSE NOT NULL;
+
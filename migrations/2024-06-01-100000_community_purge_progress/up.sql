@@ -0,0 +1,9 @@
+-- Progress of purging multiple communities at once, eg all communities of an instance.
+CREATE TABLE community_purge_progress (
+    id serial PRIMARY KEY,
+    total int NOT NULL,
+    purged int NOT NULL DEFAULT 0,
+    published timestamptz NOT NULL DEFAULT now(),
+    finished timestamptz
+);
+
@@ -0,0 +1,3 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN hide_nsfw_from_resolve;
+
@@ -0,0 +1,3 @@
+ALTER TABLE local_site_federation
+    ADD COLUMN hide_nsfw_from_resolve boolean DEFAULT FALSE NOT NULL;
+
@@ -0,0 +1,6 @@
+ALTER TABLE post_like
+    DROP COLUMN activity_ap_id;
+
+ALTER TABLE comment_like
+    DROP COLUMN activity_ap_id;
+
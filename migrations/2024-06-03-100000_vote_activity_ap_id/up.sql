@@ -0,0 +1,7 @@
+-- Id of the federated activity which created the vote, for tracing scores back to activities
+ALTER TABLE post_like
+    ADD COLUMN activity_ap_id varchar(255);
+
+ALTER TABLE comment_like
+    ADD COLUMN activity_ap_id varchar(255);
+
@@ -0,0 +1,4 @@
+DROP TABLE resolve_object_log;
+
+DROP TYPE resolve_object_type_enum;
+
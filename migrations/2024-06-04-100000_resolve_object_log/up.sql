@@ -0,0 +1,18 @@
+CREATE TYPE resolve_object_type_enum AS enum (
+    'Post',
+    'Comment',
+    'Person',
+    'Community'
+);
+
+-- Remote objects which users fetched with resolve_object
+CREATE TABLE resolve_object_log (
+    id serial PRIMARY KEY,
+    query text NOT NULL,
+    person_id int NOT NULL REFERENCES person ON UPDATE CASCADE ON DELETE CASCADE,
+    object_type resolve_object_type_enum,
+    published timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_resolve_object_log_published ON resolve_object_log (published DESC);
+
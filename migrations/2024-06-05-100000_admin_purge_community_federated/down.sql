@@ -0,0 +1,3 @@
+ALTER TABLE admin_purge_community
+    DROP COLUMN federated;
+
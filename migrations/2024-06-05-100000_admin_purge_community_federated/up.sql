@@ -0,0 +1,3 @@
+ALTER TABLE admin_purge_community
+    ADD COLUMN federated boolean NOT NULL DEFAULT TRUE;
+
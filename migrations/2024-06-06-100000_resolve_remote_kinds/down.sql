@@ -0,0 +1,5 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN resolve_remote_posts,
+    DROP COLUMN resolve_remote_comments,
+    DROP COLUMN resolve_remote_actors;
+
@@ -0,0 +1,5 @@
+ALTER TABLE local_site_federation
+    ADD COLUMN resolve_remote_posts boolean DEFAULT TRUE NOT NULL,
+    ADD COLUMN resolve_remote_comments boolean DEFAULT TRUE NOT NULL,
+    ADD COLUMN resolve_remote_actors boolean DEFAULT TRUE NOT NULL;
+
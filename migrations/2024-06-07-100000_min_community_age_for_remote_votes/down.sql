@@ -0,0 +1,3 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN min_community_age_for_remote_votes;
+
@@ -0,0 +1,5 @@
+-- Federated votes on posts and comments in communities younger than this many days are rejected.
+-- 0 disables the check.
+ALTER TABLE local_site_federation
+    ADD COLUMN min_community_age_for_remote_votes int DEFAULT 0 NOT NULL;
+
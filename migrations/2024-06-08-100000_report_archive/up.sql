@@ -0,0 +1,15 @@
+-- Open reports of purged communities, which would otherwise be deleted together with the reported
+-- posts and comments. Everything is referenced by ap_id, so that entries outlive the community.
+CREATE TABLE report_archive (
+    id serial PRIMARY KEY,
+    community_actor_id varchar(255) NOT NULL,
+    object_ap_id varchar(255) NOT NULL,
+    reporter_actor_id varchar(255) NOT NULL,
+    original_content text NOT NULL,
+    reason text NOT NULL,
+    reported timestamptz NOT NULL,
+    archived timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_report_archive_community ON report_archive (community_actor_id);
+
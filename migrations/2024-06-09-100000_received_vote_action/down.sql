@@ -0,0 +1 @@
+DROP TABLE received_vote_action;
@@ -0,0 +1,9 @@
+-- Time of the latest vote or undo which was applied for a remote person and post or comment, so
+-- that activities which arrive out of order don't overwrite newer ones.
+CREATE TABLE received_vote_action (
+    person_id int REFERENCES person ON UPDATE CASCADE ON DELETE CASCADE NOT NULL,
+    object_id varchar(255) NOT NULL,
+    published timestamptz NOT NULL,
+    PRIMARY KEY (person_id, object_id)
+);
+
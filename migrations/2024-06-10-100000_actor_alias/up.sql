@@ -0,0 +1,13 @@
+-- Other urls of remote persons and communities, from their alsoKnownAs field. Each alias belongs
+-- to exactly one actor.
+CREATE TABLE actor_alias (
+    alias varchar(255) PRIMARY KEY,
+    person_id int REFERENCES person ON UPDATE CASCADE ON DELETE CASCADE,
+    community_id int REFERENCES community ON UPDATE CASCADE ON DELETE CASCADE,
+    CHECK (num_nonnulls (person_id, community_id) = 1)
+);
+
+CREATE INDEX idx_actor_alias_person ON actor_alias (person_id);
+
+CREATE INDEX idx_actor_alias_community ON actor_alias (community_id);
+
@@ -0,0 +1,4 @@
+-- Read-only mode for maintenance. Incoming federated votes are acknowledged, but not stored.
+ALTER TABLE local_site_federation
+    ADD COLUMN read_only boolean DEFAULT FALSE NOT NULL;
+
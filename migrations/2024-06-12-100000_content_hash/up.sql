@@ -0,0 +1,12 @@
+-- SHA-256 hashes of the content of received posts and comments, so that copies of the same content
+-- can be found without knowing their url. Each hash belongs to exactly one post or comment.
+CREATE TABLE content_hash (
+    id serial PRIMARY KEY,
+    hash varchar(64) NOT NULL,
+    post_id int UNIQUE REFERENCES post ON UPDATE CASCADE ON DELETE CASCADE,
+    comment_id int UNIQUE REFERENCES COMMENT ON UPDATE CASCADE ON DELETE CASCADE,
+    CHECK (num_nonnulls (post_id, comment_id) = 1)
+);
+
+CREATE INDEX idx_content_hash_hash ON content_hash (hash);
+
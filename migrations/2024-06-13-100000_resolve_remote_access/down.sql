@@ -0,0 +1,6 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN resolve_remote_access,
+    DROP COLUMN resolve_remote_min_account_age;
+
+DROP TYPE resolve_remote_access_enum;
+
@@ -0,0 +1,13 @@
+CREATE TYPE resolve_remote_access_enum AS enum (
+    'All',
+    'VerifiedEmail',
+    'Admins'
+);
+
+-- Which users may fetch remote objects with resolve_object, others can only resolve objects which
+-- are known already. Users whose account is younger than the minimum age in days are also limited
+-- to local objects.
+ALTER TABLE local_site_federation
+    ADD COLUMN resolve_remote_access resolve_remote_access_enum DEFAULT 'All' NOT NULL,
+    ADD COLUMN resolve_remote_min_account_age int DEFAULT 0 NOT NULL;
+
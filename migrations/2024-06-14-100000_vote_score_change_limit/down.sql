@@ -0,0 +1,3 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN vote_score_change_limit;
+
@@ -0,0 +1,5 @@
+-- Maximum change of a post or comment score by federated votes per minute. Further votes are
+-- queued and applied gradually. 0 disables the limit.
+ALTER TABLE local_site_federation
+    ADD COLUMN vote_score_change_limit int DEFAULT 0 NOT NULL;
+
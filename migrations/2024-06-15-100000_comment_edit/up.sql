@@ -0,0 +1,11 @@
+-- Previous versions of received comments, stored when a refetched comment was edited on its
+-- instance. `published` is the time at which the stored version was written.
+CREATE TABLE comment_edit (
+    id serial PRIMARY KEY,
+    comment_id int REFERENCES COMMENT ON UPDATE CASCADE ON DELETE CASCADE NOT NULL,
+    content text NOT NULL,
+    published timestamptz NOT NULL
+);
+
+CREATE INDEX idx_comment_edit_comment ON comment_edit (comment_id);
+
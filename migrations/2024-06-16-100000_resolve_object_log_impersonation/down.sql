@@ -0,0 +1,3 @@
+ALTER TABLE resolve_object_log
+    DROP COLUMN impersonated_person_id;
+
@@ -0,0 +1,4 @@
+-- Local user as whom an admin resolved the object
+ALTER TABLE resolve_object_log
+    ADD COLUMN impersonated_person_id int REFERENCES person ON UPDATE CASCADE ON DELETE CASCADE;
+
@@ -0,0 +1,3 @@
+ALTER TABLE community_purge_progress
+    DROP COLUMN failed;
+
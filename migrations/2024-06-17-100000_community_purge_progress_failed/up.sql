@@ -0,0 +1,4 @@
+-- Communities which couldn't be purged, as the purge continues with the next one
+ALTER TABLE community_purge_progress
+    ADD COLUMN failed int NOT NULL DEFAULT 0;
+
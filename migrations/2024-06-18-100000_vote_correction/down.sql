@@ -0,0 +1,2 @@
+DROP TABLE vote_correction;
+
@@ -0,0 +1,18 @@
+-- Corrections of the vote counts of remote posts and comments with the counts of their origin
+-- instance. Every correction is kept. The triggers count corrections like votes, so the counts
+-- are always the stored votes plus the sum of all corrections, and deleting a correction reverts
+-- it.
+CREATE TABLE vote_correction (
+    id serial PRIMARY KEY,
+    post_id int REFERENCES post ON UPDATE CASCADE ON DELETE CASCADE,
+    comment_id int REFERENCES COMMENT ON UPDATE CASCADE ON DELETE CASCADE,
+    upvotes bigint NOT NULL,
+    downvotes bigint NOT NULL,
+    published timestamptz NOT NULL DEFAULT now(),
+    CHECK (num_nonnulls (post_id, comment_id) = 1)
+);
+
+CREATE INDEX idx_vote_correction_post ON vote_correction (post_id);
+
+CREATE INDEX idx_vote_correction_comment ON vote_correction (comment_id);
+
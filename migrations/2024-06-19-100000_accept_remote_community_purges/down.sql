@@ -0,0 +1,3 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN accept_remote_community_purges;
+
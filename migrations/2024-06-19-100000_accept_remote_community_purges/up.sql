@@ -0,0 +1,5 @@
+-- Whether communities of other instances are purged when an admin of their instance purges them.
+-- Otherwise they are only removed.
+ALTER TABLE local_site_federation
+    ADD COLUMN accept_remote_community_purges boolean DEFAULT FALSE NOT NULL;
+
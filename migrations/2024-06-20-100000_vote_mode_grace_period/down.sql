@@ -0,0 +1,14 @@
+ALTER TABLE local_site_federation
+    DROP COLUMN vote_mode_grace_period;
+
+DROP TRIGGER vote_mode_updated ON community;
+
+DROP TRIGGER vote_mode_updated ON local_site;
+
+DROP FUNCTION community_vote_mode_updated, local_site_vote_mode_updated;
+
+ALTER TABLE community
+    DROP COLUMN vote_mode_updated;
+
+ALTER TABLE local_site
+    DROP COLUMN vote_mode_updated;
@@ -0,0 +1,47 @@
+-- Federated votes sent less than this many minutes before the vote federation mode was changed
+-- are still accepted.
+ALTER TABLE local_site_federation
+    ADD COLUMN vote_mode_grace_period integer DEFAULT 0 NOT NULL;
+
+-- When the vote federation modes of a community or the downvote setting of the site last changed,
+-- which the grace period is measured from. Other edits don't change it, unlike `updated`.
+ALTER TABLE community
+    ADD COLUMN vote_mode_updated timestamptz;
+
+ALTER TABLE local_site
+    ADD COLUMN vote_mode_updated timestamptz;
+
+CREATE FUNCTION community_vote_mode_updated ()
+    RETURNS TRIGGER
+    LANGUAGE plpgsql
+    AS $$
+BEGIN
+    IF (NEW.post_upvotes, NEW.post_downvotes, NEW.comment_upvotes, NEW.comment_downvotes) IS DISTINCT FROM (OLD.post_upvotes, OLD.post_downvotes, OLD.comment_upvotes, OLD.comment_downvotes) THEN
+        NEW.vote_mode_updated = now();
+    END IF;
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER vote_mode_updated
+    BEFORE UPDATE ON community
+    FOR EACH ROW
+    EXECUTE FUNCTION community_vote_mode_updated ();
+
+CREATE FUNCTION local_site_vote_mode_updated ()
+    RETURNS TRIGGER
+    LANGUAGE plpgsql
+    AS $$
+BEGIN
+    IF NEW.enable_downvotes IS DISTINCT FROM OLD.enable_downvotes THEN
+        NEW.vote_mode_updated = now();
+    END IF;
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER vote_mode_updated
+    BEFORE UPDATE ON local_site
+    FOR EACH ROW
+    EXECUTE FUNCTION local_site_vote_mode_updated ();
+
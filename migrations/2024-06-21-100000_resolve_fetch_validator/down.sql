@@ -0,0 +1 @@
+DROP TABLE resolve_fetch_validator;
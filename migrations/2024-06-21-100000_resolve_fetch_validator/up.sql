@@ -0,0 +1,11 @@
+-- The ETag and Last-Modified headers of the last fetch of a remote object by resolve_object, so
+-- that it can be fetched again conditionally. Rows which weren't updated for a while are removed
+-- by a scheduled task.
+CREATE TABLE resolve_fetch_validator (
+    ap_id varchar(255) PRIMARY KEY,
+    etag text,
+    last_modified text,
+    updated timestamptz NOT NULL DEFAULT now(),
+    CHECK (num_nonnulls (etag, last_modified) > 0)
+);
+
@@ -1,3 +1,4 @@
+use activitypub_federation::config::{FederationConfig, FederationMiddleware};
 use actix_web::{guard, web};
 use lemmy_api::{
   comment::{
@@ -73,10 +74,20 @@ use lemmy_api::{
     federated_instances::get_federated_instances,
     leave_admin::leave_admin,
     list_all_media::list_all_media,
+    list_federated_vote_rejections::list_federated_vote_rejections,
+    list_resolved_objects::list_resolved_objects,
     mod_log::get_mod_log,
     purge::{
       comment::purge_comment,
-      community::purge_community,
+      community::{
+        get_purge_communities_progress,
+        get_purge_community_status,
+        list_archived_reports,
+        purge_communities_from_instance,
+        purge_community,
+        purge_community_content,
+        resend_purge_community,
+      },
       person::purge_person,
       post::purge_post,
     },
@@ -85,9 +96,11 @@ use lemmy_api::{
       list::list_registration_applications,
       unread_count::get_unread_registration_application_count,
     },
+    set_instance_vote_rate_limit::set_instance_vote_rate_limit,
   },
   sitemap::get_sitemap,
 };
+use lemmy_api_common::context::LemmyContext;
 use lemmy_api_crud::{
   comment::{
     create::create_comment,
@@ -127,16 +140,23 @@ use lemmy_api_crud::{
 use lemmy_apub::api::{
   list_comments::list_comments,
   list_posts::list_posts,
+  preview_federated_vote::preview_federated_vote,
   read_community::get_community,
   read_person::read_person,
+  replay_federated_vote::replay_federated_vote,
   resolve_object::resolve_object,
+  resolve_objects::resolve_objects,
   search::search,
   user_settings_backup::{export_settings, import_settings},
 };
 use lemmy_routes::images::image_proxy;
 use lemmy_utils::rate_limit::RateLimitCell;
 
-pub fn config(cfg: &mut web::ServiceConfig, rate_limit: &RateLimitCell) {
+pub fn config(
+  cfg: &mut web::ServiceConfig,
+  rate_limit: &RateLimitCell,
+  resolve_federation_config: &FederationConfig<LemmyContext>,
+) {
   cfg.service(
     web::scope("/api/v3")
       .route("/image_proxy", web::get().to(image_proxy))
@@ -163,8 +183,15 @@ pub fn config(cfg: &mut web::ServiceConfig, rate_limit: &RateLimitCell) {
       .service(
         web::resource("/resolve_object")
           .wrap(rate_limit.message())
+          .wrap(FederationMiddleware::new(resolve_federation_config.clone()))
           .route(web::get().to(resolve_object)),
       )
+      .service(
+        web::resource("/resolve_objects")
+          .wrap(rate_limit.message())
+          .wrap(FederationMiddleware::new(resolve_federation_config.clone()))
+          .route(web::post().to(resolve_objects)),
+      )
       // Community
       .service(
         web::resource("/community")
@@ -362,10 +389,51 @@ pub fn config(cfg: &mut web::ServiceConfig, rate_limit: &RateLimitCell) {
             web::put().to(approve_registration_application),
           )
           .route("/list_all_media", web::get().to(list_all_media))
+          .route(
+            "/federated_vote_rejection/list",
+            web::get().to(list_federated_vote_rejections),
+          )
+          .route(
+            "/resolved_object/list",
+            web::get().to(list_resolved_objects),
+          )
+          .route(
+            "/federated_vote/preview",
+            web::get().to(preview_federated_vote),
+          )
+          .route(
+            "/federated_vote/replay",
+            web::post().to(replay_federated_vote),
+          )
+          .route(
+            "/instance/vote_rate_limit",
+            web::put().to(set_instance_vote_rate_limit),
+          )
           .service(
             web::scope("/purge")
               .route("/person", web::post().to(purge_person))
               .route("/community", web::post().to(purge_community))
+              .route(
+                "/instance_communities",
+                web::post().to(purge_communities_from_instance),
+              )
+              .route(
+                "/instance_communities/progress",
+                web::get().to(get_purge_communities_progress),
+              )
+              .route(
+                "/community/status",
+                web::get().to(get_purge_community_status),
+              )
+              .route("/community/resend", web::post().to(resend_purge_community))
+              .route(
+                "/community/archived_reports",
+                web::get().to(list_archived_reports),
+              )
+              .route(
+                "/community/content",
+                web::post().to(purge_community_content),
+              )
               .route("/post", web::post().to(purge_post))
               .route("/comment", web::post().to(purge_comment)),
           ),
@@ -24,6 +24,7 @@ use lemmy_db_schema::{
     community::{Community, CommunityUpdateForm},
     instance::Instance,
     local_site::{LocalSite, LocalSiteInsertForm},
+    local_site_federation::{LocalSiteFederation, LocalSiteFederationInsertForm},
     local_site_rate_limit::{LocalSiteRateLimit, LocalSiteRateLimitInsertForm},
     local_user::{LocalUser, LocalUserInsertForm},
     person::{Person, PersonInsertForm, PersonUpdateForm},
@@ -519,5 +520,11 @@ async fn initialize_local_site_2022_10_10(
     .build();
   LocalSiteRateLimit::create(pool, &local_site_rate_limit_form).await?;
 
+  // Create the federation settings table
+  let local_site_federation_form = LocalSiteFederationInsertForm::builder()
+    .local_site_id(local_site.id)
+    .build();
+  LocalSiteFederation::create(pool, &local_site_federation_form).await?;
+
   Ok(())
 }
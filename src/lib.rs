@@ -27,7 +27,7 @@ use clap::Parser;
 use lemmy_api_common::{
   context::LemmyContext,
   lemmy_db_views::structs::SiteView,
-  request::client_builder,
+  request::{client_builder, resolve_client_builder},
   send_activity::{ActivityChannel, MATCH_OUTGOING_ACTIVITIES},
   utils::{
     check_private_instance_and_federation_enabled,
@@ -35,7 +35,8 @@ use lemmy_api_common::{
   },
 };
 use lemmy_apub::{
-  activities::{handle_outgoing_activities, match_outgoing_activities},
+  activities::{handle_outgoing_activities, match_outgoing_activities, voting::flush_vote_batch},
+  fetcher::{fetch_headers::FetchHeaders, response_size_limit::ResponseSizeLimit},
   objects::instance::ApubSite,
   VerifyUrlData,
   FEDERATION_HTTP_FETCH_LIMIT,
@@ -164,11 +165,6 @@ pub async fn start_lemmy_server(args: CmdArgs) -> LemmyResult<()> {
     rate_limit_cell.clone(),
   );
 
-  let scheduled_tasks = (!args.disable_scheduled_tasks).then(|| {
-    // Schedules various cleanup tasks for the DB
-    tokio::task::spawn(scheduled_tasks::setup(context.clone()))
-  });
-
   if let Some(prometheus) = SETTINGS.prometheus.clone() {
     serve_prometheus(prometheus, context.clone())?;
   }
@@ -182,10 +178,32 @@ pub async fn start_lemmy_server(args: CmdArgs) -> LemmyResult<()> {
     .debug(cfg!(debug_assertions))
     .http_signature_compat(true)
     .url_verifier(Box::new(VerifyUrlData(context.inner_pool().clone())));
+  // Resolving objects has a separate client, so that its requests can go through a proxy and
+  // have a configurable size limit
+  let mut resolve_client = ClientBuilder::new(resolve_client_builder(&SETTINGS)?.build()?)
+    .with(TracingMiddleware::default())
+    .with(FetchHeaders);
+  if let Some(max_response_size) = SETTINGS.resolve_object.max_response_size {
+    resolve_client = resolve_client.with(ResponseSizeLimit::new(max_response_size)?);
+  }
+  let mut resolve_federation_config = federation_config.clone();
+  resolve_federation_config.client(resolve_client.build());
   if local_site.federation_signed_fetch {
     let site: ApubSite = site_view.site.into();
     federation_config.signed_fetch_actor(&site);
+    // Users enter the urls which are resolved, so these fetches are only signed if the remote
+    // instance denies them without a signature
+    let signed_resolve_config = resolve_federation_config
+      .clone()
+      .signed_fetch_actor(&site)
+      .build()
+      .await?;
+    let resolve_context = context
+      .clone()
+      .with_signed_resolve_config(signed_resolve_config);
+    resolve_federation_config.app_data(resolve_context);
   }
+  let resolve_federation_config = resolve_federation_config.build().await?;
   let federation_config = federation_config.build().await?;
 
   MATCH_OUTGOING_ACTIVITIES
@@ -195,6 +213,10 @@ pub async fn start_lemmy_server(args: CmdArgs) -> LemmyResult<()> {
     .expect("set function pointer");
   let request_data = federation_config.to_request_data();
   let outgoing_activities_task = tokio::task::spawn(handle_outgoing_activities(request_data));
+  let scheduled_tasks = (!args.disable_scheduled_tasks).then(|| {
+    // Schedules various cleanup tasks for the DB, and refetches remote objects
+    tokio::task::spawn(scheduled_tasks::setup(federation_config.to_request_data()))
+  });
 
   let server = if !args.disable_http_server {
     if let Some(startup_server_handle) = startup_server_handle {
@@ -203,6 +225,7 @@ pub async fn start_lemmy_server(args: CmdArgs) -> LemmyResult<()> {
 
     Some(create_http_server(
       federation_config.clone(),
+      resolve_federation_config,
       SETTINGS.clone(),
       federation_enabled,
     )?)
@@ -238,6 +261,8 @@ pub async fn start_lemmy_server(args: CmdArgs) -> LemmyResult<()> {
   if let Some(server) = server {
     server.stop(true).await;
   }
+  // Votes which were received last are still waiting to be written
+  flush_vote_batch(&federation_config.to_request_data()).await?;
   if let Some(federate) = federate {
     federate.cancel().await?;
   }
@@ -270,6 +295,7 @@ fn create_startup_server() -> LemmyResult<ServerHandle> {
 
 fn create_http_server(
   federation_config: FederationConfig<LemmyContext>,
+  resolve_federation_config: FederationConfig<LemmyContext>,
   settings: Settings,
   federation_enabled: bool,
 ) -> LemmyResult<ServerHandle> {
@@ -314,7 +340,7 @@ fn create_http_server(
 
     // The routes
     app
-      .configure(|cfg| api_routes_http::config(cfg, &rate_limit_cell))
+      .configure(|cfg| api_routes_http::config(cfg, &rate_limit_cell, &resolve_federation_config))
       .configure(|cfg| {
         if federation_enabled {
           lemmy_apub::http::routes::config(cfg);
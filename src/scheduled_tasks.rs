@@ -1,3 +1,4 @@
+use activitypub_federation::config::Data;
 use chrono::{DateTime, TimeZone, Utc};
 use clokwerk::{AsyncScheduler, TimeUnits as CTimeUnits};
 use diesel::{
@@ -11,6 +12,7 @@ use diesel::{
 };
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use lemmy_api_common::context::LemmyContext;
+use lemmy_apub::activities::voting::reconcile::reconcile_votes;
 use lemmy_db_schema::{
   schema::{
     captcha_answer,
@@ -20,6 +22,9 @@ use lemmy_db_schema::{
     person,
     post,
     received_activity,
+    received_vote_action,
+    resolve_fetch_validator,
+    resolve_object_log,
     sent_activity,
   },
   source::{
@@ -29,13 +34,13 @@ use lemmy_db_schema::{
   utils::{get_conn, naive_now, now, DbPool, DELETED_REPLACEMENT_TEXT},
 };
 use lemmy_routes::nodeinfo::NodeInfo;
-use lemmy_utils::error::LemmyResult;
+use lemmy_utils::{error::LemmyResult, settings::structs::VoteReconciliationConfig};
 use reqwest_middleware::ClientWithMiddleware;
 use std::time::Duration;
 use tracing::{error, info, warn};
 
 /// Schedules various cleanup tasks for lemmy in a background thread
-pub async fn setup(context: LemmyContext) -> LemmyResult<()> {
+pub async fn setup(context: Data<LemmyContext>) -> LemmyResult<()> {
   // Setup the connections
   let mut scheduler = AsyncScheduler::new();
   startup_jobs(&mut context.pool()).await;
@@ -72,15 +77,32 @@ pub async fn setup(context: LemmyContext) -> LemmyResult<()> {
   });
 
   let context_1 = context.clone();
-  // Clear old activities every week
+  // Clear old activities, received vote actions, resolved objects and fetch validators every week
   scheduler.every(CTimeUnits::weeks(1)).run(move || {
     let context = context_1.clone();
 
     async move {
       clear_old_activities(&mut context.pool()).await;
+      clear_old_received_vote_actions(&mut context.pool()).await;
+      clear_old_resolved_objects(&mut context.pool()).await;
+      clear_old_resolve_fetch_validators(&mut context.pool()).await;
     }
   });
 
+  if let Some(config) = context.settings().vote_reconciliation.clone() {
+    let context_1 = context.clone();
+    // Correct the vote counts of recent remote posts and comments in the configured interval
+    let interval = u32::try_from(config.interval).unwrap_or(u32::MAX);
+    scheduler.every(CTimeUnits::minutes(interval)).run(move || {
+      let context = context_1.clone();
+      let config = config.clone();
+
+      async move {
+        reconcile_remote_votes(&config, &context).await;
+      }
+    });
+  }
+
   let context_1 = context.clone();
   // Daily tasks:
   // - Overwrite deleted & removed posts and comments every day
@@ -112,6 +134,8 @@ async fn startup_jobs(pool: &mut DbPool<'_>) {
   update_hot_ranks(pool).await;
   update_banned_when_expired(pool).await;
   clear_old_activities(pool).await;
+  clear_old_received_vote_actions(pool).await;
+  clear_old_resolved_objects(pool).await;
   overwrite_deleted_posts_and_comments(pool).await;
   delete_old_denied_users(pool).await;
 }
@@ -319,6 +343,86 @@ async fn clear_old_activities(pool: &mut DbPool<'_>) {
   }
 }
 
+/// Clear the times of received votes and undos after the same time as the received activities,
+/// votes which are delivered later than that can't be told apart from new ones anyway
+async fn clear_old_received_vote_actions(pool: &mut DbPool<'_>) {
+  info!("Clearing old received vote actions...");
+  let conn = get_conn(pool).await;
+
+  match conn {
+    Ok(mut conn) => {
+      diesel::delete(
+        received_vote_action::table
+          .filter(received_vote_action::published.lt(now() - IntervalDsl::days(7))),
+      )
+      .execute(&mut conn)
+      .await
+      .map(|_| info!("Done."))
+      .map_err(|e| error!("Failed to clear old received vote actions: {e}"))
+      .ok();
+    }
+    Err(e) => {
+      error!("Failed to get connection from pool: {e}");
+    }
+  }
+}
+
+/// Clear the log of resolved objects after some time, it is only meant for recent activity
+async fn clear_old_resolved_objects(pool: &mut DbPool<'_>) {
+  info!("Clearing old resolved objects...");
+  let conn = get_conn(pool).await;
+
+  match conn {
+    Ok(mut conn) => {
+      diesel::delete(
+        resolve_object_log::table
+          .filter(resolve_object_log::published.lt(now() - IntervalDsl::days(30))),
+      )
+      .execute(&mut conn)
+      .await
+      .map(|_| info!("Done."))
+      .map_err(|e| error!("Failed to clear old resolved objects: {e}"))
+      .ok();
+    }
+    Err(e) => {
+      error!("Failed to get connection from pool: {e}");
+    }
+  }
+}
+
+/// Clear the validators of objects which resolve_object didn't fetch in full for a while. They
+/// are stored again by the next full fetch, so this only keeps the table from growing.
+async fn clear_old_resolve_fetch_validators(pool: &mut DbPool<'_>) {
+  info!("Clearing old resolve fetch validators...");
+  let conn = get_conn(pool).await;
+
+  match conn {
+    Ok(mut conn) => {
+      diesel::delete(
+        resolve_fetch_validator::table
+          .filter(resolve_fetch_validator::updated.lt(now() - IntervalDsl::days(30))),
+      )
+      .execute(&mut conn)
+      .await
+      .map(|_| info!("Done."))
+      .map_err(|e| error!("Failed to clear old resolve fetch validators: {e}"))
+      .ok();
+    }
+    Err(e) => {
+      error!("Failed to get connection from pool: {e}");
+    }
+  }
+}
+
+/// Refetches recent remote posts and comments, and corrects their vote counts if they drifted
+async fn reconcile_remote_votes(config: &VoteReconciliationConfig, context: &Data<LemmyContext>) {
+  info!("Reconciling vote counts of remote objects...");
+  match reconcile_votes(config, context).await {
+    Ok(corrected) => info!("Done. Corrected vote counts of {corrected} remote objects"),
+    Err(e) => error!("Failed to reconcile vote counts: {e}"),
+  }
+}
+
 async fn delete_old_denied_users(pool: &mut DbPool<'_>) {
   LocalUser::delete_old_denied_local_users(pool)
     .await